@@ -81,6 +81,15 @@ pub enum WinxError {
     #[error("File {path} is too large: {size} bytes (max {max_size})")]
     FileTooLarge { path: PathBuf, size: u64, max_size: u64 },
 
+    /// The file changed on disk since winx last read or wrote it (e.g. the user
+    /// edited it in their IDE). `diff` shows what changed, when known.
+    #[error(
+        "{} changed on disk since you last read it; call ReadFiles again before editing \
+         so those changes aren't overwritten.\n{diff}",
+        path.display()
+    )]
+    FileChangedExternally { path: PathBuf, diff: String },
+
     /// Error when writing to a file
     #[error("Failed to write file {path}: {message}")]
     FileWriteError { path: PathBuf, message: String },
@@ -173,6 +182,123 @@ impl From<anyhow::Error> for WinxError {
     }
 }
 
+impl WinxError {
+    /// Stable, machine-readable code for this error, sent as `data.code` in MCP
+    /// errors. Codes are part of the protocol: never rename one, only add.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::ShellInitializationError(_) => "shell_init_failed",
+            Self::WorkspacePathError(_) => "workspace_path",
+            Self::BashStateLockError(_) => "state_lock_failed",
+            Self::BashStateNotInitialized => "not_initialized",
+            Self::CommandExecutionError(_) => "command_failed",
+            Self::ArgumentParseError(_) => "invalid_arguments",
+            Self::FileAccessError { .. } => "file_access",
+            Self::PathSecurityError { .. } => "path_security",
+            Self::CommandNotAllowed(_) => "command_not_allowed",
+            Self::ThreadIdMismatch(_) => "thread_id_mismatch",
+            Self::DeserializationError(_) => "deserialization",
+            Self::SerializationError(_) => "serialization",
+            Self::SearchReplaceSyntaxError(_) | Self::SearchReplaceSyntaxErrorDetailed { .. } => {
+                "search_replace_syntax"
+            }
+            Self::SearchBlockNotFound(_) => "search_block_not_found",
+            Self::SearchBlockAmbiguous { .. } => "search_block_ambiguous",
+            Self::JsonParseError(_) => "invalid_json",
+            Self::FileTooLarge { .. } => "file_too_large",
+            Self::FileChangedExternally { .. } => "file_changed_externally",
+            Self::FileWriteError { .. } => "file_write_failed",
+            Self::DataLoadingError(_) => "data_loading_failed",
+            Self::ParameterValidationError { .. } => "invalid_parameter",
+            Self::MissingParameterError { .. } => "missing_parameter",
+            Self::NullValueError { .. } => "null_value",
+            Self::RecoverableSuggestionError { .. } => "recoverable",
+            Self::ContextSaveError(_) => "context_save_failed",
+            Self::CommandTimeout { .. } => "command_timeout",
+            Self::InteractiveCommandDetected { .. } => "interactive_command",
+            Self::CommandAlreadyRunning { .. } => "command_already_running",
+            Self::ProcessCleanupError { .. } => "process_cleanup_failed",
+            Self::BufferOverflow { .. } => "output_too_large",
+            Self::SessionRecoveryError { .. } => "session_recovery_failed",
+            Self::ResourceAllocationError { .. } => "resource_allocation_failed",
+            Self::IoError(_) => "io",
+            Self::ConfigurationError(_) => "configuration",
+            Self::ParseError(_) => "parse",
+            Self::InvalidInput(_) => "invalid_input",
+            Self::FileError(_) => "file",
+        }
+    }
+
+    /// The MCP error `data`: `code` plus whatever structured fields the variant
+    /// carries (`path`, `line`, `field`, `suggestions`, ...), so a client can
+    /// recover programmatically instead of parsing the English message.
+    pub fn data(&self) -> serde_json::Value {
+        let mut data = serde_json::Map::new();
+        data.insert("code".to_string(), self.code().into());
+        let mut set = |key: &str, value: serde_json::Value| {
+            data.insert(key.to_string(), value);
+        };
+        match self {
+            Self::FileAccessError { path, .. }
+            | Self::PathSecurityError { path, .. }
+            | Self::FileWriteError { path, .. } => set("path", path.display().to_string().into()),
+            Self::FileTooLarge { path, size, max_size } => {
+                set("path", path.display().to_string().into());
+                set("size", (*size).into());
+                set("max_size", (*max_size).into());
+            }
+            Self::FileChangedExternally { path, diff } => {
+                // `kind` predates `code`; kept for clients that already match on it.
+                set("kind", self.code().into());
+                set("path", path.display().to_string().into());
+                set("diff", diff.as_str().into());
+            }
+            Self::SearchBlockAmbiguous { match_count, suggestions, .. } => {
+                set("match_count", (*match_count).into());
+                set("suggestions", suggestions.clone().into());
+            }
+            Self::SearchReplaceSyntaxErrorDetailed {
+                line_number, block_type, suggestions, ..
+            } => {
+                if let Some(line) = line_number {
+                    set("line", (*line).into());
+                }
+                if let Some(block_type) = block_type {
+                    set("block_type", block_type.as_str().into());
+                }
+                set("suggestions", suggestions.clone().into());
+            }
+            Self::ParameterValidationError { field, .. }
+            | Self::MissingParameterError { field, .. }
+            | Self::NullValueError { field } => set("field", field.as_str().into()),
+            Self::RecoverableSuggestionError { suggestion, .. } => {
+                set("suggestions", vec![suggestion.clone()].into());
+            }
+            Self::BashStateNotInitialized => set(
+                "suggestions",
+                vec!["Call Initialize with type=\"first_call\" and a workspace path".to_string()]
+                    .into(),
+            ),
+            Self::CommandTimeout { command, timeout_seconds } => {
+                set("command", command.as_str().into());
+                set("timeout_seconds", (*timeout_seconds).into());
+            }
+            Self::InteractiveCommandDetected { command } => set("command", command.as_str().into()),
+            Self::CommandAlreadyRunning { current_command, duration_seconds } => {
+                set("command", current_command.as_str().into());
+                set("duration_seconds", (*duration_seconds).into());
+            }
+            Self::BufferOverflow { size, max_size } => {
+                set("size", (*size).into());
+                set("max_size", (*max_size).into());
+            }
+            Self::IoError(err) => set("io_kind", format!("{:?}", err.kind()).into()),
+            _ => {}
+        }
+        serde_json::Value::Object(data)
+    }
+}
+
 /// Advanced error recovery and suggestion options
 pub struct ErrorRecovery;
 
@@ -238,6 +364,9 @@ impl Clone for WinxError {
             Self::FileTooLarge { path, size, max_size } => {
                 Self::FileTooLarge { path: path.clone(), size: *size, max_size: *max_size }
             }
+            Self::FileChangedExternally { path, diff } => {
+                Self::FileChangedExternally { path: path.clone(), diff: diff.clone() }
+            }
             Self::FileWriteError { path, message } => {
                 Self::FileWriteError { path: path.clone(), message: message.clone() }
             }
@@ -291,3 +420,32 @@ impl Clone for WinxError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_carries_code_and_structured_fields() {
+        let err = WinxError::SearchReplaceSyntaxErrorDetailed {
+            message: "missing =======".to_string(),
+            line_number: Some(12),
+            block_type: None,
+            suggestions: vec!["add a ======= divider".to_string()],
+        };
+        let data = err.data();
+        assert_eq!(data["code"], "search_replace_syntax");
+        assert_eq!(data["line"], 12);
+        assert_eq!(data["suggestions"][0], "add a ======= divider");
+        assert!(data.get("block_type").is_none());
+
+        let data = WinxError::FileChangedExternally {
+            path: PathBuf::from("/w/a.rs"),
+            diff: String::new(),
+        }
+        .data();
+        assert_eq!(data["code"], "file_changed_externally");
+        assert_eq!(data["kind"], data["code"]);
+        assert_eq!(data["path"], "/w/a.rs");
+    }
+}
@@ -20,6 +20,7 @@
 // Module docs name products (ChatGPT, OAuth, cloudflared) — prose, not code idents.
 #![allow(clippy::doc_markdown)]
 
+use std::future::IntoFuture;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
@@ -37,7 +38,7 @@ use rmcp::transport::streamable_http_server::{
 use tower_http::limit::RequestBodyLimitLayer;
 use tower_http::timeout::TimeoutLayer;
 
-use crate::server::{SessionIsolation, WinxService};
+use crate::server::{shutdown_signal, SessionIsolation, WinxService, SHUTDOWN_GRACE};
 
 type BoxError = Box<dyn std::error::Error + Send + Sync>;
 
@@ -91,6 +92,7 @@ pub async fn start_http_server(
     // shell — real multi-tenant isolation needs per-client tokens, which the
     // single shared-token model doesn't provide.
     let shared = WinxService::with_isolation(SessionIsolation::Strict);
+    let service = shared.clone();
     let mcp_service = StreamableHttpService::new(
         move || Ok(shared.clone()),
         Arc::new(LocalSessionManager::default()),
@@ -121,7 +123,19 @@ pub async fn start_http_server(
     }
     // `into_make_service_with_connect_info` puts the peer address in request
     // extensions so the auth middleware can log who is hammering the endpoint.
-    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
+    let serving = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .into_future();
+    // Keep serving while `shutdown` waits for in-flight calls (see
+    // `start_winx_server`).
+    tokio::pin!(serving);
+    tokio::select! {
+        result = &mut serving => {
+            result?;
+            return Ok(());
+        }
+        signal = shutdown_signal() => tracing::info!("{signal} received, shutting down"),
+    }
+    service.shutdown(SHUTDOWN_GRACE).await;
     Ok(())
 }
 
@@ -3,7 +3,7 @@
 //! Winx is a high-performance Rust implementation of the Model Context Protocol (MCP).
 //! It provides core tools for shell execution and file management with extreme efficiency.
 
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 use winx_code_agent::{start_winx_server, Result, WinxError};
 
 /// Winx - High Performance MCP Server
@@ -21,6 +21,11 @@ struct Cli {
     #[arg(long)]
     debug: bool,
 
+    /// Serve without changing anything: file edits return the diff they would
+    /// write, and commands and other mutating tools are described, not run
+    #[arg(long)]
+    dry_run: bool,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -62,6 +67,74 @@ enum Commands {
         #[arg(long)]
         allow_query_token: bool,
     },
+
+    /// Print a sanitized markdown bug report (version, platform, redacted
+    /// `WINX_*` config, optional binaries) to attach to a GitHub issue
+    ReportBug {
+        /// Write the report to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+
+    /// Print the newest crash report (written when winx panics), to attach to
+    /// a GitHub issue
+    Report {
+        /// Open it in the default app instead of printing it
+        #[arg(long)]
+        open: bool,
+    },
+
+    /// Check which optional external binaries (git, python3, curl, `ImageMagick`,
+    /// screen/tmux, ...) are installed and how winx degrades without each, then
+    /// the shell, state dirs, `.winx` config files and MCP client configs
+    Doctor,
+
+    /// Approve a workspace's `.winx/hooks.toml` so its hooks run (again needed
+    /// after any edit to the file)
+    TrustHooks {
+        /// Workspace root (default: the current directory)
+        workspace: Option<std::path::PathBuf>,
+    },
+
+    /// Print a bash/zsh/fish/powershell completion script for this binary
+    /// (install commands are in the README)
+    Completions {
+        #[arg(value_enum)]
+        shell: winx_code_agent::utils::cli_docs::Shell,
+    },
+
+    /// Print the man page in roff, for `man -l -` or a `man1` directory
+    Man,
+
+    /// Read or change settings in `.winx/config.toml` (project) or
+    /// `~/.winx/config.toml` (global, under every project's)
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+#[derive(clap::Subcommand)]
+enum ConfigAction {
+    /// Print the effective value of a dotted key (e.g. `format.enabled`)
+    Get {
+        key: String,
+        /// Workspace root (default: the current directory)
+        #[arg(long)]
+        workspace: Option<std::path::PathBuf>,
+    },
+    /// Set a dotted key to a TOML value (`true`, `["a", "b"]`; other text is a
+    /// string)
+    Set {
+        key: String,
+        value: String,
+        /// Write the global file instead of the project's
+        #[arg(long)]
+        global: bool,
+        /// Workspace root (default: the current directory)
+        #[arg(long)]
+        workspace: Option<std::path::PathBuf>,
+    },
 }
 
 /// Logging setup
@@ -87,6 +160,10 @@ fn main() -> Result<()> {
     let cli = Cli::parse();
 
     setup_logging(cli.verbose, cli.debug);
+    winx_code_agent::utils::crash_report::install();
+    if cli.dry_run {
+        winx_code_agent::utils::dry_run::enable();
+    }
 
     // Opt-in Landlock sandbox (WINX_SANDBOX=1), confining writes to the cwd (the
     // workspace) + /tmp. Applied BEFORE the tokio runtime is built, so its worker
@@ -118,11 +195,114 @@ async fn async_main(cli: Cli) -> Result<()> {
         Some(Commands::Serve {
             http: true, bind, token, allowed_host, allow_query_token, ..
         }) => run_http_server(bind, token, allowed_host, allow_query_token).await,
+        Some(Commands::ReportBug { output }) => report_bug(output.as_deref()),
+        Some(Commands::Report { open }) => report(open),
+        Some(Commands::Doctor) => doctor(),
+        Some(Commands::TrustHooks { workspace }) => trust_hooks(workspace),
+        Some(Commands::Config { action }) => config(action),
+        Some(Commands::Completions { shell }) => {
+            print(&winx_code_agent::utils::cli_docs::completions(cli_command(), shell))
+        }
+        Some(Commands::Man) => print(&winx_code_agent::utils::cli_docs::man_page(cli_command())),
         // Default: stdio transport for local MCP clients.
         None | Some(Commands::Serve { .. }) => run_server().await,
     }
 }
 
+/// Writes the `report-bug` markdown to `output`, or stdout.
+fn report_bug(output: Option<&std::path::Path>) -> Result<()> {
+    use std::io::Write;
+
+    let report = winx_code_agent::utils::bug_report::render();
+    match output {
+        Some(path) => {
+            std::fs::write(path, report)?;
+            tracing::warn!("bug report written to {}", path.display());
+        }
+        None => std::io::stdout().write_all(report.as_bytes())?,
+    }
+    Ok(())
+}
+
+/// Prints the newest crash report, or opens it with `open`.
+fn report(open: bool) -> Result<()> {
+    let Some(path) = winx_code_agent::utils::crash_report::latest() else {
+        return print("No crash reports found.\n");
+    };
+    if open {
+        winx_code_agent::tools::context_save::try_open_file(&path.to_string_lossy())?;
+        return print(&format!("{}\n", path.display()));
+    }
+    tracing::warn!("crash report {}", path.display());
+    print(&std::fs::read_to_string(&path)?)
+}
+
+/// Prints the `doctor` report for the current directory to stdout.
+fn doctor() -> Result<()> {
+    use std::io::Write;
+
+    let report = winx_code_agent::utils::doctor::report(&std::env::current_dir()?);
+    std::io::stdout().write_all(report.as_bytes())?;
+    Ok(())
+}
+
+/// Records the workspace's hooks file as trusted.
+fn trust_hooks(workspace: Option<std::path::PathBuf>) -> Result<()> {
+    use std::io::Write;
+
+    let workspace = match workspace {
+        Some(path) => path,
+        None => std::env::current_dir()?,
+    };
+    let message = winx_code_agent::utils::hooks::trust(&workspace)?;
+    std::io::stdout().write_all(message.as_bytes())?;
+    Ok(())
+}
+
+/// The clap command under the installed binary's name, which completions must
+/// register for.
+fn cli_command() -> clap::Command {
+    Cli::command().bin_name(env!("CARGO_BIN_NAME"))
+}
+
+fn print(text: &str) -> Result<()> {
+    use std::io::Write;
+
+    std::io::stdout().write_all(text.as_bytes())?;
+    Ok(())
+}
+
+fn config(action: ConfigAction) -> Result<()> {
+    use std::io::Write;
+    use winx_code_agent::utils::project_config;
+
+    let workspace = |path: Option<std::path::PathBuf>| match path {
+        Some(path) => Ok(path),
+        None => std::env::current_dir(),
+    };
+    let message = match action {
+        ConfigAction::Get { key, workspace: path } => {
+            match project_config::get(&workspace(path)?, &key) {
+                Some(toml::Value::String(text)) => format!("{text}\n"),
+                Some(value) => format!("{value}\n"),
+                None => return Err(WinxError::ConfigurationError(format!("{key} is not set"))),
+            }
+        }
+        ConfigAction::Set { key, value, global, workspace: path } => {
+            let file = if global {
+                project_config::global_path()
+                    .ok_or_else(|| WinxError::ConfigurationError("no home directory".to_string()))?
+            } else {
+                workspace(path)?.join(project_config::CONFIG_FILE)
+            };
+            project_config::set(&file, &key, &value)?;
+            format!("Set {key} in {}\n", file.display())
+        }
+    };
+    std::io::stdout().write_all(message.as_bytes())?;
+    Ok(())
+}
+
 /// Executes the remote MCP server over Streamable HTTP.
 async fn run_http_server(
     bind: String,
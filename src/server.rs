@@ -1,15 +1,20 @@
-//! Winx MCP Server implementation using rmcp 1.7
+//! Winx MCP Server implementation using rmcp 1.8
 //! Core MCP tools only - High performance shell and file management
 
 use rmcp::{
     model::{
-        Annotated, CallToolRequestParams, CallToolResult, Content, GetPromptRequestParams,
-        GetPromptResult, Implementation, ListPromptsResult, ListResourcesResult, ListToolsResult,
-        PaginatedRequestParams, Prompt, PromptMessage, PromptMessageRole, ProtocolVersion,
-        RawResource, ReadResourceRequestParams, ReadResourceResult, ResourceContents,
-        ServerCapabilities, ServerInfo, Tool, ToolAnnotations,
+        Annotated, CallToolRequestParams, CallToolResult, ClientResult, Content,
+        CreateElicitationRequest, CreateElicitationRequestParams, ElicitationAction,
+        GetPromptRequestParams, GetPromptResult, Implementation, ListPromptsResult,
+        ListResourceTemplatesResult, ListResourcesResult, ListRootsRequest, ListToolsResult,
+        LoggingLevel, LoggingMessageNotificationParam, Notification, PaginatedRequestParams,
+        Prompt, PromptArgument, PromptMessage, PromptMessageRole, ProtocolVersion, RawResource,
+        RawResourceTemplate, ReadResourceRequestParams, ReadResourceResult, ResourceContents,
+        ResourceUpdatedNotificationParam, ServerCapabilities, ServerInfo, ServerNotification,
+        ServerRequest, SetLevelRequestParams, SubscribeRequestParams, Tool, ToolAnnotations,
+        UnsubscribeRequestParams,
     },
-    service::{RequestContext, RoleServer},
+    service::{NotificationContext, Peer, RequestContext, RoleServer},
     transport::stdio,
     ErrorData as McpError, ServerHandler, ServiceExt,
 };
@@ -17,21 +22,29 @@ use schemars::schema_for;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fmt::Write as FmtWrite;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, OnceLock};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use tracing::{info, warn};
 
 use crate::errors::WinxError;
 use crate::state::bash_state::generate_thread_id;
 use crate::state::BashState;
+use crate::tools::browser::BrowserResult;
 use crate::types::{
-    normalize_thread_id, BashCommand, CodeMap, ContextSave, FileWriteOrEdit, Initialize,
-    MultiFileEdit, ReadFiles, ReadImage, UndoEdit,
+    normalize_thread_id, AuditDependencies, AuditQuery, BashCommand, BashCommandAction, Browser,
+    CodeMap, CodeMapOutput, ContextSave, CopyFile, CreateArchive, CreateDirectory, DeleteFile,
+    DependencyAuditOutput, ExtractArchive, FileChangesOutput, FileWriteOrEdit, GetFileChanges,
+    GitHub, HttpRequest, Initialize, MemorySearch, MemoryWrite, MoveFile, MultiFileEdit,
+    NotebookEdit, ReadFiles, ReadImage, RenameSymbol, Scaffold, ScanTodos, ShellEnv, SystemStats,
+    SystemStatsOutput, TextOutput, TodoScanOutput, UndoEdit,
 };
+use crate::utils::file_watch::WatchList;
+use crate::utils::metrics::Metrics;
+use crate::utils::rate_limit::Limiter;
 
 /// Map a domain [`WinxError`] to the right JSON-RPC error kind.
 ///
@@ -41,12 +54,16 @@ use crate::types::{
 /// (PTY spawn, IO, lock poisoning, persistence) stay `internal_error`.
 fn to_mcp_error(tool: &str, err: &WinxError) -> McpError {
     let msg = format!("{tool} failed: {err}");
+    // Every error carries `data.code` (see `WinxError::code`) plus its structured
+    // fields, so clients can branch on the code instead of the message.
+    let data = Some(err.data());
     // Exhaustive on purpose — NO wildcard arm. A new `WinxError` variant must be
     // classified by hand (client vs server) or the build breaks. This is the
     // guard that keeps the JSON-RPC error codes honest over time.
     match err {
         // Client-caused: the model can fix its own input or usage. -> invalid_request
-        WinxError::BashStateNotInitialized
+        WinxError::FileChangedExternally { .. }
+        | WinxError::BashStateNotInitialized
         | WinxError::CommandNotAllowed(_)
         | WinxError::PathSecurityError { .. }
         | WinxError::ThreadIdMismatch(_)
@@ -67,7 +84,7 @@ fn to_mcp_error(tool: &str, err: &WinxError) -> McpError {
         | WinxError::SearchBlockAmbiguous { .. }
         | WinxError::FileTooLarge { .. }
         | WinxError::InteractiveCommandDetected { .. }
-        | WinxError::CommandAlreadyRunning { .. } => McpError::invalid_request(msg, None),
+        | WinxError::CommandAlreadyRunning { .. } => McpError::invalid_request(msg, data),
         // Server-caused: genuine internal faults the model cannot fix. -> internal_error
         WinxError::ShellInitializationError(_)
         | WinxError::BashStateLockError(_)
@@ -83,7 +100,7 @@ fn to_mcp_error(tool: &str, err: &WinxError) -> McpError {
         | WinxError::ResourceAllocationError { .. }
         | WinxError::IoError(_)
         | WinxError::ConfigurationError(_)
-        | WinxError::FileError(_) => McpError::internal_error(msg, None),
+        | WinxError::FileError(_) => McpError::internal_error(msg, data),
     }
 }
 
@@ -186,7 +203,13 @@ const READ_FILES_DESCRIPTION: &str =
      - Do NOT use this for binary files or images — use ReadImage for images. \
      - Provide absolute paths only (~ allowed) \
      - Only if the task requires line numbers understanding: \
-     - You may extract a range of lines. E.g., `/path/to/file:1-10` for lines 1-10. You can drop start or end like `/path/to/file:1-` or `/path/to/file:-10`";
+     - You may extract a range of lines. E.g., `/path/to/file:1-10` for lines 1-10. You can drop start or end like `/path/to/file:1-` or `/path/to/file:-10` \
+     - For huge files (logs, dumps) pass max_bytes to stream a window without loading the whole file; the output ends with the byte_offset to pass on the next call. max_tokens overrides the per-file token budget.";
+
+const GET_FILE_CHANGES_DESCRIPTION: &str =
+    "- Reports workspace files created, modified or deleted since your previous GetFileChanges call (e.g. edits the user made in their editor). \
+     - The first call only records a baseline; call it again later to see what changed. Pass reset=true to start a fresh baseline. \
+     - Your own FileWriteOrEdit/MultiFileEdit writes are not reported. Modified files you read earlier are flagged: re-read them before editing.";
 
 const FILE_WRITE_OR_EDIT_DESCRIPTION: &str =
     "- Writes or edits a file based on the percentage of changes. \
@@ -275,11 +298,50 @@ const UNDO_EDIT_DESCRIPTION: &str =
      - Refused if the file changed on disk since your edit (so an undo never discards newer changes), and a brand-new file's creation cannot be undone (no prior content) - use BashCommand rm for that. \
      - Provide file_path (absolute, ~ allowed).";
 
+const MOVE_FILE_DESCRIPTION: &str =
+    "- Moves or renames a file or directory inside the workspace. \
+     - Provide source and destination (absolute, ~ allowed); destination is the full new path, not a directory to move into. Missing parent directories are created. \
+     - An existing destination file is only replaced with overwrite=true; an existing directory is never replaced. \
+     - Symlinks are moved as links. A file you read before the move stays editable at its new path.";
+
+const COPY_FILE_DESCRIPTION: &str =
+    "- Copies a file, or a directory recursively, inside the workspace. \
+     - Provide source and destination (absolute, ~ allowed); destination is the full path of the copy. Missing parent directories are created. \
+     - An existing destination file is only replaced with overwrite=true; an existing directory is never replaced. Symlinks are copied as links, never followed.";
+
+const DELETE_FILE_DESCRIPTION: &str =
+    "- Deletes a file or directory inside the workspace. \
+     - By default it is moved to <workspace>/.winx/trash/ (restore it with MoveFile); pass permanent=true to delete immediately. \
+     - A non-empty directory needs recursive=true. The workspace root and protected paths are refused.";
+
+const CREATE_DIRECTORY_DESCRIPTION: &str =
+    "- Creates a directory and any missing parents inside the workspace (like mkdir -p). Succeeds if it already exists.";
+
+const CREATE_ARCHIVE_DESCRIPTION: &str =
+    "- Packs files and directories into an archive; the extension of archive_path picks the format: .zip, .tar, .tar.gz/.tgz or .tar.zst/.tzst. \
+     - paths must be under the current working directory and are stored relative to it (cd first to choose the layout). \
+     - An existing archive is only replaced with overwrite=true.";
+
+const EXTRACT_ARCHIVE_DESCRIPTION: &str =
+    "- Unpacks a .zip, .tar, .tar.gz/.tgz or .tar.zst/.tzst archive into destination (created if missing). \
+     - Archives with absolute or `..` member paths, or symlinks pointing outside the workspace, are refused before anything is written. \
+     - Files that already exist in destination are only replaced with overwrite=true; otherwise nothing is extracted.";
+
 const CONTEXT_SAVE_DESCRIPTION: &str =
     "Saves provided description and file contents of all the relevant file paths or globs in a single text file. \
      - Provide random 3 word unqiue id or whatever user provided. \
      - Leave project path as empty string if no project path";
 
+const MEMORY_WRITE_DESCRIPTION: &str =
+    "- Remembers a durable fact across sessions, e.g. \"uses pnpm, not npm\" or \"deploys with fly.io\". One short, self-contained sentence per call. \
+     - Scoped to the current workspace; global=true for facts about the user that hold everywhere. Initialize lists the remembered facts at the start of every session. \
+     - To correct or drop a fact, pass its id (from MemorySearch or the Initialize list) in replaces, with the new fact or an empty one. \
+     - For a task handoff with files and notes, use ContextSave instead.";
+
+const MEMORY_SEARCH_DESCRIPTION: &str =
+    "- Recalls remembered facts (see MemoryWrite) for this workspace plus the global ones, by keyword; an empty query lists them all, newest first. \
+     - Each result shows its id, for use in MemoryWrite replaces.";
+
 const CODE_MAP_DESCRIPTION: &str =
     "- Navigate code structure via tree-sitter - the semantic layer plain grep/rg can't give you. Pick an `operation`: \
      - operation=\"outline\": map symbols (functions, types, methods, classes, ...). `path` to a FILE returns that file's definitions; `path` to a DIRECTORY (or empty = the whole workspace) returns a relevance-ranked, token-budgeted symbol map across files. Use it instead of reading whole files just to learn their shape. \
@@ -288,13 +350,104 @@ const CODE_MAP_DESCRIPTION: &str =
      - 11 languages (rust, js/ts, go, c, c++, java, ruby, c#, php, lua); other files return no symbols. Note: C/C++ grammars tag definitions only, so references reads 0 for `.c`/`.h`/`.cpp`. \
      - For plain-text/regex search or file discovery, use rg / grep / fd / find via BashCommand.";
 
+const RENAME_SYMBOL_DESCRIPTION: &str =
+    "- Renames an identifier (function, type, method, field, variable) across the workspace using tree-sitter, instead of a risky text search/replace. \
+     - Only identifier tokens spelled exactly like name change; mentions in comments, strings or longer names are listed, not changed. Name-based, not scope-aware: every identifier with that name under path is renamed, so scope it with path when the name is common. \
+     - Returns a preview of every changed line by default; call again with apply=true to write (files need not be read first; undo per file with UndoEdit). \
+     - Same 11 languages as CodeMap.";
+
+const AUDIT_DEPENDENCIES_DESCRIPTION: &str =
+    "- Audits a project's locked dependencies: known vulnerabilities from the OSV database and a license summary. \
+     - Reads Cargo.lock, package-lock.json and pinned (==) requirements.txt entries; path is a lockfile or the directory holding them (empty = workspace root). \
+     - Each vulnerability lists its severity, advisory/CVE ids and the versions that fix it, so you can propose the bump. Only package names and versions are sent to api.osv.dev; offline=true skips the lookup.";
+
+const SCAN_TODOS_DESCRIPTION: &str =
+    "- Lists TODO/FIXME/HACK comments under path (empty = the whole workspace) with file:line, the comment text, and, from git blame, who last touched the line and how many days ago. \
+     - Only tags right after a comment marker count; pass tags for others (e.g. XXX, NOTE). Oldest first, so long-standing debt leads; returns structured items you can turn into a task list.";
+
+const SCAFFOLD_DESCRIPTION: &str =
+    "- Creates a project skeleton or module boilerplate from a template: rust-bin, rust-lib, node, python (a new ./name directory), rust-module, python-module (files in path), or a user template from ~/.winx/templates/<template>/. Call with no template to list them. \
+     - {{name}} and {{snake_name}} are filled in from name. Never overwrites: fails if any file exists. Files are written as one MultiFileEdit batch (mode gate, protected paths, undo per file).";
+
+const NOTEBOOK_EDIT_DESCRIPTION: &str =
+    "- Edits Jupyter notebooks (.ipynb) cell by cell; use it instead of ReadFiles/FileWriteOrEdit on notebook JSON. \
+     - operation=\"list\" shows every cell's index, type, output count and source. edit replaces the source of cell index (cell_type optionally changes its type; a code cell's stale outputs are cleared), insert adds a cell before index (omitted = at the end), delete removes one, clear_outputs clears one cell or, without index, all of them. \
+     - The notebook is rewritten the way Jupyter saves it; the file need not be read first, and UndoEdit reverts a change.";
+
+const SHELL_ENV_DESCRIPTION: &str =
+    "- Gets, sets or unsets an environment variable of the BashCommand shell session, so later commands see it; prefer it to export/unset through BashCommand. \
+     - set keeps the value out of the command line and shell history; values of secret-looking names (TOKEN, KEY, SECRET, PASSWORD, CREDENTIAL) are masked in results. \
+     - dotenv (e.g. \".env\") also writes the change to that file, replacing any existing assignment.";
+
+const SYSTEM_STATS_DESCRIPTION: &str =
+    "- Reports the machine's CPU cores, usage and load average, available memory, free disk on the workspace and home filesystems, and NVIDIA GPU usage (via nvidia-smi). \
+     - Check it when builds or tests are unexpectedly slow, get killed, or fail to write, before changing code.";
+
+const BROWSER_DESCRIPTION: &str =
+    "- Drives a headless Chromium (launched on first use, kept until close) for debugging web apps and end-to-end checks. \
+     - operation: navigate (url, http(s) only; waits for the load event), screenshot (PNG of the 1280x800 viewport), text (the rendered page text), click (CSS selector; reports a navigation it triggers), eval (JavaScript expression, promises awaited, JSON result), close. \
+     - Only public hosts load unless the server sets WINX_PRIVATE_URLS=1; with it, start the app under test with BashCommand (is_background) and navigate to its local URL. The page state carries over between calls.";
+
+const GITHUB_DESCRIPTION: &str =
+    "- Works with the workspace's GitHub repository (the origin remote): list_issues (state, limit), create_issue (title, body), create_pull_request (title, body, base; from the current branch, which must be pushed), pr_comments (number), ci_status (branch, default current: recent Actions runs with their ids), run_logs (run_id: tails of the failed jobs' logs). \
+     - Needs a token in GITHUB_TOKEN/GH_TOKEN or the global winx config (github.token). \
+     - Typical loop: commit and push with BashCommand, create_pull_request, then ci_status and run_logs on failures.";
+
+const HTTP_REQUEST_DESCRIPTION: &str =
+    "- Sends one HTTP request and summarizes the response (status, headers, timing, body pretty-printed if JSON); prefer it to curl through BashCommand for API calls. \
+     - method (default GET), url, headers, json or body. Redirects are not followed; the Location header is shown. \
+     - Only public hosts are reachable: loopback, private and link-local addresses are refused unless the server sets WINX_PRIVATE_URLS=1. \
+     - profile names an auth profile from the global winx config ([http.profiles.<name>] with base_url and headers): url may then be a path under base_url, and the profile's headers are never sent anywhere else. \
+     - The body shown is clipped to max_bytes (default 20000); save_to writes the full body to a file instead of flooding the context.";
+
+const AUDIT_QUERY_DESCRIPTION: &str =
+    "- Searches the audit log of mutating tool calls (commands run, files written with their hashes before and after) and verifies its hash chain, reporting any tampering. \
+     - Filters: tool, session (a thread_id), path (text in the summary or a touched file), since (Unix seconds), failed_only; returns the newest limit (default 50) entries. \
+     - Logging is off unless the user sets [audit] enabled = true in the global winx config.";
+
 static WINX_TOOLS: OnceLock<Vec<Tool>> = OnceLock::new();
 static WINX_PROMPTS: OnceLock<Vec<Prompt>> = OnceLock::new();
 
 fn winx_tools() -> Vec<Tool> {
-    WINX_TOOLS.get_or_init(build_winx_tools).clone()
+    WINX_TOOLS
+        .get_or_init(|| build_winx_tools().into_iter().map(with_output_schema).collect())
+        .clone()
+}
+
+/// Output schema for a tool's `structured_content`. MCP requires an object
+/// schema at the root, so an untagged enum (`anyOf`) is stamped as one.
+fn output_schema<T: schemars::JsonSchema>() -> Arc<serde_json::Map<String, Value>> {
+    let mut schema = (*schema_to_input_schema::<T>()).clone();
+    schema.entry("type").or_insert_with(|| Value::String("object".to_string()));
+    Arc::new(schema)
+}
+
+/// Attach the tool's output schema: its typed result where it has one, else
+/// [`TextOutput`] (see [`text_structured_content`]).
+fn with_output_schema(mut tool: Tool) -> Tool {
+    tool.output_schema = Some(match tool.name.as_ref() {
+        "CodeMap" => output_schema::<CodeMapOutput>(),
+        "GetFileChanges" => output_schema::<FileChangesOutput>(),
+        "AuditDependencies" => output_schema::<DependencyAuditOutput>(),
+        "ScanTodos" => output_schema::<TodoScanOutput>(),
+        "SystemStats" => output_schema::<SystemStatsOutput>(),
+        _ => output_schema::<TextOutput>(),
+    });
+    tool
 }
 
+/// Give a successful text-only result the [`TextOutput`] structured content
+/// its schema promises.
+fn text_structured_content(result: &mut CallToolResult) {
+    if result.structured_content.is_some() || result.is_error == Some(true) {
+        return;
+    }
+    let text: Vec<&str> =
+        result.content.iter().filter_map(|c| c.as_text()).map(|t| t.text.as_str()).collect();
+    result.structured_content = serde_json::to_value(TextOutput { text: text.join("\n") }).ok();
+}
+
+#[allow(clippy::too_many_lines)]
 fn build_winx_tools() -> Vec<Tool> {
     vec![
         mcp_tool::<Initialize>(
@@ -327,21 +480,125 @@ fn build_winx_tools() -> Vec<Tool> {
             UNDO_EDIT_DESCRIPTION,
             ToolAnnotations::new().destructive(true).open_world(false),
         ),
+        mcp_tool::<MoveFile>(
+            "MoveFile",
+            MOVE_FILE_DESCRIPTION,
+            ToolAnnotations::new().destructive(true).open_world(false),
+        ),
+        mcp_tool::<CopyFile>(
+            "CopyFile",
+            COPY_FILE_DESCRIPTION,
+            ToolAnnotations::new().destructive(true).open_world(false),
+        ),
+        mcp_tool::<DeleteFile>(
+            "DeleteFile",
+            DELETE_FILE_DESCRIPTION,
+            ToolAnnotations::new().destructive(true).open_world(false),
+        ),
+        mcp_tool::<CreateDirectory>(
+            "CreateDirectory",
+            CREATE_DIRECTORY_DESCRIPTION,
+            ToolAnnotations::new().destructive(false).open_world(false),
+        ),
+        mcp_tool::<CreateArchive>(
+            "CreateArchive",
+            CREATE_ARCHIVE_DESCRIPTION,
+            ToolAnnotations::new().destructive(true).open_world(false),
+        ),
+        mcp_tool::<ExtractArchive>(
+            "ExtractArchive",
+            EXTRACT_ARCHIVE_DESCRIPTION,
+            ToolAnnotations::new().destructive(true).open_world(false),
+        ),
         mcp_tool::<ContextSave>(
             "ContextSave",
             CONTEXT_SAVE_DESCRIPTION,
             ToolAnnotations::new().destructive(false).open_world(false),
         ),
+        mcp_tool::<MemoryWrite>(
+            "MemoryWrite",
+            MEMORY_WRITE_DESCRIPTION,
+            ToolAnnotations::new().destructive(false).open_world(false),
+        ),
+        mcp_tool::<MemorySearch>(
+            "MemorySearch",
+            MEMORY_SEARCH_DESCRIPTION,
+            ToolAnnotations::new().read_only(true).open_world(false),
+        ),
         mcp_tool::<ReadImage>(
             "ReadImage",
-            "Read an image from the shell.",
-            ToolAnnotations::new().read_only(true).open_world(false),
+            "Read an image from the shell. Pass max_width and/or jpeg_quality to shrink large \
+             screenshots before they reach the context. An http(s) URL is accepted in place of a \
+             path when the server sets WINX_IMAGE_URLS=1.",
+            ToolAnnotations::new()
+                .read_only(true)
+                .open_world(crate::tools::read_image::url_fetch_enabled()),
         ),
         mcp_tool::<CodeMap>(
             "CodeMap",
             CODE_MAP_DESCRIPTION,
             ToolAnnotations::new().read_only(true).open_world(false),
         ),
+        mcp_tool::<GetFileChanges>(
+            "GetFileChanges",
+            GET_FILE_CHANGES_DESCRIPTION,
+            ToolAnnotations::new().read_only(true).open_world(false),
+        ),
+        mcp_tool::<RenameSymbol>(
+            "RenameSymbol",
+            RENAME_SYMBOL_DESCRIPTION,
+            ToolAnnotations::new().destructive(true).open_world(false),
+        ),
+        mcp_tool::<AuditDependencies>(
+            "AuditDependencies",
+            AUDIT_DEPENDENCIES_DESCRIPTION,
+            ToolAnnotations::new().read_only(true).open_world(true),
+        ),
+        mcp_tool::<Scaffold>(
+            "Scaffold",
+            SCAFFOLD_DESCRIPTION,
+            ToolAnnotations::new().destructive(false).open_world(false),
+        ),
+        mcp_tool::<NotebookEdit>(
+            "NotebookEdit",
+            NOTEBOOK_EDIT_DESCRIPTION,
+            ToolAnnotations::new().destructive(true).open_world(false),
+        ),
+        mcp_tool::<ShellEnv>(
+            "ShellEnv",
+            SHELL_ENV_DESCRIPTION,
+            ToolAnnotations::new().destructive(false).open_world(false),
+        ),
+        mcp_tool::<SystemStats>(
+            "SystemStats",
+            SYSTEM_STATS_DESCRIPTION,
+            ToolAnnotations::new().read_only(true).open_world(false),
+        ),
+        mcp_tool::<Browser>(
+            "Browser",
+            BROWSER_DESCRIPTION,
+            ToolAnnotations::new().destructive(true).open_world(true),
+        ),
+        mcp_tool::<GitHub>(
+            "GitHub",
+            GITHUB_DESCRIPTION,
+            ToolAnnotations::new().destructive(false).open_world(true),
+        ),
+        mcp_tool::<HttpRequest>(
+            "HttpRequest",
+            HTTP_REQUEST_DESCRIPTION,
+            ToolAnnotations::new().destructive(true).open_world(true),
+        ),
+        mcp_tool::<ScanTodos>(
+            "ScanTodos",
+            SCAN_TODOS_DESCRIPTION,
+            ToolAnnotations::new().read_only(true).open_world(false),
+        ),
+        mcp_tool::<AuditQuery>(
+            "AuditQuery",
+            AUDIT_QUERY_DESCRIPTION,
+            ToolAnnotations::new().read_only(true).open_world(false),
+        ),
     ]
 }
 
@@ -357,12 +614,34 @@ fn winx_prompts() -> Vec<Prompt> {
         .clone()
 }
 
+/// Items per page of `tools/list` and `resources/list`.
+const PAGE_SIZE: usize = 100;
+
+/// The page of `items` the opaque `cursor` (a decimal offset handed out as a
+/// previous `next_cursor`) points at, plus the cursor of the page after it.
+fn paginate<T>(
+    items: Vec<T>,
+    request: Option<&PaginatedRequestParams>,
+) -> Result<(Vec<T>, Option<String>), McpError> {
+    let offset = match request.and_then(|request| request.cursor.as_deref()) {
+        None => 0,
+        Some(cursor) => cursor.parse::<usize>().map_err(|_| {
+            McpError::invalid_params(format!("Invalid pagination cursor: {cursor:?}"), None)
+        })?,
+    };
+    let next_cursor = (offset + PAGE_SIZE < items.len()).then(|| (offset + PAGE_SIZE).to_string());
+    Ok((items.into_iter().skip(offset).take(PAGE_SIZE).collect(), next_cursor))
+}
+
 fn append_command_section<const N: usize>(
     output: &mut String,
     title: &str,
     cwd: &Path,
     args: [&str; N],
 ) {
+    if !crate::utils::capabilities::available("git") {
+        return;
+    }
     let Ok(command_output) = Command::new("git").args(["-C"]).arg(cwd).args(args).output() else {
         return;
     };
@@ -446,6 +725,120 @@ impl Drop for SessionGuard {
     }
 }
 
+/// How often subscribed resources are checked for changes.
+const RESOURCE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Most workspace files `resources/list` returns.
+const MAX_LISTED_RESOURCES: usize = 1000;
+/// Largest workspace file `resources/read` returns.
+const MAX_RESOURCE_BYTES: u64 = 1024 * 1024;
+
+/// `resources/subscribe` state: the watched files and, per URI, the peers to
+/// notify. One poll task runs while anything is subscribed.
+#[derive(Default)]
+struct ResourceSubscriptions {
+    files: WatchList,
+    peers: HashMap<String, Vec<Peer<RoleServer>>>,
+    polling: bool,
+}
+
+/// Whether `a` and `b` are the same client connection: every clone of a peer
+/// shares the handshake info stored when it initialized.
+fn same_peer(a: &Peer<RoleServer>, b: &Peer<RoleServer>) -> bool {
+    match (a.peer_info(), b.peer_info()) {
+        (Some(a), Some(b)) => Arc::ptr_eq(&a, &b),
+        _ => false,
+    }
+}
+
+/// Poll the subscribed files every [`RESOURCE_POLL_INTERVAL`] and send
+/// `notifications/resources/updated` for each change, until nothing is
+/// subscribed any more.
+fn spawn_resource_poller(subscriptions: Arc<Mutex<ResourceSubscriptions>>) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(RESOURCE_POLL_INTERVAL).await;
+            let mut updates = Vec::new();
+            {
+                let mut subs = subscriptions.lock().await;
+                if subs.files.is_empty() {
+                    subs.polling = false;
+                    return;
+                }
+                for uri in subs.files.poll() {
+                    for peer in subs.peers.get(&uri).into_iter().flatten() {
+                        updates.push((uri.clone(), peer.clone()));
+                    }
+                }
+            }
+            for (uri, peer) in updates {
+                let param = ResourceUpdatedNotificationParam { uri: uri.clone() };
+                if let Err(e) = peer.notify_resource_updated(param).await {
+                    warn!(%uri, "resource update notification failed: {e}");
+                }
+            }
+        }
+    });
+}
+
+/// Ask the user to confirm `message` through MCP elicitation. `Some(true)` only
+/// for an explicit yes; `None` when the client can't be asked (it doesn't
+/// advertise elicitation, or the request failed), so the caller proceeds as it
+/// did before elicitation existed.
+async fn confirm_with_user(peer: &Peer<RoleServer>, message: String) -> Option<bool> {
+    if !peer.peer_info().is_some_and(|info| info.capabilities.elicitation.is_some()) {
+        return None;
+    }
+    let params: CreateElicitationRequestParams = serde_json::from_value(serde_json::json!({
+        "message": message,
+        "requestedSchema": {
+            "type": "object",
+            "properties": {
+                "confirm": { "type": "boolean", "title": "Proceed", "default": false },
+            },
+            "required": ["confirm"],
+        },
+    }))
+    .ok()?;
+    // `Peer::create_elicitation` needs rmcp's `elicitation` feature (and its
+    // `url` dependency); the plain request does the same.
+    let request = ServerRequest::CreateElicitationRequest(CreateElicitationRequest::new(params));
+    match peer.send_request(request).await {
+        Ok(ClientResult::CreateElicitationResult(result)) => Some(
+            matches!(result.action, ElicitationAction::Accept)
+                && result.content.as_ref().and_then(|c| c.get("confirm")).and_then(Value::as_bool)
+                    == Some(true),
+        ),
+        Ok(_) => {
+            warn!("unexpected elicitation response, proceeding without confirmation");
+            None
+        }
+        Err(e) => {
+            warn!("elicitation failed, proceeding without confirmation: {e}");
+            None
+        }
+    }
+}
+
+/// Order of MCP log levels, least severe first.
+fn severity(level: LoggingLevel) -> u8 {
+    match level {
+        LoggingLevel::Debug => 0,
+        LoggingLevel::Info => 1,
+        LoggingLevel::Notice => 2,
+        LoggingLevel::Warning => 3,
+        LoggingLevel::Error => 4,
+        LoggingLevel::Critical => 5,
+        LoggingLevel::Alert => 6,
+        LoggingLevel::Emergency => 7,
+    }
+}
+
+/// Whether `path` lies inside one of the client's MCP `roots`.
+fn within_roots(path: &Path, roots: &[PathBuf]) -> bool {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    roots.iter().any(|root| path.starts_with(root))
+}
+
 /// How an empty `thread_id` is resolved by the session registry.
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum SessionIsolation {
@@ -472,6 +865,61 @@ pub struct WinxService {
     pub version: String,
     /// How empty `thread_id`s are resolved (see [`SessionIsolation`]).
     isolation: SessionIsolation,
+    /// Workspace files clients subscribed to via `resources/subscribe`.
+    subscriptions: Arc<Mutex<ResourceSubscriptions>>,
+    /// Directories the client shared as MCP `roots`, canonicalized. `None`
+    /// until it sends a non-empty list (or if it doesn't support roots), which
+    /// leaves workspaces unrestricted.
+    roots: Arc<Mutex<Option<Vec<PathBuf>>>>,
+    /// `[limits]` bookkeeping (see [`crate::utils::rate_limit`]).
+    limiter: Arc<Mutex<Limiter>>,
+    /// Per-tool call counts and timings, for `mcp://stats` and OTLP export.
+    metrics: Arc<Mutex<Metrics>>,
+    /// The disabled tools `tools/list` last reflected, to tell when a config
+    /// change or a workspace switch calls for `notifications/tools/list_changed`.
+    listed_disabled: Arc<Mutex<Vec<String>>>,
+    /// Least severe level of the `notifications/message` log lines sent to
+    /// the client. `None` (nothing sent) until it calls `logging/setLevel`.
+    log_level: Arc<Mutex<Option<LoggingLevel>>>,
+    /// Set by [`WinxService::shutdown`]; tool calls are refused from then on.
+    shutting_down: Arc<AtomicBool>,
+    /// Tool calls running right now (see [`InFlightCall`]).
+    in_flight_calls: Arc<AtomicUsize>,
+}
+
+/// Longest [`WinxService::shutdown`] waits for running tool calls.
+pub const SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+
+/// Counts a tool call as running until dropped.
+struct InFlightCall(Arc<AtomicUsize>);
+
+impl InFlightCall {
+    fn enter(count: &Arc<AtomicUsize>) -> Self {
+        count.fetch_add(1, Ordering::SeqCst);
+        Self(count.clone())
+    }
+}
+
+impl Drop for InFlightCall {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Resolves on SIGINT (Ctrl-C) or SIGTERM, naming the signal.
+pub async fn shutdown_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        if let Ok(mut term) = signal(SignalKind::terminate()) {
+            return tokio::select! {
+                _ = tokio::signal::ctrl_c() => "SIGINT",
+                _ = term.recv() => "SIGTERM",
+            };
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+    "SIGINT"
 }
 
 impl Default for WinxService {
@@ -495,9 +943,48 @@ impl WinxService {
             sessions: Arc::new(Mutex::new(SessionRegistry::default())),
             version: env!("CARGO_PKG_VERSION").to_string(),
             isolation,
+            subscriptions: Arc::new(Mutex::new(ResourceSubscriptions::default())),
+            roots: Arc::new(Mutex::new(None)),
+            log_level: Arc::new(Mutex::new(None)),
+            listed_disabled: Arc::new(Mutex::new(Vec::new())),
+            metrics: Arc::new(Mutex::new(Metrics::default())),
+            limiter: Arc::new(Mutex::new(Limiter::default())),
+            shutting_down: Arc::new(AtomicBool::new(false)),
+            in_flight_calls: Arc::new(AtomicUsize::new(0)),
         }
     }
 
+    /// The workspace of an existing, initialized session, looked up without
+    /// creating one (an unknown thread has no workspace).
+    async fn workspace_of(&self, thread_id: &str) -> Option<std::path::PathBuf> {
+        let slot = {
+            let reg = self.sessions.lock().await;
+            let key = match (thread_id.is_empty(), &self.isolation) {
+                (false, _) => thread_id.to_string(),
+                (true, SessionIsolation::Lenient) => {
+                    reg.last_active.clone().unwrap_or_else(|| "default".to_string())
+                }
+                (true, SessionIsolation::Strict) => "anonymous".to_string(),
+            };
+            reg.slots.get(&key)?.clone()
+        };
+        let guard = slot.lock().await;
+        guard.as_ref().filter(|state| state.initialized).map(|state| state.workspace_root.clone())
+    }
+
+    /// Run the `on_error` hooks of the workspace a failed call ran in.
+    async fn error_hooks(&self, thread_id: &str, tool: &str, message: &str) -> Option<String> {
+        let workspace = self.workspace_of(thread_id).await?;
+        let (tool, message) = (tool.to_string(), message.to_string());
+        tokio::task::spawn_blocking(move || {
+            let event = crate::utils::hooks::Event::Error { tool: &tool, message: &message };
+            crate::utils::hooks::run(&workspace, &event).render()
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+
     /// Resolve the session slot for a `thread_id`, creating it if absent.
     ///
     /// An empty `thread_id` resolves, under [`SessionIsolation::Lenient`], to the
@@ -565,18 +1052,195 @@ impl WinxService {
         let reg = self.sessions.lock().await;
         reg.last_active.as_ref().and_then(|key| reg.slots.get(key).cloned())
     }
+
+    /// Tools disabled for the active workspace (see
+    /// [`crate::utils::project_config::disabled_tools`]). `Initialize` never is.
+    async fn disabled_tools(&self) -> Vec<String> {
+        let workspace = self.active_workspace().await;
+        let mut disabled = crate::utils::project_config::disabled_tools(workspace.as_deref());
+        disabled.retain(|tool| tool != "Initialize");
+        disabled.sort();
+        disabled.dedup();
+        disabled
+    }
+
+    /// Send `notifications/tools/list_changed` when the disabled tools differ
+    /// from what the client last listed.
+    async fn notify_if_tools_changed(&self, peer: &Peer<RoleServer>) {
+        let disabled = self.disabled_tools().await;
+        {
+            let mut listed = self.listed_disabled.lock().await;
+            if *listed == disabled {
+                return;
+            }
+            *listed = disabled;
+        }
+        if let Err(e) = peer.notify_tool_list_changed().await {
+            warn!("tools/list_changed notification failed: {e}");
+        }
+    }
+
+    /// Send `data` to the client as an MCP log line, if it asked for `level`.
+    async fn log_to_client(&self, peer: &Peer<RoleServer>, level: LoggingLevel, data: String) {
+        let Some(min) = *self.log_level.lock().await else {
+            return;
+        };
+        if severity(level) < severity(min) {
+            return;
+        }
+        let param = LoggingMessageNotificationParam {
+            level,
+            logger: Some("winx".to_string()),
+            data: Value::String(data),
+        };
+        // Plain notification: `Peer::notify_logging_message` is deprecated
+        // (SEP-2577), but clients still show `notifications/message`.
+        let notification = ServerNotification::LoggingMessageNotification(Notification::new(param));
+        if let Err(e) = peer.send_notification(notification).await {
+            warn!("log notification failed: {e}");
+        }
+    }
+
+    /// Fetch the client's `roots` and close every session whose workspace is no
+    /// longer inside one. Only under [`SessionIsolation::Lenient`]: the HTTP
+    /// transport shares one service between clients, so one client's roots
+    /// must not scope another's sessions.
+    async fn refresh_roots(&self, peer: &Peer<RoleServer>) {
+        if self.isolation == SessionIsolation::Strict
+            || !peer.peer_info().is_some_and(|info| info.capabilities.roots.is_some())
+        {
+            return;
+        }
+        // `Peer::list_roots` is deprecated (SEP-2577), but clients still send
+        // roots, so ask with the plain request.
+        let request = ServerRequest::ListRootsRequest(ListRootsRequest::default());
+        let roots: Vec<PathBuf> = match peer.send_request(request).await {
+            Ok(ClientResult::ListRootsResult(result)) => result
+                .roots
+                .iter()
+                .filter_map(|root| crate::utils::path::path_from_file_uri(&root.uri))
+                .map(|path| path.canonicalize().unwrap_or(path))
+                .collect(),
+            Ok(_) => {
+                warn!("unexpected roots/list response, workspaces stay unrestricted");
+                return;
+            }
+            Err(e) => {
+                warn!("roots/list failed, workspaces stay unrestricted: {e}");
+                return;
+            }
+        };
+        info!(?roots, "client roots");
+        let roots = (!roots.is_empty()).then_some(roots);
+        *self.roots.lock().await = roots.clone();
+        let Some(roots) = roots else {
+            return;
+        };
+        let slots: Vec<SharedBashState> =
+            self.sessions.lock().await.slots.values().cloned().collect();
+        for slot in slots {
+            let mut guard = slot.lock().await;
+            if let Some(workspace) = guard.as_ref().map(|state| state.workspace_root.clone()) {
+                if !within_roots(&workspace, &roots) {
+                    warn!(workspace = %workspace.display(), "closing session outside the roots");
+                    *guard = None;
+                }
+            }
+        }
+    }
+
+    /// The workspace root of the most recently active, initialized session —
+    /// the workspace MCP resources are served from and `tools/list` follows.
+    async fn active_workspace(&self) -> Option<std::path::PathBuf> {
+        let slot = self.active_slot().await?;
+        let guard = slot.lock().await;
+        guard.as_ref().filter(|state| state.initialized).map(|state| state.workspace_root.clone())
+    }
+
+    /// The file a `file://` resource URI names, confined to the resource workspace.
+    async fn resource_path(&self, uri: &str) -> Result<std::path::PathBuf, McpError> {
+        let workspace = self.active_workspace().await.ok_or_else(|| {
+            McpError::invalid_request("No initialized workspace; call Initialize first", None)
+        })?;
+        let path = crate::utils::path::path_from_file_uri(uri).ok_or_else(|| {
+            McpError::invalid_request(format!("Unknown resource URI: {uri}"), None)
+        })?;
+        crate::utils::path::validate_path_in_workspace(&path, &workspace)
+            .map_err(|e| McpError::invalid_request(format!("{uri}: {e}"), None))
+    }
+
+    /// Workspace files as resources, relative paths as names, capped at
+    /// [`MAX_LISTED_RESOURCES`].
+    async fn workspace_resources(&self) -> Vec<Annotated<RawResource>> {
+        let Some(workspace) = self.active_workspace().await else {
+            return Vec::new();
+        };
+        let files = tokio::task::spawn_blocking(move || {
+            let mut files = crate::utils::repo::walk_workspace_files(&workspace);
+            files.sort();
+            files
+                .into_iter()
+                .take(MAX_LISTED_RESOURCES)
+                .map(|path| {
+                    let name = path.strip_prefix(&workspace).unwrap_or(&path).display().to_string();
+                    (crate::utils::path::file_uri(&path), name)
+                })
+                .collect::<Vec<_>>()
+        })
+        .await
+        .unwrap_or_default();
+        files
+            .into_iter()
+            .map(|(uri, name)| Annotated {
+                raw: RawResource {
+                    uri,
+                    name,
+                    description: None,
+                    mime_type: None,
+                    size: None,
+                    title: None,
+                    icons: None,
+                    meta: None,
+                },
+                annotations: None,
+            })
+            .collect()
+    }
+
+    /// Contents of a workspace file resource, redacted and size-capped.
+    async fn read_workspace_resource(&self, uri: &str) -> Result<String, McpError> {
+        let path = self.resource_path(uri).await?;
+        let len = std::fs::metadata(&path)
+            .map_err(|e| McpError::invalid_request(format!("{uri}: {e}"), None))?
+            .len();
+        if len > MAX_RESOURCE_BYTES {
+            return Err(McpError::invalid_request(
+                format!("{uri} is {len} bytes; resources are capped at {MAX_RESOURCE_BYTES}"),
+                None,
+            ));
+        }
+        let (text, _) =
+            tokio::task::spawn_blocking(move || crate::utils::text_encoding::read_text(&path))
+                .await
+                .map_err(|e| McpError::internal_error(e.to_string(), None))?
+                .map_err(|e| McpError::invalid_request(format!("{uri}: {e}"), None))?;
+        Ok(crate::utils::redact::redact(&text).into_owned())
+    }
 }
 
 /// `ServerHandler` implementation
 impl ServerHandler for WinxService {
     fn get_info(&self) -> ServerInfo {
-        ServerInfo::new(
-            ServerCapabilities::builder()
-                .enable_tools()
-                .enable_resources()
-                .enable_prompts()
-                .build(),
-        )
+        let mut capabilities = ServerCapabilities::builder()
+            .enable_tools()
+            .enable_tool_list_changed()
+            .enable_resources()
+            .enable_resources_subscribe()
+            .enable_prompts()
+            .build();
+        // Set directly: the builder's `enable_logging` is deprecated (SEP-2577).
+        capabilities.logging = Some(serde_json::Map::new());
+        ServerInfo::new(capabilities)
         .with_server_info(
             Implementation::new("winx-mcp-server", self.version.clone())
                 .with_title("Winx High-Performance MCP"),
@@ -592,19 +1256,26 @@ impl ServerHandler for WinxService {
 
     async fn list_tools(
         &self,
-        _request: Option<PaginatedRequestParams>,
+        request: Option<PaginatedRequestParams>,
         _context: RequestContext<RoleServer>,
     ) -> Result<ListToolsResult, McpError> {
-        Ok(ListToolsResult { tools: winx_tools(), next_cursor: None, meta: None })
+        let disabled = self.disabled_tools().await;
+        let tools: Vec<Tool> = winx_tools()
+            .into_iter()
+            .filter(|tool| !disabled.iter().any(|name| *name == tool.name))
+            .collect();
+        *self.listed_disabled.lock().await = disabled;
+        let (tools, next_cursor) = paginate(tools, request.as_ref())?;
+        Ok(ListToolsResult { tools, next_cursor, meta: None })
     }
 
     async fn list_resources(
         &self,
-        _param: Option<PaginatedRequestParams>,
+        param: Option<PaginatedRequestParams>,
         _context: RequestContext<RoleServer>,
     ) -> Result<ListResourcesResult, McpError> {
-        Ok(ListResourcesResult {
-            resources: vec![Annotated {
+        let mut resources = vec![
+            Annotated {
                 raw: RawResource {
                     uri: "file://readme".into(),
                     name: "README".into(),
@@ -616,6 +1287,46 @@ impl ServerHandler for WinxService {
                     meta: None,
                 },
                 annotations: None,
+            },
+            Annotated {
+                raw: RawResource {
+                    uri: "mcp://stats".into(),
+                    name: "Stats".into(),
+                    description: Some("Tool calls, errors and timings since startup".into()),
+                    mime_type: Some("text/markdown".into()),
+                    size: None,
+                    title: None,
+                    icons: None,
+                    meta: None,
+                },
+                annotations: None,
+            },
+        ];
+        resources.extend(self.workspace_resources().await);
+        let (resources, next_cursor) = paginate(resources, param.as_ref())?;
+        Ok(ListResourcesResult { resources, next_cursor, meta: None })
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, McpError> {
+        Ok(ListResourceTemplatesResult {
+            resource_templates: vec![Annotated {
+                raw: RawResourceTemplate {
+                    uri_template: "file://{path}".into(),
+                    name: "Workspace file".into(),
+                    title: None,
+                    description: Some(
+                        "Any file in the active workspace, by absolute path (files past the \
+                         resources/list cap included)"
+                            .into(),
+                    ),
+                    mime_type: None,
+                    icons: None,
+                },
+                annotations: None,
             }],
             next_cursor: None,
             meta: None,
@@ -627,7 +1338,19 @@ impl ServerHandler for WinxService {
         _request: Option<PaginatedRequestParams>,
         _context: RequestContext<RoleServer>,
     ) -> Result<ListPromptsResult, McpError> {
-        Ok(ListPromptsResult { prompts: winx_prompts(), next_cursor: None, meta: None })
+        let mut prompts = winx_prompts();
+        for prompt in crate::utils::prompt_library::load() {
+            if prompts.iter().any(|p| p.name == prompt.name) {
+                continue;
+            }
+            let arguments = prompt
+                .args
+                .into_iter()
+                .map(|arg| PromptArgument::new(arg.name).with_required(arg.required))
+                .collect();
+            prompts.push(Prompt::new(prompt.name, prompt.description, Some(arguments)));
+        }
+        Ok(ListPromptsResult { prompts, next_cursor: None, meta: None })
     }
 
     async fn get_prompt(
@@ -636,10 +1359,22 @@ impl ServerHandler for WinxService {
         _context: RequestContext<RoleServer>,
     ) -> Result<GetPromptResult, McpError> {
         if request.name != "KnowledgeTransfer" {
-            return Err(McpError::invalid_request(
-                format!("Unknown prompt: {}", request.name),
-                None,
-            ));
+            // Built-ins shadow library prompts of the same name (see list_prompts).
+            let prompt = crate::utils::prompt_library::load()
+                .into_iter()
+                .find(|prompt| prompt.name == request.name)
+                .ok_or_else(|| {
+                    McpError::invalid_request(format!("Unknown prompt: {}", request.name), None)
+                })?;
+            let text = prompt
+                .render(request.arguments.as_ref())
+                .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
+            let mut result =
+                GetPromptResult::new(vec![PromptMessage::new_text(PromptMessageRole::User, text)]);
+            if let Some(description) = prompt.description {
+                result = result.with_description(description);
+            }
+            return Ok(result);
         }
 
         // This prompt embeds git status/diff and file context, so scrub it too —
@@ -667,23 +1402,90 @@ impl ServerHandler for WinxService {
                     param.uri.clone(),
                 )],
             },
-            _ => {
-                return Err(McpError::invalid_request(
-                    format!("Unknown resource URI: {}", param.uri),
-                    None,
-                ));
+            "mcp://stats" => {
+                vec![ResourceContents::text(self.metrics.lock().await.render(), param.uri.clone())]
             }
+            uri => vec![ResourceContents::text(
+                self.read_workspace_resource(uri).await?,
+                param.uri.clone(),
+            )],
         };
 
         Ok(ReadResourceResult::new(content))
     }
 
+    async fn subscribe(
+        &self,
+        request: SubscribeRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        let path = self.resource_path(&request.uri).await?;
+        let mut subs = self.subscriptions.lock().await;
+        if !subs.peers.contains_key(&request.uri) {
+            subs.files.watch(request.uri.clone(), path);
+        }
+        let peers = subs.peers.entry(request.uri).or_default();
+        if !peers.iter().any(|peer| same_peer(peer, &context.peer)) {
+            peers.push(context.peer);
+        }
+        if !subs.polling {
+            subs.polling = true;
+            spawn_resource_poller(self.subscriptions.clone());
+        }
+        Ok(())
+    }
+
+    async fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        let mut subs = self.subscriptions.lock().await;
+        // Only the caller stops getting updates; the file stays watched while
+        // another client is subscribed to it.
+        if let Some(peers) = subs.peers.get_mut(&request.uri) {
+            peers.retain(|peer| !same_peer(peer, &context.peer));
+            if peers.is_empty() {
+                subs.peers.remove(&request.uri);
+                subs.files.unwatch(&request.uri);
+            }
+        }
+        Ok(())
+    }
+
+    async fn set_level(
+        &self,
+        request: SetLevelRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        *self.log_level.lock().await = Some(request.level);
+        Ok(())
+    }
+
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        let service = self.clone();
+        tokio::spawn(async move { service.refresh_roots(&context.peer).await });
+    }
+
+    async fn on_roots_list_changed(&self, context: NotificationContext<RoleServer>) {
+        let service = self.clone();
+        tokio::spawn(async move { service.refresh_roots(&context.peer).await });
+    }
+
+    #[allow(clippy::too_many_lines)]
     async fn call_tool(
         &self,
         param: CallToolRequestParams,
-        _context: RequestContext<RoleServer>,
+        context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
         let tool = param.name.to_string();
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err(McpError::invalid_request(
+                format!("{tool} refused: winx is shutting down; restart or reconnect the server"),
+                Some(serde_json::json!({ "code": "shutting_down", "kind": "shutting_down" })),
+            ));
+        }
+        let _in_flight = InFlightCall::enter(&self.in_flight_calls);
         let args_value = param.arguments.map(Value::Object);
         // Audit trail: one structured line per tool call, including the outcome
         // and wall-clock. Successes were previously silent — only errors logged —
@@ -693,18 +1495,134 @@ impl ServerHandler for WinxService {
         let summary =
             crate::utils::redact::redact(&audit_summary(&tool, args_value.as_ref())).into_owned();
         let started = std::time::Instant::now();
+        let thread_id = args_value
+            .as_ref()
+            .and_then(|args| args.get("thread_id"))
+            .and_then(Value::as_str)
+            .map(normalize_thread_id)
+            .unwrap_or_default();
+
+        // The workspace's .winx/config.toml over the global one (just the global
+        // one before Initialize): `[tools] disabled` and `[limits]`.
+        let workspace = self.workspace_of(&thread_id).await;
+        let config = crate::utils::project_config::load_or_global(workspace.as_deref());
+        let disabled = tool != "Initialize" && config.tools.disabled.contains(&tool);
+        let admission = if disabled {
+            Ok(None)
+        } else {
+            let bytes = crate::utils::rate_limit::write_payload_bytes(&tool, args_value.as_ref());
+            self.limiter.lock().await.admit(&tool, &thread_id, bytes, &config.limits, started)
+        };
+        // A BashCommand holds its concurrent_commands slot until this drops.
+        let (refusal, _command_permit) = match admission {
+            Ok(permit) => (None, permit),
+            Err(refusal) => (Some(refusal), None),
+        };
+
+        let mutating = winx_tools().iter().any(|t| {
+            t.name == tool.as_str()
+                && t.annotations.as_ref().and_then(|a| a.read_only_hint) != Some(true)
+        });
+        // `--dry-run`: mutating tools are described instead of run, except the
+        // ones that simulate their own writes.
+        let dry_run = mutating
+            && crate::utils::dry_run::enabled()
+            && !crate::utils::dry_run::simulated_by_tool(&tool, args_value.as_ref());
+        // `[audit] enabled`: calls that run a mutating tool go to the audit log,
+        // with the hashes of the files they touch from before and after.
+        let audited = mutating
+            && !disabled
+            && refusal.is_none()
+            && !crate::utils::dry_run::enabled()
+            && crate::utils::project_config::audit_config().enabled;
+        let audited_files: Vec<(PathBuf, Option<String>)> = if audited {
+            crate::utils::audit_log::touched_paths(&tool, args_value.as_ref())
+                .into_iter()
+                .map(|path| {
+                    let path = PathBuf::from(crate::utils::path::expand_user(&path));
+                    let path = match &workspace {
+                        Some(root) if path.is_relative() => root.join(path),
+                        _ => path,
+                    };
+                    let before = crate::utils::audit_log::file_hash(&path);
+                    (path, before)
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
 
-        let result = match tool.as_str() {
-            "Initialize" => self.handle_initialize(args_value).await,
-            "BashCommand" => self.handle_bash_command(args_value).await,
-            "ReadFiles" => self.handle_read_files(args_value).await,
-            "FileWriteOrEdit" => self.handle_file_write_or_edit(args_value).await,
-            "MultiFileEdit" => self.handle_multi_file_edit(args_value).await,
-            "UndoEdit" => self.handle_undo_edit(args_value).await,
-            "ContextSave" => self.handle_context_save(args_value).await,
-            "ReadImage" => self.handle_read_image(args_value).await,
-            "CodeMap" => self.handle_code_map(args_value).await,
-            _ => Err(McpError::invalid_request(format!("Unknown tool: {tool}"), None)),
+        let result = if disabled {
+            Err(McpError::invalid_request(
+                format!("{tool} is disabled by the winx config ([tools] disabled)"),
+                Some(serde_json::json!({ "code": "tool_disabled" })),
+            ))
+        } else if let Some(refusal) = refusal {
+            Err(McpError::invalid_request(
+                format!(
+                    "{tool} refused: {} ([limits] {}); retry in {}s",
+                    refusal.message,
+                    refusal.limit,
+                    refusal.retry_after.as_secs().max(1)
+                ),
+                Some(serde_json::json!({
+                    "code": "rate_limited",
+                    "kind": "rate_limited",
+                    "limit": refusal.limit,
+                    "retry_after_secs": refusal.retry_after.as_secs().max(1),
+                })),
+            ))
+        } else if dry_run {
+            Ok(CallToolResult::success(vec![Content::text(crate::utils::dry_run::describe(
+                &tool,
+                args_value.as_ref(),
+                &summary,
+            ))]))
+        } else {
+            match tool.as_str() {
+                "Initialize" => self.handle_initialize(args_value).await,
+                "BashCommand" => self.handle_bash_command(args_value, &context.peer).await,
+                "ReadFiles" => self.handle_read_files(args_value).await,
+                "FileWriteOrEdit" => self.handle_file_write_or_edit(args_value).await,
+                "MultiFileEdit" => self.handle_multi_file_edit(args_value).await,
+                "UndoEdit" => self.handle_undo_edit(args_value).await,
+                "MoveFile" => self.handle_move_file(args_value).await,
+                "CopyFile" => self.handle_copy_file(args_value).await,
+                "DeleteFile" => self.handle_delete_file(args_value).await,
+                "CreateDirectory" => self.handle_create_directory(args_value).await,
+                "CreateArchive" => self.handle_create_archive(args_value).await,
+                "ExtractArchive" => self.handle_extract_archive(args_value).await,
+                "ContextSave" => self.handle_context_save(args_value).await,
+                "MemoryWrite" => self.handle_memory_write(args_value).await,
+                "MemorySearch" => self.handle_memory_search(args_value).await,
+                "ReadImage" => self.handle_read_image(args_value).await,
+                "CodeMap" => self.handle_code_map(args_value).await,
+                "GetFileChanges" => self.handle_get_file_changes(args_value).await,
+                "RenameSymbol" => self.handle_rename_symbol(args_value).await,
+                "AuditDependencies" => self.handle_audit_dependencies(args_value).await,
+                "ScanTodos" => self.handle_scan_todos(args_value).await,
+                "Scaffold" => self.handle_scaffold(args_value).await,
+                "NotebookEdit" => self.handle_notebook_edit(args_value).await,
+                "ShellEnv" => self.handle_shell_env(args_value).await,
+                "SystemStats" => self.handle_system_stats(args_value).await,
+                "Browser" => self.handle_browser(args_value).await,
+                "GitHub" => self.handle_github(args_value).await,
+                "HttpRequest" => self.handle_http_request(args_value).await,
+                "AuditQuery" => self.handle_audit_query(args_value).await,
+                _ => Err(McpError::invalid_request(format!("Unknown tool: {tool}"), None)),
+            }
+        };
+
+        // The workspace's on_error hooks (see utils::hooks) add their notes to the
+        // error the model sees.
+        let result = match result {
+            Err(mut error) => {
+                if let Some(note) = self.error_hooks(&thread_id, &tool, &error.message).await {
+                    error.message = format!("{}\n\n{note}", error.message).into();
+                }
+                Err(error)
+            }
+            ok => ok,
         };
 
         // Scrub credentials (provider keys, JWTs, PEM blocks, user:pass URLs)
@@ -712,6 +1630,7 @@ impl ServerHandler for WinxService {
         // search results — before it reaches the model. `WINX_NO_REDACT` opts out.
         let result = match result {
             Ok(mut call) => {
+                text_structured_content(&mut call);
                 redact_result(&mut call);
                 Ok(call)
             }
@@ -724,12 +1643,48 @@ impl ServerHandler for WinxService {
             }
         };
 
+        if audited {
+            let files = audited_files
+                .into_iter()
+                .map(|(path, before)| crate::utils::audit_log::FileChange {
+                    after: crate::utils::audit_log::file_hash(&path),
+                    path: path.display().to_string(),
+                    before,
+                })
+                .collect();
+            let outcome =
+                crate::utils::audit_log::append(&tool, &thread_id, &summary, result.is_ok(), files);
+            if let Err(e) = outcome {
+                warn!(tool = %tool, "could not write the audit log: {e}");
+            }
+        }
+
         let ms = started.elapsed().as_millis();
+        // Unknown names are pooled so a client can't grow the table without bound.
+        let known = winx_tools().iter().any(|t| t.name == tool.as_str());
+        let metric = if known { tool.as_str() } else { "(unknown)" };
+        self.metrics.lock().await.record(metric, started.elapsed(), result.is_ok());
+        if let Some(endpoint) = crate::utils::project_config::otlp_endpoint() {
+            crate::utils::metrics::start_export(&self.metrics, endpoint).await;
+        }
         match &result {
             Ok(_) => info!(tool = %tool, ms, "tool call ok — {summary}"),
             // error.message was already redacted above.
             Err(error) => warn!(tool = %tool, ms, "tool call error — {summary}: {}", error.message),
         }
+        // The same audit line, to clients that asked for logs (`logging/setLevel`).
+        let (level, line) = match &result {
+            Ok(_) => (LoggingLevel::Info, format!("{tool} ok in {ms} ms — {summary}")),
+            Err(error) => (
+                LoggingLevel::Error,
+                format!("{tool} failed after {ms} ms — {summary}: {}", error.message),
+            ),
+        };
+        crate::utils::crash_report::record_call(&line);
+        self.log_to_client(&context.peer, level, line).await;
+        // Initialize may have switched workspaces, and configs can change
+        // between calls.
+        self.notify_if_tools_changed(&context.peer).await;
         result
     }
 }
@@ -757,6 +1712,12 @@ fn audit_summary(tool: &str, args: Option<&Value>) -> String {
             }
         }
         "FileWriteOrEdit" | "ReadImage" | "UndoEdit" => format!("path={}", s("file_path")),
+        "MoveFile" | "CopyFile" => format!("src={} dst={}", s("source"), s("destination")),
+        "DeleteFile" | "CreateDirectory" | "AuditDependencies" | "ScanTodos" => {
+            format!("path={}", s("path"))
+        }
+        "CreateArchive" => format!("archive={}", s("archive_path")),
+        "ExtractArchive" => format!("archive={} dst={}", s("archive_path"), s("destination")),
         "MultiFileEdit" => {
             format!("files={}", args.get("files").and_then(Value::as_array).map_or(0, Vec::len))
         }
@@ -768,7 +1729,27 @@ fn audit_summary(tool: &str, args: Option<&Value>) -> String {
         }
         "Initialize" => format!("ws={} mode={}", s("any_workspace_path"), s("mode_name")),
         "ContextSave" => format!("id={}", s("id")),
+        "MemoryWrite" => {
+            let global = args.get("global").and_then(Value::as_bool).unwrap_or(false);
+            format!("global={global} chars={}", s("fact").len())
+        }
+        "MemorySearch" => format!("query={:?}", clip(s("query"))),
         "CodeMap" => format!("op={} path={} name={}", s("operation"), s("path"), s("name")),
+        "RenameSymbol" => {
+            let apply = args.get("apply").and_then(Value::as_bool).unwrap_or(false);
+            format!("{}->{} path={} apply={apply}", s("name"), s("new_name"), s("path"))
+        }
+        "Scaffold" => format!("template={} name={} path={}", s("template"), s("name"), s("path")),
+        "NotebookEdit" => format!("{} {}", s("operation"), s("path")),
+        // Never the value.
+        "ShellEnv" => format!("{} {} dotenv={}", s("operation"), s("name"), s("dotenv")),
+        "HttpRequest" => {
+            format!("{} {} profile={}", s("method"), s("url"), s("profile")).trim().to_string()
+        }
+        "GitHub" => format!("{} {}", s("operation"), s("title")).trim_end().to_string(),
+        "Browser" => {
+            format!("{} {}{}", s("operation"), s("url"), s("selector")).trim_end().to_string()
+        }
         _ => String::new(),
     }
 }
@@ -893,6 +1874,42 @@ impl WinxService {
         text
     }
 
+    /// Stop taking tool calls, wait up to `grace` for the running ones (a
+    /// `BashCommand` waiting on its command included), then save every
+    /// session's state, so a restarted server resumes each `thread_id` where it
+    /// left off.
+    pub async fn shutdown(&self, grace: Duration) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+        let deadline = Instant::now() + grace;
+        while self.in_flight_calls.load(Ordering::SeqCst) > 0 && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        let running = self.in_flight_calls.load(Ordering::SeqCst);
+        if running > 0 {
+            warn!("{running} tool call(s) still running after {grace:?}; not waiting longer");
+        }
+
+        let slots: Vec<SharedBashState> =
+            self.sessions.lock().await.slots.values().cloned().collect();
+        let mut saved = 0;
+        for slot in slots {
+            // A call that outlived the grace period still holds its session;
+            // that session keeps the state it last saved.
+            let Ok(guard) = tokio::time::timeout(Duration::from_millis(500), slot.lock()).await
+            else {
+                warn!("a session is still busy; keeping its last saved state");
+                continue;
+            };
+            if let Some(state) = guard.as_ref() {
+                match state.save_state_to_disk() {
+                    Ok(()) => saved += 1,
+                    Err(error) => warn!("Failed to persist bash state: {}", error),
+                }
+            }
+        }
+        info!("saved {saved} session(s) before shutting down");
+    }
+
     async fn persist_state(&self, slot: &SharedBashState) {
         let guard = slot.lock().await;
         if let Some(state) = guard.as_ref() {
@@ -948,10 +1965,36 @@ impl WinxService {
             thread_id = generate_thread_id();
             initialize.thread_id.clone_from(&thread_id);
         }
+        // With client roots, the workspace defaults to the first root and must
+        // lie inside one of them.
+        let roots = self.roots.lock().await.clone();
+        if let Some(roots) = &roots {
+            if initialize.any_workspace_path.trim().is_empty() {
+                initialize.any_workspace_path = roots[0].display().to_string();
+            }
+        }
         let (slot, _session_guard) = self.session_for(&thread_id).await;
 
         match crate::tools::initialize::handle_tool_call(&slot, initialize).await {
             Ok(result) => {
+                if let Some(roots) = &roots {
+                    let mut guard = slot.lock().await;
+                    let workspace = guard.as_ref().map(|state| state.workspace_root.clone());
+                    if let Some(workspace) = workspace.filter(|ws| !within_roots(ws, roots)) {
+                        *guard = None;
+                        let shared: Vec<String> =
+                            roots.iter().map(|root| root.display().to_string()).collect();
+                        return Err(McpError::invalid_request(
+                            format!(
+                                "{} is outside the directories the client shared ({}); pick a \
+                                 workspace inside one of them",
+                                workspace.display(),
+                                shared.join(", ")
+                            ),
+                            None,
+                        ));
+                    }
+                }
                 self.persist_state(&slot).await;
                 Ok(CallToolResult::success(vec![Content::text(result)]))
             }
@@ -959,7 +2002,11 @@ impl WinxService {
         }
     }
 
-    async fn handle_bash_command(&self, args: Option<Value>) -> Result<CallToolResult, McpError> {
+    async fn handle_bash_command(
+        &self,
+        args: Option<Value>,
+        peer: &Peer<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
         let args = args.ok_or_else(|| McpError::invalid_request("Missing arguments", None))?;
         let bash_command: BashCommand = serde_json::from_value(args).map_err(|e| {
             McpError::invalid_request(
@@ -970,6 +2017,21 @@ impl WinxService {
             )
         })?;
 
+        // Hard-to-undo commands are confirmed with the user when the client
+        // supports elicitation; other clients run them as before.
+        if let BashCommandAction::Command { command, .. } = &bash_command.action_json {
+            if let Some(action) = crate::utils::bash_parser::destructive_action(command) {
+                let message =
+                    format!("The agent wants to run `{command}`, which {action}. Run it?");
+                if confirm_with_user(peer, message).await == Some(false) {
+                    return Err(McpError::invalid_request(
+                        format!("The user declined to run `{command}` ({action})."),
+                        None,
+                    ));
+                }
+            }
+        }
+
         let (slot, _session_guard) =
             self.session_for(&normalize_thread_id(&bash_command.thread_id)).await;
         match crate::tools::bash_command::handle_tool_call(&slot, bash_command).await {
@@ -1053,6 +2115,110 @@ impl WinxService {
         }
     }
 
+    async fn handle_move_file(&self, args: Option<Value>) -> Result<CallToolResult, McpError> {
+        let args = args.ok_or_else(|| McpError::invalid_request("Missing arguments", None))?;
+        let mv: MoveFile = Self::lenient_from_value(args).map_err(|e| {
+            McpError::invalid_request(format!("Invalid MoveFile parameters: {e}"), None)
+        })?;
+
+        let (slot, _session_guard) = self.session_for(&normalize_thread_id(&mv.thread_id)).await;
+        match crate::tools::file_ops::handle_move(&slot, mv).await {
+            Ok(result) => {
+                self.persist_state(&slot).await;
+                Ok(CallToolResult::success(vec![Content::text(result)]))
+            }
+            Err(e) => Err(to_mcp_error("MoveFile", &e)),
+        }
+    }
+
+    async fn handle_copy_file(&self, args: Option<Value>) -> Result<CallToolResult, McpError> {
+        let args = args.ok_or_else(|| McpError::invalid_request("Missing arguments", None))?;
+        let cp: CopyFile = Self::lenient_from_value(args).map_err(|e| {
+            McpError::invalid_request(format!("Invalid CopyFile parameters: {e}"), None)
+        })?;
+
+        let (slot, _session_guard) = self.session_for(&normalize_thread_id(&cp.thread_id)).await;
+        match crate::tools::file_ops::handle_copy(&slot, cp).await {
+            Ok(result) => {
+                self.persist_state(&slot).await;
+                Ok(CallToolResult::success(vec![Content::text(result)]))
+            }
+            Err(e) => Err(to_mcp_error("CopyFile", &e)),
+        }
+    }
+
+    async fn handle_delete_file(&self, args: Option<Value>) -> Result<CallToolResult, McpError> {
+        let args = args.ok_or_else(|| McpError::invalid_request("Missing arguments", None))?;
+        let del: DeleteFile = Self::lenient_from_value(args).map_err(|e| {
+            McpError::invalid_request(format!("Invalid DeleteFile parameters: {e}"), None)
+        })?;
+
+        let (slot, _session_guard) = self.session_for(&normalize_thread_id(&del.thread_id)).await;
+        match crate::tools::file_ops::handle_delete(&slot, del).await {
+            Ok(result) => {
+                self.persist_state(&slot).await;
+                Ok(CallToolResult::success(vec![Content::text(result)]))
+            }
+            Err(e) => Err(to_mcp_error("DeleteFile", &e)),
+        }
+    }
+
+    async fn handle_create_directory(
+        &self,
+        args: Option<Value>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = args.ok_or_else(|| McpError::invalid_request("Missing arguments", None))?;
+        let mkdir: CreateDirectory = Self::lenient_from_value(args).map_err(|e| {
+            McpError::invalid_request(format!("Invalid CreateDirectory parameters: {e}"), None)
+        })?;
+
+        let (slot, _session_guard) = self.session_for(&normalize_thread_id(&mkdir.thread_id)).await;
+        match crate::tools::file_ops::handle_create_directory(&slot, mkdir).await {
+            Ok(result) => {
+                self.persist_state(&slot).await;
+                Ok(CallToolResult::success(vec![Content::text(result)]))
+            }
+            Err(e) => Err(to_mcp_error("CreateDirectory", &e)),
+        }
+    }
+
+    async fn handle_create_archive(&self, args: Option<Value>) -> Result<CallToolResult, McpError> {
+        let args = args.ok_or_else(|| McpError::invalid_request("Missing arguments", None))?;
+        let create: CreateArchive = Self::lenient_from_value(args).map_err(|e| {
+            McpError::invalid_request(format!("Invalid CreateArchive parameters: {e}"), None)
+        })?;
+
+        let (slot, _session_guard) =
+            self.session_for(&normalize_thread_id(&create.thread_id)).await;
+        match crate::tools::archive::handle_create(&slot, create).await {
+            Ok(result) => {
+                self.persist_state(&slot).await;
+                Ok(CallToolResult::success(vec![Content::text(result)]))
+            }
+            Err(e) => Err(to_mcp_error("CreateArchive", &e)),
+        }
+    }
+
+    async fn handle_extract_archive(
+        &self,
+        args: Option<Value>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = args.ok_or_else(|| McpError::invalid_request("Missing arguments", None))?;
+        let extract: ExtractArchive = Self::lenient_from_value(args).map_err(|e| {
+            McpError::invalid_request(format!("Invalid ExtractArchive parameters: {e}"), None)
+        })?;
+
+        let (slot, _session_guard) =
+            self.session_for(&normalize_thread_id(&extract.thread_id)).await;
+        match crate::tools::archive::handle_extract(&slot, extract).await {
+            Ok(result) => {
+                self.persist_state(&slot).await;
+                Ok(CallToolResult::success(vec![Content::text(result)]))
+            }
+            Err(e) => Err(to_mcp_error("ExtractArchive", &e)),
+        }
+    }
+
     async fn handle_context_save(&self, args: Option<Value>) -> Result<CallToolResult, McpError> {
         let args = args.ok_or_else(|| McpError::invalid_request("Missing arguments", None))?;
         let context_save: ContextSave = Self::lenient_from_value(args).map_err(|e| {
@@ -1090,6 +2256,35 @@ impl WinxService {
         }
     }
 
+    async fn handle_memory_write(&self, args: Option<Value>) -> Result<CallToolResult, McpError> {
+        let args = args.ok_or_else(|| McpError::invalid_request("Missing arguments", None))?;
+        let write: MemoryWrite = Self::lenient_from_value(args).map_err(|e| {
+            McpError::invalid_request(format!("Invalid MemoryWrite parameters: {e}"), None)
+        })?;
+
+        // The memory store lives outside the session: no persist_state.
+        let (slot, _session_guard) = self.session_for(&normalize_thread_id(&write.thread_id)).await;
+        match crate::tools::memory::handle_write(&slot, write).await {
+            Ok(result) => Ok(CallToolResult::success(vec![Content::text(result)])),
+            Err(e) => Err(to_mcp_error("MemoryWrite", &e)),
+        }
+    }
+
+    async fn handle_memory_search(&self, args: Option<Value>) -> Result<CallToolResult, McpError> {
+        let args = args.ok_or_else(|| McpError::invalid_request("Missing arguments", None))?;
+        let search: MemorySearch = Self::lenient_from_value(args).map_err(|e| {
+            McpError::invalid_request(format!("Invalid MemorySearch parameters: {e}"), None)
+        })?;
+
+        // The memory store lives outside the session: no persist_state.
+        let (slot, _session_guard) =
+            self.session_for(&normalize_thread_id(&search.thread_id)).await;
+        match crate::tools::memory::handle_search(&slot, search).await {
+            Ok(result) => Ok(CallToolResult::success(vec![Content::text(result)])),
+            Err(e) => Err(to_mcp_error("MemorySearch", &e)),
+        }
+    }
+
     async fn handle_code_map(&self, args: Option<Value>) -> Result<CallToolResult, McpError> {
         let args = args.ok_or_else(|| McpError::invalid_request("Missing arguments", None))?;
         let code_map: CodeMap = Self::lenient_from_value(args).map_err(|e| {
@@ -1108,14 +2303,245 @@ impl WinxService {
             Err(e) => Err(to_mcp_error("CodeMap", &e)),
         }
     }
+
+    async fn handle_get_file_changes(
+        &self,
+        args: Option<Value>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = args.unwrap_or_else(|| Value::Object(serde_json::Map::new()));
+        let get_changes: GetFileChanges = Self::lenient_from_value(args).map_err(|e| {
+            McpError::invalid_request(format!("Invalid GetFileChanges parameters: {e}"), None)
+        })?;
+
+        // The baseline is in-memory session state only: no persist_state.
+        let (slot, _session_guard) =
+            self.session_for(&normalize_thread_id(&get_changes.thread_id)).await;
+        match crate::tools::file_changes::handle_tool_call(&slot, get_changes).await {
+            Ok((text, structured)) => {
+                let mut result = CallToolResult::success(vec![Content::text(text)]);
+                result.structured_content = Some(structured);
+                Ok(result)
+            }
+            Err(e) => Err(to_mcp_error("GetFileChanges", &e)),
+        }
+    }
+
+    async fn handle_rename_symbol(&self, args: Option<Value>) -> Result<CallToolResult, McpError> {
+        let args = args.ok_or_else(|| McpError::invalid_request("Missing arguments", None))?;
+        let rename: RenameSymbol = Self::lenient_from_value(args).map_err(|e| {
+            McpError::invalid_request(format!("Invalid RenameSymbol parameters: {e}"), None)
+        })?;
+
+        let (slot, _session_guard) =
+            self.session_for(&normalize_thread_id(&rename.thread_id)).await;
+        match crate::tools::rename_symbol::handle_tool_call(&slot, rename).await {
+            Ok(result) => {
+                self.persist_state(&slot).await;
+                Ok(CallToolResult::success(vec![Content::text(result)]))
+            }
+            Err(e) => Err(to_mcp_error("RenameSymbol", &e)),
+        }
+    }
+
+    async fn handle_audit_dependencies(
+        &self,
+        args: Option<Value>,
+    ) -> Result<CallToolResult, McpError> {
+        let args = args.unwrap_or_else(|| Value::Object(serde_json::Map::new()));
+        let audit: AuditDependencies = Self::lenient_from_value(args).map_err(|e| {
+            McpError::invalid_request(format!("Invalid AuditDependencies parameters: {e}"), None)
+        })?;
+
+        // Read-only: no persist_state (nothing in the session changes).
+        let (slot, _session_guard) = self.session_for(&normalize_thread_id(&audit.thread_id)).await;
+        match crate::tools::dependency_audit::handle_tool_call(&slot, audit).await {
+            Ok((text, structured)) => {
+                let mut result = CallToolResult::success(vec![Content::text(text)]);
+                result.structured_content = Some(structured);
+                Ok(result)
+            }
+            Err(e) => Err(to_mcp_error("AuditDependencies", &e)),
+        }
+    }
+
+    async fn handle_scan_todos(&self, args: Option<Value>) -> Result<CallToolResult, McpError> {
+        let args = args.unwrap_or_else(|| Value::Object(serde_json::Map::new()));
+        let scan: ScanTodos = Self::lenient_from_value(args).map_err(|e| {
+            McpError::invalid_request(format!("Invalid ScanTodos parameters: {e}"), None)
+        })?;
+
+        // Read-only: no persist_state (nothing in the session changes).
+        let (slot, _session_guard) = self.session_for(&normalize_thread_id(&scan.thread_id)).await;
+        match crate::tools::todo_scan::handle_tool_call(&slot, scan).await {
+            Ok((text, structured)) => {
+                let mut result = CallToolResult::success(vec![Content::text(text)]);
+                result.structured_content = Some(structured);
+                Ok(result)
+            }
+            Err(e) => Err(to_mcp_error("ScanTodos", &e)),
+        }
+    }
+
+    async fn handle_scaffold(&self, args: Option<Value>) -> Result<CallToolResult, McpError> {
+        let args = args.unwrap_or_else(|| Value::Object(serde_json::Map::new()));
+        let scaffold: Scaffold = Self::lenient_from_value(args).map_err(|e| {
+            McpError::invalid_request(format!("Invalid Scaffold parameters: {e}"), None)
+        })?;
+
+        let (slot, _session_guard) =
+            self.session_for(&normalize_thread_id(&scaffold.thread_id)).await;
+        match crate::tools::scaffold::handle_tool_call(&slot, scaffold).await {
+            Ok(result) => {
+                self.persist_state(&slot).await;
+                Ok(CallToolResult::success(vec![Content::text(result)]))
+            }
+            Err(e) => Err(to_mcp_error("Scaffold", &e)),
+        }
+    }
+
+    async fn handle_notebook_edit(&self, args: Option<Value>) -> Result<CallToolResult, McpError> {
+        let args = args.ok_or_else(|| McpError::invalid_request("Missing arguments", None))?;
+        let edit: NotebookEdit = Self::lenient_from_value(args).map_err(|e| {
+            McpError::invalid_request(format!("Invalid NotebookEdit parameters: {e}"), None)
+        })?;
+
+        let (slot, _session_guard) = self.session_for(&normalize_thread_id(&edit.thread_id)).await;
+        match crate::tools::notebook_edit::handle_tool_call(&slot, edit).await {
+            Ok(result) => {
+                self.persist_state(&slot).await;
+                Ok(CallToolResult::success(vec![Content::text(result)]))
+            }
+            Err(e) => Err(to_mcp_error("NotebookEdit", &e)),
+        }
+    }
+
+    async fn handle_shell_env(&self, args: Option<Value>) -> Result<CallToolResult, McpError> {
+        let args = args.ok_or_else(|| McpError::invalid_request("Missing arguments", None))?;
+        let shell_env: ShellEnv = Self::lenient_from_value(args).map_err(|e| {
+            McpError::invalid_request(format!("Invalid ShellEnv parameters: {e}"), None)
+        })?;
+
+        let (slot, _session_guard) =
+            self.session_for(&normalize_thread_id(&shell_env.thread_id)).await;
+        match crate::tools::shell_env::handle_tool_call(&slot, shell_env).await {
+            Ok(result) => {
+                self.persist_state(&slot).await;
+                Ok(CallToolResult::success(vec![Content::text(result)]))
+            }
+            Err(e) => Err(to_mcp_error("ShellEnv", &e)),
+        }
+    }
+
+    async fn handle_system_stats(&self, args: Option<Value>) -> Result<CallToolResult, McpError> {
+        let args = args.unwrap_or_else(|| Value::Object(serde_json::Map::new()));
+        let stats: SystemStats = Self::lenient_from_value(args).map_err(|e| {
+            McpError::invalid_request(format!("Invalid SystemStats parameters: {e}"), None)
+        })?;
+
+        // Read-only: no persist_state (nothing in the session changes).
+        let (slot, _session_guard) = self.session_for(&normalize_thread_id(&stats.thread_id)).await;
+        match crate::tools::system_stats::handle_tool_call(&slot, stats).await {
+            Ok((text, structured)) => {
+                let mut result = CallToolResult::success(vec![Content::text(text)]);
+                result.structured_content = Some(structured);
+                Ok(result)
+            }
+            Err(e) => Err(to_mcp_error("SystemStats", &e)),
+        }
+    }
+
+    async fn handle_audit_query(&self, args: Option<Value>) -> Result<CallToolResult, McpError> {
+        let args = args.unwrap_or_else(|| Value::Object(serde_json::Map::new()));
+        let query: AuditQuery = Self::lenient_from_value(args).map_err(|e| {
+            McpError::invalid_request(format!("Invalid AuditQuery parameters: {e}"), None)
+        })?;
+
+        let filter = crate::utils::audit_log::Filter {
+            tool: query.tool,
+            thread_id: normalize_thread_id(&query.session),
+            path: query.path,
+            since: query.since,
+            failed_only: query.failed_only,
+            limit: query.limit,
+        };
+        let enabled = crate::utils::project_config::audit_config().enabled;
+        // Scanning the (possibly rotated) log files is plain file I/O.
+        let query = tokio::task::spawn_blocking(move || crate::utils::audit_log::query(&filter))
+            .await
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        match query {
+            Ok((log, result)) => Ok(CallToolResult::success(vec![Content::text(
+                crate::utils::audit_log::render(&log, enabled, &result),
+            )])),
+            Err(e) => Err(to_mcp_error("AuditQuery", &e)),
+        }
+    }
+
+    async fn handle_browser(&self, args: Option<Value>) -> Result<CallToolResult, McpError> {
+        let args = args.ok_or_else(|| McpError::invalid_request("Missing arguments", None))?;
+        let browser: Browser = Self::lenient_from_value(args).map_err(|e| {
+            McpError::invalid_request(format!("Invalid Browser parameters: {e}"), None)
+        })?;
+
+        // The browser lives outside the session; nothing to persist.
+        let (slot, _session_guard) =
+            self.session_for(&normalize_thread_id(&browser.thread_id)).await;
+        match crate::tools::browser::handle_tool_call(&slot, browser).await {
+            Ok(BrowserResult::Text(text)) => Ok(CallToolResult::success(vec![Content::text(text)])),
+            Ok(BrowserResult::Screenshot(data)) => {
+                Ok(CallToolResult::success(vec![Content::image(data, "image/png".to_string())]))
+            }
+            Err(e) => Err(to_mcp_error("Browser", &e)),
+        }
+    }
+
+    async fn handle_github(&self, args: Option<Value>) -> Result<CallToolResult, McpError> {
+        let args = args.ok_or_else(|| McpError::invalid_request("Missing arguments", None))?;
+        let github: GitHub = Self::lenient_from_value(args).map_err(|e| {
+            McpError::invalid_request(format!("Invalid GitHub parameters: {e}"), None)
+        })?;
+
+        // Only the remote repository changes; nothing to persist.
+        let (slot, _session_guard) =
+            self.session_for(&normalize_thread_id(&github.thread_id)).await;
+        match crate::tools::github::handle_tool_call(&slot, github).await {
+            Ok(result) => Ok(CallToolResult::success(vec![Content::text(result)])),
+            Err(e) => Err(to_mcp_error("GitHub", &e)),
+        }
+    }
+
+    async fn handle_http_request(&self, args: Option<Value>) -> Result<CallToolResult, McpError> {
+        let args = args.ok_or_else(|| McpError::invalid_request("Missing arguments", None))?;
+        let request: HttpRequest = Self::lenient_from_value(args).map_err(|e| {
+            McpError::invalid_request(format!("Invalid HttpRequest parameters: {e}"), None)
+        })?;
+
+        let (slot, _session_guard) =
+            self.session_for(&normalize_thread_id(&request.thread_id)).await;
+        match crate::tools::http_request::handle_tool_call(&slot, request).await {
+            Ok(result) => Ok(CallToolResult::success(vec![Content::text(result)])),
+            Err(e) => Err(to_mcp_error("HttpRequest", &e)),
+        }
+    }
 }
 
 /// Create and start the Winx MCP server
 pub async fn start_winx_server() -> Result<(), Box<dyn std::error::Error>> {
     info!("Starting Winx MCP Server");
     let service = WinxService::new();
-    let server = service.serve(stdio()).await?;
-    server.waiting().await?;
+    let server = service.clone().serve(stdio()).await?;
+    // The server keeps running while `shutdown` waits for in-flight calls;
+    // it stops when `waiting` is dropped.
+    let waiting = server.waiting();
+    tokio::pin!(waiting);
+    tokio::select! {
+        result = &mut waiting => {
+            result?;
+            return Ok(());
+        }
+        signal = shutdown_signal() => info!("{signal} received, shutting down"),
+    }
+    service.shutdown(SHUTDOWN_GRACE).await;
     Ok(())
 }
 
@@ -1123,6 +2549,13 @@ pub async fn start_winx_server() -> Result<(), Box<dyn std::error::Error>> {
 mod session_registry_tests {
     use super::*;
 
+    #[test]
+    fn roots_match_whole_path_components() {
+        let roots = vec![PathBuf::from("/work/app")];
+        assert!(within_roots(Path::new("/work/app/src"), &roots));
+        assert!(!within_roots(Path::new("/work/application"), &roots));
+    }
+
     #[tokio::test]
     async fn distinct_threads_get_distinct_sessions() {
         let svc = WinxService::new();
@@ -1192,7 +2625,8 @@ mod session_registry_tests {
 
 #[cfg(test)]
 mod schema_tests {
-    use super::{schema_to_input_schema, strip_schema_titles};
+    use super::{schema_to_input_schema, strip_schema_titles, text_structured_content, winx_tools};
+    use rmcp::model::{CallToolResult, Content};
     use serde_json::json;
 
     #[test]
@@ -1249,6 +2683,49 @@ mod schema_tests {
             "mode_name enum not inlined: {blob}"
         );
     }
+
+    #[test]
+    fn every_tool_declares_an_object_output_schema() {
+        for tool in winx_tools() {
+            let schema = tool.output_schema.unwrap_or_default();
+            assert_eq!(schema.get("type"), Some(&json!("object")), "{}", tool.name);
+        }
+    }
+
+    #[test]
+    fn text_results_get_matching_structured_content() {
+        let mut result = CallToolResult::success(vec![Content::text("a"), Content::text("b")]);
+        text_structured_content(&mut result);
+        assert_eq!(result.structured_content, Some(json!({"text": "a\nb"})));
+        let mut error = CallToolResult::error(vec![Content::text("boom")]);
+        text_structured_content(&mut error);
+        assert_eq!(error.structured_content, None);
+    }
+}
+
+#[cfg(test)]
+mod pagination_tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    fn cursor(cursor: &str) -> PaginatedRequestParams {
+        PaginatedRequestParams::default().with_cursor(Some(cursor.to_string()))
+    }
+
+    #[test]
+    fn pages_follow_the_cursor_to_the_end() {
+        let items: Vec<usize> = (0..250).collect();
+        let (first, next) = paginate(items.clone(), None).unwrap();
+        assert_eq!((first.len(), next.as_deref()), (PAGE_SIZE, Some("100")));
+        let (last, next) = paginate(items, Some(&cursor("200"))).unwrap();
+        assert_eq!((last[0], last.len(), next), (200, 50, None));
+    }
+
+    #[test]
+    fn rejects_a_malformed_cursor() {
+        assert!(paginate(vec![1, 2, 3], Some(&cursor("page-two"))).is_err());
+        assert_eq!(paginate(Vec::<u8>::new(), None).unwrap(), (Vec::new(), None));
+    }
 }
 
 #[cfg(test)]
@@ -1286,6 +2763,21 @@ mod error_mapping_tests {
         );
     }
 
+    #[test]
+    fn external_change_carries_structured_data() {
+        let err = to_mcp_error(
+            "FileWriteOrEdit",
+            &WinxError::FileChangedExternally {
+                path: PathBuf::from("/ws/a.rs"),
+                diff: "-old\n+new".into(),
+            },
+        );
+        assert_eq!(err.code, ErrorCode::INVALID_REQUEST);
+        let data = err.data.unwrap_or_default();
+        assert_eq!(data["kind"], "file_changed_externally");
+        assert_eq!(data["path"], "/ws/a.rs");
+    }
+
     #[test]
     fn server_caused_errors_stay_internal_error() {
         assert_eq!(
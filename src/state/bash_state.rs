@@ -17,14 +17,22 @@ use crate::state::pty::PtyShell;
 use crate::types::{
     AllowedCommands, AllowedGlobs, BashCommandMode, BashMode, FileEditMode, Modes, WriteIfEmptyMode,
 };
+use crate::utils::file_watch::WorkspaceSnapshot;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileWhitelistData {
     pub file_hash: String,
     pub line_ranges_read: Vec<(usize, usize)>,
     pub total_lines: usize,
+    /// The content `file_hash` was taken of, kept for small files only so an
+    /// external-change error can show what changed. In-memory only.
+    #[serde(skip)]
+    pub seen_content: Option<Arc<str>>,
 }
 
+/// Keep [`FileWhitelistData::seen_content`] only for files up to this size.
+const SEEN_CONTENT_MAX_BYTES: usize = 256 * 1024;
+
 /// Clamp ranges to `1..=total_lines`, drop inverted/empty ones, and sort. Shared
 /// by the coverage queries so both tolerate overlapping/out-of-bounds ranges.
 fn clamped_sorted(ranges: &[(usize, usize)], total_lines: usize) -> Vec<(usize, usize)> {
@@ -58,11 +66,17 @@ impl FileWhitelistData {
         line_ranges_read: Vec<(usize, usize)>,
         total_lines: usize,
     ) -> Self {
-        let mut data = Self { file_hash, line_ranges_read: Vec::new(), total_lines };
+        let mut data =
+            Self { file_hash, line_ranges_read: Vec::new(), total_lines, seen_content: None };
         data.merge_ranges(line_ranges_read);
         data
     }
 
+    /// Remember `content` (the text `file_hash` covers) if it is small enough.
+    pub fn set_seen_content(&mut self, content: &str) {
+        self.seen_content = (content.len() <= SEEN_CONTENT_MAX_BYTES).then(|| Arc::from(content));
+    }
+
     pub fn is_read_enough(&self) -> bool {
         self.get_percentage_read() >= 99.0
     }
@@ -161,12 +175,18 @@ pub struct BashState {
     pub file_edit_mode: FileEditMode,
     pub write_if_empty_mode: WriteIfEmptyMode,
     pub whitelist_for_overwrite: HashMap<String, FileWhitelistData>,
+    /// `[files] allowed_globs` from the workspace's `.winx/config.toml`, applied
+    /// on top of the mode's globs. Reloaded whenever the workspace changes.
+    pub project_globs: AllowedGlobs,
     pub pty_shell: Arc<Mutex<Option<PtyShell>>>,
     pub initialized: bool,
     /// In-memory ring of recent edit checkpoints for `UndoEdit` (newest at the
     /// back). Deliberately not part of `BashStateSnapshot`: undo is for immediate
     /// mid-session recovery, not across restarts.
     pub edit_checkpoints: VecDeque<EditCheckpoint>,
+    /// Baseline for `GetFileChanges`: the workspace as of the previous call.
+    /// In-memory only, like `edit_checkpoints`.
+    pub file_watch_baseline: Option<WorkspaceSnapshot>,
 }
 
 impl Default for BashState {
@@ -192,9 +212,11 @@ impl BashState {
                 allowed_globs: AllowedGlobs::All("all".to_string()),
             },
             whitelist_for_overwrite: HashMap::new(),
+            project_globs: AllowedGlobs::All("all".to_string()),
             pty_shell: Arc::new(Mutex::new(None)),
             initialized: false,
             edit_checkpoints: VecDeque::new(),
+            file_watch_baseline: None,
         }
     }
 
@@ -245,6 +267,7 @@ impl BashState {
 
     pub fn update_workspace_root(&mut self, path: &Path) -> Result<()> {
         self.workspace_root = path.to_path_buf();
+        self.project_globs = project_globs(path);
         Ok(())
     }
 
@@ -253,11 +276,12 @@ impl BashState {
     }
 
     pub fn is_file_edit_allowed(&self, path: &str) -> bool {
-        self.file_edit_mode.allowed_globs.is_allowed(path)
+        self.file_edit_mode.allowed_globs.is_allowed(path) && self.project_globs.is_allowed(path)
     }
 
     pub fn is_file_write_allowed(&self, path: &str) -> bool {
         self.write_if_empty_mode.allowed_globs.is_allowed(path)
+            && self.project_globs.is_allowed(path)
     }
     pub fn save_state_to_disk(&self) -> Result<()> {
         let snapshot = self.snapshot();
@@ -283,6 +307,7 @@ impl BashState {
 
         self.cwd = PathBuf::from(cwd);
         self.workspace_root = PathBuf::from(root);
+        self.project_globs = project_globs(&self.workspace_root);
         self.mode = mode;
         self.bash_command_mode = bmode;
         self.file_edit_mode = emode;
@@ -318,6 +343,24 @@ impl BashState {
     }
 }
 
+/// The workspace's `[files] allowed_globs`, with relative globs anchored at
+/// `workspace_root` (as for `code_writer` globs). No setting allows everything.
+fn project_globs(workspace_root: &Path) -> AllowedGlobs {
+    let config = crate::utils::project_config::load(workspace_root);
+    let Some(globs) = config.files.allowed_globs else {
+        return AllowedGlobs::All("all".to_string());
+    };
+    let root = workspace_root.to_string_lossy();
+    AllowedGlobs::List(
+        globs
+            .into_iter()
+            .map(
+                |glob| if Path::new(&glob).is_absolute() { glob } else { format!("{root}/{glob}") },
+            )
+            .collect(),
+    )
+}
+
 pub fn generate_thread_id() -> String {
     let mut rng = rand::rng();
     format!("tid_{:x}", rng.random::<u64>())
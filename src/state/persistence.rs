@@ -334,6 +334,7 @@ impl FileWhitelistDataSnapshot {
             file_hash: self.file_hash.clone(),
             line_ranges_read: self.line_ranges_read.clone(),
             total_lines: self.total_lines,
+            seen_content: None,
         }
     }
 }
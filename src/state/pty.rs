@@ -56,7 +56,7 @@ fn attachable_command(restricted_mode: bool) -> (CommandBuilder, Option<String>,
         .unwrap_or_default();
     if !requested.is_empty() && requested != "0" && requested != "false" {
         let session = format!("winx-{}-{}", std::process::id(), timestamp_millis());
-        if requested == "tmux" && command_available("tmux") {
+        if requested == "tmux" && crate::utils::capabilities::available("tmux") {
             let mut cmd = CommandBuilder::new("tmux");
             cmd.args(["new-session", "-A", "-s", &session, "bash"]);
             if restricted_mode {
@@ -64,7 +64,7 @@ fn attachable_command(restricted_mode: bool) -> (CommandBuilder, Option<String>,
             }
             return (cmd, Some(format!("tmux attach -t {session}")), false);
         }
-        if command_available("screen") {
+        if crate::utils::capabilities::available("screen") {
             // Parity with wcgw: ensure a sane ~/.screenrc and reap sessions whose
             // creating winx process has died before spawning a fresh one.
             ensure_screenrc();
@@ -94,7 +94,7 @@ fn attachable_command(restricted_mode: bool) -> (CommandBuilder, Option<String>,
 fn preferred_shell(restricted_mode: bool) -> String {
     if !restricted_mode {
         if let Ok(requested) = std::env::var("WINX_SHELL") {
-            if requested == "zsh" && command_available("zsh") {
+            if requested == "zsh" && crate::utils::capabilities::available("zsh") {
                 return "zsh".to_string();
             }
         }
@@ -102,13 +102,6 @@ fn preferred_shell(restricted_mode: bool) -> String {
     "bash".to_string()
 }
 
-fn command_available(command: &str) -> bool {
-    Command::new("sh")
-        .args(["-c", &format!("command -v {command}")])
-        .output()
-        .is_ok_and(|output| output.status.success())
-}
-
 /// Create `~/.screenrc` with a large scrollback if the user has none, matching
 /// wcgw's `check_if_screen_command_available`. Never overwrites an existing file.
 fn ensure_screenrc() {
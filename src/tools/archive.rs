@@ -0,0 +1,521 @@
+//! Implementation of the `CreateArchive` and `ExtractArchive` tools.
+//!
+//! The format comes from the archive's extension: `.zip`, `.tar`,
+//! `.tar.gz`/`.tgz` or `.tar.zst`/`.tzst`. Both directions shell out (`tar`,
+//! with `zstd` for `.zst`; `zip`/`unzip`) and say which binary is missing
+//! rather than failing opaquely.
+//!
+//! Extraction is staged so a hostile archive can't write outside the
+//! destination: member names are listed and checked first (no absolute paths,
+//! no `..`, no member below one that isn't a directory, which could be a
+//! symlink the archiver writes through), the archive is unpacked into a fresh
+//! directory inside the
+//! destination, every extracted symlink must resolve inside the workspace, and
+//! only then are files moved into place. Any failed check leaves the
+//! destination as it was.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+use crate::errors::{Result, WinxError};
+use crate::state::bash_state::BashState;
+use crate::tools::file_ops::{
+    check_destination, ensure_parent, forget_or_rekey, io_error, resolve_entry, session, Access,
+};
+use crate::types::{CreateArchive, ExtractArchive};
+use crate::utils::capabilities::available;
+use crate::utils::syntax::output_with_timeout;
+
+/// Upper bound for one tar/zip/unzip run.
+const ARCHIVE_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Zip,
+    Tar,
+    TarGz,
+    TarZst,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Option<Self> {
+        const SUFFIXES: [(&str, Format); 6] = [
+            (".zip", Format::Zip),
+            (".tar.gz", Format::TarGz),
+            (".tgz", Format::TarGz),
+            (".tar.zst", Format::TarZst),
+            (".tzst", Format::TarZst),
+            (".tar", Format::Tar),
+        ];
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        SUFFIXES.iter().find(|(suffix, _)| name.ends_with(suffix)).map(|&(_, format)| format)
+    }
+
+    /// tar's compression flag, if any.
+    fn tar_flag(self) -> Option<&'static str> {
+        match self {
+            Self::TarGz => Some("-z"),
+            Self::TarZst => Some("--zstd"),
+            Self::Zip | Self::Tar => None,
+        }
+    }
+
+    /// The binaries needed to create (`false`) or extract (`true`) this format.
+    fn programs(self, extract: bool) -> &'static [&'static str] {
+        match (self, extract) {
+            (Self::Zip, false) => &["zip"],
+            (Self::Zip, true) => &["unzip"],
+            (Self::TarZst, _) => &["tar", "zstd"],
+            (Self::Tar | Self::TarGz, _) => &["tar"],
+        }
+    }
+}
+
+fn format_of(path: &Path, extract: bool) -> Result<Format> {
+    let format = Format::from_path(path).ok_or_else(|| {
+        WinxError::InvalidInput(format!(
+            "{}: unsupported archive type; use .zip, .tar, .tar.gz/.tgz or .tar.zst/.tzst",
+            path.display()
+        ))
+    })?;
+    if let Some(missing) = format.programs(extract).iter().find(|p| !available(p)) {
+        return Err(WinxError::CommandExecutionError(format!(
+            "`{missing}` is not installed, so {} can't be handled",
+            path.display()
+        )));
+    }
+    Ok(format)
+}
+
+/// Run an archiver, turning a non-zero exit or a timeout into an error that
+/// carries its stderr.
+fn run(mut command: Command, what: &str) -> Result<Vec<u8>> {
+    command.env("LC_ALL", "C");
+    let output = output_with_timeout(command, None, ARCHIVE_TIMEOUT).ok_or_else(|| {
+        WinxError::CommandTimeout {
+            command: what.to_string(),
+            timeout_seconds: ARCHIVE_TIMEOUT.as_secs(),
+        }
+    })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(WinxError::CommandExecutionError(format!(
+            "{what} failed ({}): {}",
+            output.status,
+            stderr.trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+/// Reject a member name that would land outside the extraction directory.
+fn check_member(name: &str) -> Result<()> {
+    let escapes = Path::new(name)
+        .components()
+        .any(|c| matches!(c, Component::RootDir | Component::Prefix(_) | Component::ParentDir));
+    if escapes {
+        return Err(WinxError::PathSecurityError {
+            path: PathBuf::from(name),
+            message: "archive member would be written outside the destination; refusing to \
+                      extract"
+                .to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Reject a member listed below another member that isn't a directory. Such a
+/// parent is normally a symlink: unpacking `d -> /outside` and then `d/x` would
+/// write through the link before the extracted symlinks are checked. Both
+/// `tar -t` and `unzip -Z1` list directories with a trailing `/`.
+fn check_parents(names: &[&str]) -> Result<()> {
+    let non_dirs: HashSet<PathBuf> = names
+        .iter()
+        .filter(|name| !name.ends_with('/'))
+        .map(|name| clean(Path::new(name)))
+        .collect();
+    for name in names {
+        let path = clean(Path::new(name));
+        if let Some(parent) = path.ancestors().skip(1).find(|a| non_dirs.contains(*a)) {
+            return Err(WinxError::PathSecurityError {
+                path: path.clone(),
+                message: format!(
+                    "archive member {name} is inside {}, which is not a directory (a symlink?); \
+                     refusing to extract",
+                    parent.display()
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Lexically resolve `.` and `..` (the path may not exist yet).
+fn clean(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::CurDir => {}
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Every non-directory entry under `dir`, as paths relative to it.
+fn walk(dir: &Path, rel: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir.join(rel)).map_err(|e| io_error(dir, "listing", &e))? {
+        let entry = entry.map_err(|e| io_error(dir, "listing", &e))?;
+        let rel = rel.join(entry.file_name());
+        if entry.file_type().is_ok_and(|t| t.is_dir()) {
+            walk(dir, &rel, out)?;
+        } else {
+            out.push(rel);
+        }
+    }
+    Ok(())
+}
+
+/// Run `work` on the session's state on the blocking pool: an archiver may run
+/// for up to [`ARCHIVE_TIMEOUT`]. The guard stays held, so the slot remains
+/// locked while the state is away.
+async fn on_blocking_pool<T: Send + 'static>(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    thread_id: &str,
+    work: impl FnOnce(&mut BashState) -> Result<T> + Send + 'static,
+) -> Result<T> {
+    let mut guard = bash_state_arc.lock().await;
+    session(&mut guard, thread_id)?;
+    let mut state = guard.take().ok_or(WinxError::BashStateNotInitialized)?;
+    let (state, result) = tokio::task::spawn_blocking(move || {
+        let result = work(&mut state);
+        (state, result)
+    })
+    .await
+    .map_err(|e| WinxError::CommandExecutionError(format!("archive task failed: {e}")))?;
+    *guard = Some(state);
+    result
+}
+
+#[instrument(level = "info", skip(bash_state_arc, args))]
+pub async fn handle_create(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    args: CreateArchive,
+) -> Result<String> {
+    let thread_id = args.thread_id.clone();
+    on_blocking_pool(bash_state_arc, &thread_id, move |bash_state| create(bash_state, &args)).await
+}
+
+fn create(bash_state: &mut BashState, args: &CreateArchive) -> Result<String> {
+    let archive = resolve_entry(bash_state, &args.archive_path, &Access::Create)?;
+    let format = format_of(&archive, false)?;
+    if args.paths.is_empty() {
+        return Err(WinxError::InvalidInput("paths is empty".to_string()));
+    }
+
+    let cwd = bash_state.cwd.canonicalize().unwrap_or_else(|_| bash_state.cwd.clone());
+    let mut members = Vec::new();
+    for raw in &args.paths {
+        let path = resolve_entry(bash_state, raw, &Access::Read)?;
+        if fs::symlink_metadata(&path).is_err() {
+            let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+            return Err(io_error(&path, "archiving", &missing));
+        }
+        let rel = path.strip_prefix(&cwd).map_err(|_| {
+            WinxError::InvalidInput(format!(
+                "{} is not under the working directory {}; members are stored relative to it, \
+                 so cd to a common parent first",
+                path.display(),
+                cwd.display()
+            ))
+        })?;
+        // Never let a member name be read as an option.
+        let rel = if rel.to_string_lossy().starts_with('-') {
+            Path::new(".").join(rel)
+        } else {
+            rel.to_path_buf()
+        };
+        members.push(if rel.as_os_str().is_empty() { PathBuf::from(".") } else { rel });
+    }
+
+    check_destination(&archive, args.overwrite)?;
+    ensure_parent(&archive)?;
+    if archive.exists() {
+        // zip would otherwise add to the old archive rather than replace it.
+        fs::remove_file(&archive).map_err(|e| io_error(&archive, "replacing", &e))?;
+    }
+    let mut command = if format == Format::Zip {
+        let mut command = Command::new("zip");
+        command.args(["-r", "-y", "-q"]).arg(&archive);
+        command
+    } else {
+        let mut command = Command::new("tar");
+        command.arg("-c").args(format.tar_flag()).arg("-f").arg(&archive).arg("--");
+        command
+    };
+    command.args(&members).current_dir(&cwd);
+    run(command, "creating the archive")?;
+    forget_or_rekey(bash_state, &archive, None);
+
+    let size = fs::metadata(&archive).map_or(0, |m| m.len());
+    Ok(format!("Created {} ({size} bytes) from {} path(s)", archive.display(), members.len()))
+}
+
+#[instrument(level = "info", skip(bash_state_arc, args))]
+pub async fn handle_extract(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    args: ExtractArchive,
+) -> Result<String> {
+    let thread_id = args.thread_id.clone();
+    on_blocking_pool(bash_state_arc, &thread_id, move |bash_state| extract(bash_state, &args)).await
+}
+
+fn extract(bash_state: &mut BashState, args: &ExtractArchive) -> Result<String> {
+    let archive = resolve_entry(bash_state, &args.archive_path, &Access::Read)?;
+    let format = format_of(&archive, true)?;
+    let destination = resolve_entry(bash_state, &args.destination, &Access::Create)?;
+    if fs::symlink_metadata(&destination).is_ok_and(|meta| !meta.is_dir()) {
+        return Err(WinxError::FileAccessError {
+            path: destination,
+            message: "destination exists and is not a directory".to_string(),
+        });
+    }
+
+    // 1. Check every member name before anything is written.
+    let mut list = if format == Format::Zip {
+        let mut command = Command::new("unzip");
+        command.arg("-Z1").arg(&archive);
+        command
+    } else {
+        let mut command = Command::new("tar");
+        command.arg("-t").args(format.tar_flag()).arg("-f").arg(&archive);
+        command
+    };
+    list.current_dir(&bash_state.workspace_root);
+    let listing = run(list, "listing the archive")?;
+    let listing = String::from_utf8_lossy(&listing);
+    let names: Vec<&str> = listing.lines().filter(|l| !l.is_empty()).collect();
+    for name in &names {
+        check_member(name)?;
+    }
+    check_parents(&names)?;
+
+    // 2. Unpack into a staging directory inside the destination (same
+    //    filesystem, so the final moves are renames).
+    fs::create_dir_all(&destination).map_err(|e| io_error(&destination, "creating", &e))?;
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis());
+    let staging = destination.join(format!(".winx-extract-{}-{stamp}", std::process::id()));
+    fs::create_dir(&staging).map_err(|e| io_error(&staging, "creating", &e))?;
+    let result = unpack_and_place(bash_state, &archive, format, &staging, &destination, args);
+    let _ = fs::remove_dir_all(&staging);
+    let placed = result?;
+
+    Ok(format!(
+        "Extracted {placed} file(s) from {} into {}",
+        archive.display(),
+        destination.display()
+    ))
+}
+
+fn unpack_and_place(
+    bash_state: &mut BashState,
+    archive: &Path,
+    format: Format,
+    staging: &Path,
+    destination: &Path,
+    args: &ExtractArchive,
+) -> Result<usize> {
+    let mut unpack = if format == Format::Zip {
+        let mut command = Command::new("unzip");
+        command.args(["-q", "-o"]).arg(archive).arg("-d").arg(staging);
+        command
+    } else {
+        let mut command = Command::new("tar");
+        command.arg("-x").args(format.tar_flag()).arg("-f").arg(archive);
+        command.arg("--no-same-owner").arg("-C").arg(staging);
+        command
+    };
+    unpack.current_dir(staging);
+    run(unpack, "extracting the archive")?;
+
+    // 3. Symlinks must stay inside the workspace once in their final place.
+    let mut files = Vec::new();
+    walk(staging, Path::new(""), &mut files)?;
+    let root = bash_state.workspace_root.canonicalize().unwrap_or_default();
+    for rel in &files {
+        let Ok(target) = fs::read_link(staging.join(rel)) else { continue };
+        let from = destination.join(rel);
+        let resolved = clean(&from.parent().unwrap_or(destination).join(&target));
+        if !resolved.starts_with(&root) {
+            return Err(WinxError::PathSecurityError {
+                path: from,
+                message: format!(
+                    "archive symlink points outside the workspace (-> {}); refusing to extract",
+                    target.display()
+                ),
+            });
+        }
+    }
+
+    // 4. Check every final path, then move into place.
+    let mut conflicts = Vec::new();
+    for rel in &files {
+        let target = destination.join(rel);
+        if !bash_state.is_file_write_allowed(&target.to_string_lossy()) {
+            return Err(WinxError::FileAccessError {
+                path: target,
+                message:
+                    "File operation not allowed by the current mode or .winx/config.toml [files]."
+                        .to_string(),
+            });
+        }
+        crate::utils::protected_paths::check_path(&target, &bash_state.workspace_root)?;
+        if let Ok(meta) = fs::symlink_metadata(&target) {
+            if meta.is_dir() || !args.overwrite {
+                conflicts.push(rel.display().to_string());
+            }
+        }
+    }
+    if !conflicts.is_empty() {
+        let shown = conflicts.iter().take(5).cloned().collect::<Vec<_>>().join(", ");
+        let more = conflicts.len().saturating_sub(5);
+        return Err(WinxError::FileAccessError {
+            path: destination.to_path_buf(),
+            message: format!(
+                "{} file(s) already exist in the destination ({shown}{}); pass overwrite=true \
+                 to replace them",
+                conflicts.len(),
+                if more > 0 { format!(", and {more} more") } else { String::new() }
+            ),
+        });
+    }
+    for rel in &files {
+        let target = destination.join(rel);
+        ensure_parent(&target)?;
+        fs::rename(staging.join(rel), &target).map_err(|e| io_error(&target, "placing", &e))?;
+        forget_or_rekey(bash_state, &target, None);
+    }
+    Ok(files.len())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn format_follows_the_extension() {
+        assert_eq!(Format::from_path(Path::new("a/b.ZIP")), Some(Format::Zip));
+        assert_eq!(Format::from_path(Path::new("dist.tgz")), Some(Format::TarGz));
+        assert_eq!(Format::from_path(Path::new("dist.tar.zst")), Some(Format::TarZst));
+        assert_eq!(Format::from_path(Path::new("dist.tar")), Some(Format::Tar));
+        assert_eq!(Format::from_path(Path::new("dist.rar")), None);
+    }
+
+    #[test]
+    fn traversal_members_are_refused() {
+        assert!(check_member("pkg/lib/index.js").is_ok());
+        assert!(check_member("./pkg/").is_ok());
+        assert!(check_member("../outside").is_err());
+        assert!(check_member("pkg/../../outside").is_err());
+        assert!(check_member("/etc/passwd").is_err());
+    }
+
+    #[test]
+    fn members_below_a_non_directory_are_refused() {
+        assert!(check_parents(&["dist/", "dist/a.txt", "./dist/sub/", "dist/sub/b.txt"]).is_ok());
+        assert!(check_parents(&["link", "link/x"]).is_err());
+        assert!(check_parents(&["./link", "link/sub/x"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn symlink_then_member_through_it_is_refused() {
+        if !available("tar") || !is_gnu_tar() {
+            return;
+        }
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let outside = TempDir::new().unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.join("d")).unwrap();
+        fs::write(root.join("x"), "evil").unwrap();
+        let tar = |args: &[&str]| {
+            let status = Command::new("tar").args(args).current_dir(&root).status().unwrap();
+            assert!(status.success());
+        };
+        tar(&["-cf", "evil.tar", "d"]);
+        tar(&["-rf", "evil.tar", "--transform", "s,^x$,d/x,", "x"]);
+        fs::remove_file(root.join("d")).unwrap();
+
+        let mut bs = BashState::new();
+        bs.cwd = root.clone();
+        bs.workspace_root = root.clone();
+        bs.current_thread_id = "t".to_string();
+        let st = Arc::new(Mutex::new(Some(bs)));
+        let extract = ExtractArchive {
+            archive_path: "evil.tar".into(),
+            destination: "out".into(),
+            overwrite: false,
+            thread_id: "t".into(),
+        };
+        // Refused from the listing, before the archiver runs at all.
+        let error = handle_extract(&st, extract).await.unwrap_err().to_string();
+        assert!(error.contains("not a directory"), "got {error}");
+        assert!(!outside.path().join("x").exists(), "nothing written through the link");
+        assert!(!root.join("out").exists());
+    }
+
+    fn is_gnu_tar() -> bool {
+        Command::new("tar")
+            .arg("--version")
+            .output()
+            .is_ok_and(|out| String::from_utf8_lossy(&out.stdout).contains("GNU tar"))
+    }
+
+    #[tokio::test]
+    async fn tar_gz_round_trip_refuses_to_clobber() {
+        if !available("tar") {
+            return;
+        }
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("dist/sub")).unwrap();
+        fs::write(root.join("dist/a.txt"), "a").unwrap();
+        fs::write(root.join("dist/sub/b.txt"), "b").unwrap();
+        let mut bs = BashState::new();
+        bs.cwd = root.clone();
+        bs.workspace_root = root.clone();
+        bs.current_thread_id = "t".to_string();
+        let st = Arc::new(Mutex::new(Some(bs)));
+
+        let create = CreateArchive {
+            archive_path: "out.tar.gz".into(),
+            paths: vec!["dist".into()],
+            overwrite: false,
+            thread_id: "t".into(),
+        };
+        handle_create(&st, create).await.unwrap();
+
+        let extract = ExtractArchive {
+            archive_path: "out.tar.gz".into(),
+            destination: "unpacked".into(),
+            overwrite: false,
+            thread_id: "t".into(),
+        };
+        handle_extract(&st, extract.clone()).await.unwrap();
+        assert_eq!(fs::read_to_string(root.join("unpacked/dist/sub/b.txt")).unwrap(), "b");
+        assert!(handle_extract(&st, extract).await.is_err(), "existing files need overwrite");
+        let leftovers: Vec<_> = fs::read_dir(root.join("unpacked")).unwrap().collect();
+        assert_eq!(leftovers.len(), 1, "staging directory is cleaned up");
+    }
+}
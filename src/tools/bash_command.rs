@@ -778,6 +778,24 @@ async fn execute_command(
         let allow_shell_probe = matches!(bash_state.mode, crate::types::Modes::Wcgw);
         crate::utils::bash_parser::assert_single_statement(command, allow_shell_probe)?;
     }
+    crate::utils::protected_paths::check_command(
+        command,
+        &bash_state.cwd,
+        &bash_state.workspace_root,
+    )?;
+    // Hook scripts can run for up to their timeout: keep them off the async
+    // workers.
+    let hooks = {
+        let (workspace, command) = (bash_state.workspace_root.clone(), command.to_string());
+        tokio::task::spawn_blocking(move || {
+            crate::utils::hooks::run(&workspace, &crate::utils::hooks::Event::PreCommand(&command))
+        })
+        .await
+        .unwrap_or_default()
+    };
+    if let Some(reason) = hooks.blocked {
+        return Err(WinxError::CommandNotAllowed(reason));
+    }
 
     // If background execution requested, start new shell - matches WCGW Python is_background handling
     if is_background {
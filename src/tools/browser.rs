@@ -0,0 +1,731 @@
+//! Implementation of the `Browser` tool: navigate, screenshot, read the rendered
+//! text, click and evaluate JavaScript in a headless Chromium, for debugging web
+//! apps and end-to-end checks without leaving the agent.
+//!
+//! One browser is launched on first use (`WINX_CHROME`, else the first of
+//! `chromium`, `google-chrome`, ... on `PATH`) with a throwaway profile and the
+//! `DevTools` port on loopback, and is reused by later calls until `close`. It is
+//! driven over the Chrome `DevTools` Protocol (CDP): JSON messages on a WebSocket,
+//! for which the small client below speaks just enough of RFC 6455 to talk to a
+//! local `DevTools` endpoint. Only http(s) pages on public hosts are loaded
+//! (`WINX_PRIVATE_URLS=1` allows local ones; only the URL given to `navigate` is
+//! checked, not redirects, subresources or pages a script opens), a restricted mode
+//! has to list the browser binary among its allowed commands, and as root the
+//! browser (which then runs unsandboxed) only starts with
+//! `WINX_BROWSER_NO_SANDBOX=1`.
+
+use std::fmt::Write as FmtWrite;
+use std::process::Stdio;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use base64::Engine;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::TcpStream;
+use tokio::process::{Child, Command};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::instrument;
+
+use crate::errors::{Result, WinxError};
+use crate::state::bash_state::BashState;
+use crate::types::{Browser, BrowserOperation};
+
+/// Browser binaries tried, in order, when `WINX_CHROME` is unset.
+const BROWSER_CANDIDATES: &[&str] =
+    &["chromium", "chromium-browser", "google-chrome", "google-chrome-stable", "chrome"];
+/// Set to `1` to allow launching Chromium without its sandbox when running as
+/// root.
+const NO_SANDBOX_ENV: &str = "WINX_BROWSER_NO_SANDBOX";
+const LAUNCH_TIMEOUT_SECS: u64 = 20;
+const CALL_TIMEOUT_SECS: u64 = 30;
+const LOAD_TIMEOUT_SECS: u64 = 30;
+/// How long a click is watched for starting a navigation.
+const CLICK_SETTLE_MS: u64 = 500;
+/// Page text and `eval` results are clipped to this many characters.
+const MAX_TEXT_CHARS: usize = 50_000;
+/// Largest WebSocket message accepted (screenshots are the big ones).
+const MAX_MESSAGE_BYTES: usize = 64 * 1024 * 1024;
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+/// Unclaimed CDP events kept for `wait_for`.
+const MAX_PENDING_EVENTS: usize = 256;
+
+const OP_CONTINUATION: u8 = 0x0;
+const OP_TEXT: u8 = 0x1;
+const OP_BINARY: u8 = 0x2;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xA;
+
+/// What a `Browser` call produced.
+pub enum BrowserResult {
+    Text(String),
+    /// Base64 PNG.
+    Screenshot(String),
+}
+
+fn browser_error(message: impl std::fmt::Display) -> WinxError {
+    WinxError::CommandExecutionError(format!("Browser: {message}"))
+}
+
+/// A final, masked client frame.
+fn encode_frame(opcode: u8, payload: &[u8], mask: [u8; 4]) -> Vec<u8> {
+    let len = payload.len();
+    let mut frame = vec![0x80 | opcode];
+    match (u8::try_from(len), u16::try_from(len)) {
+        (Ok(short), _) if short < 126 => frame.push(0x80 | short),
+        (_, Ok(medium)) => {
+            frame.push(0x80 | 0x7e);
+            frame.extend_from_slice(&medium.to_be_bytes());
+        }
+        _ => {
+            frame.push(0x80 | 0x7f);
+            frame.extend_from_slice(&u64::try_from(len).unwrap_or(u64::MAX).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(&mask);
+    frame.extend(payload.iter().enumerate().map(|(i, byte)| byte ^ mask[i % 4]));
+    frame
+}
+
+fn new_mask() -> [u8; 4] {
+    rand::random::<u32>().to_be_bytes()
+}
+
+/// One frame as `(fin, opcode, unmasked payload)`.
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Result<(bool, u8, Vec<u8>)> {
+    let mut head = [0u8; 2];
+    reader.read_exact(&mut head).await?;
+    let len = match head[1] & 0x7f {
+        126 => u64::from(reader.read_u16().await?),
+        127 => reader.read_u64().await?,
+        len => u64::from(len),
+    };
+    let len = usize::try_from(len)
+        .ok()
+        .filter(|&len| len <= MAX_MESSAGE_BYTES)
+        .ok_or_else(|| browser_error("WebSocket frame too large"))?;
+    let mut mask = None;
+    if head[1] & 0x80 != 0 {
+        let mut key = [0u8; 4];
+        reader.read_exact(&mut key).await?;
+        mask = Some(key);
+    }
+    let mut payload = vec![0; len];
+    reader.read_exact(&mut payload).await?;
+    if let Some(mask) = mask {
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+    }
+    Ok((head[0] & 0x80 != 0, head[0] & 0x0f, payload))
+}
+
+/// Forward every JSON message on the socket to `messages`, answering pings,
+/// until the connection closes. Runs as its own task so a caller's timeout
+/// can never cut a frame in half.
+async fn read_messages<R: AsyncRead + Unpin, W: AsyncWrite + Unpin>(
+    mut reader: R,
+    writer: Arc<Mutex<W>>,
+    messages: mpsc::UnboundedSender<Value>,
+) {
+    let mut message = Vec::new();
+    while let Ok((fin, opcode, payload)) = read_frame(&mut reader).await {
+        match opcode {
+            OP_PING => {
+                let pong = encode_frame(OP_PONG, &payload, new_mask());
+                if writer.lock().await.write_all(&pong).await.is_err() {
+                    break;
+                }
+            }
+            OP_CLOSE => break,
+            OP_TEXT | OP_BINARY | OP_CONTINUATION => {
+                message.extend_from_slice(&payload);
+                if message.len() > MAX_MESSAGE_BYTES {
+                    break;
+                }
+                if fin {
+                    if let Ok(value) = serde_json::from_slice(&message) {
+                        if messages.send(value).is_err() {
+                            break;
+                        }
+                    }
+                    message.clear();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `(host:port, path)` of a `ws://` URL.
+fn split_ws_url(url: &str) -> Option<(&str, &str)> {
+    let rest = url.strip_prefix("ws://")?;
+    let slash = rest.find('/')?;
+    Some((&rest[..slash], &rest[slash..]))
+}
+
+/// An HTTP response head, read byte by byte so nothing past it is consumed.
+async fn read_head(stream: &mut TcpStream) -> Result<String> {
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() > MAX_HEAD_BYTES {
+            return Err(browser_error("oversized HTTP response head"));
+        }
+        head.push(stream.read_u8().await?);
+    }
+    Ok(String::from_utf8_lossy(&head).into_owned())
+}
+
+fn status_code(head: &str) -> Option<&str> {
+    head.lines().next()?.split_whitespace().nth(1)
+}
+
+fn content_length(head: &str) -> Option<usize> {
+    head.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        if name.trim().eq_ignore_ascii_case("content-length") {
+            value.trim().parse().ok()
+        } else {
+            None
+        }
+    })
+}
+
+/// A JSON document from the `DevTools` HTTP endpoint at `host`.
+async fn http_json(host: &str, method: &str, path: &str) -> Result<Value> {
+    let mut stream = TcpStream::connect(host).await?;
+    let request = format!("{method} {path} HTTP/1.1\r\nHost: {host}\r\n\r\n");
+    stream.write_all(request.as_bytes()).await?;
+    let head = read_head(&mut stream).await?;
+    if status_code(&head) != Some("200") {
+        return Err(browser_error(format!(
+            "{method} {path} failed: {}",
+            head.lines().next().unwrap_or("")
+        )));
+    }
+    let length = content_length(&head)
+        .filter(|&length| length <= MAX_MESSAGE_BYTES)
+        .ok_or_else(|| browser_error("DevTools response without a usable Content-Length"))?;
+    let mut body = vec![0; length];
+    stream.read_exact(&mut body).await?;
+    serde_json::from_slice(&body)
+        .map_err(|e| browser_error(format!("unexpected DevTools response: {e}")))
+}
+
+/// A CDP connection to one target.
+struct Cdp {
+    writer: Arc<Mutex<OwnedWriteHalf>>,
+    messages: mpsc::UnboundedReceiver<Value>,
+    reader: JoinHandle<()>,
+    next_id: u64,
+    /// Names of events received while waiting for replies, oldest first.
+    events: Vec<String>,
+}
+
+impl Drop for Cdp {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+impl Cdp {
+    async fn connect(url: &str) -> Result<Self> {
+        let (host, path) =
+            split_ws_url(url).ok_or_else(|| browser_error(format!("bad DevTools URL {url}")))?;
+        let mut stream = TcpStream::connect(host).await?;
+        let key =
+            base64::engine::general_purpose::STANDARD.encode(rand::random::<u128>().to_be_bytes());
+        let request = format!(
+            "GET {path} HTTP/1.1\r\nHost: {host}\r\nUpgrade: websocket\r\nConnection: \
+             Upgrade\r\nSec-WebSocket-Key: {key}\r\nSec-WebSocket-Version: 13\r\n\r\n"
+        );
+        stream.write_all(request.as_bytes()).await?;
+        let head = read_head(&mut stream).await?;
+        if status_code(&head) != Some("101") {
+            return Err(browser_error(format!(
+                "WebSocket handshake failed: {}",
+                head.lines().next().unwrap_or("")
+            )));
+        }
+        let (read_half, write_half) = stream.into_split();
+        let writer = Arc::new(Mutex::new(write_half));
+        let (sender, messages) = mpsc::unbounded_channel();
+        let reader =
+            tokio::spawn(read_messages(BufReader::new(read_half), Arc::clone(&writer), sender));
+        Ok(Self { writer, messages, reader, next_id: 0, events: Vec::new() })
+    }
+
+    fn record(&mut self, message: &Value) {
+        if let Some(event) = message["method"].as_str() {
+            if self.events.len() >= MAX_PENDING_EVENTS {
+                self.events.remove(0);
+            }
+            self.events.push(event.to_string());
+        }
+    }
+
+    /// Send `method` and wait for its result.
+    async fn call(&mut self, method: &str, params: Value) -> Result<Value> {
+        self.next_id += 1;
+        let id = self.next_id;
+        let request = json!({ "id": id, "method": method, "params": params }).to_string();
+        let frame = encode_frame(OP_TEXT, request.as_bytes(), new_mask());
+        self.writer.lock().await.write_all(&frame).await?;
+        let deadline = Instant::now() + Duration::from_secs(CALL_TIMEOUT_SECS);
+        loop {
+            let message = tokio::time::timeout_at(deadline, self.messages.recv())
+                .await
+                .map_err(|_| browser_error(format!("{method} timed out")))?
+                .ok_or_else(|| browser_error("the browser disconnected"))?;
+            if message["id"].as_u64() != Some(id) {
+                self.record(&message);
+                continue;
+            }
+            if let Some(error) = message.get("error") {
+                let text = error["message"].as_str().unwrap_or("failed");
+                return Err(browser_error(format!("{method}: {text}")));
+            }
+            return Ok(message.get("result").cloned().unwrap_or(Value::Null));
+        }
+    }
+
+    /// Wait up to `timeout` for `event`, counting one that arrived during an
+    /// earlier call. Consumes it and everything before it.
+    async fn wait_for(&mut self, event: &str, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(index) = self.events.iter().position(|e| e == event) {
+                self.events.drain(..=index);
+                return true;
+            }
+            match tokio::time::timeout_at(deadline, self.messages.recv()).await {
+                Ok(Some(message)) => self.record(&message),
+                _ => return false,
+            }
+        }
+    }
+
+    /// Evaluate `expression` in the page, awaiting promises, and return its
+    /// JSON value (`Null` for `undefined`).
+    async fn evaluate(&mut self, expression: &str) -> Result<Value> {
+        let params =
+            json!({ "expression": expression, "returnByValue": true, "awaitPromise": true });
+        let reply = self.call("Runtime.evaluate", params).await?;
+        if let Some(details) = reply.get("exceptionDetails") {
+            let message = details["exception"]["description"]
+                .as_str()
+                .or_else(|| details["text"].as_str())
+                .unwrap_or("uncaught exception");
+            return Err(browser_error(format!("JavaScript error: {message}")));
+        }
+        Ok(reply["result"]["value"].clone())
+    }
+}
+
+/// A running browser with a CDP connection to its page.
+struct Chromium {
+    child: Child,
+    profile: tempfile::TempDir,
+    page: Cdp,
+}
+
+impl Chromium {
+    fn is_alive(&mut self) -> bool {
+        !self.page.reader.is_finished() && matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// Kill the browser and wait for it, so its profile can be removed.
+    async fn shutdown(mut self) {
+        let _ = self.child.kill().await;
+        let _ = self.profile.close();
+    }
+}
+
+fn find_browser() -> Option<String> {
+    if let Some(program) = std::env::var("WINX_CHROME").ok().filter(|p| !p.trim().is_empty()) {
+        return Some(program);
+    }
+    BROWSER_CANDIDATES
+        .iter()
+        .find(|program| crate::utils::capabilities::available(program))
+        .map(ToString::to_string)
+}
+
+/// The `ws://` URL from Chromium's `DevTools listening on ...` stderr line.
+fn devtools_url(line: &str) -> Option<String> {
+    let (_, url) = line.split_once("DevTools listening on ")?;
+    let url = url.trim();
+    url.starts_with("ws://").then(|| url.to_string())
+}
+
+fn first_page(targets: &Value) -> Option<String> {
+    let page = targets.as_array()?.iter().find(|target| target["type"] == "page")?;
+    page["webSocketDebuggerUrl"].as_str().map(ToString::to_string)
+}
+
+/// The WebSocket URL of the browser's first page, opening one if it has none.
+async fn page_target(host: &str) -> Result<String> {
+    if let Some(url) = first_page(&http_json(host, "GET", "/json/list").await?) {
+        return Ok(url);
+    }
+    let target = http_json(host, "PUT", "/json/new?about:blank").await?;
+    target["webSocketDebuggerUrl"]
+        .as_str()
+        .map(ToString::to_string)
+        .ok_or_else(|| browser_error("could not open a page"))
+}
+
+#[cfg(unix)]
+fn is_root(path: &std::path::Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    path.metadata().is_ok_and(|meta| meta.uid() == 0)
+}
+
+#[cfg(not(unix))]
+fn is_root(_path: &std::path::Path) -> bool {
+    false
+}
+
+async fn launch(program: &str) -> Result<Chromium> {
+    let profile = tempfile::Builder::new().prefix("winx-browser-").tempdir()?;
+    let mut command = Command::new(program);
+    command
+        .args([
+            "--headless=new",
+            "--remote-debugging-port=0",
+            "--no-first-run",
+            "--no-default-browser-check",
+            "--window-size=1280,800",
+        ])
+        .arg(format!("--user-data-dir={}", profile.path().display()))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    // Chromium refuses to start its sandbox as root, the usual case in containers.
+    // Without it a page that exploits the renderer runs as root, and `eval` hands
+    // pages arbitrary script, so that takes an explicit opt-in. The profile
+    // directory was just created by this process, so its owner is us.
+    if is_root(profile.path()) {
+        if std::env::var(NO_SANDBOX_ENV).as_deref() != Ok("1") {
+            return Err(browser_error(format!(
+                "refusing to run Chromium as root, where it only starts without its sandbox; \
+                 run winx as a regular user, or set {NO_SANDBOX_ENV}=1 to accept that"
+            )));
+        }
+        command.arg("--no-sandbox");
+    }
+    command.arg("about:blank");
+    let mut child =
+        command.spawn().map_err(|e| browser_error(format!("could not start {program}: {e}")))?;
+    let stderr = child.stderr.take().ok_or_else(|| browser_error("no stderr from the browser"))?;
+    let mut lines = BufReader::new(stderr).lines();
+    let startup = async {
+        let mut tail: Vec<String> = Vec::new();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if let Some(url) = devtools_url(&line) {
+                return Ok(url);
+            }
+            if tail.len() == 5 {
+                tail.remove(0);
+            }
+            tail.push(line);
+        }
+        Err(browser_error(format!("{program} exited during startup: {}", tail.join(" | "))))
+    };
+    let browser_url =
+        tokio::time::timeout(Duration::from_secs(LAUNCH_TIMEOUT_SECS), startup).await.map_err(
+            |_| browser_error(format!("{program} did not start in {LAUNCH_TIMEOUT_SECS}s")),
+        )??;
+    // Keep draining stderr so a chatty browser never blocks on a full pipe.
+    tokio::spawn(async move { while let Ok(Some(_)) = lines.next_line().await {} });
+
+    let (host, _) = split_ws_url(&browser_url)
+        .ok_or_else(|| browser_error(format!("bad DevTools URL {browser_url}")))?;
+    let page_url =
+        tokio::time::timeout(Duration::from_secs(LAUNCH_TIMEOUT_SECS), page_target(host))
+            .await
+            .map_err(|_| browser_error("the DevTools endpoint did not answer"))??;
+    let mut page = Cdp::connect(&page_url).await?;
+    page.call("Page.enable", json!({})).await?;
+    Ok(Chromium { child, profile, page })
+}
+
+/// The one browser this server drives, shared by every session.
+fn browser() -> &'static Mutex<Option<Chromium>> {
+    static BROWSER: OnceLock<Mutex<Option<Chromium>>> = OnceLock::new();
+    BROWSER.get_or_init(|| Mutex::new(None))
+}
+
+fn check_url(url: &str) -> Result<()> {
+    let lower = url.to_ascii_lowercase();
+    if lower == "about:blank" || lower.starts_with("http://") || lower.starts_with("https://") {
+        Ok(())
+    } else {
+        Err(WinxError::InvalidInput(format!(
+            "url must be http(s):// or about:blank; file:// and other schemes are not loaded \
+             (got {url:?})"
+        )))
+    }
+}
+
+fn clip(text: &str) -> String {
+    let total = text.chars().count();
+    if total <= MAX_TEXT_CHARS {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(MAX_TEXT_CHARS).collect();
+    format!("{kept}\n\n[truncated: showing {MAX_TEXT_CHARS} of {total} characters]")
+}
+
+fn required<'a>(value: &'a str, field: &str, operation: &str) -> Result<&'a str> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(WinxError::MissingParameterError {
+            field: field.to_string(),
+            message: format!("{operation} needs {field}"),
+        });
+    }
+    Ok(value)
+}
+
+async fn run(page: &mut Cdp, args: &Browser) -> Result<BrowserResult> {
+    match args.operation {
+        BrowserOperation::Navigate => {
+            let url = required(&args.url, "url", "navigate")?;
+            check_url(url)?;
+            if !url.eq_ignore_ascii_case("about:blank") {
+                crate::utils::url_guard::check_public(url).await?;
+            }
+            page.events.clear();
+            let navigated = page.call("Page.navigate", json!({ "url": url })).await?;
+            if let Some(error) = navigated["errorText"].as_str().filter(|e| !e.is_empty()) {
+                return Err(browser_error(format!("could not load {url}: {error}")));
+            }
+            let loaded =
+                page.wait_for("Page.loadEventFired", Duration::from_secs(LOAD_TIMEOUT_SECS)).await;
+            let href = page.evaluate("location.href").await?;
+            let title = page.evaluate("document.title").await?;
+            let mut out = format!(
+                "Loaded {} (title: {:?})",
+                href.as_str().unwrap_or(url),
+                title.as_str().unwrap_or("")
+            );
+            if !loaded {
+                let _ = write!(
+                    out,
+                    "\nThe load event did not fire within {LOAD_TIMEOUT_SECS}s; the page may \
+                     still be loading."
+                );
+            }
+            Ok(BrowserResult::Text(out))
+        }
+        BrowserOperation::Screenshot => {
+            let shot = page.call("Page.captureScreenshot", json!({ "format": "png" })).await?;
+            let data = shot["data"]
+                .as_str()
+                .ok_or_else(|| browser_error("the screenshot came back empty"))?;
+            Ok(BrowserResult::Screenshot(data.to_string()))
+        }
+        BrowserOperation::Text => {
+            let text = page.evaluate("document.body ? document.body.innerText : ''").await?;
+            Ok(BrowserResult::Text(clip(text.as_str().unwrap_or(""))))
+        }
+        BrowserOperation::Click => {
+            let selector = required(&args.selector, "selector", "click")?;
+            let literal = serde_json::to_string(selector)
+                .map_err(|e| WinxError::SerializationError(e.to_string()))?;
+            page.events.clear();
+            let clicked = page
+                .evaluate(&format!(
+                    "(() => {{ const el = document.querySelector({literal}); if (!el) return \
+                     false; el.scrollIntoView({{ block: 'center' }}); el.click(); return true; \
+                     }})()"
+                ))
+                .await?;
+            if clicked != Value::Bool(true) {
+                return Err(browser_error(format!("no element matches {selector:?}")));
+            }
+            let mut out = format!("Clicked {selector:?}.");
+            if page
+                .wait_for("Page.frameStartedLoading", Duration::from_millis(CLICK_SETTLE_MS))
+                .await
+            {
+                let loaded = page
+                    .wait_for("Page.loadEventFired", Duration::from_secs(LOAD_TIMEOUT_SECS))
+                    .await;
+                out.push_str(if loaded {
+                    " The page navigated and finished loading."
+                } else {
+                    " The page started navigating and is still loading."
+                });
+            }
+            Ok(BrowserResult::Text(out))
+        }
+        BrowserOperation::Eval => {
+            let expression = required(&args.expression, "expression", "eval")?;
+            let value = page.evaluate(expression).await?;
+            let text = serde_json::to_string_pretty(&value)
+                .map_err(|e| WinxError::SerializationError(e.to_string()))?;
+            Ok(BrowserResult::Text(clip(&text)))
+        }
+        // Handled by the caller, which owns the browser.
+        BrowserOperation::Close => Ok(BrowserResult::Text(String::new())),
+    }
+}
+
+#[instrument(level = "info", skip(bash_state_arc, args))]
+pub async fn handle_tool_call(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    args: Browser,
+) -> Result<BrowserResult> {
+    let program = find_browser();
+    {
+        let mut guard = bash_state_arc.lock().await;
+        let bash_state = crate::tools::file_ops::session(&mut guard, &args.thread_id)?;
+        // Pages run arbitrary code against live sites, so a restricted mode
+        // has to allow the browser like any other command.
+        let gate = program.as_deref().unwrap_or(BROWSER_CANDIDATES[0]);
+        if !bash_state.is_command_allowed(gate) {
+            return Err(WinxError::CommandNotAllowed(format!(
+                "Browser not allowed in current mode (allow the `{gate}` command to enable it)"
+            )));
+        }
+    }
+
+    let mut slot = browser().lock().await;
+    if args.operation == BrowserOperation::Close {
+        let Some(chromium) = slot.take() else {
+            return Ok(BrowserResult::Text("No browser is running.".to_string()));
+        };
+        chromium.shutdown().await;
+        return Ok(BrowserResult::Text("Browser closed.".to_string()));
+    }
+    if slot.as_mut().is_some_and(|chromium| !chromium.is_alive()) {
+        if let Some(dead) = slot.take() {
+            dead.shutdown().await;
+        }
+    }
+    if slot.is_none() {
+        let program = program.ok_or_else(|| {
+            browser_error(
+                "no Chromium found; install chromium or google-chrome, or set WINX_CHROME to the \
+                 browser binary",
+            )
+        })?;
+        *slot = Some(launch(&program).await?);
+    }
+    let Some(chromium) = slot.as_mut() else {
+        return Err(browser_error("the browser failed to start"));
+    };
+    let result = run(&mut chromium.page, &args).await;
+    if result.is_err() && !chromium.is_alive() {
+        if let Some(dead) = slot.take() {
+            dead.shutdown().await;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[tokio::test]
+    async fn frames_round_trip_at_every_length_encoding() {
+        for len in [5, 300, 70_000] {
+            let payload: Vec<u8> = (0..len).map(|i| u8::try_from(i % 251).unwrap()).collect();
+            let frame = encode_frame(OP_TEXT, &payload, [1, 2, 3, 4]);
+            let (fin, opcode, decoded) = read_frame(&mut frame.as_slice()).await.unwrap();
+            assert!(fin);
+            assert_eq!(opcode, OP_TEXT);
+            assert_eq!(decoded, payload);
+        }
+    }
+
+    /// An unmasked server frame, with the length in the given encoding (7, 16
+    /// or 64 bits).
+    fn server_frame(fin: bool, opcode: u8, payload: &[u8], length_bits: u8) -> Vec<u8> {
+        let mut frame = vec![if fin { 0x80 } else { 0 } | opcode];
+        match length_bits {
+            7 => frame.push(u8::try_from(payload.len()).unwrap()),
+            16 => {
+                frame.push(126);
+                frame.extend_from_slice(&u16::try_from(payload.len()).unwrap().to_be_bytes());
+            }
+            _ => {
+                frame.push(127);
+                frame.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+            }
+        }
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    #[tokio::test]
+    async fn reads_unmasked_and_extended_length_frames() {
+        for bits in [7, 16, 64] {
+            let frame = server_frame(true, OP_TEXT, b"{}", bits);
+            let (fin, opcode, payload) = read_frame(&mut frame.as_slice()).await.unwrap();
+            assert_eq!((fin, opcode, payload.as_slice()), (true, OP_TEXT, b"{}".as_slice()));
+        }
+        // The mask is applied per byte, cycling through its four bytes.
+        let frame = encode_frame(OP_TEXT, b"abcdef", [0xff, 0, 0xff, 0]);
+        assert_eq!(&frame[6..], &[b'a' ^ 0xff, b'b', b'c' ^ 0xff, b'd', b'e' ^ 0xff, b'f']);
+
+        let mut oversized = vec![0x81, 127];
+        oversized.extend_from_slice(&(MAX_MESSAGE_BYTES as u64 + 1).to_be_bytes());
+        assert!(read_frame(&mut oversized.as_slice()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn reassembles_fragments_and_answers_pings_in_between() {
+        let mut stream = server_frame(false, OP_TEXT, br#"{"id":"#, 7);
+        stream.extend(server_frame(true, OP_PING, b"hi", 7));
+        stream.extend(server_frame(false, OP_CONTINUATION, b"4", 16));
+        stream.extend(server_frame(true, OP_CONTINUATION, b"2}", 64));
+        stream.extend(server_frame(true, OP_TEXT, br#"{"id":7}"#, 7));
+        stream.extend(server_frame(true, OP_CLOSE, b"", 7));
+        stream.extend(server_frame(true, OP_TEXT, br#"{"id":8}"#, 7));
+
+        let writer = Arc::new(Mutex::new(Vec::new()));
+        let (sender, mut messages) = mpsc::unbounded_channel();
+        read_messages(stream.as_slice(), Arc::clone(&writer), sender).await;
+        assert_eq!(messages.recv().await, Some(json!({ "id": 42 })));
+        assert_eq!(messages.recv().await, Some(json!({ "id": 7 })));
+        assert_eq!(messages.recv().await, None, "nothing is read past a close frame");
+
+        let pong = writer.lock().await.clone();
+        let (fin, opcode, payload) = read_frame(&mut pong.as_slice()).await.unwrap();
+        assert_eq!((fin, opcode, payload.as_slice()), (true, OP_PONG, b"hi".as_slice()));
+        assert_eq!(pong[1] & 0x80, 0x80, "client frames are masked");
+    }
+
+    #[test]
+    fn parses_devtools_endpoints() {
+        let line = "DevTools listening on ws://127.0.0.1:41235/devtools/browser/ab-12\n";
+        let url = devtools_url(line).unwrap();
+        assert_eq!(split_ws_url(&url), Some(("127.0.0.1:41235", "/devtools/browser/ab-12")));
+        let targets = json!([
+            { "type": "service_worker", "webSocketDebuggerUrl": "ws://h/sw" },
+            { "type": "page", "webSocketDebuggerUrl": "ws://h/devtools/page/1" },
+        ]);
+        assert_eq!(first_page(&targets).as_deref(), Some("ws://h/devtools/page/1"));
+        let head =
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\ncontent-length: 42\r\n\r\n";
+        assert_eq!((status_code(head), content_length(head)), (Some("200"), Some(42)));
+    }
+
+    #[test]
+    fn only_web_urls_are_loaded() {
+        assert!(check_url("https://example.com").is_ok());
+        assert!(check_url("about:blank").is_ok());
+        assert!(check_url("file:///etc/passwd").is_err());
+        assert!(check_url("javascript:alert(1)").is_err());
+    }
+}
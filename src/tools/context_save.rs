@@ -168,7 +168,7 @@ fn collect_relevant_files(
     Ok((relevant_files, warnings))
 }
 
-fn resolve_memory_dir() -> Result<PathBuf> {
+pub(crate) fn resolve_memory_dir() -> Result<PathBuf> {
     let app_dir = match get_app_dir_xdg() {
         Ok(dir) => dir,
         Err(e) => {
@@ -576,7 +576,7 @@ fn read_files_content(file_paths: &[PathBuf], max_files: usize) -> Result<String
 /// # Returns
 ///
 /// A Result indicating success or failure
-fn try_open_file(file_path: &str) -> Result<()> {
+pub fn try_open_file(file_path: &str) -> Result<()> {
     if std::env::consts::OS != "macos" && std::env::consts::OS != "linux" {
         // Skip on unsupported platforms
         return Ok(());
@@ -588,29 +588,21 @@ fn try_open_file(file_path: &str) -> Result<()> {
     } else {
         // Try to find which command is available on Linux
         for cmd in &["xdg-open", "gnome-open", "kde-open"] {
-            let status = std::process::Command::new("which")
-                .arg(cmd)
-                .stdout(std::process::Stdio::null())
-                .stderr(std::process::Stdio::null())
-                .status();
-
-            if let Ok(status) = status {
-                if status.success() {
-                    // Found an available command, use it
-                    let _ = std::process::Command::new(cmd)
-                        .arg(file_path)
-                        .stdout(std::process::Stdio::null())
-                        .stderr(std::process::Stdio::null())
-                        .spawn()
-                        .map_err(|e| {
-                            WinxError::CommandExecutionError(format!(
-                                "Failed to spawn open command: {e}"
-                            ))
-                        })?;
-
-                    // We don't wait for the command to complete
-                    return Ok(());
-                }
+            if crate::utils::capabilities::available(cmd) {
+                // Found an available command, use it
+                let _ = std::process::Command::new(cmd)
+                    .arg(file_path)
+                    .stdout(std::process::Stdio::null())
+                    .stderr(std::process::Stdio::null())
+                    .spawn()
+                    .map_err(|e| {
+                        WinxError::CommandExecutionError(format!(
+                            "Failed to spawn open command: {e}"
+                        ))
+                    })?;
+
+                // We don't wait for the command to complete
+                return Ok(());
             }
         }
 
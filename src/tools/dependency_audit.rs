@@ -0,0 +1,539 @@
+//! Implementation of the `AuditDependencies` tool: known vulnerabilities and
+//! licenses of a project's locked dependencies, so the agent can propose the
+//! version bumps itself.
+//!
+//! Reads `Cargo.lock`, `package-lock.json` (v1-v3) and pinned `requirements.txt`
+//! lines, asks the OSV database (`api.osv.dev`, via `curl`) which locked
+//! versions are affected, and tallies licenses from the lockfile (npm) or the
+//! local cargo registry cache. Only package names and versions leave the
+//! machine; `offline=true` skips the lookup entirely.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write as FmtWrite;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+use crate::errors::{Result, WinxError};
+use crate::state::bash_state::BashState;
+use crate::types::{AuditDependencies, DependencyAuditOutput, DependencyVulnerability};
+use crate::utils::path::resolve_in_workspace;
+
+/// Lockfiles the tool understands, looked up directly in the given directory.
+const LOCKFILES: &[&str] = &["Cargo.lock", "package-lock.json", "requirements.txt"];
+const OSV_API: &str = "https://api.osv.dev/v1";
+/// OSV's limit on queries per batch request.
+const OSV_BATCH: usize = 1000;
+/// Advisories fetched in full; the rest are listed by id only.
+const MAX_ADVISORY_DETAILS: usize = 100;
+const CURL_TIMEOUT_SECS: u64 = 30;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Package {
+    ecosystem: &'static str,
+    name: String,
+    version: String,
+    license: Option<String>,
+}
+
+fn toml_str<'a>(table: &'a toml::Value, key: &str) -> Option<&'a str> {
+    table.get(key).and_then(toml::Value::as_str)
+}
+
+/// Registry packages from a `Cargo.lock` (workspace members, path and git
+/// dependencies are not on crates.io, so OSV can't know them).
+fn parse_cargo_lock(text: &str) -> Result<Vec<Package>> {
+    let lock: toml::Table = toml::from_str(text)
+        .map_err(|e| WinxError::InvalidInput(format!("Cargo.lock: {}", e.message())))?;
+    let packages = lock.get("package").and_then(toml::Value::as_array).cloned().unwrap_or_default();
+    Ok(packages
+        .iter()
+        .filter(|pkg| toml_str(pkg, "source").is_some_and(|src| src.starts_with("registry+")))
+        .filter_map(|pkg| {
+            Some(Package {
+                ecosystem: "crates.io",
+                name: toml_str(pkg, "name")?.to_string(),
+                version: toml_str(pkg, "version")?.to_string(),
+                license: None,
+            })
+        })
+        .collect())
+}
+
+/// Installed packages from a `package-lock.json`: the v2/v3 `packages` map, or
+/// the nested v1 `dependencies` tree.
+fn parse_package_lock(text: &str) -> Result<Vec<Package>> {
+    fn walk_v1(deps: &serde_json::Map<String, Value>, out: &mut Vec<Package>) {
+        for (name, entry) in deps {
+            if let Some(version) = entry.get("version").and_then(Value::as_str) {
+                out.push(Package {
+                    ecosystem: "npm",
+                    name: name.clone(),
+                    version: version.to_string(),
+                    license: None,
+                });
+            }
+            if let Some(nested) = entry.get("dependencies").and_then(Value::as_object) {
+                walk_v1(nested, out);
+            }
+        }
+    }
+
+    let lock: Value = serde_json::from_str(text)
+        .map_err(|e| WinxError::InvalidInput(format!("package-lock.json: {e}")))?;
+    let mut out = Vec::new();
+    if let Some(packages) = lock.get("packages").and_then(Value::as_object) {
+        for (key, entry) in packages {
+            // "" is the project itself; links are workspace packages.
+            let Some((_, name)) = key.rsplit_once("node_modules/") else { continue };
+            if entry.get("link").and_then(Value::as_bool) == Some(true) {
+                continue;
+            }
+            let Some(version) = entry.get("version").and_then(Value::as_str) else { continue };
+            let name = entry.get("name").and_then(Value::as_str).unwrap_or(name);
+            out.push(Package {
+                ecosystem: "npm",
+                name: name.to_string(),
+                version: version.to_string(),
+                license: entry.get("license").and_then(Value::as_str).map(str::to_string),
+            });
+        }
+    } else if let Some(deps) = lock.get("dependencies").and_then(Value::as_object) {
+        walk_v1(deps, &mut out);
+    }
+    Ok(out)
+}
+
+/// `name==version` lines of a `requirements.txt`, plus how many requirements
+/// were not pinned to one version (and so can't be checked).
+fn parse_requirements(text: &str) -> (Vec<Package>, usize) {
+    let mut packages = Vec::new();
+    let mut unpinned = 0;
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() || line.starts_with('-') {
+            continue;
+        }
+        let spec = line.split(';').next().unwrap_or(line);
+        match spec.split_once("==") {
+            Some((name, version)) if !version.contains([',', '*']) => {
+                let name = name.split('[').next().unwrap_or(name).trim();
+                packages.push(Package {
+                    ecosystem: "PyPI",
+                    name: name.to_string(),
+                    // A trailing `\` continues onto `--hash` lines.
+                    version: version.trim().trim_end_matches('\\').trim_end().to_string(),
+                    license: None,
+                });
+            }
+            _ => unpinned += 1,
+        }
+    }
+    (packages, unpinned)
+}
+
+/// `$CARGO_HOME/registry/src/<index>/` directories, where cargo unpacks crates.
+fn cargo_registry_dirs() -> Vec<PathBuf> {
+    let cargo_home = std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| home::home_dir().map(|home| home.join(".cargo")));
+    let Some(src) = cargo_home.map(|home| home.join("registry").join("src")) else {
+        return Vec::new();
+    };
+    std::fs::read_dir(src)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default()
+}
+
+/// The `license` of a crate already unpacked in the local registry cache.
+fn cargo_license(registry_dirs: &[PathBuf], name: &str, version: &str) -> Option<String> {
+    registry_dirs.iter().find_map(|dir| {
+        let manifest = std::fs::read_to_string(dir.join(format!("{name}-{version}/Cargo.toml")));
+        let manifest: toml::Table = toml::from_str(&manifest.ok()?).ok()?;
+        manifest.get("package")?.get("license")?.as_str().map(str::to_string)
+    })
+}
+
+/// Whether every alternative of an SPDX-ish license expression is copyleft, so
+/// there is no permissive option to pick.
+fn is_copyleft(license: &str) -> bool {
+    const COPYLEFT: &[&str] = &["GPL", "MPL", "EUPL", "SSPL"];
+    let upper = license.to_uppercase().replace(['(', ')'], "");
+    upper
+        .split(" OR ")
+        .flat_map(|alt| alt.split('/'))
+        .all(|alt| COPYLEFT.iter().any(|marker| alt.contains(marker)))
+}
+
+async fn curl_json(url: &str, body: Option<&Value>) -> Result<Value> {
+    let mut command = tokio::process::Command::new("curl");
+    command
+        .args(["--silent", "--show-error", "--fail", "--proto", "=https"])
+        .args(["--max-time", &CURL_TIMEOUT_SECS.to_string()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if body.is_some() {
+        command.args(["-H", "Content-Type: application/json", "--data-binary", "@-"]);
+    }
+    let mut child = command
+        .arg("--")
+        .arg(url)
+        .spawn()
+        .map_err(|e| WinxError::CommandExecutionError(format!("failed to run curl: {e}")))?;
+    if let (Some(body), Some(mut stdin)) = (body, child.stdin.take()) {
+        stdin.write_all(body.to_string().as_bytes()).await?;
+    }
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        return Err(WinxError::CommandExecutionError(format!(
+            "OSV request failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    serde_json::from_slice(&output.stdout)
+        .map_err(|e| WinxError::DeserializationError(format!("OSV response: {e}")))
+}
+
+/// OSV advisory ids affecting each package, in `packages` order.
+async fn osv_ids(packages: &[Package]) -> Result<Vec<Vec<String>>> {
+    let mut ids = Vec::with_capacity(packages.len());
+    for chunk in packages.chunks(OSV_BATCH) {
+        let queries: Vec<Value> = chunk
+            .iter()
+            .map(|pkg| {
+                json!({
+                    "package": { "name": pkg.name, "ecosystem": pkg.ecosystem },
+                    "version": pkg.version,
+                })
+            })
+            .collect();
+        let response =
+            curl_json(&format!("{OSV_API}/querybatch"), Some(&json!({ "queries": queries })))
+                .await?;
+        let results = response.get("results").and_then(Value::as_array);
+        for index in 0..chunk.len() {
+            let vulns = results.and_then(|r| r.get(index)).and_then(|r| r.get("vulns"));
+            ids.push(
+                vulns
+                    .and_then(Value::as_array)
+                    .map(|vulns| {
+                        vulns
+                            .iter()
+                            .filter_map(|v| v.get("id").and_then(Value::as_str))
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            );
+        }
+    }
+    Ok(ids)
+}
+
+/// A vulnerability entry for `pkg` from an OSV advisory (`None` = details not
+/// fetched, which still reports the id).
+fn vulnerability(pkg: &Package, id: &str, advisory: Option<&Value>) -> DependencyVulnerability {
+    let text = |key: &str| advisory.and_then(|a| a.get(key)).and_then(Value::as_str);
+    let severity = advisory
+        .and_then(|a| a.pointer("/database_specific/severity"))
+        .and_then(Value::as_str)
+        .or_else(|| advisory.and_then(|a| a.pointer("/severity/0/score")).and_then(Value::as_str))
+        .unwrap_or("unknown");
+    let strings = |value: Option<&Value>| -> Vec<String> {
+        value
+            .and_then(Value::as_array)
+            .map(|items| items.iter().filter_map(Value::as_str).map(str::to_string).collect())
+            .unwrap_or_default()
+    };
+    let mut fixed_in = BTreeSet::new();
+    let affected = advisory.and_then(|a| a.get("affected")).and_then(Value::as_array);
+    for entry in affected.into_iter().flatten() {
+        if entry.pointer("/package/name").and_then(Value::as_str) != Some(pkg.name.as_str()) {
+            continue;
+        }
+        let ranges = entry.get("ranges").and_then(Value::as_array);
+        for events in ranges.into_iter().flatten().filter_map(|r| r.get("events")?.as_array()) {
+            fixed_in
+                .extend(events.iter().filter_map(|e| e.get("fixed")?.as_str()).map(str::to_string));
+        }
+    }
+    DependencyVulnerability {
+        package: pkg.name.clone(),
+        version: pkg.version.clone(),
+        ecosystem: pkg.ecosystem.to_string(),
+        id: id.to_string(),
+        aliases: strings(advisory.and_then(|a| a.get("aliases"))),
+        severity: severity.to_string(),
+        summary: text("summary")
+            .or_else(|| text("details"))
+            .unwrap_or("")
+            .lines()
+            .next()
+            .unwrap_or("")
+            .to_string(),
+        fixed_in: fixed_in.into_iter().collect(),
+    }
+}
+
+async fn check_vulnerabilities(
+    packages: &[Package],
+    output: &mut DependencyAuditOutput,
+) -> Result<()> {
+    let ids = osv_ids(packages).await?;
+    let unique: BTreeSet<&String> = ids.iter().flatten().collect();
+    let mut fetches = tokio::task::JoinSet::new();
+    for id in unique.iter().take(MAX_ADVISORY_DETAILS) {
+        let id = (*id).clone();
+        fetches.spawn(async move {
+            let advisory = curl_json(&format!("{OSV_API}/vulns/{id}"), None).await.ok();
+            (id, advisory)
+        });
+    }
+    let mut advisories = HashMap::new();
+    while let Some(joined) = fetches.join_next().await {
+        if let Ok((id, Some(advisory))) = joined {
+            advisories.insert(id, advisory);
+        }
+    }
+    if unique.len() > MAX_ADVISORY_DETAILS {
+        output.notes.push(format!(
+            "{} advisories found; details fetched for the first {MAX_ADVISORY_DETAILS}",
+            unique.len()
+        ));
+    }
+    for (pkg, ids) in packages.iter().zip(&ids) {
+        for id in ids {
+            output.vulnerabilities.push(vulnerability(pkg, id, advisories.get(id)));
+        }
+    }
+    output.vulnerabilities_checked = true;
+    Ok(())
+}
+
+/// The lockfiles to read for `target` (a lockfile or a directory).
+fn lockfiles(target: &Path) -> Result<Vec<PathBuf>> {
+    if target.is_file() {
+        let name = target.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if !LOCKFILES.contains(&name) {
+            return Err(WinxError::InvalidInput(format!(
+                "{} is not a supported lockfile ({})",
+                target.display(),
+                LOCKFILES.join(", ")
+            )));
+        }
+        return Ok(vec![target.to_path_buf()]);
+    }
+    let found: Vec<PathBuf> =
+        LOCKFILES.iter().map(|name| target.join(name)).filter(|path| path.is_file()).collect();
+    if found.is_empty() {
+        return Err(WinxError::InvalidInput(format!(
+            "No {} in {}; pass the directory that holds the lockfile",
+            LOCKFILES.join(", "),
+            target.display()
+        )));
+    }
+    Ok(found)
+}
+
+fn render(output: &DependencyAuditOutput) -> String {
+    let mut text =
+        format!("Audited {} packages from {}.\n", output.packages, output.lockfiles.join(", "));
+    if output.vulnerabilities_checked {
+        if output.vulnerabilities.is_empty() {
+            text.push_str("\nNo known vulnerabilities (OSV).\n");
+        } else {
+            let _ = writeln!(text, "\nVulnerabilities ({}):", output.vulnerabilities.len());
+            for vuln in &output.vulnerabilities {
+                let mut ids = vec![vuln.id.clone()];
+                ids.extend(vuln.aliases.iter().filter(|a| a.starts_with("CVE-")).cloned());
+                let _ = write!(
+                    text,
+                    "- [{}] {} {} ({}): {}",
+                    vuln.severity,
+                    vuln.package,
+                    vuln.version,
+                    vuln.ecosystem,
+                    ids.join(" / ")
+                );
+                if !vuln.summary.is_empty() {
+                    let _ = write!(text, " - {}", vuln.summary);
+                }
+                if !vuln.fixed_in.is_empty() {
+                    let _ = write!(text, ". Fixed in {}", vuln.fixed_in.join(", "));
+                }
+                text.push('\n');
+            }
+        }
+    }
+    let mut licenses: Vec<(&String, &usize)> = output.licenses.iter().collect();
+    licenses.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    let licenses: Vec<String> =
+        licenses.iter().map(|(license, count)| format!("{license} ({count})")).collect();
+    let _ = writeln!(text, "\nLicenses: {}", licenses.join(", "));
+    if !output.copyleft.is_empty() {
+        let _ = writeln!(text, "Copyleft only: {}", output.copyleft.join(", "));
+    }
+    for note in &output.notes {
+        let _ = writeln!(text, "Note: {note}");
+    }
+    text
+}
+
+#[instrument(level = "info", skip(bash_state_arc, args))]
+pub async fn handle_tool_call(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    args: AuditDependencies,
+) -> Result<(String, Value)> {
+    let (cwd, workspace_root) = {
+        let guard = bash_state_arc.lock().await;
+        let bash_state = guard.as_ref().ok_or(WinxError::BashStateNotInitialized)?;
+        (bash_state.cwd.clone(), bash_state.workspace_root.clone())
+    };
+    let target = resolve_in_workspace(&args.path, &cwd, &workspace_root).map_err(|e| {
+        WinxError::PathSecurityError { path: PathBuf::from(&args.path), message: e.to_string() }
+    })?;
+
+    let mut output = DependencyAuditOutput::default();
+    let mut packages = BTreeSet::new();
+    let registry_dirs = cargo_registry_dirs();
+    for lockfile in lockfiles(&target)? {
+        let text = std::fs::read_to_string(&lockfile).map_err(|e| WinxError::FileAccessError {
+            path: lockfile.clone(),
+            message: e.to_string(),
+        })?;
+        let parsed: Vec<Package> = match lockfile.file_name().and_then(|n| n.to_str()) {
+            Some("Cargo.lock") => parse_cargo_lock(&text)?
+                .into_iter()
+                .map(|pkg| Package {
+                    license: cargo_license(&registry_dirs, &pkg.name, &pkg.version),
+                    ..pkg
+                })
+                .collect(),
+            Some("package-lock.json") => parse_package_lock(&text)?,
+            _ => {
+                let (pinned, unpinned) = parse_requirements(&text);
+                if unpinned > 0 {
+                    output.notes.push(format!(
+                        "{unpinned} requirement(s) in requirements.txt are not pinned with == \
+                         and were not checked"
+                    ));
+                }
+                pinned
+            }
+        };
+        packages.extend(parsed);
+        let rel = lockfile.strip_prefix(&workspace_root).unwrap_or(&lockfile);
+        output.lockfiles.push(rel.display().to_string());
+    }
+    let packages: Vec<Package> = packages.into_iter().collect();
+    output.packages = packages.len();
+
+    for pkg in &packages {
+        let license = pkg.license.clone().unwrap_or_else(|| "unknown".to_string());
+        if is_copyleft(&license) {
+            output.copyleft.push(format!("{} {} ({license})", pkg.name, pkg.version));
+        }
+        *output.licenses.entry(license).or_insert(0) += 1;
+    }
+
+    if args.offline {
+        output.notes.push("offline: vulnerabilities were not checked".to_string());
+    } else if !crate::utils::capabilities::available("curl") {
+        output.notes.push("curl is not installed, so vulnerabilities were not checked".to_string());
+    } else if let Err(e) = check_vulnerabilities(&packages, &mut output).await {
+        output.vulnerabilities.clear();
+        output.notes.push(format!("vulnerabilities were not checked: {e}"));
+    }
+
+    Ok((render(&output), crate::tools::structured_json(&output)?))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn parses_lockfiles() {
+        let cargo = r#"
+version = 4
+
+[[package]]
+name = "winx-code-agent"
+version = "0.2.0"
+
+[[package]]
+name = "time"
+version = "0.1.43"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "forked"
+version = "1.0.0"
+source = "git+https://example.com/forked#abc"
+"#;
+        let crates = parse_cargo_lock(cargo).unwrap();
+        assert_eq!(crates.len(), 1, "only registry crates are checked");
+        assert_eq!((crates[0].name.as_str(), crates[0].version.as_str()), ("time", "0.1.43"));
+
+        let npm = r#"{"lockfileVersion": 3, "packages": {
+            "": {"name": "app"},
+            "node_modules/lodash": {"version": "4.17.20", "license": "MIT"},
+            "node_modules/a/node_modules/@scope/b": {"version": "1.0.0"},
+            "node_modules/local": {"link": true}
+        }}"#;
+        let mut npm = parse_package_lock(npm).unwrap();
+        npm.sort();
+        let names: Vec<&str> = npm.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["@scope/b", "lodash"]);
+        assert_eq!(npm[1].license.as_deref(), Some("MIT"));
+
+        let (pinned, unpinned) = parse_requirements(
+            "django==3.2.0  # web\nrequests[socks]==2.19.0\nflask>=2\n-r dev.txt\n",
+        );
+        assert_eq!(pinned.len(), 2);
+        assert_eq!(pinned[1].name, "requests");
+        assert_eq!(unpinned, 1);
+    }
+
+    #[test]
+    fn reads_osv_advisory() {
+        let pkg = Package {
+            ecosystem: "crates.io",
+            name: "time".into(),
+            version: "0.1.43".into(),
+            license: None,
+        };
+        let advisory = json!({
+            "id": "RUSTSEC-2020-0071",
+            "aliases": ["CVE-2020-26235"],
+            "summary": "Potential segfault in the time crate",
+            "database_specific": {"severity": "MEDIUM"},
+            "affected": [{"package": {"name": "time"}, "ranges": [{"events": [
+                {"introduced": "0"}, {"fixed": "0.2.23"}
+            ]}]}]
+        });
+        let vuln = vulnerability(&pkg, "RUSTSEC-2020-0071", Some(&advisory));
+        assert_eq!(vuln.severity, "MEDIUM");
+        assert_eq!(vuln.fixed_in, ["0.2.23"]);
+        assert_eq!(vuln.aliases, ["CVE-2020-26235"]);
+        let bare = vulnerability(&pkg, "GHSA-x", None);
+        assert_eq!((bare.severity.as_str(), bare.summary.as_str()), ("unknown", ""));
+    }
+
+    #[test]
+    fn copyleft_needs_every_option_copyleft() {
+        assert!(is_copyleft("GPL-3.0-only"));
+        assert!(is_copyleft("LGPL-2.1 OR MPL-2.0"));
+        assert!(!is_copyleft("MIT OR GPL-2.0"));
+        assert!(!is_copyleft("MIT/Apache-2.0"));
+        assert!(!is_copyleft("unknown"));
+    }
+}
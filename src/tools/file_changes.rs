@@ -0,0 +1,209 @@
+//! Implementation of the `GetFileChanges` tool.
+//!
+//! Reports workspace files created, modified or deleted since the previous call
+//! in this session, so the agent notices edits the user made in their IDE
+//! mid-conversation. The first call (or `reset`) only records a baseline. Files
+//! whose content still matches what winx itself last wrote/read are not
+//! reported as modified, and modified files this session had read are flagged
+//! so the agent re-reads them before editing.
+
+use std::fmt::Write as FmtWrite;
+use std::path::Path;
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use crate::errors::{Result, WinxError};
+use crate::state::bash_state::BashState;
+use crate::tools::file_write_or_edit::hash_content;
+use crate::types::{FileChangesOutput, GetFileChanges};
+use crate::utils::file_watch::WorkspaceSnapshot;
+use crate::utils::symbol_cache;
+
+/// List at most this many paths per category in the text block (the structured
+/// content carries them all).
+const MAX_LISTED_PER_KIND: usize = 100;
+
+pub async fn handle_tool_call(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    args: GetFileChanges,
+) -> Result<(String, serde_json::Value)> {
+    let (root, baseline) = {
+        let mut guard = bash_state_arc.lock().await;
+        let bash_state = guard.as_mut().ok_or(WinxError::BashStateNotInitialized)?;
+        let root = bash_state.workspace_root.clone();
+        let root = root.canonicalize().unwrap_or(root);
+        (root, bash_state.file_watch_baseline.take())
+    };
+
+    // The walk stats every file; keep it off the async worker threads.
+    let scan_root = root.clone();
+    let scanned = tokio::task::spawn_blocking(move || WorkspaceSnapshot::scan(&scan_root)).await;
+
+    let mut guard = bash_state_arc.lock().await;
+    let bash_state = guard.as_mut().ok_or(WinxError::BashStateNotInitialized)?;
+    let current = match scanned {
+        Ok(current) => current,
+        Err(e) => {
+            bash_state.file_watch_baseline = baseline;
+            return Err(WinxError::CommandExecutionError(format!("workspace scan failed: {e}")));
+        }
+    };
+
+    // A workspace switch since the last call invalidates the old baseline.
+    let previous = baseline.filter(|prev| !args.reset && prev.root() == root);
+    let mut output = FileChangesOutput { watched_files: current.len(), ..Default::default() };
+    let text = if let Some(previous) = previous {
+        let diff = previous.diff(&current);
+        for path in diff.modified.iter().chain(&diff.deleted) {
+            symbol_cache::invalidate(path);
+        }
+        let rel = |path: &Path| path.strip_prefix(&root).unwrap_or(path).display().to_string();
+        output.created = diff.created.iter().map(|p| rel(p)).collect();
+        output.deleted = diff.deleted.iter().map(|p| rel(p)).collect();
+        for path in &diff.modified {
+            match read_state(bash_state, path) {
+                ReadState::OwnContent => continue,
+                ReadState::StaleRead => output.stale_reads.push(rel(path)),
+                ReadState::NotRead => {}
+            }
+            output.modified.push(rel(path));
+        }
+        render_changes(&output)
+    } else {
+        output.baseline = true;
+        format!(
+            "Now watching {} workspace files. Call GetFileChanges again to see what was created, \
+             modified or deleted since this point.",
+            current.len()
+        )
+    };
+    bash_state.file_watch_baseline = Some(current);
+
+    Ok((text, crate::tools::structured_json(&output)?))
+}
+
+enum ReadState {
+    /// On-disk content is exactly what winx last read or wrote (its own edit).
+    OwnContent,
+    /// The session read this file, but the content has changed since.
+    StaleRead,
+    /// The session never read this file.
+    NotRead,
+}
+
+fn read_state(bash_state: &BashState, path: &Path) -> ReadState {
+    let Some(whitelist) = bash_state.whitelist_for_overwrite.get(&*path.to_string_lossy()) else {
+        return ReadState::NotRead;
+    };
+    match crate::utils::text_encoding::read_text(path) {
+        Ok((content, _)) if hash_content(&content) == whitelist.file_hash => ReadState::OwnContent,
+        _ => ReadState::StaleRead,
+    }
+}
+
+fn render_changes(output: &FileChangesOutput) -> String {
+    if output.created.is_empty() && output.modified.is_empty() && output.deleted.is_empty() {
+        return format!(
+            "No changes in the {} watched workspace files since the last check.",
+            output.watched_files
+        );
+    }
+    let mut text =
+        format!("Changes since the last check ({} files watched):\n", output.watched_files);
+    let kinds = [
+        ("created", &output.created),
+        ("modified", &output.modified),
+        ("deleted", &output.deleted),
+    ];
+    for (kind, paths) in kinds {
+        if paths.is_empty() {
+            continue;
+        }
+        let _ = writeln!(text, "{kind}:");
+        for path in paths.iter().take(MAX_LISTED_PER_KIND) {
+            if output.stale_reads.contains(path) {
+                let _ = writeln!(text, "  {path}  (read earlier; re-read before editing)");
+            } else {
+                let _ = writeln!(text, "  {path}");
+            }
+        }
+        if paths.len() > MAX_LISTED_PER_KIND {
+            let _ = writeln!(text, "  (...{} more)", paths.len() - MAX_LISTED_PER_KIND);
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::state::bash_state::FileWhitelistData;
+    use tempfile::TempDir;
+
+    fn state_in(dir: &TempDir) -> Arc<Mutex<Option<BashState>>> {
+        let mut bs = BashState::new();
+        let root = dir.path().canonicalize().unwrap();
+        bs.cwd = root.clone();
+        bs.workspace_root = root;
+        Arc::new(Mutex::new(Some(bs)))
+    }
+
+    fn args() -> GetFileChanges {
+        GetFileChanges { thread_id: String::new(), reset: false }
+    }
+
+    #[tokio::test]
+    async fn first_call_records_baseline_then_reports_changes() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::write(root.join("a.txt"), "one").unwrap();
+        let st = state_in(&dir);
+
+        let (_, structured) = handle_tool_call(&st, args()).await.unwrap();
+        assert_eq!(structured["baseline"], true);
+
+        std::fs::write(root.join("a.txt"), "one, edited in the IDE").unwrap();
+        std::fs::write(root.join("b.txt"), "new").unwrap();
+        let (text, structured) = handle_tool_call(&st, args()).await.unwrap();
+        assert_eq!(structured["baseline"], false);
+        assert_eq!(structured["modified"][0], "a.txt");
+        assert_eq!(structured["created"][0], "b.txt");
+        assert!(text.contains("modified:"), "got {text}");
+
+        // Reported once: the next poll starts from the new baseline.
+        let (text, _) = handle_tool_call(&st, args()).await.unwrap();
+        assert!(text.starts_with("No changes"), "got {text}");
+    }
+
+    #[tokio::test]
+    async fn own_writes_are_skipped_and_stale_reads_flagged() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let ours = root.join("ours.txt");
+        let theirs = root.join("theirs.txt");
+        std::fs::write(&ours, "v1").unwrap();
+        std::fs::write(&theirs, "v1").unwrap();
+        let st = state_in(&dir);
+        handle_tool_call(&st, args()).await.unwrap();
+
+        std::fs::write(&ours, "winx wrote this").unwrap();
+        std::fs::write(&theirs, "the user changed this").unwrap();
+        {
+            let mut guard = st.lock().await;
+            let bs = guard.as_mut().unwrap();
+            for (path, content) in [(&ours, "winx wrote this"), (&theirs, "v1")] {
+                bs.whitelist_for_overwrite.insert(
+                    path.to_string_lossy().to_string(),
+                    FileWhitelistData::new(hash_content(content), vec![(1, 1)], 1),
+                );
+            }
+        }
+        let (_, structured) = handle_tool_call(&st, args()).await.unwrap();
+        let modified = structured["modified"].as_array().unwrap();
+        assert_eq!(modified.len(), 1);
+        assert_eq!(modified[0], "theirs.txt");
+        assert_eq!(structured["stale_reads"][0], "theirs.txt");
+    }
+}
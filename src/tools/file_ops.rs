@@ -0,0 +1,418 @@
+//! Implementation of the `MoveFile`, `CopyFile`, `DeleteFile` and
+//! `CreateDirectory` tools.
+//!
+//! Routine file management without going through `BashCommand`, so each
+//! operation is checked individually: workspace confinement, the mode's write
+//! globs, and protected paths. Deletes go to `<workspace>/.winx/trash/` by
+//! default rather than the desktop trash: it is on the same filesystem (a
+//! rename, never a copy), it exists on headless machines and over SSH, and it
+//! is inside the workspace, so the agent can restore with `MoveFile` — the
+//! system trash is outside the workspace and out of reach of these tools.
+//! Symlinks are moved, copied and deleted as links; their targets are never
+//! followed or touched, including a symlink already at a copy's destination.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+use crate::errors::{Result, WinxError};
+use crate::state::bash_state::BashState;
+use crate::types::{normalize_thread_id, CopyFile, CreateDirectory, DeleteFile, MoveFile};
+use crate::utils::path::{expand_user, validate_path_in_workspace};
+
+/// Trash location, relative to the workspace root.
+const TRASH_SUBDIR: &str = ".winx/trash";
+
+/// Which mode gate an operation is subject to.
+pub(crate) enum Access {
+    /// Only read (a copy's source): no mode gate or protection check.
+    Read,
+    /// Removing or replacing existing content (the edit globs).
+    Modify,
+    /// Creating something new (the write globs).
+    Create,
+}
+
+pub(crate) fn session<'a>(
+    guard: &'a mut tokio::sync::MutexGuard<'_, Option<BashState>>,
+    thread_id: &str,
+) -> Result<&'a mut BashState> {
+    let bash_state = guard.as_mut().ok_or(WinxError::BashStateNotInitialized)?;
+    let thread_id = normalize_thread_id(thread_id);
+    if thread_id != bash_state.current_thread_id {
+        return Err(WinxError::ThreadIdMismatch(thread_id));
+    }
+    Ok(bash_state)
+}
+
+/// Resolve `raw` to a workspace-confined path naming the entry itself: the
+/// parent directory is canonicalized, the last component is kept as-is, so a
+/// symlink resolves to the link rather than its target. Then, unless it is
+/// only read, apply the mode gate and the protected-path guard.
+pub(crate) fn resolve_entry(bash_state: &BashState, raw: &str, access: &Access) -> Result<PathBuf> {
+    let expanded = expand_user(raw);
+    let path = if Path::new(&expanded).is_absolute() {
+        PathBuf::from(&expanded)
+    } else {
+        bash_state.cwd.join(&expanded)
+    };
+    let invalid = |message: &str| WinxError::PathSecurityError {
+        path: path.clone(),
+        message: message.to_string(),
+    };
+    let name = path.file_name().ok_or_else(|| invalid("path has no final component"))?;
+    let parent = path.parent().ok_or_else(|| invalid("path has no parent directory"))?;
+    let parent = validate_path_in_workspace(parent, &bash_state.workspace_root)
+        .map_err(|e| invalid(&e.to_string()))?;
+    let entry = parent.join(name);
+
+    let root = bash_state.workspace_root.canonicalize().unwrap_or_default();
+    if entry == root || !entry.starts_with(&root) {
+        return Err(invalid("refusing to operate on the workspace root itself"));
+    }
+    let entry_str = entry.to_string_lossy();
+    let allowed = match access {
+        Access::Read => return Ok(entry),
+        Access::Modify => bash_state.is_file_edit_allowed(&entry_str),
+        Access::Create => bash_state.is_file_write_allowed(&entry_str),
+    };
+    if !allowed {
+        return Err(WinxError::FileAccessError {
+            path: entry,
+            message: "File operation not allowed by the current mode or .winx/config.toml [files]."
+                .to_string(),
+        });
+    }
+    crate::utils::protected_paths::check_path(&entry, &bash_state.workspace_root)?;
+    Ok(entry)
+}
+
+fn exists(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok()
+}
+
+pub(crate) fn io_error(path: &Path, action: &str, e: &std::io::Error) -> WinxError {
+    WinxError::FileAccessError { path: path.to_path_buf(), message: format!("{action}: {e}") }
+}
+
+/// Fail unless `destination` is free, or is a file `overwrite` may replace.
+pub(crate) fn check_destination(destination: &Path, overwrite: bool) -> Result<()> {
+    let Ok(meta) = fs::symlink_metadata(destination) else {
+        return Ok(());
+    };
+    let message = if meta.is_dir() {
+        "destination is an existing directory; give the full target path instead"
+    } else if overwrite {
+        return Ok(());
+    } else {
+        "destination already exists; pass overwrite=true to replace it"
+    };
+    Err(WinxError::FileAccessError { path: destination.to_path_buf(), message: message.into() })
+}
+
+pub(crate) fn ensure_parent(path: &Path) -> Result<()> {
+    match path.parent() {
+        Some(parent) => {
+            fs::create_dir_all(parent).map_err(|e| io_error(parent, "creating parent", &e))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Copy `source` to `destination`: files by content, directories recursively,
+/// symlinks as links (never followed). Returns the number of files copied.
+fn copy_entry(source: &Path, destination: &Path) -> Result<usize> {
+    let meta = fs::symlink_metadata(source).map_err(|e| io_error(source, "reading", &e))?;
+    // An existing symlink at the destination (allowed by `overwrite`) is
+    // replaced, not written through: following it could land outside the
+    // workspace, past the protected-path check on the unresolved path.
+    if fs::symlink_metadata(destination).is_ok_and(|dest| dest.file_type().is_symlink()) {
+        fs::remove_file(destination).map_err(|e| io_error(destination, "replacing link", &e))?;
+    }
+    if meta.file_type().is_symlink() {
+        let target = fs::read_link(source).map_err(|e| io_error(source, "reading link", &e))?;
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&target, destination)
+                .map_err(|e| io_error(destination, "creating link", &e))?;
+            return Ok(0);
+        }
+        #[cfg(not(unix))]
+        return Err(WinxError::FileAccessError {
+            path: source.to_path_buf(),
+            message: format!("copying symlinks (-> {}) isn't supported here", target.display()),
+        });
+    }
+    if meta.is_dir() {
+        fs::create_dir_all(destination).map_err(|e| io_error(destination, "creating", &e))?;
+        let mut copied = 0;
+        for child in fs::read_dir(source).map_err(|e| io_error(source, "listing", &e))? {
+            let child = child.map_err(|e| io_error(source, "listing", &e))?;
+            copied += copy_entry(&child.path(), &destination.join(child.file_name()))?;
+        }
+        return Ok(copied);
+    }
+    fs::copy(source, destination).map_err(|e| io_error(destination, "copying", &e))?;
+    Ok(1)
+}
+
+fn remove_entry(path: &Path) -> Result<()> {
+    let meta = fs::symlink_metadata(path).map_err(|e| io_error(path, "reading", &e))?;
+    let removed = if meta.is_dir() { fs::remove_dir_all(path) } else { fs::remove_file(path) };
+    removed.map_err(|e| io_error(path, "deleting", &e))
+}
+
+/// Move `source` to `destination`, falling back to copy + delete across
+/// filesystems.
+fn move_entry(source: &Path, destination: &Path) -> Result<()> {
+    match fs::rename(source, destination) {
+        Ok(()) => Ok(()),
+        Err(e) if e.raw_os_error() == Some(libc::EXDEV) => {
+            copy_entry(source, destination)?;
+            remove_entry(source)
+        }
+        Err(e) => Err(io_error(source, "moving", &e)),
+    }
+}
+
+/// Drop whitelist entries for `path` and anything under it; with `to`, re-key
+/// them under the new location instead (a move keeps "already read" status).
+pub(crate) fn forget_or_rekey(bash_state: &mut BashState, path: &Path, to: Option<&Path>) {
+    let keys: Vec<String> = bash_state
+        .whitelist_for_overwrite
+        .keys()
+        .filter(|key| Path::new(key).starts_with(path))
+        .cloned()
+        .collect();
+    for key in keys {
+        let Some(data) = bash_state.whitelist_for_overwrite.remove(&key) else { continue };
+        crate::utils::symbol_cache::invalidate(Path::new(&key));
+        if let Some(to) = to {
+            let rest = Path::new(&key).strip_prefix(path).unwrap_or(Path::new(""));
+            let new_key =
+                if rest.as_os_str().is_empty() { to.to_path_buf() } else { to.join(rest) };
+            bash_state.whitelist_for_overwrite.insert(new_key.to_string_lossy().to_string(), data);
+        }
+    }
+}
+
+#[instrument(level = "info", skip(bash_state_arc, args))]
+pub async fn handle_move(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    args: MoveFile,
+) -> Result<String> {
+    let mut guard = bash_state_arc.lock().await;
+    let bash_state = session(&mut guard, &args.thread_id)?;
+    let source = resolve_entry(bash_state, &args.source, &Access::Modify)?;
+    let destination = resolve_entry(bash_state, &args.destination, &Access::Create)?;
+    if !exists(&source) {
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        return Err(io_error(&source, "moving", &missing));
+    }
+    if destination.starts_with(&source) {
+        return Err(WinxError::FileAccessError {
+            path: destination,
+            message: "can't move a directory into itself".to_string(),
+        });
+    }
+    check_destination(&destination, args.overwrite)?;
+    ensure_parent(&destination)?;
+    move_entry(&source, &destination)?;
+    forget_or_rekey(bash_state, &source, Some(&destination));
+    Ok(format!("Moved {} -> {}", source.display(), destination.display()))
+}
+
+#[instrument(level = "info", skip(bash_state_arc, args))]
+pub async fn handle_copy(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    args: CopyFile,
+) -> Result<String> {
+    let mut guard = bash_state_arc.lock().await;
+    let bash_state = session(&mut guard, &args.thread_id)?;
+    let source = resolve_entry(bash_state, &args.source, &Access::Read)?;
+    let destination = resolve_entry(bash_state, &args.destination, &Access::Create)?;
+    if destination.starts_with(&source) {
+        return Err(WinxError::FileAccessError {
+            path: destination,
+            message: "can't copy a directory into itself".to_string(),
+        });
+    }
+    check_destination(&destination, args.overwrite)?;
+    ensure_parent(&destination)?;
+    // The destination's whitelist entry (if it was read) no longer matches.
+    forget_or_rekey(bash_state, &destination, None);
+    let copied = copy_entry(&source, &destination)?;
+    Ok(format!("Copied {} -> {} ({copied} file(s))", source.display(), destination.display()))
+}
+
+#[instrument(level = "info", skip(bash_state_arc, args))]
+pub async fn handle_delete(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    args: DeleteFile,
+) -> Result<String> {
+    let mut guard = bash_state_arc.lock().await;
+    let bash_state = session(&mut guard, &args.thread_id)?;
+    let path = resolve_entry(bash_state, &args.path, &Access::Modify)?;
+    let meta = fs::symlink_metadata(&path).map_err(|e| io_error(&path, "deleting", &e))?;
+    if meta.is_dir() && !args.recursive {
+        let empty = fs::read_dir(&path).is_ok_and(|mut entries| entries.next().is_none());
+        if !empty {
+            return Err(WinxError::FileAccessError {
+                path,
+                message: "directory is not empty; pass recursive=true to delete it".to_string(),
+            });
+        }
+    }
+
+    let root = bash_state.workspace_root.canonicalize().unwrap_or_default();
+    let trash = root.join(TRASH_SUBDIR);
+    let message = if args.permanent || path.starts_with(&trash) {
+        remove_entry(&path)?;
+        format!("Deleted {} permanently", path.display())
+    } else {
+        let stamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis());
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let trashed = trash.join(format!("{stamp}-{name}"));
+        ensure_parent(&trashed)?;
+        move_entry(&path, &trashed)?;
+        format!(
+            "Moved {} to the trash: {} (restore with MoveFile; pass permanent=true to skip the \
+             trash)",
+            path.display(),
+            trashed.display()
+        )
+    };
+    forget_or_rekey(bash_state, &path, None);
+    Ok(message)
+}
+
+#[instrument(level = "info", skip(bash_state_arc, args))]
+pub async fn handle_create_directory(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    args: CreateDirectory,
+) -> Result<String> {
+    let mut guard = bash_state_arc.lock().await;
+    let bash_state = session(&mut guard, &args.thread_id)?;
+    let path = resolve_entry(bash_state, &args.path, &Access::Create)?;
+    if fs::symlink_metadata(&path).is_ok_and(|meta| !meta.is_dir()) {
+        return Err(WinxError::FileAccessError {
+            path,
+            message: "a file with this name already exists".to_string(),
+        });
+    }
+    fs::create_dir_all(&path).map_err(|e| io_error(&path, "creating", &e))?;
+    Ok(format!("Created directory {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use tempfile::TempDir;
+
+    fn state_in(dir: &TempDir) -> Arc<Mutex<Option<BashState>>> {
+        let mut bs = BashState::new();
+        let root = dir.path().canonicalize().unwrap();
+        bs.cwd = root.clone();
+        bs.workspace_root = root;
+        bs.current_thread_id = "t".to_string();
+        Arc::new(Mutex::new(Some(bs)))
+    }
+
+    #[tokio::test]
+    async fn move_copy_delete_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::write(root.join("a.txt"), "hello").unwrap();
+        let st = state_in(&dir);
+
+        let mkdir = CreateDirectory { path: "out/nested".into(), thread_id: "t".into() };
+        handle_create_directory(&st, mkdir).await.unwrap();
+        assert!(root.join("out/nested").is_dir());
+
+        let copy = CopyFile {
+            source: "a.txt".into(),
+            destination: "out/b.txt".into(),
+            overwrite: false,
+            thread_id: "t".into(),
+        };
+        handle_copy(&st, copy.clone()).await.unwrap();
+        assert_eq!(std::fs::read_to_string(root.join("out/b.txt")).unwrap(), "hello");
+        assert!(handle_copy(&st, copy).await.is_err(), "no silent overwrite");
+
+        let mv = MoveFile {
+            source: "out".into(),
+            destination: "moved".into(),
+            overwrite: false,
+            thread_id: "t".into(),
+        };
+        handle_move(&st, mv).await.unwrap();
+        assert!(root.join("moved/b.txt").is_file());
+
+        let del = |path: &str, recursive| DeleteFile {
+            path: path.into(),
+            recursive,
+            permanent: false,
+            thread_id: "t".into(),
+        };
+        assert!(handle_delete(&st, del("moved", false)).await.is_err());
+        let text = handle_delete(&st, del("moved", true)).await.unwrap();
+        assert!(text.contains(".winx/trash"), "got {text}");
+        assert!(!root.join("moved").exists());
+    }
+
+    #[tokio::test]
+    async fn refuses_protected_and_outside_paths() {
+        let dir = TempDir::new().unwrap();
+        let st = state_in(&dir);
+        let mkdir = |path: &str| CreateDirectory { path: path.into(), thread_id: "t".into() };
+        assert!(handle_create_directory(&st, mkdir(".git/objects/xx")).await.is_err());
+        assert!(handle_create_directory(&st, mkdir("../escape")).await.is_err());
+        let del = DeleteFile {
+            path: dir.path().to_string_lossy().to_string(),
+            recursive: true,
+            permanent: true,
+            thread_id: "t".into(),
+        };
+        assert!(handle_delete(&st, del).await.is_err(), "workspace root is never deletable");
+        // Nor the directories holding a protected path.
+        std::fs::create_dir_all(dir.path().join(".git/objects")).unwrap();
+        std::fs::create_dir_all(dir.path().join(".winx")).unwrap();
+        let del_git = DeleteFile {
+            path: ".git".into(),
+            recursive: true,
+            permanent: true,
+            thread_id: "t".into(),
+        };
+        assert!(handle_delete(&st, del_git).await.is_err());
+        let mv_winx = MoveFile {
+            source: ".winx".into(),
+            destination: "elsewhere".into(),
+            overwrite: false,
+            thread_id: "t".into(),
+        };
+        assert!(handle_move(&st, mv_winx).await.is_err());
+        assert!(dir.path().join(".git/objects").is_dir() && dir.path().join(".winx").is_dir());
+
+        // Overwriting a destination symlink replaces the link, not its target.
+        let outside = TempDir::new().unwrap();
+        let target = outside.path().join("victim.txt");
+        std::fs::write(&target, "untouched").unwrap();
+        std::fs::write(dir.path().join("a.txt"), "new").unwrap();
+        std::os::unix::fs::symlink(&target, dir.path().join("link.txt")).unwrap();
+        let copy = CopyFile {
+            source: "a.txt".into(),
+            destination: "link.txt".into(),
+            overwrite: true,
+            thread_id: "t".into(),
+        };
+        handle_copy(&st, copy).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "untouched");
+        assert!(!dir.path().join("link.txt").is_symlink());
+        assert_eq!(std::fs::read_to_string(dir.path().join("link.txt")).unwrap(), "new");
+    }
+}
@@ -6,6 +6,7 @@
 use regex::Regex;
 use sha2::{Digest, Sha256};
 use similar::{ChangeTag, TextDiff};
+use std::collections::HashSet;
 use std::fmt::Write as FmtWrite;
 use std::fs;
 use std::io::{BufWriter, Write};
@@ -17,7 +18,9 @@ use tracing::{debug, error, info, instrument, warn};
 use crate::errors::{Result, WinxError};
 use crate::state::bash_state::{BashState, FileWhitelistData};
 use crate::types::{normalize_thread_id, FileWriteOrEdit};
+use crate::utils::editorconfig::{self, EndOfLine, IndentStyle};
 use crate::utils::path::{expand_user, validate_path_in_workspace};
+use crate::utils::text_encoding::{self, Encoding};
 
 static SEARCH_MARKER: OnceLock<std::result::Result<Regex, regex::Error>> = OnceLock::new();
 static DIVIDER_MARKER: OnceLock<std::result::Result<Regex, regex::Error>> = OnceLock::new();
@@ -943,20 +946,31 @@ pub async fn handle_tool_call(
     file_write_or_edit: FileWriteOrEdit,
 ) -> Result<String> {
     let mut bash_state_guard = bash_state_arc.lock().await;
-    let bash_state = bash_state_guard.as_mut().ok_or(WinxError::BashStateNotInitialized)?;
+    let bash_state = bash_state_guard.as_ref().ok_or(WinxError::BashStateNotInitialized)?;
 
     let thread_id = normalize_thread_id(&file_write_or_edit.thread_id);
     if thread_id != bash_state.current_thread_id {
         return Err(WinxError::ThreadIdMismatch(thread_id));
     }
 
-    let planned = plan_edit(
-        bash_state,
-        &file_write_or_edit.file_path,
-        file_write_or_edit.percentage_to_change,
-        &file_write_or_edit.text_or_search_replace_blocks,
-    )?;
-    commit_edit(bash_state, planned)
+    // Plan and commit on the blocking pool, as MultiFileEdit does: besides the
+    // file IO, the project formatter can run for up to 30s. The guard stays held,
+    // so the slot remains locked while the state is away.
+    let mut state = bash_state_guard.take().ok_or(WinxError::BashStateNotInitialized)?;
+    let (state, result) = tokio::task::spawn_blocking(move || {
+        let result = plan_edit(
+            &state,
+            &file_write_or_edit.file_path,
+            file_write_or_edit.percentage_to_change,
+            &file_write_or_edit.text_or_search_replace_blocks,
+        )
+        .and_then(|planned| commit_edit(&mut state, planned));
+        (state, result)
+    })
+    .await
+    .map_err(|e| WinxError::CommandExecutionError(format!("FileWriteOrEdit task failed: {e}")))?;
+    *bash_state_guard = Some(state);
+    result
 }
 
 /// A validated, computed edit that has not yet touched disk. Produced by
@@ -971,6 +985,8 @@ pub(crate) struct PlannedEdit {
     new_content: String,
     /// Prior on-disk content, for the post-edit diff. `None` for a new file.
     previous: Option<String>,
+    /// On-disk encoding to write with (the existing file's, or `.editorconfig`'s).
+    encoding: Encoding,
     tolerances: Vec<ToleranceKind>,
     uses_search_replace: bool,
 }
@@ -980,6 +996,30 @@ impl PlannedEdit {
     pub(crate) fn target(&self) -> &str {
         &self.file_path_str
     }
+
+    /// Fail if the file on disk no longer matches what this edit was planned
+    /// against.
+    pub(crate) fn verify_unchanged(&self) -> Result<()> {
+        verify_unchanged(&self.path, self.previous.as_deref())
+    }
+
+    /// What committing this edit would do, for `--dry-run`: the target and the
+    /// diff, without formatting, writing or running hooks.
+    fn preview(&self) -> String {
+        let mut out = format!("Dry run: would have {} {}", self.action, self.file_path_str);
+        match self.previous.as_deref() {
+            None => {
+                let _ = write!(out, " (new file, {} lines)", self.new_content.lines().count());
+            }
+            Some(previous) => match change_summary(previous, &self.new_content) {
+                Some(diff) => {
+                    let _ = write!(out, "\n\n{diff}");
+                }
+                None => out.push_str(" (no changes)"),
+            },
+        }
+        out
+    }
 }
 
 /// Validate and compute an edit WITHOUT writing: resolve + workspace-confine the
@@ -1002,6 +1042,7 @@ pub(crate) fn plan_edit(
 
     let path = validate_path_in_workspace(&path, &bash_state.workspace_root)
         .map_err(|e| WinxError::PathSecurityError { path: path.clone(), message: e.to_string() })?;
+    crate::utils::protected_paths::check_path(&path, &bash_state.workspace_root)?;
 
     let file_path_str = path.to_string_lossy().to_string();
 
@@ -1015,7 +1056,8 @@ pub(crate) fn plan_edit(
     if !operation_allowed {
         return Err(WinxError::FileAccessError {
             path,
-            message: "File operation not allowed in current mode.".to_string(),
+            message: "File operation not allowed by the current mode or .winx/config.toml [files]."
+                .to_string(),
         });
     }
 
@@ -1023,13 +1065,15 @@ pub(crate) fn plan_edit(
     // search/replace input, and the post-edit diff. Reading it twice opened a
     // TOCTOU window where an external write between the hash check and the edit
     // would apply the edit to (and diff against) content the hash never vetted.
-    let pre_write_content: Option<String> = if path.exists() {
-        Some(fs::read_to_string(&path).map_err(|e| WinxError::FileAccessError {
-            path: path.clone(),
-            message: format!("reading existing file before edit: {e}"),
-        })?)
+    let (pre_write_content, encoding) = if path.exists() {
+        let (content, encoding) =
+            text_encoding::read_text(&path).map_err(|e| WinxError::FileAccessError {
+                path: path.clone(),
+                message: format!("reading existing file before edit: {e}"),
+            })?;
+        (Some(content), Some(encoding))
     } else {
-        None
+        (None, None)
     };
 
     if let Some(original_content) = pre_write_content.as_deref() {
@@ -1046,13 +1090,9 @@ pub(crate) fn plan_edit(
             })?;
         let current_hash = hash_content(original_content);
         if whitelist.file_hash != current_hash {
-            return Err(WinxError::FileAccessError {
-                path,
-                message: format!(
-                    "{file_path_str} changed on disk since you last read it. \
-                     Call ReadFiles again to get the current content, then retry the edit."
-                ),
-            });
+            let diff =
+                external_change_diff(whitelist.seen_content.as_deref(), Some(original_content));
+            return Err(WinxError::FileChangedExternally { path, diff });
         }
         if !uses_search_replace && !whitelist.is_read_enough() {
             return Err(WinxError::FileAccessError {
@@ -1074,6 +1114,8 @@ pub(crate) fn plan_edit(
     } else {
         ("wrote", blocks.to_string(), Vec::new())
     };
+    let (new_content, encoding) =
+        conform_to_file(&path, pre_write_content.as_deref(), encoding, new_content)?;
 
     Ok(PlannedEdit {
         path,
@@ -1081,28 +1123,187 @@ pub(crate) fn plan_edit(
         action,
         new_content,
         previous: pre_write_content,
+        encoding,
         tolerances,
         uses_search_replace,
     })
 }
 
+/// Plan a rewrite that a tool computed from content it read itself (e.g.
+/// `RenameSymbol`), not from text the model supplied: the same path, protection
+/// and mode checks as [`plan_edit`], but no read-before-edit gate, since nothing
+/// is applied blind. Refused if the file no longer holds `previous`.
+pub(crate) fn plan_rewrite(
+    bash_state: &BashState,
+    path: &Path,
+    previous: &str,
+    new_content: String,
+) -> Result<PlannedEdit> {
+    let path = validate_path_in_workspace(path, &bash_state.workspace_root).map_err(|e| {
+        WinxError::PathSecurityError { path: path.to_path_buf(), message: e.to_string() }
+    })?;
+    crate::utils::protected_paths::check_path(&path, &bash_state.workspace_root)?;
+    let file_path_str = path.to_string_lossy().to_string();
+    if !bash_state.is_file_edit_allowed(&file_path_str) {
+        return Err(WinxError::FileAccessError {
+            path,
+            message: "File operation not allowed by the current mode or .winx/config.toml [files]."
+                .to_string(),
+        });
+    }
+
+    let (on_disk, encoding) =
+        text_encoding::read_text(&path).map_err(|e| WinxError::FileAccessError {
+            path: path.clone(),
+            message: format!("reading existing file before edit: {e}"),
+        })?;
+    if on_disk != previous {
+        let diff = external_change_diff(Some(previous), Some(&on_disk));
+        return Err(WinxError::FileChangedExternally { path, diff });
+    }
+    let (new_content, encoding) =
+        conform_to_file(&path, Some(&on_disk), Some(encoding), new_content)?;
+
+    Ok(PlannedEdit {
+        path,
+        file_path_str,
+        action: "edited",
+        new_content,
+        previous: Some(on_disk),
+        encoding,
+        tolerances: Vec::new(),
+        uses_search_replace: true,
+    })
+}
+
+/// Shape the text about to be written like the file it replaces: keep the
+/// original line endings and encoding, and apply `.editorconfig` (indentation
+/// of changed lines, final newline, and line endings/charset for new files).
+/// Fails if the text can't be represented in the file's encoding.
+fn conform_to_file(
+    path: &Path,
+    previous: Option<&str>,
+    encoding: Option<Encoding>,
+    content: String,
+) -> Result<(String, Encoding)> {
+    let config = editorconfig::resolve(path);
+    let crlf = match previous {
+        Some(previous) => previous.contains("\r\n"),
+        None => match config.end_of_line {
+            Some(eol) => eol == EndOfLine::Crlf,
+            None => content.contains("\r\n"),
+        },
+    };
+    let encoding = encoding.unwrap_or_else(|| {
+        config.charset.as_deref().and_then(Encoding::from_charset).unwrap_or_default()
+    });
+
+    let mut text = if content.contains('\r') { content.replace("\r\n", "\n") } else { content };
+    if let Some(style) = config.indent_style {
+        // Only lines the agent wrote: untouched lines keep their indentation so
+        // an edit doesn't reformat the rest of a legacy file.
+        let unchanged: HashSet<&str> =
+            previous.unwrap_or_default().lines().map(|l| l.trim_end_matches('\r')).collect();
+        text = reindent_changed(&text, style, config.indent_size.unwrap_or(4), &unchanged);
+    }
+    match config.insert_final_newline {
+        Some(true) if !text.is_empty() && !text.ends_with('\n') => text.push('\n'),
+        Some(false) => text.truncate(text.trim_end_matches('\n').len()),
+        _ => {}
+    }
+    if crlf {
+        text = text.replace('\n', "\r\n");
+    }
+
+    if let Err(c) = text_encoding::encode(&text, encoding) {
+        return Err(WinxError::FileWriteError {
+            path: path.to_path_buf(),
+            message: format!(
+                "'{c}' (U+{:04X}) can't be written to this {} file; use an ASCII \
+                 replacement or escape instead",
+                u32::from(c),
+                encoding.name()
+            ),
+        });
+    }
+    Ok((text, encoding))
+}
+
+/// Re-indent the lines of `text` not found in `unchanged` to `style`, counting
+/// a tab as `size` columns. Alignment narrower than one indent level is kept.
+fn reindent_changed(
+    text: &str,
+    style: IndentStyle,
+    size: usize,
+    unchanged: &HashSet<&str>,
+) -> String {
+    let mut out = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let body = line.strip_suffix('\n').unwrap_or(line);
+        let rest = body.trim_start_matches([' ', '\t']);
+        let indent = &body[..body.len() - rest.len()];
+        let width: usize = indent.chars().map(|c| if c == '\t' { size } else { 1 }).sum();
+        let wrong = match style {
+            IndentStyle::Space => indent.contains('\t'),
+            IndentStyle::Tab => indent.contains(' ') && width >= size,
+        };
+        if !wrong || rest.is_empty() || unchanged.contains(body) {
+            out.push_str(line);
+            continue;
+        }
+        match style {
+            IndentStyle::Space => out.push_str(&" ".repeat(width)),
+            IndentStyle::Tab => {
+                out.push_str(&"\t".repeat(width / size));
+                out.push_str(&" ".repeat(width % size));
+            }
+        }
+        out.push_str(&line[indent.len()..]);
+    }
+    out
+}
+
 /// Write a [`PlannedEdit`] to disk atomically and refresh the whitelist/stats.
 /// Returns the success message (including the post-edit diff). This is the only
 /// step that mutates the filesystem.
+///
+/// Blocking: the formatter, syntax checks and `post_edit` hooks run here, so
+/// async handlers go through [`commit_edit_blocking`] or `spawn_blocking`.
 pub(crate) fn commit_edit(bash_state: &mut BashState, planned: PlannedEdit) -> Result<String> {
+    if crate::utils::dry_run::enabled() {
+        return Ok(planned.preview());
+    }
     let PlannedEdit {
         path,
         file_path_str,
         action,
         new_content,
         previous,
+        encoding,
         tolerances,
         uses_search_replace,
     } = planned;
 
+    // Opt-in project formatter, applied in memory so the formatted text is what
+    // gets written, diffed, syntax-checked and whitelisted.
+    let (new_content, format_note) =
+        apply_formatter(&bash_state.workspace_root, &path, new_content);
+
+    // Last check right before the write: the file may have changed since it was
+    // planned (MultiFileEdit plans every file before committing any).
+    verify_unchanged(&path, previous.as_deref())?;
+
     // `mkdir -p` for new files (no-op for edits, whose parent already exists).
     ensure_parent_dirs(&path)?;
-    write_no_follow(&path, new_content.as_bytes())?;
+    let bytes =
+        text_encoding::encode(&new_content, encoding).map_err(|c| WinxError::FileWriteError {
+            path: path.clone(),
+            message: format!(
+                "the formatter produced '{c}', which {} can't encode",
+                encoding.name()
+            ),
+        })?;
+    write_no_follow(&path, &bytes)?;
 
     // Record an undo checkpoint AFTER the write succeeded (never a phantom one for
     // a failed edit). Only existing files: a brand-new file has no prior content
@@ -1117,7 +1318,7 @@ pub(crate) fn commit_edit(bash_state: &mut BashState, planned: PlannedEdit) -> R
         });
     }
 
-    let result = operation_result(
+    let mut result = operation_result(
         action,
         &file_path_str,
         &path,
@@ -1125,6 +1326,16 @@ pub(crate) fn commit_edit(bash_state: &mut BashState, planned: PlannedEdit) -> R
         &tolerances,
         previous.as_deref(),
     );
+    if let Some(note) = format_note {
+        let _ = write!(result, "\n\n{note}");
+    }
+    let hooks = crate::utils::hooks::run(
+        &bash_state.workspace_root,
+        &crate::utils::hooks::Event::PostEdit(&path),
+    );
+    if let Some(note) = hooks.render() {
+        let _ = write!(result, "\n\n{note}");
+    }
     refresh_whitelist_and_stats(
         bash_state,
         file_path_str,
@@ -1135,6 +1346,49 @@ pub(crate) fn commit_edit(bash_state: &mut BashState, planned: PlannedEdit) -> R
     Ok(result)
 }
 
+/// [`commit_edit`] on the blocking pool, for an async handler holding the
+/// session `slot`: syntax checks such as `cargo check` run for up to 60s. The
+/// slot stays locked by the caller's guard while the state is away.
+pub(crate) async fn commit_edit_blocking(
+    slot: &mut Option<BashState>,
+    planned: PlannedEdit,
+) -> Result<String> {
+    let mut state = slot.take().ok_or(WinxError::BashStateNotInitialized)?;
+    let (state, result) = tokio::task::spawn_blocking(move || {
+        let result = commit_edit(&mut state, planned);
+        (state, result)
+    })
+    .await
+    .map_err(|e| WinxError::CommandExecutionError(format!("edit task failed: {e}")))?;
+    *slot = Some(state);
+    result
+}
+
+/// Run the workspace's formatter (if enabled) over `content`, returning the text
+/// to write and a note for the result: what the formatter changed relative to
+/// the agent's edit (so its next SEARCH block matches), or why it was skipped.
+fn apply_formatter(
+    workspace_root: &Path,
+    path: &Path,
+    content: String,
+) -> (String, Option<String>) {
+    use crate::utils::formatter::{self, Formatted};
+    match formatter::format(workspace_root, path, &content) {
+        None => (content, None),
+        Some(Formatted::Changed { formatter, content: formatted }) => {
+            let note = change_summary(&content, &formatted).map(|diff| {
+                format!("Auto-formatted with {formatter}. Formatter {}", diff.replacen('C', "c", 1))
+            });
+            (formatted, note)
+        }
+        Some(Formatted::Failed { formatter, message }) => {
+            let note =
+                format!("Auto-format skipped, {formatter} failed (written as-is): {message}");
+            (content, Some(note))
+        }
+    }
+}
+
 /// After a successful write, re-read the file to re-whitelist it at its new hash
 /// (so a follow-up edit sees a fresh, fully-read entry) and record the
 /// edit/write in the workspace stats. Stats failures are non-fatal — they only
@@ -1154,9 +1408,10 @@ fn refresh_whitelist_and_stats(
     // later ReadFiles sees them and the hash matches.
     let hash = hash_content(new_content);
     let total_lines = new_content.lines().count();
-    bash_state
-        .whitelist_for_overwrite
-        .insert(file_path_str, FileWhitelistData::new(hash, vec![(1, total_lines)], total_lines));
+    let mut whitelist = FileWhitelistData::new(hash, vec![(1, total_lines)], total_lines);
+    whitelist.set_seen_content(new_content);
+    bash_state.whitelist_for_overwrite.insert(file_path_str, whitelist);
+    crate::utils::symbol_cache::invalidate(path);
 
     let (kind, stats) = if uses_search_replace {
         ("edit", crate::utils::workspace_stats::record_edit(&bash_state.workspace_root, path))
@@ -1168,6 +1423,43 @@ fn refresh_whitelist_and_stats(
     }
 }
 
+/// Fail with [`WinxError::FileChangedExternally`] if `path` no longer holds
+/// `expected` (`None` = the file must not exist yet).
+pub(crate) fn verify_unchanged(path: &Path, expected: Option<&str>) -> Result<()> {
+    let on_disk = match text_encoding::read_text(path) {
+        Ok((content, _)) => Some(content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => {
+            return Err(WinxError::FileAccessError {
+                path: path.to_path_buf(),
+                message: format!("re-reading file before write: {e}"),
+            });
+        }
+    };
+    if on_disk.as_deref() == expected {
+        return Ok(());
+    }
+    Err(WinxError::FileChangedExternally {
+        path: path.to_path_buf(),
+        diff: external_change_diff(expected, on_disk.as_deref()),
+    })
+}
+
+/// What changed between the version winx last saw and what is on disk now, for
+/// a [`WinxError::FileChangedExternally`] message.
+fn external_change_diff(seen: Option<&str>, on_disk: Option<&str>) -> String {
+    match (seen, on_disk) {
+        (Some(seen), Some(now)) => change_summary(seen, now)
+            .map(|diff| format!("External {}", diff.replacen("Changes", "changes", 1)))
+            .unwrap_or_default(),
+        (None, Some(_)) => "(The version you read is not kept for large files, so no diff is \
+                            available.)"
+            .to_string(),
+        (Some(_), None) => "(The file was deleted.)".to_string(),
+        (None, None) => String::new(),
+    }
+}
+
 /// Lines of context shown around each hunk in the post-edit diff.
 const DIFF_CONTEXT_LINES: usize = 3;
 /// Cap on the rendered diff. Past this, the success message carries only the
@@ -1234,7 +1526,9 @@ fn operation_result(
     if let Some(diff) = previous.and_then(|prev| change_summary(prev, content)) {
         let _ = write!(result, "\n\n{diff}");
     }
-    if let Some(warning) = crate::utils::syntax::syntax_warning(path, content) {
+    if let Some(warning) = crate::utils::syntax::syntax_warning(path, content)
+        .or_else(|| crate::utils::syntax::external_check_warning(path))
+    {
         let _ = write!(result, "\n\n{warning}");
     }
     result
@@ -1380,6 +1674,62 @@ mod indentation_tests {
         Ok(())
     }
 
+    #[test]
+    fn reindent_only_touches_changed_lines() {
+        let previous = "fn a() {\n\told();\n}\n";
+        let unchanged: HashSet<&str> = previous.lines().collect();
+        let text = "fn a() {\n\told();\n\t\tnew();\n}\n";
+        let out = reindent_changed(text, IndentStyle::Space, 4, &unchanged);
+        assert_eq!(out, "fn a() {\n\told();\n        new();\n}\n");
+
+        let out = reindent_changed("x\n      y\n", IndentStyle::Tab, 4, &HashSet::new());
+        assert_eq!(out, "x\n\t  y\n");
+    }
+
+    #[test]
+    fn new_files_follow_editorconfig() -> Result<()> {
+        let dir = tempfile::TempDir::new()?;
+        fs::write(
+            dir.path().join(".editorconfig"),
+            "root = true\n[*.txt]\nend_of_line = crlf\ninsert_final_newline = true\n\
+             charset = utf-8-bom\n",
+        )?;
+        let path = dir.path().join("new.txt");
+        let (text, encoding) = conform_to_file(&path, None, None, "a\nb".to_string())?;
+        assert_eq!(text, "a\r\nb\r\n");
+        assert_eq!(encoding, Encoding::Utf8Bom);
+
+        // An existing file keeps its own line endings and encoding.
+        let (text, encoding) =
+            conform_to_file(&path, Some("a\nb\n"), Some(Encoding::Latin1), "a\nc".to_string())?;
+        assert_eq!(text, "a\nc\n");
+        assert_eq!(encoding, Encoding::Latin1);
+        assert!(
+            conform_to_file(&path, Some(""), Some(Encoding::Latin1), "\u{20ac}".into()).is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn verify_unchanged_reports_external_diff() -> Result<()> {
+        let dir = tempfile::TempDir::new()?;
+        let path = dir.path().join("f.txt");
+        fs::write(&path, "one\ntwo\n")?;
+        verify_unchanged(&path, Some("one\ntwo\n"))?;
+
+        fs::write(&path, "one\nTWO\n")?;
+        match verify_unchanged(&path, Some("one\ntwo\n")) {
+            Err(WinxError::FileChangedExternally { diff, .. }) => {
+                assert!(diff.starts_with("External changes"), "got {diff}");
+                assert!(diff.contains("+TWO"), "got {diff}");
+            }
+            other => return Err(WinxError::ArgumentParseError(format!("got {other:?}"))),
+        }
+        // A file planned as new must still not exist at write time.
+        assert!(verify_unchanged(&path, None).is_err());
+        Ok(())
+    }
+
     #[test]
     fn change_summary_is_none_for_identical_content() {
         assert!(change_summary("a\nb\nc\n", "a\nb\nc\n").is_none());
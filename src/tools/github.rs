@@ -0,0 +1,536 @@
+//! Implementation of the `GitHub` tool: issues, pull requests and Actions runs
+//! of the workspace's repository, so the fix → commit → PR loop can close
+//! without leaving the agent.
+//!
+//! The repository is the workspace's `origin` remote, and requests go to the
+//! REST API through `curl`. The token comes from `GITHUB_TOKEN`/`GH_TOKEN` or
+//! `[github] token` in the global `~/.winx/config.toml` (never a project's).
+//! It reaches curl through a private header file, so it never shows up on a
+//! command line, and curl drops it on redirects to other hosts (log downloads).
+
+use std::fmt::Write as FmtWrite;
+use std::io::Write as IoWrite;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+use crate::errors::{Result, WinxError};
+use crate::state::bash_state::BashState;
+use crate::types::{GitHub, GitHubOperation};
+
+const API: &str = "https://api.github.com";
+const CURL_TIMEOUT_SECS: u64 = 60;
+const DEFAULT_ISSUES: usize = 30;
+const DEFAULT_RUNS: usize = 10;
+const MAX_LIMIT: usize = 100;
+/// Jobs whose logs `run_logs` fetches.
+const MAX_LOG_JOBS: usize = 5;
+/// Lines kept from the end of each job log.
+const LOG_TAIL_LINES: usize = 150;
+
+/// The repository and credentials a call works against.
+struct Repo {
+    owner: String,
+    name: String,
+    token: String,
+}
+
+impl Repo {
+    fn path(&self, rest: &str) -> String {
+        format!("/repos/{}/{}{rest}", self.owner, self.name)
+    }
+}
+
+/// `(owner, repo)` from a GitHub remote URL (https, ssh or scp-like).
+fn parse_remote(url: &str) -> Option<(String, String)> {
+    let (scheme, rest) = url.trim().split_once("github.com")?;
+    if !(scheme.is_empty() || scheme.ends_with('@') || scheme.ends_with("//")) {
+        return None;
+    }
+    let rest = rest.strip_prefix(':').or_else(|| rest.strip_prefix('/'))?;
+    let rest = rest.trim_end_matches('/');
+    let rest = rest.strip_suffix(".git").unwrap_or(rest);
+    let (owner, name) = rest.split_once('/')?;
+    (!owner.is_empty() && !name.is_empty() && !name.contains('/'))
+        .then(|| (owner.to_string(), name.to_string()))
+}
+
+fn token() -> Option<String> {
+    ["GITHUB_TOKEN", "GH_TOKEN"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|token| !token.trim().is_empty()))
+        .or_else(crate::utils::project_config::github_token)
+}
+
+/// Trimmed stdout of `git <args>` in `cwd`, if it succeeded.
+async fn git(cwd: &Path, args: &[&str]) -> Option<String> {
+    let output = tokio::process::Command::new("git")
+        .arg("-C")
+        .arg(cwd)
+        .args(args)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+async fn current_branch(cwd: &Path) -> Result<String> {
+    match git(cwd, &["rev-parse", "--abbrev-ref", "HEAD"]).await {
+        Some(branch) if branch != "HEAD" && !branch.is_empty() => Ok(branch),
+        _ => Err(WinxError::InvalidInput(
+            "not on a branch (detached HEAD or no commits yet)".to_string(),
+        )),
+    }
+}
+
+/// Percent-encode `text` for a URL query value.
+fn encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                char::from(b).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+/// GitHub's error message from a failed response body.
+fn api_error(body: &[u8]) -> String {
+    let Ok(value) = serde_json::from_slice::<Value>(body) else {
+        return String::from_utf8_lossy(body).chars().take(300).collect();
+    };
+    let mut message = value["message"].as_str().unwrap_or("request failed").to_string();
+    let details: Vec<&str> = value["errors"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|error| error["message"].as_str())
+        .collect();
+    if !details.is_empty() {
+        let _ = write!(message, " ({})", details.join("; "));
+    }
+    message
+}
+
+/// Send one request, returning the response body of a 2xx answer.
+async fn request(repo: &Repo, method: &str, path: &str, body: Option<&Value>) -> Result<Vec<u8>> {
+    let mut headers = tempfile::Builder::new().prefix("winx-github-").tempfile()?;
+    writeln!(headers, "Authorization: Bearer {}", repo.token)?;
+    writeln!(headers, "Accept: application/vnd.github+json")?;
+    writeln!(headers, "X-GitHub-Api-Version: 2022-11-28")?;
+    headers.flush()?;
+
+    let mut command = tokio::process::Command::new("curl");
+    command
+        .args(["--silent", "--show-error", "--location", "--proto", "=https"])
+        .args(["--proto-redir", "=https", "--request", method])
+        .args(["--max-time", &CURL_TIMEOUT_SECS.to_string()])
+        .arg("--header")
+        .arg(format!("@{}", headers.path().display()))
+        .args(["--write-out", "\n%{http_code}"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if body.is_some() {
+        command.args(["--header", "Content-Type: application/json", "--data-binary", "@-"]);
+    }
+    let mut child = command
+        .arg("--")
+        .arg(format!("{API}{path}"))
+        .spawn()
+        .map_err(|e| WinxError::CommandExecutionError(format!("failed to run curl: {e}")))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Some(body) = body {
+            stdin.write_all(body.to_string().as_bytes()).await?;
+        }
+    }
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        return Err(WinxError::CommandExecutionError(format!(
+            "GitHub request failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    let mut stdout = output.stdout;
+    let split = stdout.iter().rposition(|&b| b == b'\n').unwrap_or(0);
+    let status: u16 = String::from_utf8_lossy(&stdout[split..]).trim().parse().unwrap_or(0);
+    stdout.truncate(split);
+    if !(200..300).contains(&status) {
+        return Err(WinxError::CommandExecutionError(format!(
+            "GitHub API {method} {path}: HTTP {status}: {}",
+            api_error(&stdout)
+        )));
+    }
+    Ok(stdout)
+}
+
+async fn api(repo: &Repo, method: &str, path: &str, body: Option<&Value>) -> Result<Value> {
+    let bytes = request(repo, method, path, body).await?;
+    serde_json::from_slice(&bytes)
+        .map_err(|e| WinxError::DeserializationError(format!("GitHub response: {e}")))
+}
+
+fn limit(requested: usize, default: usize) -> usize {
+    if requested == 0 {
+        default
+    } else {
+        requested.min(MAX_LIMIT)
+    }
+}
+
+fn required<'a>(value: &'a str, field: &str, operation: &str) -> Result<&'a str> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(WinxError::MissingParameterError {
+            field: field.to_string(),
+            message: format!("{operation} needs {field}"),
+        });
+    }
+    Ok(value)
+}
+
+fn render_issues(issues: &Value) -> String {
+    let issues: Vec<&Value> = issues
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|issue| issue.get("pull_request").is_none())
+        .collect();
+    if issues.is_empty() {
+        return "No issues.".to_string();
+    }
+    let mut out = String::new();
+    for issue in issues {
+        let _ = write!(out, "#{} {}", issue["number"], issue["title"].as_str().unwrap_or(""));
+        let labels: Vec<&str> = issue["labels"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|label| label["name"].as_str())
+            .collect();
+        if !labels.is_empty() {
+            let _ = write!(out, " [{}]", labels.join(", "));
+        }
+        let _ = writeln!(
+            out,
+            " ({}, {}, {} comment(s))",
+            issue["state"].as_str().unwrap_or("?"),
+            issue["user"]["login"].as_str().unwrap_or("?"),
+            issue["comments"]
+        );
+    }
+    out.trim_end().to_string()
+}
+
+/// Every comment on a pull request, oldest first: `(created_at, line)`.
+fn render_comments(conversation: &Value, reviews: &Value, inline: &Value) -> String {
+    let mut entries: Vec<(String, String)> = Vec::new();
+    let mut push = |item: &Value, time_key: &str, context: String| {
+        let body = item["body"].as_str().unwrap_or("").trim();
+        if body.is_empty() {
+            return;
+        }
+        let time = item[time_key].as_str().unwrap_or("").to_string();
+        let author = item["user"]["login"].as_str().unwrap_or("?");
+        entries.push((time.clone(), format!("{author} ({time}){context}:\n{body}")));
+    };
+    for comment in conversation.as_array().into_iter().flatten() {
+        push(comment, "created_at", String::new());
+    }
+    for review in reviews.as_array().into_iter().flatten() {
+        let state = review["state"].as_str().unwrap_or("").to_lowercase().replace('_', " ");
+        push(review, "submitted_at", format!(" [review: {state}]"));
+    }
+    for comment in inline.as_array().into_iter().flatten() {
+        let line = comment["line"].as_u64().or_else(|| comment["original_line"].as_u64());
+        let location = match line {
+            Some(line) => format!("{}:{line}", comment["path"].as_str().unwrap_or("?")),
+            None => comment["path"].as_str().unwrap_or("?").to_string(),
+        };
+        push(comment, "created_at", format!(" [{location}]"));
+    }
+    if entries.is_empty() {
+        return "No comments.".to_string();
+    }
+    entries.sort();
+    entries.into_iter().map(|(_, text)| text).collect::<Vec<_>>().join("\n\n")
+}
+
+fn render_runs(runs: &Value, branch: &str) -> String {
+    let runs = runs["workflow_runs"].as_array().map(Vec::as_slice).unwrap_or_default();
+    if runs.is_empty() {
+        return format!("No Actions runs for {branch}.");
+    }
+    let mut out = format!("Actions runs for {branch}, newest first:");
+    for run in runs {
+        let outcome = run["conclusion"].as_str().or_else(|| run["status"].as_str()).unwrap_or("?");
+        let _ = write!(
+            out,
+            "\n{}  {:<10} {} ({}, {}, {})",
+            run["id"],
+            outcome,
+            run["name"].as_str().unwrap_or("?"),
+            run["event"].as_str().unwrap_or("?"),
+            run["head_sha"].as_str().unwrap_or("").chars().take(7).collect::<String>(),
+            run["created_at"].as_str().unwrap_or("?"),
+        );
+    }
+    out
+}
+
+/// The last `LOG_TAIL_LINES` lines of a job log, without the timestamp GitHub
+/// puts on each line.
+fn log_tail(log: &str) -> String {
+    let lines: Vec<&str> = log
+        .lines()
+        .map(|line| match line.split_once(' ') {
+            Some((stamp, rest))
+                if stamp.len() >= 20 && stamp.ends_with('Z') && stamp.contains('T') =>
+            {
+                rest
+            }
+            _ => line,
+        })
+        .collect();
+    lines[lines.len().saturating_sub(LOG_TAIL_LINES)..].join("\n")
+}
+
+async fn run_logs(repo: &Repo, run_id: u64) -> Result<String> {
+    let jobs =
+        api(repo, "GET", &repo.path(&format!("/actions/runs/{run_id}/jobs?per_page=100")), None)
+            .await?;
+    let jobs = jobs["jobs"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let failed: Vec<&Value> = jobs.iter().filter(|job| job["conclusion"] == "failure").collect();
+    let (picked, note) = if failed.is_empty() {
+        (jobs.iter().collect::<Vec<_>>(), "no job failed; showing every job")
+    } else {
+        (failed, "failed jobs")
+    };
+    let mut out = format!("Run {run_id}: {} job(s), {note}.", jobs.len());
+    for job in picked.iter().take(MAX_LOG_JOBS) {
+        let _ = write!(
+            out,
+            "\n\n== {} ({}) ==",
+            job["name"].as_str().unwrap_or("?"),
+            job["conclusion"].as_str().or_else(|| job["status"].as_str()).unwrap_or("?")
+        );
+        let failed_steps: Vec<&str> = job["steps"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|step| step["conclusion"] == "failure")
+            .filter_map(|step| step["name"].as_str())
+            .collect();
+        if !failed_steps.is_empty() {
+            let _ = write!(out, "\nFailed step(s): {}", failed_steps.join(", "));
+        }
+        let log =
+            request(repo, "GET", &repo.path(&format!("/actions/jobs/{}/logs", job["id"])), None)
+                .await;
+        match log {
+            Ok(bytes) => {
+                let _ = write!(out, "\n{}", log_tail(&String::from_utf8_lossy(&bytes)));
+            }
+            Err(e) => {
+                let _ = write!(out, "\n(log unavailable: {e})");
+            }
+        }
+    }
+    if picked.len() > MAX_LOG_JOBS {
+        let _ = write!(out, "\n\n{} more job(s) not shown.", picked.len() - MAX_LOG_JOBS);
+    }
+    Ok(out)
+}
+
+async fn create_pull_request(repo: &Repo, cwd: &Path, args: &GitHub) -> Result<String> {
+    let title = required(&args.title, "title", "create_pull_request")?;
+    let head = current_branch(cwd).await?;
+    if git(cwd, &["rev-parse", "--abbrev-ref", "@{upstream}"]).await.is_none() {
+        return Err(WinxError::InvalidInput(format!(
+            "{head} has not been pushed; push it first (git push -u origin {head})"
+        )));
+    }
+    let unpushed = git(cwd, &["rev-list", "--count", "@{upstream}..HEAD"])
+        .await
+        .and_then(|count| count.parse::<u64>().ok())
+        .unwrap_or(0);
+    let base = if args.base.trim().is_empty() {
+        let info = api(repo, "GET", &repo.path(""), None).await?;
+        info["default_branch"].as_str().unwrap_or("main").to_string()
+    } else {
+        args.base.trim().to_string()
+    };
+    if base == head {
+        return Err(WinxError::InvalidInput(format!(
+            "the current branch is the base branch ({base}); commit to a feature branch first"
+        )));
+    }
+    let body = json!({ "title": title, "body": args.body, "head": head, "base": base });
+    let pull = api(repo, "POST", &repo.path("/pulls"), Some(&body)).await?;
+    let mut out = format!(
+        "Opened pull request #{} ({head} → {base}): {}",
+        pull["number"],
+        pull["html_url"].as_str().unwrap_or("")
+    );
+    if unpushed > 0 {
+        let _ = write!(
+            out,
+            "\nNote: {unpushed} local commit(s) are not pushed yet and are not in the PR."
+        );
+    }
+    Ok(out)
+}
+
+async fn run(repo: &Repo, cwd: &Path, args: &GitHub) -> Result<String> {
+    match args.operation {
+        GitHubOperation::ListIssues => {
+            let state = match args.state.trim() {
+                "" => "open",
+                state @ ("open" | "closed" | "all") => state,
+                other => {
+                    return Err(WinxError::InvalidInput(format!(
+                        "state must be open, closed or all (got {other:?})"
+                    )))
+                }
+            };
+            let per_page = limit(args.limit, DEFAULT_ISSUES);
+            let path = repo.path(&format!("/issues?state={state}&per_page={per_page}"));
+            Ok(render_issues(&api(repo, "GET", &path, None).await?))
+        }
+        GitHubOperation::CreateIssue => {
+            let title = required(&args.title, "title", "create_issue")?;
+            let body = json!({ "title": title, "body": args.body });
+            let issue = api(repo, "POST", &repo.path("/issues"), Some(&body)).await?;
+            Ok(format!(
+                "Created issue #{}: {}",
+                issue["number"],
+                issue["html_url"].as_str().unwrap_or("")
+            ))
+        }
+        GitHubOperation::CreatePullRequest => create_pull_request(repo, cwd, args).await,
+        GitHubOperation::PrComments => {
+            let number = args.number.ok_or_else(|| WinxError::MissingParameterError {
+                field: "number".to_string(),
+                message: "pr_comments needs the pull request number".to_string(),
+            })?;
+            let conversation_path = repo.path(&format!("/issues/{number}/comments?per_page=100"));
+            let reviews_path = repo.path(&format!("/pulls/{number}/reviews?per_page=100"));
+            let inline_path = repo.path(&format!("/pulls/{number}/comments?per_page=100"));
+            let (conversation, reviews, inline) = tokio::try_join!(
+                api(repo, "GET", &conversation_path, None),
+                api(repo, "GET", &reviews_path, None),
+                api(repo, "GET", &inline_path, None),
+            )?;
+            Ok(render_comments(&conversation, &reviews, &inline))
+        }
+        GitHubOperation::CiStatus => {
+            let branch = if args.branch.trim().is_empty() {
+                current_branch(cwd).await?
+            } else {
+                args.branch.trim().to_string()
+            };
+            let per_page = limit(args.limit, DEFAULT_RUNS);
+            let path =
+                repo.path(&format!("/actions/runs?branch={}&per_page={per_page}", encode(&branch)));
+            Ok(render_runs(&api(repo, "GET", &path, None).await?, &branch))
+        }
+        GitHubOperation::RunLogs => {
+            let run_id = args.run_id.ok_or_else(|| WinxError::MissingParameterError {
+                field: "run_id".to_string(),
+                message: "run_logs needs the run id (see ci_status)".to_string(),
+            })?;
+            run_logs(repo, run_id).await
+        }
+    }
+}
+
+#[instrument(level = "info", skip(bash_state_arc, args))]
+pub async fn handle_tool_call(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    args: GitHub,
+) -> Result<String> {
+    let cwd = {
+        let mut guard = bash_state_arc.lock().await;
+        let bash_state = crate::tools::file_ops::session(&mut guard, &args.thread_id)?;
+        // The requests run curl; a restricted mode has to allow it.
+        if !bash_state.is_command_allowed("curl") {
+            return Err(WinxError::CommandNotAllowed(
+                "GitHub not allowed in current mode (allow the `curl` command to enable it)"
+                    .to_string(),
+            ));
+        }
+        bash_state.cwd.clone()
+    };
+    if !crate::utils::capabilities::available("curl") {
+        return Err(WinxError::CommandExecutionError("GitHub needs curl on PATH".to_string()));
+    }
+    let remote = git(&cwd, &["remote", "get-url", "origin"]).await.ok_or_else(|| {
+        WinxError::InvalidInput(format!("{} has no git remote named origin", cwd.display()))
+    })?;
+    let (owner, name) = parse_remote(&remote).ok_or_else(|| {
+        WinxError::InvalidInput(format!("origin ({remote}) is not a github.com repository"))
+    })?;
+    let token = token().ok_or_else(|| {
+        WinxError::ConfigurationError(
+            "no GitHub token: set GITHUB_TOKEN, or run `winx config set --global github.token \
+             <token>`"
+                .to_string(),
+        )
+    })?;
+    run(&Repo { owner, name, token }, &cwd, &args).await
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn parses_github_remotes() {
+        let expected = Some(("octo".to_string(), "widget".to_string()));
+        assert_eq!(parse_remote("https://github.com/octo/widget.git"), expected);
+        assert_eq!(parse_remote("git@github.com:octo/widget.git\n"), expected);
+        assert_eq!(parse_remote("ssh://git@github.com/octo/widget"), expected);
+        assert_eq!(parse_remote("https://x-access-token@github.com/octo/widget/"), expected);
+        assert_eq!(parse_remote("https://gitlab.com/octo/widget.git"), None);
+        assert_eq!(parse_remote("https://notgithub.com/octo/widget"), None);
+    }
+
+    #[test]
+    fn renders_issues_without_pull_requests() {
+        let issues = json!([
+            { "number": 7, "title": "Crash on start", "state": "open", "comments": 2,
+              "user": { "login": "ana" }, "labels": [{ "name": "bug" }] },
+            { "number": 8, "title": "A PR", "pull_request": {}, "user": { "login": "bo" } },
+        ]);
+        assert_eq!(render_issues(&issues), "#7 Crash on start [bug] (open, ana, 2 comment(s))");
+        assert_eq!(
+            api_error(
+                json!({
+                    "message": "Validation Failed",
+                    "errors": [{ "message": "A pull request already exists" }]
+                })
+                .to_string()
+                .as_bytes()
+            ),
+            "Validation Failed (A pull request already exists)"
+        );
+    }
+
+    #[test]
+    fn log_tail_strips_timestamps() {
+        let log = "2024-05-01T10:00:00.1234567Z Run cargo test\n\
+                   2024-05-01T10:00:01.0000000Z error[E0308]: mismatched types\nplain line";
+        assert_eq!(log_tail(log), "Run cargo test\nerror[E0308]: mismatched types\nplain line");
+        assert_eq!(encode("feat/a b&c"), "feat%2Fa%20b%26c");
+    }
+}
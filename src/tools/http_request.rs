@@ -0,0 +1,388 @@
+//! Implementation of the `HttpRequest` tool: one HTTP request with a structured
+//! summary of the response, instead of free-form `curl` through `BashCommand`.
+//!
+//! The request goes through `curl` (http and https only, no redirects followed:
+//! a `Location` is reported instead, so profile headers never reach another
+//! host). Headers travel in a private header file rather than on the command
+//! line. Named auth profiles come from the global `~/.winx/config.toml`
+//! (`[http.profiles.<name>]`) and only apply to URLs under their `base_url`.
+//! The body shown is clipped to `max_bytes`; `save_to` keeps the whole body in a
+//! file, subject to the same mode and protected-path checks as any write. Only
+//! public hosts are reachable unless `WINX_PRIVATE_URLS=1` (see
+//! [`crate::utils::url_guard`]).
+
+use std::collections::BTreeMap;
+use std::fmt::Write as FmtWrite;
+use std::io::Write as IoWrite;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::Arc;
+
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+use crate::errors::{Result, WinxError};
+use crate::state::bash_state::BashState;
+use crate::tools::file_ops::{forget_or_rekey, resolve_entry, Access};
+use crate::types::HttpRequest;
+use crate::utils::project_config::HttpProfile;
+
+const METHODS: &[&str] = &["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"];
+const DEFAULT_MAX_BYTES: usize = 20_000;
+const REQUEST_TIMEOUT_SECS: u64 = 60;
+/// Responses larger than this are refused by curl.
+const MAX_RESPONSE_BYTES: u64 = 100 * 1024 * 1024;
+
+fn is_web_url(url: &str) -> bool {
+    let lower = url.to_ascii_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+/// The URL to request: `url` itself, or resolved against the profile's base.
+/// With a profile, an absolute URL must lie under `base_url`.
+fn resolve_url(url: &str, profile: Option<&HttpProfile>) -> Result<String> {
+    let url = url.trim();
+    let Some(profile) = profile else {
+        return if is_web_url(url) {
+            Ok(url.to_string())
+        } else {
+            Err(WinxError::InvalidInput(format!(
+                "url must be http(s)://, or relative with a profile (got {url:?})"
+            )))
+        };
+    };
+    let base = profile.base_url.trim().trim_end_matches('/');
+    if !is_web_url(base) {
+        return Err(WinxError::ConfigurationError(
+            "the profile's base_url must be an http(s) URL".to_string(),
+        ));
+    }
+    if !is_web_url(url) {
+        return Ok(match url.trim_start_matches('/') {
+            "" => base.to_string(),
+            path => format!("{base}/{path}"),
+        });
+    }
+    let under_base = url.strip_prefix(base).is_some_and(|rest| {
+        rest.is_empty() || rest.starts_with('/') || rest.starts_with('?') || rest.starts_with('#')
+    });
+    if under_base {
+        Ok(url.to_string())
+    } else {
+        Err(WinxError::InvalidInput(format!(
+            "{url} is outside the profile's base_url ({base}); its headers are only sent there"
+        )))
+    }
+}
+
+fn check_header(name: &str, value: &str) -> Result<()> {
+    let bad_name = name.is_empty() || name.contains([':', ' ', '\r', '\n']);
+    if bad_name || value.contains(['\r', '\n']) {
+        return Err(WinxError::InvalidInput(format!("invalid header {name:?}")));
+    }
+    Ok(())
+}
+
+/// Status code and headers of the final response in a `--dump-header` file
+/// (which holds one block per response, e.g. after a `100 Continue`).
+fn parse_head(dump: &str) -> (String, Vec<(String, String)>) {
+    let block = dump.rsplit("\r\n\r\n").find(|block| block.starts_with("HTTP/")).unwrap_or("");
+    let mut lines = block.lines();
+    let status = lines.next().unwrap_or("").trim().to_string();
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+    (status, headers)
+}
+
+/// The body as shown: pretty-printed when it is JSON, clipped to `max_bytes`.
+fn preview(body: &[u8], content_type: &str, max_bytes: usize) -> String {
+    let Ok(text) = std::str::from_utf8(body) else {
+        return format!("(binary body, {} bytes; use save_to to keep it)", body.len());
+    };
+    let pretty = content_type
+        .contains("json")
+        .then(|| serde_json::from_str::<serde_json::Value>(text).ok())
+        .flatten()
+        .and_then(|value| serde_json::to_string_pretty(&value).ok());
+    let text = pretty.as_deref().unwrap_or(text);
+    if text.len() <= max_bytes {
+        return text.to_string();
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!(
+        "{}\n\n[truncated: showing {end} of {} bytes; raise max_bytes or use save_to]",
+        &text[..end],
+        text.len()
+    )
+}
+
+/// Everything curl needs beyond the URL: headers, body and method flags.
+struct Prepared {
+    method: String,
+    headers: BTreeMap<String, String>,
+    body: Option<Vec<u8>>,
+}
+
+fn prepare(args: &HttpRequest, profile: Option<&HttpProfile>) -> Result<Prepared> {
+    let method = if args.method.trim().is_empty() {
+        "GET".to_string()
+    } else {
+        args.method.trim().to_ascii_uppercase()
+    };
+    if !METHODS.contains(&method.as_str()) {
+        return Err(WinxError::InvalidInput(format!(
+            "method must be one of {} (got {method})",
+            METHODS.join(", ")
+        )));
+    }
+    let mut headers = BTreeMap::new();
+    let given = profile.into_iter().flat_map(|p| &p.headers).chain(&args.headers);
+    for (name, value) in given {
+        check_header(name.trim(), value)?;
+        headers.insert(name.trim().to_string(), value.trim().to_string());
+    }
+    let body = match (&args.json, args.body.is_empty()) {
+        (Some(_), false) => {
+            return Err(WinxError::InvalidInput("pass either json or body, not both".to_string()))
+        }
+        (Some(json), true) => {
+            if !headers.keys().any(|name| name.eq_ignore_ascii_case("content-type")) {
+                headers.insert("Content-Type".to_string(), "application/json".to_string());
+            }
+            Some(json.to_string().into_bytes())
+        }
+        (None, false) => Some(args.body.clone().into_bytes()),
+        (None, true) => None,
+    };
+    Ok(Prepared { method, headers, body })
+}
+
+/// What curl brought back.
+struct Response {
+    status: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    seconds: f64,
+}
+
+/// curl's `--resolve` entry pinning the checked host to the addresses the
+/// guard saw, so curl can't be handed a different answer by a second lookup.
+fn pin(checked: Option<&(String, u16, Vec<SocketAddr>)>) -> Option<String> {
+    let (host, port, addrs) = checked?;
+    if host.parse::<IpAddr>().is_ok() {
+        return None;
+    }
+    let addrs: Vec<String> = addrs
+        .iter()
+        .map(|addr| match addr {
+            SocketAddr::V4(v4) => v4.ip().to_string(),
+            SocketAddr::V6(v6) => format!("[{}]", v6.ip()),
+        })
+        .collect();
+    Some(format!("{host}:{port}:{}", addrs.join(",")))
+}
+
+async fn send(url: &str, prepared: &Prepared, pinned: Option<&str>) -> Result<Response> {
+    let mut header_file = tempfile::Builder::new().prefix("winx-http-").tempfile()?;
+    for (name, value) in &prepared.headers {
+        writeln!(header_file, "{name}: {value}")?;
+    }
+    header_file.flush()?;
+    let dump = tempfile::Builder::new().prefix("winx-http-head-").tempfile()?;
+    let output_file = tempfile::Builder::new().prefix("winx-http-body-").tempfile()?;
+
+    let mut command = tokio::process::Command::new("curl");
+    command
+        .args(["--silent", "--show-error", "--proto", "=http,https"])
+        .args(["--max-time", &REQUEST_TIMEOUT_SECS.to_string()])
+        .args(["--max-filesize", &MAX_RESPONSE_BYTES.to_string()])
+        .arg("--header")
+        .arg(format!("@{}", header_file.path().display()))
+        .arg("--dump-header")
+        .arg(dump.path())
+        .arg("--output")
+        .arg(output_file.path())
+        .args(["--write-out", "%{time_total}"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if prepared.method == "HEAD" {
+        command.arg("--head");
+    } else {
+        command.args(["--request", &prepared.method]);
+    }
+    if prepared.body.is_some() {
+        command.args(["--data-binary", "@-"]);
+    }
+    if let Some(pinned) = pinned {
+        command.args(["--resolve", pinned]);
+    }
+    let mut child = command
+        .arg("--")
+        .arg(url)
+        .spawn()
+        .map_err(|e| WinxError::CommandExecutionError(format!("failed to run curl: {e}")))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Some(body) = &prepared.body {
+            stdin.write_all(body).await?;
+        }
+    }
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        return Err(WinxError::CommandExecutionError(format!(
+            "request failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    let (status, headers) = parse_head(&std::fs::read_to_string(dump.path()).unwrap_or_default());
+    Ok(Response {
+        status,
+        headers,
+        body: std::fs::read(output_file.path())?,
+        seconds: String::from_utf8_lossy(&output.stdout).trim().parse().unwrap_or(0.0),
+    })
+}
+
+fn render(response: &Response, max_bytes: usize, saved: Option<&Path>) -> String {
+    let mut out =
+        format!("{} ({} bytes in {:.2}s)", response.status, response.body.len(), response.seconds);
+    for (name, value) in &response.headers {
+        let _ = write!(out, "\n{name}: {value}");
+    }
+    if let Some(path) = saved {
+        let _ = write!(out, "\n\nBody saved to {}.", path.display());
+    }
+    if !response.body.is_empty() {
+        let content_type = response
+            .headers
+            .iter()
+            .find(|(name, _)| name == "content-type")
+            .map_or("", |(_, value)| value.as_str());
+        let _ = write!(out, "\n\n{}", preview(&response.body, content_type, max_bytes));
+    }
+    out
+}
+
+#[instrument(level = "info", skip(bash_state_arc, args))]
+pub async fn handle_tool_call(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    args: HttpRequest,
+) -> Result<String> {
+    let profile = match args.profile.trim() {
+        "" => None,
+        name => Some(crate::utils::project_config::http_profile(name).ok_or_else(|| {
+            WinxError::ConfigurationError(format!(
+                "no http profile {name:?} in the global winx config ([http.profiles.{name}])"
+            ))
+        })?),
+    };
+    let url = resolve_url(&args.url, profile.as_ref())?;
+    let prepared = prepare(&args, profile.as_ref())?;
+
+    let save_to = {
+        let mut guard = bash_state_arc.lock().await;
+        let bash_state = crate::tools::file_ops::session(&mut guard, &args.thread_id)?;
+        // The request runs curl; a restricted mode has to allow it.
+        if !bash_state.is_command_allowed("curl") {
+            return Err(WinxError::CommandNotAllowed(
+                "HttpRequest not allowed in current mode (allow the `curl` command to enable it)"
+                    .to_string(),
+            ));
+        }
+        match args.save_to.trim() {
+            "" => None,
+            raw => {
+                let expanded = crate::utils::path::expand_user(raw);
+                let candidate = bash_state.cwd.join(&expanded);
+                let access = if candidate.exists() { Access::Modify } else { Access::Create };
+                Some(resolve_entry(bash_state, raw, &access)?)
+            }
+        }
+    };
+    if !crate::utils::capabilities::available("curl") {
+        return Err(WinxError::CommandExecutionError("HttpRequest needs curl on PATH".to_string()));
+    }
+
+    let checked = crate::utils::url_guard::check_public(&url).await?;
+    let response = send(&url, &prepared, pin(checked.as_ref()).as_deref()).await?;
+    if let Some(path) = &save_to {
+        let mut guard = bash_state_arc.lock().await;
+        let bash_state = crate::tools::file_ops::session(&mut guard, &args.thread_id)?;
+        crate::tools::file_ops::check_destination(path, true)?;
+        crate::tools::file_ops::ensure_parent(path)?;
+        // Replaces a symlink at `path` rather than writing through it, so the
+        // body can't land outside the workspace.
+        crate::tools::file_write_or_edit::write_no_follow(path, &response.body)
+            .map_err(|e| crate::tools::file_ops::io_error(path, "writing", &e))?;
+        // An earlier read of the file no longer matches what is on disk.
+        forget_or_rekey(bash_state, path, None);
+    }
+    let max_bytes = if args.max_bytes == 0 { DEFAULT_MAX_BYTES } else { args.max_bytes };
+    Ok(render(&response, max_bytes, save_to.as_deref()))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn pin_lists_every_checked_address() {
+        let checked = (
+            "example.com".to_string(),
+            443,
+            vec!["93.184.216.34:443".parse().unwrap(), "[2606:2800::1]:443".parse().unwrap()],
+        );
+        assert_eq!(
+            pin(Some(&checked)).as_deref(),
+            Some("example.com:443:93.184.216.34,[2606:2800::1]")
+        );
+        let literal = ("93.184.216.34".to_string(), 80, vec!["93.184.216.34:80".parse().unwrap()]);
+        assert_eq!(pin(Some(&literal)), None);
+        assert_eq!(pin(None), None);
+    }
+
+    #[test]
+    fn profile_headers_stay_under_base_url() {
+        let profile = HttpProfile {
+            base_url: "https://api.example.com/v1/".to_string(),
+            headers: [("Authorization".to_string(), "Bearer x".to_string())].into(),
+        };
+        let p = Some(&profile);
+        assert_eq!(
+            resolve_url("/users?page=2", p).unwrap(),
+            "https://api.example.com/v1/users?page=2"
+        );
+        assert_eq!(resolve_url("", p).unwrap(), "https://api.example.com/v1");
+        assert!(resolve_url("https://api.example.com/v1/users", p).is_ok());
+        assert!(resolve_url("https://api.example.com/v10", p).is_err());
+        assert!(resolve_url("https://evil.example/v1", p).is_err());
+        assert!(resolve_url("/users", None).is_err());
+        assert!(resolve_url("file:///etc/passwd", None).is_err());
+    }
+
+    #[test]
+    fn parses_the_final_response_head() {
+        let dump = "HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 201 Created\r\nContent-Type: \
+                    application/json\r\nLocation: /items/7\r\n\r\n";
+        let (status, headers) = parse_head(dump);
+        assert_eq!(status, "HTTP/1.1 201 Created");
+        assert_eq!(headers[1], ("location".to_string(), "/items/7".to_string()));
+    }
+
+    #[test]
+    fn previews_pretty_json_and_clips() {
+        assert_eq!(preview(br#"{"a":1}"#, "application/json", 100), "{\n  \"a\": 1\n}");
+        let clipped = preview("é".repeat(10).as_bytes(), "text/plain", 5);
+        assert!(clipped.starts_with("éé\n\n[truncated: showing 4 of 20 bytes"));
+        assert!(preview(&[0xff, 0xfe], "", 10).starts_with("(binary body, 2 bytes"));
+    }
+}
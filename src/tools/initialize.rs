@@ -28,14 +28,6 @@ fn create_playground_dir() -> Result<PathBuf> {
     Ok(dir)
 }
 
-/// Whether `cmd` is on PATH (best-effort, used only for advisory hints).
-fn command_exists(cmd: &str) -> bool {
-    std::process::Command::new("sh")
-        .args(["-c", &format!("command -v {cmd}")])
-        .output()
-        .is_ok_and(|o| o.status.success())
-}
-
 fn code_writer_state(
     config: &CodeWriterConfig,
     workspace_root: &Path,
@@ -198,6 +190,7 @@ fn load_guidelines(workspace: &Path) -> String {
     }
     candidates.push(workspace.join("AGENTS.md"));
     candidates.push(workspace.join("CLAUDE.md"));
+    candidates.push(workspace.join("WINX.md"));
 
     for path in candidates {
         if path.is_file() {
@@ -294,7 +287,7 @@ pub async fn handle_tool_call(
                     .display()
             );
 
-            if command_exists("rg") {
+            if crate::utils::capabilities::available("rg") {
                 let _ = writeln!(
                     response,
                     "\n# Available commands\nUse ripgrep `rg` instead of `grep`/`find -name` — \
@@ -347,6 +340,22 @@ pub async fn handle_tool_call(
             if !guidelines.is_empty() {
                 let _ = writeln!(response, "\n# Agent guidelines\n{guidelines}");
             }
+            append_project_config(&mut response, active_workspace);
+
+            response.push_str(&crate::tools::memory::recall_for_workspace(active_workspace));
+
+            let workspace = active_workspace.to_path_buf();
+            let hooks = tokio::task::spawn_blocking(move || {
+                crate::utils::hooks::run(&workspace, &crate::utils::hooks::Event::SessionStart)
+            })
+            .await
+            .unwrap_or_default();
+            if let Some(note) = hooks.render() {
+                let _ = writeln!(response, "\n# Session start hooks\n{note}");
+            }
+            if let Some(notice) = crate::utils::hooks::untrusted_notice(active_workspace) {
+                let _ = writeln!(response, "\nNote: {notice}");
+            }
 
             if let Ok((repo_context, _)) = crate::utils::repo::get_repo_context(active_workspace) {
                 let _ = writeln!(response, "\n# Workspace structure\n{repo_context}");
@@ -416,6 +425,28 @@ pub async fn handle_tool_call(
     Ok(response)
 }
 
+/// Surface `.winx/config.toml`: the project's prompt additions, and the tools
+/// and paths it restricts, so the agent doesn't learn them by hitting errors.
+fn append_project_config(response: &mut String, workspace: &Path) {
+    let config = crate::utils::project_config::load(workspace);
+    let append = config.prompt.append.trim();
+    if !append.is_empty() {
+        let _ = writeln!(response, "\n# Project instructions\n{append}");
+    }
+    if !config.tools.disabled.is_empty() {
+        let _ = writeln!(
+            response,
+            "\nTools disabled in this workspace: {}",
+            config.tools.disabled.join(", ")
+        );
+    }
+    if let Some(globs) = config.files.allowed_globs {
+        let scope =
+            if globs.is_empty() { "nothing (read-only)".to_string() } else { globs.join(", ") };
+        let _ = writeln!(response, "\nThis workspace only allows edits to: {scope}");
+    }
+}
+
 /// Append the standard "disallow" note plus any operator-provided instructions
 /// from `WINX_SERVER_INSTRUCTIONS`, mirroring wcgw's Initialize output.
 fn append_server_instructions(response: &mut String) {
@@ -0,0 +1,269 @@
+//! Implementation of the `MemoryWrite` and `MemorySearch` tools: durable facts
+//! the agent keeps across sessions ("uses pnpm, not npm", "deploys with
+//! fly.io"). Unlike `ContextSave`, which snapshots one task for resuming it, a
+//! memory is a single sentence, recalled by keyword and listed by `Initialize`
+//! for the workspace it was written in (plus the global ones).
+//!
+//! Stored as one JSON file next to the saved contexts (`memory/facts.json` in
+//! the winx data dir), redacted before it is written.
+
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex, OnceLock, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+use crate::errors::{Result, WinxError};
+use crate::state::bash_state::BashState;
+use crate::types::{MemorySearch, MemoryWrite};
+
+/// Memories returned by a search when `max_results` is 0.
+const DEFAULT_RESULTS: usize = 10;
+/// Memories listed by `Initialize`.
+const RECALL_ON_INIT: usize = 20;
+/// Longest fact accepted; a memory is a sentence, not a document.
+const MAX_FACT_CHARS: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Fact {
+    id: String,
+    text: String,
+    /// Workspace root it applies to; `None` = every workspace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+    /// Unix seconds.
+    created: u64,
+}
+
+impl Fact {
+    fn applies_to(&self, workspace: Option<&str>) -> bool {
+        self.project.is_none() || self.project.as_deref() == workspace
+    }
+}
+
+/// Serializes read-modify-write of the store within this process.
+fn store_lock() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: OnceLock<StdMutex<()>> = OnceLock::new();
+    LOCK.get_or_init(|| StdMutex::new(())).lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn store_path() -> Result<PathBuf> {
+    Ok(crate::tools::context_save::resolve_memory_dir()?.join("facts.json"))
+}
+
+fn load(path: &Path) -> Result<Vec<Fact>> {
+    match fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json)
+            .map_err(|e| WinxError::DeserializationError(format!("{}: {e}", path.display()))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(WinxError::FileAccessError {
+            path: path.to_path_buf(),
+            message: format!("reading memories: {e}"),
+        }),
+    }
+}
+
+fn save(path: &Path, facts: &[Fact]) -> Result<()> {
+    let json = serde_json::to_string_pretty(facts)
+        .map_err(|e| WinxError::SerializationError(e.to_string()))?;
+    let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    fs::write(&tmp, json).and_then(|()| fs::rename(&tmp, path)).map_err(|e| {
+        WinxError::FileAccessError {
+            path: path.to_path_buf(),
+            message: format!("saving memories: {e}"),
+        }
+    })
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// Lowercased alphanumeric words.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Add `text` (unless empty) and drop the `replaces` ids. Returns the new fact
+/// and how many were removed.
+fn write_fact(
+    path: &Path,
+    text: &str,
+    project: Option<&str>,
+    replaces: &[String],
+) -> Result<(Option<Fact>, usize)> {
+    let _guard = store_lock();
+    let mut facts = load(path)?;
+    let before = facts.len();
+    facts.retain(|fact| !replaces.contains(&fact.id));
+    let removed = before - facts.len();
+
+    let text = crate::utils::redact::redact(text.trim()).into_owned();
+    let added = if text.is_empty() {
+        None
+    } else {
+        // An identical fact for the same scope is kept once.
+        facts.retain(|fact| !(fact.text == text && fact.project.as_deref() == project));
+        let created = now();
+        let digest = Sha256::digest(format!("{text}\0{created}\0{project:?}").as_bytes());
+        let id = digest.iter().take(4).fold(String::new(), |mut id, b| {
+            let _ = write!(id, "{b:02x}");
+            id
+        });
+        let fact = Fact { id, text, project: project.map(str::to_string), created };
+        facts.push(fact.clone());
+        Some(fact)
+    };
+    save(path, &facts)?;
+    Ok((added, removed))
+}
+
+/// Facts for `workspace` matching `query`, best first: more matched query words
+/// first, then this workspace's facts before global ones, then newest. An empty
+/// query matches everything.
+fn search<'a>(
+    facts: &'a [Fact],
+    query: &str,
+    workspace: Option<&str>,
+    limit: usize,
+) -> Vec<&'a Fact> {
+    let terms = words(query);
+    let mut scored: Vec<(usize, &Fact)> = facts
+        .iter()
+        .filter(|fact| fact.applies_to(workspace))
+        .filter_map(|fact| {
+            let fact_words = words(&fact.text);
+            let hits = terms
+                .iter()
+                .filter(|term| fact_words.iter().any(|word| word.starts_with(term.as_str())))
+                .count();
+            (terms.is_empty() || hits > 0).then_some((hits, fact))
+        })
+        .collect();
+    scored.sort_by(|(a_hits, a), (b_hits, b)| {
+        b_hits
+            .cmp(a_hits)
+            .then_with(|| b.project.is_some().cmp(&a.project.is_some()))
+            .then_with(|| b.created.cmp(&a.created))
+    });
+    scored.into_iter().take(limit).map(|(_, fact)| fact).collect()
+}
+
+fn render(facts: &[&Fact]) -> String {
+    let mut out = String::new();
+    for fact in facts {
+        let scope = if fact.project.is_some() { "" } else { " (global)" };
+        let _ = writeln!(out, "- [{}] {}{scope}", fact.id, fact.text);
+    }
+    out
+}
+
+/// The `# Remembered facts` section `Initialize` appends for `workspace`, or an
+/// empty string when there is nothing to recall.
+pub fn recall_for_workspace(workspace: &Path) -> String {
+    let Ok(facts) = store_path().and_then(|path| load(&path)) else {
+        return String::new();
+    };
+    let workspace = workspace.to_string_lossy();
+    let recalled = search(&facts, "", Some(&workspace), RECALL_ON_INIT);
+    if recalled.is_empty() {
+        return String::new();
+    }
+    format!(
+        "\n# Remembered facts\nFrom earlier sessions (MemoryWrite with replaces=[id] to correct \
+         one):\n{}",
+        render(&recalled)
+    )
+}
+
+async fn current_workspace(bash_state_arc: &Arc<Mutex<Option<BashState>>>) -> Option<String> {
+    let guard = bash_state_arc.lock().await;
+    guard.as_ref().map(|state| state.workspace_root.to_string_lossy().to_string())
+}
+
+#[instrument(level = "info", skip(bash_state_arc, args))]
+pub async fn handle_write(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    args: MemoryWrite,
+) -> Result<String> {
+    if args.fact.trim().is_empty() && args.replaces.is_empty() {
+        return Err(WinxError::InvalidInput("provide a fact, or ids in replaces".to_string()));
+    }
+    if args.fact.chars().count() > MAX_FACT_CHARS {
+        return Err(WinxError::InvalidInput(format!(
+            "a memory is one fact of at most {MAX_FACT_CHARS} characters; use ContextSave for \
+             longer notes"
+        )));
+    }
+    let workspace = current_workspace(bash_state_arc).await;
+    if workspace.is_none() && !args.global {
+        return Err(WinxError::BashStateNotInitialized);
+    }
+    let project = if args.global { None } else { workspace.as_deref() };
+    let (added, removed) = write_fact(&store_path()?, &args.fact, project, &args.replaces)?;
+
+    let mut out = match added {
+        Some(fact) => format!("Remembered [{}]: {}", fact.id, fact.text),
+        None => String::from("Nothing added."),
+    };
+    if removed > 0 {
+        let plural = if removed == 1 { "y" } else { "ies" };
+        let _ = write!(out, " Forgot {removed} replaced memor{plural}.");
+    } else if !args.replaces.is_empty() {
+        out.push_str(" None of the replaces ids matched a memory.");
+    }
+    Ok(out)
+}
+
+#[instrument(level = "info", skip(bash_state_arc, args))]
+pub async fn handle_search(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    args: MemorySearch,
+) -> Result<String> {
+    let workspace = current_workspace(bash_state_arc).await;
+    let facts = load(&store_path()?)?;
+    let limit = if args.max_results == 0 { DEFAULT_RESULTS } else { args.max_results };
+    let found = search(&facts, &args.query, workspace.as_deref(), limit);
+    if found.is_empty() {
+        return Ok("No matching memories.".to_string());
+    }
+    Ok(render(&found))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn write_replace_and_search() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("facts.json");
+        let (npm, _) = write_fact(&path, "uses npm", Some("/ws"), &[]).unwrap();
+        write_fact(&path, "deploys with fly.io", Some("/ws"), &[]).unwrap();
+        write_fact(&path, "prefers small commits", None, &[]).unwrap();
+        write_fact(&path, "uses yarn", Some("/other"), &[]).unwrap();
+
+        let npm_id = npm.unwrap().id;
+        let (_, removed) = write_fact(&path, "uses pnpm, not npm", Some("/ws"), &[npm_id]).unwrap();
+        assert_eq!(removed, 1);
+
+        let facts = load(&path).unwrap();
+        let texts = |found: Vec<&Fact>| found.iter().map(|f| f.text.clone()).collect::<Vec<_>>();
+        assert_eq!(texts(search(&facts, "pnpm", Some("/ws"), 10)), ["uses pnpm, not npm"]);
+        assert_eq!(texts(search(&facts, "deploy", Some("/ws"), 10)), ["deploys with fly.io"]);
+        // Other projects' facts never leak in; global ones always apply.
+        let all = texts(search(&facts, "", Some("/ws"), 10));
+        assert_eq!(all.len(), 3);
+        assert!(!all.iter().any(|t| t.contains("yarn")));
+        assert_eq!(all.last().unwrap(), "prefers small commits", "project facts rank first");
+    }
+}
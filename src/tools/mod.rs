@@ -4,16 +4,30 @@
 //! image, context save). The live service that wires them to the MCP protocol
 //! is [`crate::server::WinxService`].
 
+pub mod archive;
 pub mod bash_command;
+pub mod browser;
 pub mod code_map;
 pub mod context_save;
+pub mod dependency_audit;
+pub mod file_changes;
+pub mod file_ops;
 pub mod file_write_or_edit;
+pub mod github;
+pub mod http_request;
 pub mod initialize;
+pub mod memory;
 pub mod multi_file_edit;
+pub mod notebook_edit;
 pub mod outline;
 pub mod read_files;
 pub mod read_image;
 pub mod references;
+pub mod rename_symbol;
+pub mod scaffold;
+pub mod shell_env;
+pub mod system_stats;
+pub mod todo_scan;
 pub mod undo_edit;
 
 /// Serialize a tool's structured output to JSON for the MCP result's
@@ -78,8 +78,8 @@ pub async fn handle_tool_call(
 
 /// Plan every file (all-or-nothing at the compute stage), reject duplicate
 /// targets, then commit sequentially. Synchronous (file IO) — runs on the
-/// blocking pool, never on a tokio worker.
-fn apply_batch(bash_state: &mut BashState, files: &[FileEditEntry]) -> Result<String> {
+/// blocking pool, never on a tokio worker. Also how `Scaffold` writes its files.
+pub(crate) fn apply_batch(bash_state: &mut BashState, files: &[FileEditEntry]) -> Result<String> {
     // PHASE 1: plan every file (validate + compute new content) with NO writes.
     // Any failure aborts the whole batch having touched nothing on disk.
     let mut planned = Vec::with_capacity(files.len());
@@ -91,6 +91,11 @@ fn apply_batch(bash_state: &mut BashState, files: &[FileEditEntry]) -> Result<St
             &entry.text_or_search_replace_blocks,
         )
         .map_err(|e| {
+            // An external change keeps its structured form (path + diff) so the
+            // client can tell it apart from a bad edit.
+            if matches!(e, WinxError::FileChangedExternally { .. }) {
+                return e;
+            }
             // Plan failures (bad path, mode gate, stale/unread file, SEARCH miss)
             // are all caused by the agent's input, so keep them client-classified
             // (invalid_request) rather than wrapping in a server-error variant.
@@ -118,6 +123,13 @@ fn apply_batch(bash_state: &mut BashState, files: &[FileEditEntry]) -> Result<St
         }
     }
 
+    // Re-check every target right before the first write: a file changed by the
+    // user's IDE while the batch was planned aborts it with nothing written,
+    // rather than surfacing halfway through PHASE 2.
+    for edit in &planned {
+        edit.verify_unchanged()?;
+    }
+
     // PHASE 2: commit sequentially. Each write is individually atomic (temp +
     // rename). On the first failure, stop and report honestly without rolling
     // back already-written files.
@@ -137,5 +149,11 @@ fn apply_batch(bash_state: &mut BashState, files: &[FileEditEntry]) -> Result<St
         }
     }
 
+    if crate::utils::dry_run::enabled() {
+        return Ok(format!(
+            "MultiFileEdit dry run: all {total} edits validated, nothing written:\n\n{}",
+            summaries.join("\n\n")
+        ));
+    }
     Ok(format!("MultiFileEdit applied all {total} edits:\n\n{}", summaries.join("\n\n")))
 }
@@ -0,0 +1,326 @@
+//! Implementation of the `NotebookEdit` tool: cell-level edits to Jupyter
+//! notebooks (`.ipynb`), so the agent never has to search/replace inside the
+//! notebook JSON (escaped source lines, base64 outputs, ids).
+//!
+//! The notebook is parsed, changed as JSON and written back the way Jupyter
+//! writes it (sorted keys, one-space indent, source as a list of lines), through
+//! the same path as `RenameSymbol` (mode gate, protected paths, undo checkpoint).
+//! Editing a code cell's source clears its outputs, which no longer match it.
+
+use std::fmt::Write as FmtWrite;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+use crate::errors::{Result, WinxError};
+use crate::state::bash_state::BashState;
+use crate::tools::file_write_or_edit::{commit_edit_blocking, hash_content, plan_rewrite};
+use crate::types::{NotebookCellType, NotebookEdit, NotebookOperation};
+use crate::utils::path::resolve_in_workspace;
+
+/// Source lines shown per cell by `list`.
+const MAX_LISTED_LINES: usize = 15;
+
+fn invalid(message: impl Into<String>) -> WinxError {
+    WinxError::InvalidInput(message.into())
+}
+
+fn parse(text: &str) -> Result<Map<String, Value>> {
+    let notebook: Value = serde_json::from_str(text)
+        .map_err(|e| invalid(format!("not a valid notebook (JSON): {e}")))?;
+    match notebook {
+        Value::Object(map) if map.get("cells").is_some_and(Value::is_array) => Ok(map),
+        _ => Err(invalid("not a Jupyter notebook: no \"cells\" array")),
+    }
+}
+
+fn sort_keys(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.sort_keys();
+            map.values_mut().for_each(sort_keys);
+        }
+        Value::Array(items) => items.iter_mut().for_each(sort_keys),
+        _ => {}
+    }
+}
+
+/// Serialize like Jupyter: sorted keys, indent 1, trailing newline.
+fn render_json(notebook: &Map<String, Value>) -> Result<String> {
+    let mut notebook = Value::Object(notebook.clone());
+    sort_keys(&mut notebook);
+    let mut out = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut out, formatter);
+    notebook
+        .serialize(&mut serializer)
+        .map_err(|e| WinxError::SerializationError(format!("notebook: {e}")))?;
+    let mut text = String::from_utf8_lossy(&out).into_owned();
+    text.push('\n');
+    Ok(text)
+}
+
+/// A cell's source, which nbformat allows as a string or a list of lines.
+fn source_text(cell: &Value) -> String {
+    match cell.get("source") {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(lines)) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+fn source_lines(text: &str) -> Value {
+    Value::Array(text.split_inclusive('\n').map(|line| json!(line)).collect())
+}
+
+fn cell_type_name(cell_type: NotebookCellType) -> &'static str {
+    match cell_type {
+        NotebookCellType::Code => "code",
+        NotebookCellType::Markdown => "markdown",
+        NotebookCellType::Raw => "raw",
+    }
+}
+
+/// Whether cells carry an `id` (nbformat 4.5 and later).
+fn uses_cell_ids(notebook: &Map<String, Value>) -> bool {
+    let major = notebook.get("nbformat").and_then(Value::as_u64).unwrap_or(4);
+    let minor = notebook.get("nbformat_minor").and_then(Value::as_u64).unwrap_or(0);
+    major > 4 || (major == 4 && minor >= 5)
+}
+
+/// Give `cell` the fields its type requires (and drop the ones it must not
+/// have), keeping its metadata and id.
+fn set_type(cell: &mut Map<String, Value>, cell_type: NotebookCellType) {
+    cell.insert("cell_type".to_string(), json!(cell_type_name(cell_type)));
+    if cell_type == NotebookCellType::Code {
+        cell.insert("outputs".to_string(), json!([]));
+        cell.insert("execution_count".to_string(), Value::Null);
+    } else {
+        cell.remove("outputs");
+        cell.remove("execution_count");
+    }
+    cell.entry("metadata").or_insert_with(|| json!({}));
+}
+
+fn new_cell(with_id: bool, cell_type: NotebookCellType, source: &str) -> Value {
+    let mut cell = Map::new();
+    set_type(&mut cell, cell_type);
+    cell.insert("source".to_string(), source_lines(source));
+    if with_id {
+        let salt = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos());
+        let id = hash_content(&format!("{salt}{source}"));
+        cell.insert("id".to_string(), json!(id.chars().take(8).collect::<String>()));
+    }
+    Value::Object(cell)
+}
+
+fn clear_outputs(cell: &mut Value) -> bool {
+    let Some(cell) = cell.as_object_mut() else { return false };
+    if cell.get("cell_type").and_then(Value::as_str) != Some("code") {
+        return false;
+    }
+    let had_outputs = cell.get("outputs").and_then(Value::as_array).is_some_and(|o| !o.is_empty())
+        || cell.get("execution_count").is_some_and(|count| !count.is_null());
+    cell.insert("outputs".to_string(), json!([]));
+    cell.insert("execution_count".to_string(), Value::Null);
+    had_outputs
+}
+
+/// One line per cell plus the start of its source.
+fn list(cells: &[Value]) -> String {
+    let mut out = format!("{} cell(s)", cells.len());
+    for (index, cell) in cells.iter().enumerate() {
+        let cell_type = cell.get("cell_type").and_then(Value::as_str).unwrap_or("?");
+        let _ = write!(out, "\n\n[{index}] {cell_type}");
+        if cell_type == "code" {
+            let outputs = cell.get("outputs").and_then(Value::as_array).map_or(0, Vec::len);
+            match cell.get("execution_count").and_then(Value::as_u64) {
+                Some(count) => {
+                    let _ = write!(out, " (executed [{count}], {outputs} output(s))");
+                }
+                None => {
+                    let _ = write!(out, " ({outputs} output(s))");
+                }
+            }
+        }
+        let source = source_text(cell);
+        for line in source.lines().take(MAX_LISTED_LINES) {
+            let _ = write!(out, "\n  {line}");
+        }
+        let more = source.lines().count().saturating_sub(MAX_LISTED_LINES);
+        if more > 0 {
+            let _ = write!(out, "\n  ... {more} more line(s)");
+        }
+    }
+    out
+}
+
+/// Apply a changing operation to `notebook`, returning what was done.
+fn apply(notebook: &mut Map<String, Value>, args: &NotebookEdit) -> Result<String> {
+    let with_ids = uses_cell_ids(notebook);
+    let Some(Value::Array(cells)) = notebook.get_mut("cells") else {
+        return Err(invalid("not a Jupyter notebook: no \"cells\" array"));
+    };
+    let len = cells.len();
+    let index = || -> Result<usize> {
+        match args.index {
+            Some(index) if index < len => Ok(index),
+            Some(index) => Err(invalid(format!("index {index} out of range ({len} cell(s))"))),
+            None => Err(invalid("index is required for this operation")),
+        }
+    };
+    match args.operation {
+        NotebookOperation::List => Ok(String::new()),
+        NotebookOperation::Edit => {
+            let index = index()?;
+            let cell = cells[index]
+                .as_object_mut()
+                .ok_or_else(|| invalid(format!("cell {index} is not an object")))?;
+            if let Some(cell_type) = args.cell_type {
+                set_type(cell, cell_type);
+            } else if cell.get("cell_type").and_then(Value::as_str) == Some("code") {
+                set_type(cell, NotebookCellType::Code);
+            }
+            cell.insert("source".to_string(), source_lines(&args.source));
+            Ok(format!("Edited cell {index}"))
+        }
+        NotebookOperation::Insert => {
+            // Inserting at `len` appends, so only indexes past the end are invalid.
+            let index = args.index.unwrap_or(len);
+            if index > len {
+                return Err(invalid(format!("index {index} out of range ({len} cell(s))")));
+            }
+            let cell_type = args.cell_type.unwrap_or(NotebookCellType::Code);
+            cells.insert(index, new_cell(with_ids, cell_type, &args.source));
+            Ok(format!("Inserted {} cell {index}", cell_type_name(cell_type)))
+        }
+        NotebookOperation::Delete => {
+            let index = index()?;
+            cells.remove(index);
+            Ok(format!("Deleted cell {index}"))
+        }
+        NotebookOperation::ClearOutputs => {
+            let cleared = match args.index {
+                Some(_) => usize::from(clear_outputs(&mut cells[index()?])),
+                None => cells.iter_mut().map(clear_outputs).filter(|cleared| *cleared).count(),
+            };
+            Ok(format!("Cleared the outputs of {cleared} cell(s)"))
+        }
+    }
+}
+
+#[instrument(level = "info", skip(bash_state_arc, args))]
+pub async fn handle_tool_call(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    args: NotebookEdit,
+) -> Result<String> {
+    let mut guard = bash_state_arc.lock().await;
+    let bash_state = crate::tools::file_ops::session(&mut guard, &args.thread_id)?;
+    let path = resolve_in_workspace(&args.path, &bash_state.cwd, &bash_state.workspace_root)
+        .map_err(|e| WinxError::PathSecurityError {
+            path: PathBuf::from(&args.path),
+            message: e.to_string(),
+        })?;
+    if !path.extension().is_some_and(|ext| ext == "ipynb") {
+        return Err(invalid(format!("{} is not a .ipynb notebook", path.display())));
+    }
+    let (previous, _) = crate::utils::text_encoding::read_text(&path)
+        .map_err(|e| WinxError::FileAccessError { path: path.clone(), message: e.to_string() })?;
+    let mut notebook = parse(&previous)?;
+    if args.operation == NotebookOperation::List {
+        let cells = notebook.get("cells").and_then(Value::as_array).cloned().unwrap_or_default();
+        return Ok(list(&cells));
+    }
+
+    let summary = apply(&mut notebook, &args)?;
+    let planned = plan_rewrite(bash_state, &path, &previous, render_json(&notebook)?)?;
+    let result = commit_edit_blocking(&mut guard, planned).await?;
+    Ok(format!("{summary} in {}.\n\n{result}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    const NOTEBOOK: &str = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "id": "a1",
+   "metadata": {},
+   "source": ["# Title\n", "intro"]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 3,
+   "id": "b2",
+   "metadata": {},
+   "outputs": [{"name": "stdout", "output_type": "stream", "text": ["1\n"]}],
+   "source": "print(1)"
+  }
+ ],
+ "metadata": {},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"##;
+
+    fn args(operation: NotebookOperation, index: Option<usize>, source: &str) -> NotebookEdit {
+        NotebookEdit {
+            path: "nb.ipynb".to_string(),
+            operation,
+            index,
+            source: source.to_string(),
+            cell_type: None,
+            thread_id: String::new(),
+        }
+    }
+
+    #[test]
+    fn lists_cells_with_outputs() {
+        let notebook = parse(NOTEBOOK).unwrap();
+        let listing = list(notebook["cells"].as_array().unwrap());
+        assert!(listing.starts_with("2 cell(s)"));
+        assert!(listing.contains("[0] markdown\n  # Title\n  intro"));
+        assert!(listing.contains("[1] code (executed [3], 1 output(s))\n  print(1)"));
+    }
+
+    #[test]
+    fn edits_insert_delete_and_clear() {
+        let mut notebook = parse(NOTEBOOK).unwrap();
+        apply(&mut notebook, &args(NotebookOperation::Edit, Some(1), "print(2)\nx = 1")).unwrap();
+        let cell = &notebook["cells"][1];
+        assert_eq!(cell["source"], json!(["print(2)\n", "x = 1"]));
+        assert_eq!(cell["outputs"], json!([]));
+        assert_eq!(cell["id"], "b2");
+
+        apply(&mut notebook, &args(NotebookOperation::Insert, None, "y = 2")).unwrap();
+        let added = &notebook["cells"][2];
+        assert_eq!(added["cell_type"], "code");
+        assert_eq!(added["id"].as_str().unwrap().len(), 8);
+
+        apply(&mut notebook, &args(NotebookOperation::Delete, Some(0), "")).unwrap();
+        assert_eq!(notebook["cells"].as_array().unwrap().len(), 2);
+        assert!(apply(&mut notebook, &args(NotebookOperation::Delete, Some(5), "")).is_err());
+
+        let text = render_json(&notebook).unwrap();
+        assert!(text.starts_with("{\n \"cells\": [\n  {\n   \"cell_type\": \"code\""));
+        assert!(text.ends_with("}\n"));
+    }
+
+    #[test]
+    fn clear_outputs_counts_code_cells_only() {
+        let mut notebook = parse(NOTEBOOK).unwrap();
+        let summary =
+            apply(&mut notebook, &args(NotebookOperation::ClearOutputs, None, "")).unwrap();
+        assert_eq!(summary, "Cleared the outputs of 1 cell(s)");
+        assert_eq!(notebook["cells"][1]["execution_count"], Value::Null);
+    }
+}
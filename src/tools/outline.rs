@@ -21,6 +21,7 @@ use crate::utils::mmap::read_file_to_string;
 use crate::utils::path::resolve_in_workspace;
 use crate::utils::path_prob::score_paths;
 use crate::utils::repo::walk_workspace_files;
+use crate::utils::symbol_cache::{self, FileStamp};
 use crate::utils::symbols::{self, Symbol};
 
 /// Cap on files in repo-map mode when the caller passes 0.
@@ -99,8 +100,10 @@ fn render_symbols(out: &mut String, syms: &[Symbol]) {
     }
 }
 
-fn to_output(syms: Vec<Symbol>) -> Vec<OutlineSymbol> {
-    syms.into_iter().map(|s| OutlineSymbol { name: s.name, kind: s.kind, line: s.line }).collect()
+fn to_output(syms: &[Symbol]) -> Vec<OutlineSymbol> {
+    syms.iter()
+        .map(|s| OutlineSymbol { name: s.name.clone(), kind: s.kind.clone(), line: s.line })
+        .collect()
 }
 
 fn outline_one(
@@ -154,7 +157,7 @@ fn outline_one(
     let structured = OutlineOutput {
         mode: "file".to_string(),
         files_shown: 1,
-        files: vec![OutlineFile { file: rel, symbols: to_output(syms) }],
+        files: vec![OutlineFile { file: rel, symbols: to_output(&syms) }],
         truncated,
     };
     Ok((out, crate::tools::structured_json(&structured)?))
@@ -215,26 +218,35 @@ fn outline_repo(
             truncated = true;
             break;
         }
-        // read_file_to_string enforces the size cap and rejects non-UTF-8, so a
-        // separate metadata stat is redundant — skip on any read error.
-        let Ok(text) = read_file_to_string(&abs, MAX_OUTLINE_FILE_SIZE) else { continue };
+        let Some(stamp) = FileStamp::of(&abs) else { continue };
         scanned += 1;
-        let ext = ext_of(&abs);
-        let mut syms = match config_for(configs, &ext) {
-            Some(config) => symbols::extract(context, config, &text),
-            None => continue,
+        // Unchanged files come straight from the process-wide index; only new or
+        // modified ones are re-parsed, so repeated maps in a long session stay cheap.
+        let all = if let Some(cached) = symbol_cache::get(&abs, stamp) {
+            cached
+        } else {
+            let Some(config) = config_for(configs, &ext_of(&abs)) else { continue };
+            // read_file_to_string enforces the size cap and rejects non-UTF-8;
+            // an unreadable file is indexed as empty so it isn't re-read every map.
+            let syms = match read_file_to_string(&abs, MAX_OUTLINE_FILE_SIZE) {
+                Ok(text) => symbols::extract(context, config, &text),
+                Err(_) => Vec::new(),
+            };
+            symbol_cache::insert(&abs, stamp, syms)
         };
-        if syms.is_empty() {
+        if all.is_empty() {
             continue;
         }
         // Per-file symbol cap: one definition-dense file (minified/generated)
         // must not blow the budget or the structured array.
-        if syms.len() > DEFAULT_MAX_SYMBOLS {
-            syms.truncate(DEFAULT_MAX_SYMBOLS);
+        let syms = if all.len() > DEFAULT_MAX_SYMBOLS {
             truncated = true;
-        }
+            &all[..DEFAULT_MAX_SYMBOLS]
+        } else {
+            &all[..]
+        };
         let _ = writeln!(out, "{rel}");
-        render_symbols(&mut out, &syms);
+        render_symbols(&mut out, syms);
         out_files.push(OutlineFile { file: rel, symbols: to_output(syms) });
     }
 
@@ -318,6 +330,24 @@ mod tests {
         assert!(handle_tool_call(&st, args("nope_typo.rs")).await.is_err());
     }
 
+    #[tokio::test]
+    async fn repo_map_picks_up_changed_file() {
+        // The definition index is reused across calls; a rewrite must still show
+        // up on the next map (stamp mismatch), not a stale cached entry.
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("lib.rs");
+        std::fs::write(&file, "fn before() {}\n").unwrap();
+        let st = state_in(&dir);
+        let (out, _) = handle_tool_call(&st, args("")).await.unwrap();
+        assert!(out.contains("before"));
+
+        std::fs::write(&file, "fn after_the_change() {}\n").unwrap();
+        symbol_cache::invalidate(&file);
+        let (out, _) = handle_tool_call(&st, args("")).await.unwrap();
+        assert!(out.contains("after_the_change"), "got {out}");
+        assert!(!out.contains("before"));
+    }
+
     #[tokio::test]
     async fn repo_per_file_cap_marks_truncated() {
         // Regression (B1): a definition-dense file must be clipped and reported
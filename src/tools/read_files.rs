@@ -15,7 +15,7 @@ use tracing::{debug, error, info, instrument, warn};
 use crate::errors::{ErrorRecovery, Result, WinxError};
 use crate::state::bash_state::BashState;
 use crate::types::ReadFiles;
-use crate::utils::mmap::read_file_to_string;
+use crate::utils::mmap::{read_file_decoded, read_file_segment};
 use crate::utils::path::{expand_user, validate_path_in_workspace};
 
 /// Default token limits for file reading
@@ -23,11 +23,14 @@ const CODING_MAX_TOKENS: usize = 24_000;
 const NONCODING_MAX_TOKENS: usize = 8_000;
 
 /// Type alias for file reading result
-type FileReadResult = (String, bool, usize, String, (usize, usize), String, usize);
-type ReadCoverage = (Vec<(usize, usize)>, String, usize);
+type FileReadResult = (String, bool, usize, String, (usize, usize), String, usize, String);
+type ReadCoverage = (Vec<(usize, usize)>, String, usize, String);
 
 /// Maximum amount of data to read from a file
 const MAX_FILE_SIZE: u64 = 50_000_000;
+/// Ceiling on a single `max_bytes` window, whatever the caller asks for. The
+/// window is still token-budgeted afterwards; this only bounds the buffer.
+const MAX_WINDOW_BYTES: u64 = 8 * 1024 * 1024;
 
 fn range_format(start_line_num: Option<usize>, end_line_num: Option<usize>) -> String {
     let st = start_line_num.map_or(String::new(), |n| n.to_string());
@@ -50,38 +53,10 @@ async fn read_file(
     start_line_num: Option<usize>,
     end_line_num: Option<usize>,
 ) -> Result<FileReadResult> {
-    let file_path_expanded = expand_user(file_path);
-    let path = if Path::new(&file_path_expanded).is_absolute() {
-        PathBuf::from(&file_path_expanded)
-    } else {
-        cwd.join(&file_path_expanded)
-    };
-
-    if !path.exists() {
-        return Err(WinxError::FileAccessError {
-            path: path.clone(),
-            message: "File does not exist".to_string(),
-        });
-    }
-
-    let path = match validate_path_in_workspace(&path, workspace_root) {
-        Ok(canonical) => canonical,
-        Err(security_err) => {
-            return Err(WinxError::PathSecurityError {
-                path: path.clone(),
-                message: security_err.to_string(),
-            });
-        }
-    };
-
-    if !path.is_file() {
-        return Err(WinxError::FileAccessError {
-            path: path.clone(),
-            message: "Path exists but is not a file".to_string(),
-        });
-    }
+    let path = resolve_readable(file_path, cwd, workspace_root)?;
 
-    let content = read_file_to_string(&path, MAX_FILE_SIZE)?;
+    // Decoded the same way FileWriteOrEdit reads it, so the hashes agree.
+    let (content, _) = read_file_decoded(&path, MAX_FILE_SIZE)?;
     let file_hash = hash_content(&content);
     let lines: Vec<&str> = content.lines().collect();
     let total_lines = lines.len();
@@ -149,9 +124,122 @@ async fn read_file(
         (effective_start, effective_end.min(total_lines.max(1))),
         file_hash,
         total_lines,
+        content,
     ))
 }
 
+/// Validate `file_path` like [`read_file`] does and return the canonical path.
+fn resolve_readable(file_path: &str, cwd: &Path, workspace_root: &Path) -> Result<PathBuf> {
+    let expanded = expand_user(file_path);
+    let path = if Path::new(&expanded).is_absolute() {
+        PathBuf::from(&expanded)
+    } else {
+        cwd.join(&expanded)
+    };
+    if !path.exists() {
+        return Err(WinxError::FileAccessError {
+            path,
+            message: "File does not exist".to_string(),
+        });
+    }
+    let path = validate_path_in_workspace(&path, workspace_root)
+        .map_err(|e| WinxError::PathSecurityError { path: path.clone(), message: e.to_string() })?;
+    if !path.is_file() {
+        return Err(WinxError::FileAccessError {
+            path,
+            message: "Path exists but is not a file".to_string(),
+        });
+    }
+    Ok(path)
+}
+
+/// Read one `max_bytes` window of `file_path` starting at `offset`, without
+/// loading the rest of the file (large windows are memory-mapped by
+/// [`read_file_segment`]). The window is cut back to a line boundary and then to
+/// the token budget, so the returned cursor always resumes exactly where the
+/// shown text ends. Returns `(rendered, canonical path, next offset)`.
+fn read_file_window(
+    file_path: &str,
+    cwd: &Path,
+    workspace_root: &Path,
+    offset: u64,
+    max_bytes: u64,
+    max_tokens: usize,
+) -> Result<(String, String, Option<u64>)> {
+    let path = resolve_readable(file_path, cwd, workspace_root)?;
+    let file_size = std::fs::metadata(&path)?.len();
+    let canon_path = path.to_string_lossy().to_string();
+    if offset >= file_size {
+        return Ok((format!("(end of file: {file_size} bytes)"), canon_path, None));
+    }
+
+    let length = max_bytes.clamp(1, MAX_WINDOW_BYTES);
+    let mut bytes = read_file_segment(&path, offset, length, u64::MAX)?;
+    // A hand-picked offset may land inside a multi-byte character; skip its
+    // continuation bytes so the window starts on a boundary.
+    let skipped = bytes.iter().take_while(|b| (**b & 0xC0) == 0x80).count();
+    bytes.drain(..skipped);
+    let start = offset + skipped as u64;
+    let at_eof = start + bytes.len() as u64 >= file_size;
+    if !at_eof {
+        // Don't end mid-line: the next window picks the partial line up whole.
+        if let Some(nl) = bytes.iter().rposition(|b| *b == b'\n') {
+            bytes.truncate(nl + 1);
+        }
+    }
+    let mut text = match String::from_utf8(bytes) {
+        Ok(text) => text,
+        // Only an incomplete trailing character (window cut) is recoverable.
+        Err(e) if e.utf8_error().error_len().is_none() => {
+            let valid = e.utf8_error().valid_up_to();
+            let mut bytes = e.into_bytes();
+            bytes.truncate(valid);
+            String::from_utf8(bytes).unwrap_or_default()
+        }
+        Err(_) => {
+            return Err(WinxError::FileAccessError {
+                path,
+                message: format!("bytes at offset {start} are not UTF-8 text"),
+            });
+        }
+    };
+
+    let token_ids = crate::utils::encoder::encode_ids(&text);
+    let tokens_count =
+        token_ids.as_ref().map_or_else(|| crate::utils::encoder::estimate_tokens(&text), Vec::len);
+    if tokens_count > max_tokens {
+        let mut kept = text.clone();
+        truncate_to_token_budget(&mut kept, max_tokens, token_ids);
+        // Cut the ORIGINAL text (decoding may normalise) at a line boundary inside
+        // the kept prefix, so `next` maps to real file bytes.
+        let cut = crate::utils::floor_char_boundary(&text, kept.len());
+        let cut = text[..cut].rfind('\n').map_or(cut, |nl| nl + 1);
+        // Always keep at least the first line, even over budget (or with a zero
+        // budget): a window that shows nothing would return `next == start`, and
+        // a client following the cursor would loop forever.
+        let first_line = text.find('\n').map_or(text.len(), |nl| nl + 1);
+        text.truncate(cut.max(first_line));
+    }
+    if text.is_empty() {
+        // Only possible when `max_bytes` can't hold the character at `start`.
+        return Err(WinxError::ParameterValidationError {
+            field: "max_bytes".to_string(),
+            message: format!("{max_bytes} byte(s) can't hold the character at offset {start}"),
+        });
+    }
+
+    let end = start + text.len() as u64;
+    let next = (end < file_size).then_some(end);
+    let mut rendered = format!("[bytes {start}-{end} of {file_size}]\n{text}");
+    if let Some(next) = next {
+        let _ = write!(
+            rendered,
+            "\n(...truncated) Continue reading with byte_offset={next} (same max_bytes)."
+        );
+    }
+    Ok((rendered, canon_path, next))
+}
+
 fn hash_content(content: &str) -> String {
     let digest = Sha256::digest(content.as_bytes());
     digest.iter().fold(String::with_capacity(digest.len() * 2), |mut hash, byte| {
@@ -245,6 +333,7 @@ fn is_source_code_file(file_path: &str) -> bool {
         )
 }
 
+#[allow(clippy::too_many_lines)]
 pub async fn handle_tool_call(
     bash_state_arc: &Arc<Mutex<Option<BashState>>>,
     read_files: ReadFiles,
@@ -255,17 +344,54 @@ pub async fn handle_tool_call(
         (bash_state.cwd.clone(), bash_state.workspace_root.clone())
     };
 
+    if read_files.byte_offset.is_some() && read_files.file_paths.len() > 1 {
+        return Err(WinxError::ParameterValidationError {
+            field: "byte_offset".to_string(),
+            message: "a byte_offset belongs to one file; read one path at a time to resume"
+                .to_string(),
+        });
+    }
+
     let mut message = String::new();
     let mut file_ranges_dict: HashMap<String, ReadCoverage> = HashMap::new();
 
     for (index, file_path) in read_files.file_paths.iter().enumerate() {
         let clean_path = read_files.get_clean_path(index);
+        let max_tokens = read_files.max_tokens.unwrap_or_else(|| select_max_tokens(&clean_path));
+
+        if let Some(max_bytes) = read_files.max_bytes {
+            // Windowed reads see only part of the file, so they neither hash it nor
+            // grant edit coverage; the agent reads normally before editing.
+            match read_file_window(
+                &clean_path,
+                &cwd,
+                &workspace_root,
+                read_files.byte_offset.unwrap_or(0),
+                max_bytes,
+                max_tokens,
+            ) {
+                Ok((content, canon_path, _)) => {
+                    let _ = write!(message, "\n{clean_path}\n```\n{content}\n```");
+                    if let Err(e) = crate::utils::workspace_stats::record_read(
+                        &workspace_root,
+                        Path::new(&canon_path),
+                    ) {
+                        debug!("failed to record read stats: {e}");
+                    }
+                }
+                Err(e) => {
+                    let _ = write!(message, "\nError reading {file_path}: {e}");
+                }
+            }
+            continue;
+        }
+
         let start_line_num = read_files.start_line_nums.get(index).copied().flatten();
         let end_line_num = read_files.end_line_nums.get(index).copied().flatten();
 
         match read_file(
             &clean_path,
-            Some(select_max_tokens(&clean_path)),
+            Some(max_tokens),
             &cwd,
             &workspace_root,
             read_files.show_line_numbers(),
@@ -274,13 +400,14 @@ pub async fn handle_tool_call(
         )
         .await
         {
-            Ok((content, truncated, _, canon_path, line_range, file_hash, total_lines)) => {
+            Ok((content, truncated, _, canon_path, line_range, file_hash, total_lines, raw)) => {
                 let entry = file_ranges_dict
                     .entry(canon_path.clone())
-                    .or_insert_with(|| (Vec::new(), file_hash.clone(), total_lines));
+                    .or_insert_with(|| (Vec::new(), file_hash.clone(), total_lines, String::new()));
                 entry.0.push(line_range);
                 entry.1 = file_hash;
                 entry.2 = total_lines;
+                entry.3 = raw;
                 let _ = write!(
                     message,
                     "\n{}{}\n```\n{content}\n```",
@@ -315,7 +442,7 @@ pub async fn handle_tool_call(
 
     let mut bash_state_guard = bash_state_arc.lock().await;
     if let Some(bash_state) = bash_state_guard.as_mut() {
-        for (path, (ranges, file_hash, total_lines)) in file_ranges_dict {
+        for (path, (ranges, file_hash, total_lines, raw)) in file_ranges_dict {
             bash_state
                 .whitelist_for_overwrite
                 .entry(path)
@@ -325,9 +452,17 @@ pub async fn handle_tool_call(
                     // Merge (not extend): re-reading a file must not append duplicate
                     // ranges forever. Keeps line_ranges_read bounded + disjoint.
                     existing.merge_ranges(ranges.iter().copied());
+                    // Kept so a later external change can be reported as a diff.
+                    existing.set_seen_content(&raw);
                 })
                 .or_insert_with(|| {
-                    crate::state::bash_state::FileWhitelistData::new(file_hash, ranges, total_lines)
+                    let mut data = crate::state::bash_state::FileWhitelistData::new(
+                        file_hash,
+                        ranges,
+                        total_lines,
+                    );
+                    data.set_seen_content(&raw);
+                    data
                 });
         }
     }
@@ -2,7 +2,9 @@
 //!
 //! This module provides the implementation for the `ReadImage` tool, which is used
 //! to read image files and return their contents as base64-encoded data with
-//! the appropriate MIME type.
+//! the appropriate MIME type. Images can optionally be downscaled / re-encoded
+//! as JPEG (via `ImageMagick`, when installed) and, when `WINX_IMAGE_URLS=1`,
+//! fetched from an http(s) URL (via `curl`).
 
 use base64::{engine::general_purpose, Engine};
 use mime_guess::MimeGuess;
@@ -25,10 +27,39 @@ pub const SUPPORTED_MIME_TYPES: [&str; 4] = ["image/jpeg", "image/png", "image/g
 /// (screenshots, mockups, error PNGs) are orders of magnitude smaller.
 const MAX_IMAGE_BYTES: u64 = 50 * 1024 * 1024;
 
+/// Give up on a URL fetch after this many seconds.
+const URL_FETCH_TIMEOUT_SECS: u64 = 30;
+
+/// Whether fetching images from URLs is enabled via `WINX_IMAGE_URLS`. Off by
+/// default: over the HTTP transport a URL fetch is a server-side request to an
+/// arbitrary host, so the operator has to opt in.
+pub(crate) fn url_fetch_enabled() -> bool {
+    std::env::var("WINX_IMAGE_URLS").is_ok_and(|v| v == "1" || v == "true")
+}
+
+fn is_url(file_path: &str) -> bool {
+    file_path.starts_with("http://") || file_path.starts_with("https://")
+}
+
+/// Identify a supported image format from its magic bytes.
+fn sniff_mime(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
 /// Read an image from the file system
 ///
-/// This function reads an image file, base64 encodes its contents, and
-/// determines the MIME type based on the file extension.
+/// This function reads an image file and determines the MIME type based on
+/// the file extension.
 ///
 /// # Arguments
 ///
@@ -39,7 +70,7 @@ const MAX_IMAGE_BYTES: u64 = 50 * 1024 * 1024;
 ///
 /// A tuple containing:
 /// - The MIME type of the image
-/// - The base64-encoded image data
+/// - The raw image bytes
 ///
 /// # Errors
 ///
@@ -49,7 +80,7 @@ fn read_image_from_path(
     file_path: &str,
     cwd: &Path,
     workspace_root: &Path,
-) -> Result<(String, String)> {
+) -> Result<(String, Vec<u8>)> {
     debug!("Reading image: {}", file_path);
 
     // Expand the path
@@ -93,16 +124,13 @@ fn read_image_from_path(
         message: format!("Error reading file: {e}"),
     })?;
 
-    // Encode the bytes to base64
-    let image_b64 = general_purpose::STANDARD.encode(&image_bytes);
-
     // Guess the MIME type from the file extension
     let mime_type =
         MimeGuess::from_path(&path).first_raw().unwrap_or("application/octet-stream").to_string();
 
     // Verify the MIME type is a supported image type
     if SUPPORTED_MIME_TYPES.contains(&mime_type.as_str()) {
-        Ok((mime_type, image_b64))
+        Ok((mime_type, image_bytes))
     } else {
         debug!("Detected MIME type '{}' is not in the supported list", mime_type);
         // Fall back to a best effort based on common extensions
@@ -116,8 +144,133 @@ fn read_image_from_path(
         };
 
         debug!("Using fallback MIME type: {}", mime_type);
-        Ok((mime_type.to_string(), image_b64))
+        Ok((mime_type.to_string(), image_bytes))
+    }
+}
+
+/// Download an image from an http(s) URL with `curl`, bounded in size and time.
+///
+/// # Errors
+///
+/// Returns an error if URL fetching is disabled, `curl` fails, or the response
+/// isn't a supported image format
+async fn fetch_image_url(url: &str) -> Result<(String, Vec<u8>)> {
+    if !url_fetch_enabled() {
+        return Err(WinxError::RecoverableSuggestionError {
+            message: format!("Reading images from URLs is disabled: {url}"),
+            suggestion: "Set WINX_IMAGE_URLS=1 on the server, or download the image into the \
+                         workspace and pass its path"
+                .to_string(),
+        });
+    }
+    if !crate::utils::capabilities::available("curl") {
+        return Err(WinxError::RecoverableSuggestionError {
+            message: format!("Reading images from URLs needs curl, which isn't installed: {url}"),
+            suggestion: "Install curl on the server, or download the image into the workspace \
+                         and pass its path"
+                .to_string(),
+        });
+    }
+
+    let output = tokio::process::Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--location"])
+        .args(["--proto", "=http,https", "--max-redirs", "5"])
+        .args(["--max-time", &URL_FETCH_TIMEOUT_SECS.to_string()])
+        .args(["--max-filesize", &MAX_IMAGE_BYTES.to_string()])
+        .arg("--")
+        .arg(url)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| WinxError::CommandExecutionError(format!("failed to run curl: {e}")))?;
+    if !output.status.success() {
+        return Err(WinxError::CommandExecutionError(format!(
+            "failed to fetch {url}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    // Trust the bytes, not the server's Content-Type or the URL's extension.
+    let Some(mime_type) = sniff_mime(&output.stdout) else {
+        return Err(WinxError::InvalidInput(format!(
+            "{url} did not return a PNG, JPEG, GIF or WebP image"
+        )));
+    };
+    Ok((mime_type.to_string(), output.stdout))
+}
+
+/// Downscale to `max_width` and/or re-encode as JPEG at `jpeg_quality` using
+/// `ImageMagick` (`magick`, or the older `convert`). Images already narrower than
+/// `max_width` are never upscaled.
+///
+/// # Errors
+///
+/// Returns an error if `ImageMagick` isn't installed or fails on the input
+async fn transform_image(
+    bytes: Vec<u8>,
+    mime_type: &str,
+    max_width: Option<u32>,
+    jpeg_quality: Option<u8>,
+) -> Result<(String, Vec<u8>)> {
+    use tokio::io::AsyncWriteExt;
+
+    // Keep the input format unless a JPEG quality asks for re-encoding.
+    let (out_mime, out_format) = match jpeg_quality {
+        Some(_) => ("image/jpeg", "jpeg"),
+        None => (mime_type, mime_type.strip_prefix("image/").unwrap_or("png")),
+    };
+    let mut args: Vec<String> = vec!["-".to_string()];
+    if let Some(width) = max_width {
+        args.extend(["-resize".to_string(), format!("{width}x>")]);
+    }
+    if let Some(quality) = jpeg_quality {
+        args.extend(["-quality".to_string(), quality.clamp(1, 100).to_string()]);
+    }
+    args.push(format!("{out_format}:-"));
+
+    let mut last_err = String::from("not on PATH");
+    for program in ["magick", "convert"] {
+        if !crate::utils::capabilities::available(program) {
+            continue;
+        }
+        let mut child = match tokio::process::Command::new(program)
+            .args(&args)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                last_err = format!("{program}: {e}");
+                continue;
+            }
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            // Write on a task so a large image can't deadlock against a full
+            // stdout pipe.
+            let input = bytes.clone();
+            tokio::spawn(async move {
+                let _ = stdin.write_all(&input).await;
+            });
+        }
+        let output = child.wait_with_output().await?;
+        if output.status.success() && !output.stdout.is_empty() {
+            return Ok((out_mime.to_string(), output.stdout));
+        }
+        return Err(WinxError::CommandExecutionError(format!(
+            "{program} failed to convert the image: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
     }
+    Err(WinxError::RecoverableSuggestionError {
+        message: format!(
+            "max_width/jpeg_quality need ImageMagick, which wasn't found ({last_err})"
+        ),
+        suggestion: "Install ImageMagick or call ReadImage without max_width/jpeg_quality"
+            .to_string(),
+    })
 }
 
 /// Handle the `ReadImage` tool call
@@ -164,8 +317,19 @@ pub async fn handle_tool_call(
         workspace_root = bash_state.workspace_root.clone();
     }
 
-    // Read the image file
-    read_image_from_path(&read_image.file_path, &cwd, &workspace_root)
+    let (mime_type, bytes) = if is_url(&read_image.file_path) {
+        fetch_image_url(&read_image.file_path).await?
+    } else {
+        read_image_from_path(&read_image.file_path, &cwd, &workspace_root)?
+    };
+    let (mime_type, bytes) = if read_image.max_width.is_some() || read_image.jpeg_quality.is_some()
+    {
+        transform_image(bytes, &mime_type, read_image.max_width, read_image.jpeg_quality).await?
+    } else {
+        (mime_type, bytes)
+    };
+
+    Ok((mime_type, general_purpose::STANDARD.encode(&bytes)))
 }
 
 #[cfg(test)]
@@ -180,10 +344,27 @@ mod tests {
         let ws = TempDir::new().unwrap();
         let img = ws.path().join("shot.png");
         fs::write(&img, b"\x89PNG\r\n\x1a\nfake").unwrap();
-        let (mime, b64) =
+        let (mime, bytes) =
             read_image_from_path(img.to_str().unwrap(), ws.path(), ws.path()).unwrap();
         assert_eq!(mime, "image/png");
-        assert!(!b64.is_empty());
+        assert!(!bytes.is_empty());
+    }
+
+    #[test]
+    fn sniffs_image_formats_from_magic_bytes() {
+        assert_eq!(sniff_mime(b"\x89PNG\r\n\x1a\nrest"), Some("image/png"));
+        assert_eq!(sniff_mime(&[0xFF, 0xD8, 0xFF, 0xE0]), Some("image/jpeg"));
+        assert_eq!(sniff_mime(b"GIF89a..."), Some("image/gif"));
+        assert_eq!(sniff_mime(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff_mime(b"<!doctype html>"), None);
+    }
+
+    #[test]
+    fn url_paths_are_detected() {
+        assert!(is_url("https://example.com/a.png"));
+        assert!(is_url("http://localhost:8000/shot.jpg"));
+        assert!(!is_url("/tmp/https.png"));
+        assert!(!is_url("file:///etc/passwd"));
     }
 
     #[test]
@@ -0,0 +1,344 @@
+//! Implementation of the `RenameSymbol` tool: rename an identifier across the
+//! workspace by walking each file's tree-sitter syntax tree.
+//!
+//! Only identifier tokens spelled exactly like the old name change, so
+//! `parse_config` inside a string, a comment or `parse_config_file` is left
+//! alone (those mentions are reported instead). Like `CodeMap references` it is
+//! name-based, not scope-aware: every identifier with that name in scope
+//! changes. The default is a preview; `apply=true` writes every file through the
+//! same path as `MultiFileEdit` (mode gate, protected paths, undo checkpoints),
+//! after re-checking that none changed since it was scanned.
+
+use std::fmt::Write as FmtWrite;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+use crate::errors::{Result, WinxError};
+use crate::state::bash_state::BashState;
+use crate::tools::file_write_or_edit::{commit_edit, plan_rewrite};
+use crate::types::RenameSymbol;
+use crate::utils::path::resolve_in_workspace;
+use crate::utils::repo::walk_workspace_files;
+use crate::utils::symbols;
+
+/// Skip files larger than this when scanning.
+const MAX_FILE_SIZE: u64 = 2_000_000;
+/// A rename touching more files than this must be scoped with `path`, for the
+/// same reason `MultiFileEdit` caps its batches.
+const MAX_FILES_CHANGED: usize = 100;
+/// Changed lines shown in a preview.
+const MAX_PREVIEW_LINES: usize = 200;
+/// Comment/string mentions listed by location.
+const MAX_MENTIONS_LISTED: usize = 20;
+
+/// One file the rename would change.
+struct FileRename {
+    path: PathBuf,
+    previous: String,
+    renamed: String,
+    occurrences: usize,
+    /// 1-based lines of other mentions of the old name (comments, strings,
+    /// longer identifiers), which are not changed.
+    other_mentions: Vec<usize>,
+    /// Whether the new name is already an identifier in this file.
+    collides: bool,
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Leaf node kinds that spell a name across the supported grammars
+/// (`identifier`, `type_identifier`, `field_identifier`, ..., Ruby's `constant`,
+/// PHP's `name`).
+fn is_name_node(kind: &str) -> bool {
+    kind.ends_with("identifier") || kind == "constant" || kind == "name"
+}
+
+/// Byte ranges of every identifier token in `text` spelled `name`, or `None`
+/// when the file's language isn't supported or it fails to parse.
+fn identifier_ranges(ext: &str, text: &str, name: &str) -> Option<Vec<std::ops::Range<usize>>> {
+    let language = symbols::language_for(ext)?;
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&language).ok()?;
+    let tree = parser.parse(text, None)?;
+    let mut cursor = tree.walk();
+    let mut ranges = Vec::new();
+    loop {
+        let node = cursor.node();
+        if node.child_count() == 0
+            && is_name_node(node.kind())
+            && text.get(node.byte_range()) == Some(name)
+        {
+            ranges.push(node.byte_range());
+        }
+        if cursor.goto_first_child() || cursor.goto_next_sibling() {
+            continue;
+        }
+        loop {
+            if !cursor.goto_parent() {
+                return Some(ranges);
+            }
+            if cursor.goto_next_sibling() {
+                break;
+            }
+        }
+    }
+}
+
+fn line_of(text: &str, byte: usize) -> usize {
+    text[..byte].matches('\n').count() + 1
+}
+
+/// The rename of one file, or `None` if it has no identifier to change.
+fn rename_in(path: &Path, text: String, name: &str, new_name: &str) -> Option<FileRename> {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let ranges = identifier_ranges(ext, &text, name)?;
+    if ranges.is_empty() {
+        return None;
+    }
+    let mut renamed = text.clone();
+    for range in ranges.iter().rev() {
+        renamed.replace_range(range.clone(), new_name);
+    }
+    let other_mentions = text
+        .match_indices(name)
+        .filter(|(start, _)| !ranges.iter().any(|range| range.start == *start))
+        .map(|(start, _)| line_of(&text, start))
+        .collect();
+    let collides = identifier_ranges(ext, &text, new_name).is_some_and(|r| !r.is_empty());
+    Some(FileRename {
+        path: path.to_path_buf(),
+        previous: text,
+        renamed,
+        occurrences: ranges.len(),
+        other_mentions,
+        collides,
+    })
+}
+
+/// Every file under `root` the rename would change.
+fn scan(root: &Path, name: &str, new_name: &str) -> Vec<FileRename> {
+    let candidates =
+        if root.is_file() { vec![root.to_path_buf()] } else { walk_workspace_files(root) };
+    candidates
+        .iter()
+        .filter(|path| {
+            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+            symbols::supports(ext) && path.metadata().is_ok_and(|m| m.len() <= MAX_FILE_SIZE)
+        })
+        .filter_map(|path| {
+            let (text, _) = crate::utils::text_encoding::read_text(path).ok()?;
+            if !text.contains(name) {
+                return None;
+            }
+            rename_in(path, text, name, new_name)
+        })
+        .collect()
+}
+
+fn rel(path: &Path, workspace_root: &Path) -> String {
+    path.strip_prefix(workspace_root).unwrap_or(path).display().to_string()
+}
+
+/// Mentions that were not renamed and files where the new name already exists.
+fn notes(files: &[FileRename], workspace_root: &Path, name: &str, new_name: &str) -> String {
+    let mut out = String::new();
+    let mentions: Vec<String> = files
+        .iter()
+        .flat_map(|file| {
+            let rel = rel(&file.path, workspace_root);
+            file.other_mentions.iter().map(move |line| format!("{rel}:{line}"))
+        })
+        .collect();
+    if !mentions.is_empty() {
+        let listed = mentions.iter().take(MAX_MENTIONS_LISTED).cloned().collect::<Vec<_>>();
+        let more = mentions.len().saturating_sub(MAX_MENTIONS_LISTED);
+        let _ = write!(
+            out,
+            "\nNot renamed: {} other mention(s) of `{name}` in comments, strings or longer names: \
+             {}{}",
+            mentions.len(),
+            listed.join(", "),
+            if more > 0 { format!(" (+{more} more)") } else { String::new() }
+        );
+    }
+    let collisions: Vec<String> =
+        files.iter().filter(|f| f.collides).map(|f| rel(&f.path, workspace_root)).collect();
+    if !collisions.is_empty() {
+        let _ = write!(
+            out,
+            "\nWarning: `{new_name}` is already used in {}; check the rename doesn't collide.",
+            collisions.join(", ")
+        );
+    }
+    out
+}
+
+fn preview(files: &[FileRename], workspace_root: &Path) -> String {
+    let mut out = String::new();
+    let mut shown = 0;
+    for file in files {
+        let _ = writeln!(out, "\n{} ({}):", rel(&file.path, workspace_root), file.occurrences);
+        let old_lines = file.previous.lines();
+        for (number, (old, new)) in old_lines.zip(file.renamed.lines()).enumerate() {
+            if old == new {
+                continue;
+            }
+            if shown == MAX_PREVIEW_LINES {
+                out.push_str("  ...\n");
+                return out;
+            }
+            shown += 1;
+            let _ =
+                writeln!(out, "  {}- {}\n  {}+ {}", number + 1, old.trim(), number + 1, new.trim());
+        }
+    }
+    out
+}
+
+/// Plan every file, then commit them in turn (see `multi_file_edit`).
+fn apply(bash_state: &mut BashState, files: Vec<FileRename>) -> Result<String> {
+    let mut planned = Vec::with_capacity(files.len());
+    for file in files {
+        planned.push(plan_rewrite(bash_state, &file.path, &file.previous, file.renamed)?);
+    }
+    let total = planned.len();
+    let mut summaries = Vec::with_capacity(total);
+    for (committed, edit) in planned.into_iter().enumerate() {
+        let target = edit.target().to_string();
+        match commit_edit(bash_state, edit) {
+            Ok(summary) => summaries.push(format!("[{target}]\n{summary}")),
+            Err(e) => {
+                return Err(WinxError::CommandExecutionError(format!(
+                    "RenameSymbol: committed {committed} of {total} files, then failed writing \
+                     {target}: {e}\nThe {committed} already-written file(s) were NOT rolled back. \
+                     Re-run RenameSymbol to finish the rename."
+                )));
+            }
+        }
+    }
+    Ok(summaries.join("\n\n"))
+}
+
+#[instrument(level = "info", skip(bash_state_arc, args))]
+pub async fn handle_tool_call(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    args: RenameSymbol,
+) -> Result<String> {
+    let (name, new_name) = (args.name.trim().to_string(), args.new_name.trim().to_string());
+    if !is_identifier(&name) || !is_identifier(&new_name) {
+        return Err(WinxError::InvalidInput(format!(
+            "name and new_name must be plain identifiers (got {name:?} and {new_name:?})"
+        )));
+    }
+    if name == new_name {
+        return Err(WinxError::InvalidInput("new_name is the same as name".to_string()));
+    }
+    let (cwd, workspace_root) = {
+        let mut guard = bash_state_arc.lock().await;
+        let bash_state = crate::tools::file_ops::session(&mut guard, &args.thread_id)?;
+        (bash_state.cwd.clone(), bash_state.workspace_root.clone())
+    };
+    let workspace_root = workspace_root.canonicalize().unwrap_or(workspace_root);
+    let root = resolve_in_workspace(&args.path, &cwd, &workspace_root).map_err(|e| {
+        WinxError::PathSecurityError { path: PathBuf::from(&args.path), message: e.to_string() }
+    })?;
+    if !root.exists() {
+        return Err(WinxError::FileAccessError {
+            path: root,
+            message: "path not found".to_string(),
+        });
+    }
+
+    // Parsing every file is CPU-bound; keep it off the async workers.
+    let (scan_name, scan_new) = (name.clone(), new_name.clone());
+    let mut files =
+        tokio::task::spawn_blocking(move || scan(&root, &scan_name, &scan_new)).await.map_err(
+            |e| WinxError::CommandExecutionError(format!("RenameSymbol scan failed: {e}")),
+        )?;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    if files.is_empty() {
+        return Ok(format!("No identifier `{name}` found; nothing to rename."));
+    }
+    if files.len() > MAX_FILES_CHANGED {
+        return Err(WinxError::InvalidInput(format!(
+            "Renaming `{name}` would change {} files (limit {MAX_FILES_CHANGED}); scope it with \
+             path and rename in parts.",
+            files.len()
+        )));
+    }
+    let occurrences: usize = files.iter().map(|f| f.occurrences).sum();
+    let summary =
+        format!("`{name}` -> `{new_name}`: {occurrences} occurrence(s) in {} file(s)", files.len());
+    let notes = notes(&files, &workspace_root, &name, &new_name);
+
+    if !args.apply {
+        return Ok(format!(
+            "Preview of {summary} (nothing written; call again with apply=true):\n{}{notes}",
+            preview(&files, &workspace_root)
+        ));
+    }
+
+    let mut guard = bash_state_arc.lock().await;
+    crate::tools::file_ops::session(&mut guard, &args.thread_id)?;
+    let mut state = guard.take().ok_or(WinxError::BashStateNotInitialized)?;
+    let (state, result) = tokio::task::spawn_blocking(move || {
+        let result = apply(&mut state, files);
+        (state, result)
+    })
+    .await
+    .map_err(|e| WinxError::CommandExecutionError(format!("RenameSymbol task failed: {e}")))?;
+    *guard = Some(state);
+    Ok(format!("Renamed {summary}.{notes}\n\n{}", result?))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn renames_identifiers_only() {
+        let text = "// parse_config reads it\nfn parse_config() {}\nfn main() {\n    \
+                    parse_config();\n    let s = \"parse_config\";\n    parse_config_file();\n}\n"
+            .to_string();
+        let file = rename_in(Path::new("lib.rs"), text, "parse_config", "load_config").unwrap();
+        assert_eq!(file.occurrences, 2);
+        assert!(file.renamed.contains("fn load_config() {}"));
+        assert!(file.renamed.contains("    load_config();"));
+        assert!(file.renamed.contains("// parse_config reads it"));
+        assert!(file.renamed.contains("\"parse_config\""));
+        assert!(file.renamed.contains("parse_config_file();"));
+        assert_eq!(file.other_mentions, [1, 5, 6]);
+        assert!(!file.collides);
+    }
+
+    #[test]
+    fn covers_types_fields_and_collisions() {
+        let text = "struct Point { x: i32 }\nfn f(p: Point) -> i32 { let x = p.x; x }\n";
+        let file = rename_in(Path::new("a.rs"), text.to_string(), "x", "p").unwrap();
+        // The field definition, the field access and the local binding + use.
+        assert_eq!(file.occurrences, 4);
+        assert!(file.collides, "p is already a parameter");
+        let file =
+            rename_in(Path::new("a.ts"), "class Point {}\nnew Point();\n".into(), "Point", "Vec2")
+                .unwrap();
+        assert_eq!(file.renamed, "class Vec2 {}\nnew Vec2();\n");
+        assert!(rename_in(Path::new("notes.txt"), "Point".into(), "Point", "Vec2").is_none());
+    }
+
+    #[test]
+    fn rejects_non_identifiers() {
+        assert!(is_identifier("parse_config"));
+        assert!(is_identifier("$scope"));
+        assert!(!is_identifier("a.b"));
+        assert!(!is_identifier("1abc"));
+        assert!(!is_identifier(""));
+    }
+}
@@ -0,0 +1,310 @@
+//! Implementation of the `Scaffold` tool: project skeletons and module
+//! boilerplate from named templates.
+//!
+//! Templates are the embedded ones below plus any directory under
+//! `~/.winx/templates/<template>/` (a user template with an embedded name
+//! replaces it). `{{name}}` and `{{snake_name}}` are substituted in both file
+//! paths and contents. Files are written as one `MultiFileEdit` batch, so every
+//! file is planned (mode gate, protected paths, workspace confinement) before
+//! any is written, and each write is atomic with an undo checkpoint. Existing
+//! files are never overwritten.
+
+use std::fmt::Write as FmtWrite;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+use crate::errors::{Result, WinxError};
+use crate::state::bash_state::BashState;
+use crate::types::{FileEditEntry, Scaffold};
+
+/// User template files larger than this are skipped (templates are text).
+const MAX_TEMPLATE_FILE_SIZE: u64 = 1_000_000;
+/// Files a user template may hold; `MultiFileEdit`'s batch cap.
+const MAX_TEMPLATE_FILES: usize = 100;
+
+struct Template {
+    name: &'static str,
+    description: &'static str,
+    /// `(path relative to the target directory, content)`.
+    files: &'static [(&'static str, &'static str)],
+}
+
+const EMBEDDED: &[Template] = &[
+    Template {
+        name: "rust-bin",
+        description: "Cargo binary crate in ./{{name}}",
+        files: &[
+            (
+                "{{name}}/Cargo.toml",
+                "[package]\nname = \"{{name}}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+                 [dependencies]\n",
+            ),
+            ("{{name}}/src/main.rs", "fn main() {\n    println!(\"Hello from {{name}}!\");\n}\n"),
+            ("{{name}}/.gitignore", "/target\n"),
+        ],
+    },
+    Template {
+        name: "rust-lib",
+        description: "Cargo library crate in ./{{name}}",
+        files: &[
+            (
+                "{{name}}/Cargo.toml",
+                "[package]\nname = \"{{name}}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+                 [dependencies]\n",
+            ),
+            (
+                "{{name}}/src/lib.rs",
+                "//! {{name}}.\n\npub fn add(left: u64, right: u64) -> u64 {\n    left + right\n\
+                 }\n\n\
+                 #[cfg(test)]\nmod tests {\n    use super::*;\n\n    #[test]\n    fn it_works() \
+                 {\n        assert_eq!(add(2, 2), 4);\n    }\n}\n",
+            ),
+            ("{{name}}/.gitignore", "/target\n"),
+        ],
+    },
+    Template {
+        name: "node",
+        description: "Node.js package (ES modules, node --test) in ./{{name}}",
+        files: &[
+            (
+                "{{name}}/package.json",
+                "{\n  \"name\": \"{{name}}\",\n  \"version\": \"0.1.0\",\n  \
+                 \"type\": \"module\",\n  \"main\": \"index.js\",\n  \"scripts\": {\n    \
+                 \"test\": \"node --test\"\n  }\n}\n",
+            ),
+            ("{{name}}/index.js", "export function greet(who) {\n  return `Hello, ${who}!`;\n}\n"),
+            (
+                "{{name}}/test/index.test.js",
+                "import { test } from 'node:test';\nimport assert from 'node:assert/strict';\n\
+                 import { greet } from '../index.js';\n\ntest('greet', () => {\n  \
+                 assert.equal(greet('you'), 'Hello, you!');\n});\n",
+            ),
+            ("{{name}}/.gitignore", "node_modules/\n"),
+        ],
+    },
+    Template {
+        name: "python",
+        description: "Python package (src layout, pyproject.toml, pytest) in ./{{name}}",
+        files: &[
+            (
+                "{{name}}/pyproject.toml",
+                "[build-system]\nrequires = [\"setuptools>=68\"]\n\
+                 build-backend = \"setuptools.build_meta\"\n\n[project]\nname = \"{{name}}\"\n\
+                 version = \"0.1.0\"\nrequires-python = \">=3.9\"\n\n\
+                 [project.optional-dependencies]\n\
+                 test = [\"pytest\"]\n",
+            ),
+            (
+                "{{name}}/src/{{snake_name}}/__init__.py",
+                "\"\"\"{{name}}.\"\"\"\n\n__version__ = \"0.1.0\"\n",
+            ),
+            (
+                "{{name}}/tests/test_{{snake_name}}.py",
+                "import {{snake_name}}\n\n\ndef test_version():\n    \
+                 assert {{snake_name}}.__version__\n",
+            ),
+            ("{{name}}/.gitignore", "__pycache__/\n*.egg-info/\n.venv/\n"),
+        ],
+    },
+    Template {
+        name: "rust-module",
+        description: "Rust module file {{snake_name}}.rs with a test module",
+        files: &[(
+            "{{snake_name}}.rs",
+            "//! {{name}}.\n\n#[cfg(test)]\nmod tests {\n    #[test]\n    fn it_works() {}\n}\n",
+        )],
+    },
+    Template {
+        name: "python-module",
+        description: "Python module {{snake_name}}.py plus test_{{snake_name}}.py",
+        files: &[
+            ("{{snake_name}}.py", "\"\"\"{{name}}.\"\"\"\n"),
+            (
+                "test_{{snake_name}}.py",
+                "import {{snake_name}}\n\n\ndef test_import():\n    assert {{snake_name}}\n",
+            ),
+        ],
+    },
+];
+
+fn user_templates_dir() -> Option<PathBuf> {
+    home::home_dir().map(|home| home.join(".winx").join("templates"))
+}
+
+/// Names of the user templates in `dir`, sorted.
+fn user_template_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|entry| entry.file_name().to_str().map(ToString::to_string))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Every file under a user template directory, as `(relative path, content)`.
+fn read_user_template(root: &Path) -> Result<Vec<(String, String)>> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            if path.metadata().is_ok_and(|m| m.len() > MAX_TEMPLATE_FILE_SIZE) {
+                continue;
+            }
+            let content = std::fs::read_to_string(&path).map_err(|e| {
+                WinxError::FileAccessError { path: path.clone(), message: e.to_string() }
+            })?;
+            let rel = path.strip_prefix(root).unwrap_or(&path).to_string_lossy().to_string();
+            files.push((rel, content));
+            if files.len() > MAX_TEMPLATE_FILES {
+                return Err(WinxError::InvalidInput(format!(
+                    "template {} has more than {MAX_TEMPLATE_FILES} files",
+                    root.display()
+                )));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// The files of `template`: the user's copy if there is one, else the embedded.
+fn template_files(user_dir: Option<&Path>, template: &str) -> Result<Vec<(String, String)>> {
+    if let Some(dir) = user_dir.map(|dir| dir.join(template)).filter(|dir| dir.is_dir()) {
+        return read_user_template(&dir);
+    }
+    EMBEDDED
+        .iter()
+        .find(|t| t.name == template)
+        .map(|t| t.files.iter().map(|(p, c)| ((*p).to_string(), (*c).to_string())).collect())
+        .ok_or_else(|| {
+            WinxError::InvalidInput(format!(
+                "unknown template '{template}'. {}",
+                list_templates(user_dir)
+            ))
+        })
+}
+
+fn list_templates(user_dir: Option<&Path>) -> String {
+    let user = user_dir.map(user_template_names).unwrap_or_default();
+    let mut out = "Templates:".to_string();
+    for template in EMBEDDED.iter().filter(|t| !user.iter().any(|name| name == t.name)) {
+        let _ = write!(out, "\n  {:<14} {}", template.name, template.description);
+    }
+    for name in &user {
+        let _ = write!(out, "\n  {name:<14} (user template)");
+    }
+    out
+}
+
+fn is_valid_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn substitute(text: &str, name: &str) -> String {
+    text.replace("{{snake_name}}", &name.replace('-', "_")).replace("{{name}}", name)
+}
+
+#[instrument(level = "info", skip(bash_state_arc, args))]
+pub async fn handle_tool_call(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    args: Scaffold,
+) -> Result<String> {
+    let user_dir = user_templates_dir();
+    let template = args.template.trim();
+    if template.is_empty() {
+        return Ok(list_templates(user_dir.as_deref()));
+    }
+    if !is_valid_name(template) {
+        return Err(WinxError::InvalidInput(format!("invalid template name '{template}'")));
+    }
+    let name = args.name.trim();
+    if !is_valid_name(name) {
+        return Err(WinxError::InvalidInput(format!(
+            "name must start with a letter and use only letters, digits, '-' and '_' (got {name:?})"
+        )));
+    }
+    let files = template_files(user_dir.as_deref(), template)?;
+
+    let mut guard = bash_state_arc.lock().await;
+    let bash_state = crate::tools::file_ops::session(&mut guard, &args.thread_id)?;
+    let base = if args.path.trim().is_empty() {
+        bash_state.cwd.clone()
+    } else {
+        let expanded = crate::utils::path::expand_user(args.path.trim());
+        bash_state.cwd.join(expanded)
+    };
+    let entries: Vec<FileEditEntry> = files
+        .iter()
+        .map(|(path, content)| FileEditEntry {
+            file_path: base.join(substitute(path, name)).to_string_lossy().to_string(),
+            percentage_to_change: 100,
+            text_or_search_replace_blocks: substitute(content, name),
+        })
+        .collect();
+    let existing: Vec<&str> = entries
+        .iter()
+        .filter(|entry| Path::new(&entry.file_path).exists())
+        .map(|entry| entry.file_path.as_str())
+        .collect();
+    if !existing.is_empty() {
+        return Err(WinxError::InvalidInput(format!(
+            "Scaffold never overwrites; these files already exist: {}",
+            existing.join(", ")
+        )));
+    }
+    let created: Vec<String> = entries.iter().map(|entry| entry.file_path.clone()).collect();
+
+    let mut state = guard.take().ok_or(WinxError::BashStateNotInitialized)?;
+    let (state, result) = tokio::task::spawn_blocking(move || {
+        let result = crate::tools::multi_file_edit::apply_batch(&mut state, &entries);
+        (state, result)
+    })
+    .await
+    .map_err(|e| WinxError::CommandExecutionError(format!("Scaffold task failed: {e}")))?;
+    *guard = Some(state);
+    result?;
+    Ok(format!(
+        "Scaffolded '{template}' as {name} ({} file(s)):\n{}",
+        created.len(),
+        created.iter().map(|path| format!("  {path}")).collect::<Vec<_>>().join("\n")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn embedded_templates_substitute_names() {
+        let files = template_files(None, "python").unwrap();
+        let paths: Vec<String> = files.iter().map(|(p, _)| substitute(p, "my-tool")).collect();
+        assert!(paths.contains(&"my-tool/src/my_tool/__init__.py".to_string()));
+        assert!(substitute(&files[0].1, "my-tool").contains("name = \"my-tool\""));
+        assert!(template_files(None, "nope").unwrap_err().to_string().contains("rust-bin"));
+    }
+
+    #[test]
+    fn user_templates_override_embedded_ones() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("rust-bin/src")).unwrap();
+        std::fs::write(dir.path().join("rust-bin/src/main.rs"), "// {{name}}\n").unwrap();
+        let files = template_files(Some(dir.path()), "rust-bin").unwrap();
+        assert_eq!(files, [("src/main.rs".to_string(), "// {{name}}\n".to_string())]);
+        let listing = list_templates(Some(dir.path()));
+        assert!(listing.contains("rust-bin       (user template)"));
+        assert!(listing.contains("rust-lib"));
+    }
+}
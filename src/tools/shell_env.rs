@@ -0,0 +1,257 @@
+//! Implementation of the `ShellEnv` tool: read, set and unset environment
+//! variables of the persistent `BashCommand` shell, optionally mirroring the
+//! change into a dotenv file.
+//!
+//! The commands run through `BashCommand` itself (mode gate, hooks, the busy
+//! check), so every later command sees the change. A value being set never
+//! appears on the command line: it is handed over in a private temp file the
+//! shell reads and deletes, keeping it out of the terminal echo and shell
+//! history. Values of secret-looking names (`*TOKEN*`, `*KEY*`, ...) are masked
+//! in the result, and dotenv writes report only which file changed.
+
+use std::io::Write as IoWrite;
+use std::sync::{Arc, OnceLock};
+
+use regex::Regex;
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+use crate::errors::{Result, WinxError};
+use crate::state::bash_state::BashState;
+use crate::tools::file_write_or_edit::{commit_edit_blocking, plan_edit, plan_rewrite};
+use crate::types::{BashCommand, BashCommandAction, ShellEnv, ShellEnvOperation};
+use crate::utils::redact::is_secret_name;
+
+const COMMAND_TIMEOUT_SECS: f32 = 10.0;
+
+fn is_valid_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn sh_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+/// What the result shows for `value`: masked for secret-looking names.
+fn shown(name: &str, value: &str) -> String {
+    if is_secret_name(name) {
+        format!("<set, {} chars>", value.chars().count())
+    } else {
+        format!("{value:?}")
+    }
+}
+
+/// The shell prints `__WINX_ENV__[set]__<hex value>__END`; the format string is
+/// split so the echoed command line itself never matches.
+fn get_command(name: &str) -> String {
+    format!(
+        "printf '__WINX_%s__%s__%s__END\\n' ENV \"${{{name}+set}}\" \"$(printf %s \"${name}\" | od \
+         -An -tx1 | tr -d ' \\n')\""
+    )
+}
+
+fn marker() -> &'static Regex {
+    static MARKER: OnceLock<Regex> = OnceLock::new();
+    MARKER.get_or_init(|| {
+        #[allow(clippy::expect_used)]
+        Regex::new(r"__WINX_ENV__(set)?__([0-9a-f\s]*?)__END").expect("marker pattern compiles")
+    })
+}
+
+/// `Some(value)` if the variable is set, from the shell's output.
+fn parse_get(output: &str) -> Result<Option<String>> {
+    let caps = marker().captures(output).ok_or_else(|| {
+        WinxError::CommandExecutionError(format!("could not read the variable: {output}"))
+    })?;
+    if caps.get(1).is_none() {
+        return Ok(None);
+    }
+    let hex: Vec<u8> = caps[2].bytes().filter(u8::is_ascii_hexdigit).collect();
+    let bytes: Vec<u8> = hex
+        .chunks(2)
+        .filter_map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect();
+    Ok(Some(String::from_utf8_lossy(&bytes).into_owned()))
+}
+
+/// Run `command` in the session's shell, returning its output.
+async fn run(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    thread_id: &str,
+    command: String,
+) -> Result<String> {
+    let bash_command = BashCommand {
+        action_json: BashCommandAction::Command {
+            command,
+            is_background: false,
+            allow_multi: true,
+        },
+        wait_for_seconds: Some(COMMAND_TIMEOUT_SECS),
+        thread_id: thread_id.to_string(),
+    };
+    crate::tools::bash_command::handle_tool_call(bash_state_arc, bash_command).await
+}
+
+/// A dotenv value, double-quoted when it needs to be.
+fn dotenv_value(value: &str) -> String {
+    if !value.is_empty()
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || "_-./:@,+".contains(c))
+    {
+        return value.to_string();
+    }
+    let escaped =
+        value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('$', "\\$");
+    format!("\"{escaped}\"")
+}
+
+/// `text` with `name` set to `value` (replacing its first assignment and
+/// dropping any others), or removed when `value` is `None`.
+fn update_dotenv(text: &str, name: &str, value: Option<&str>) -> String {
+    let is_assignment = |line: &str| {
+        let line = line.trim_start();
+        let line = line.strip_prefix("export ").map_or(line, str::trim_start);
+        line.strip_prefix(name).is_some_and(|rest| rest.trim_start().starts_with('='))
+    };
+    let new_line = value.map(|value| format!("{name}={}", dotenv_value(value)));
+    let mut lines: Vec<String> = Vec::new();
+    let mut replaced = false;
+    for line in text.lines() {
+        if !is_assignment(line) {
+            lines.push(line.to_string());
+        } else if let (Some(new_line), false) = (&new_line, replaced) {
+            lines.push(new_line.clone());
+            replaced = true;
+        }
+    }
+    if let (Some(new_line), false) = (new_line, replaced) {
+        lines.push(new_line);
+    }
+    let mut out = lines.join("\n");
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+/// Write the change into `dotenv` through the edit path (mode gate, protected
+/// paths, undo checkpoint). The edit summary is dropped: its diff holds the value.
+async fn write_dotenv(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    args: &ShellEnv,
+    value: Option<&str>,
+) -> Result<String> {
+    let mut guard = bash_state_arc.lock().await;
+    let bash_state = crate::tools::file_ops::session(&mut guard, &args.thread_id)?;
+    let expanded = crate::utils::path::expand_user(args.dotenv.trim());
+    let path = bash_state.cwd.join(expanded);
+    let planned = if path.exists() {
+        let (previous, _) = crate::utils::text_encoding::read_text(&path).map_err(|e| {
+            WinxError::FileAccessError { path: path.clone(), message: e.to_string() }
+        })?;
+        let updated = update_dotenv(&previous, &args.name, value);
+        plan_rewrite(bash_state, &path, &previous, updated)?
+    } else if value.is_some() {
+        let content = update_dotenv("", &args.name, value);
+        plan_edit(bash_state, &path.to_string_lossy(), 100, &content)?
+    } else {
+        return Ok(format!("{} does not exist; nothing to remove.", path.display()));
+    };
+    let target = planned.target().to_string();
+    commit_edit_blocking(&mut guard, planned).await?;
+    Ok(format!("Updated {target}."))
+}
+
+/// Hand `value` to the shell through a private temp file (mode 0600) that the
+/// export command reads and deletes.
+fn value_file(value: &str) -> Result<std::path::PathBuf> {
+    let mut file = tempfile::Builder::new().prefix("winx-env-").tempfile()?;
+    file.write_all(value.as_bytes())?;
+    file.into_temp_path().keep().map_err(|e| WinxError::IoError(e.error))
+}
+
+#[instrument(level = "info", skip(bash_state_arc, args))]
+pub async fn handle_tool_call(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    args: ShellEnv,
+) -> Result<String> {
+    let name = args.name.trim().to_string();
+    if !is_valid_name(&name) {
+        return Err(WinxError::InvalidInput(format!(
+            "name must be a shell variable name like API_URL (got {name:?})"
+        )));
+    }
+    let args = ShellEnv { name, ..args };
+    let name = args.name.as_str();
+    let with_dotenv = !args.dotenv.trim().is_empty();
+
+    match args.operation {
+        ShellEnvOperation::Get => {
+            let output = run(bash_state_arc, &args.thread_id, get_command(name)).await?;
+            Ok(match parse_get(&output)? {
+                Some(value) => format!("{name}={}", shown(name, &value)),
+                None => format!("{name} is not set."),
+            })
+        }
+        ShellEnvOperation::Set => {
+            let file = value_file(&args.value)?;
+            let quoted = sh_quote(&file.to_string_lossy());
+            let command = format!(
+                "export {name}=\"$(cat -- {quoted}; printf x)\"; {name}=\"${{{name}%x}}\"; rm -f \
+                 -- {quoted}"
+            );
+            let result = run(bash_state_arc, &args.thread_id, command).await;
+            // The shell normally deletes it; make sure a failed run doesn't leave it.
+            let _ = std::fs::remove_file(&file);
+            result?;
+            let mut out = format!("Set {name}={} in the shell session.", shown(name, &args.value));
+            if with_dotenv {
+                out.push(' ');
+                out.push_str(&write_dotenv(bash_state_arc, &args, Some(&args.value)).await?);
+            }
+            Ok(out)
+        }
+        ShellEnvOperation::Unset => {
+            run(bash_state_arc, &args.thread_id, format!("unset {name}")).await?;
+            let mut out = format!("Unset {name} in the shell session.");
+            if with_dotenv {
+                out.push(' ');
+                out.push_str(&write_dotenv(bash_state_arc, &args, None).await?);
+            }
+            Ok(out)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn parses_the_marker_not_the_echo() {
+        let echo = get_command("API_URL");
+        let output = format!("{echo}\n__WINX_ENV__set__68 74\r\n7470__END\n");
+        assert_eq!(parse_get(&output).unwrap().as_deref(), Some("http"));
+        assert_eq!(parse_get("__WINX_ENV______END").unwrap(), None);
+        assert!(parse_get(&echo).is_err());
+    }
+
+    #[test]
+    fn secrets_are_masked() {
+        assert_eq!(shown("API_URL", "http://x"), "\"http://x\"");
+        assert_eq!(shown("github_token", "abc"), "<set, 3 chars>");
+    }
+
+    #[test]
+    fn dotenv_updates_replace_append_and_remove() {
+        let text = "# config\nexport API_URL=old\nOTHER=1\nAPI_URL=dup\n";
+        assert_eq!(
+            update_dotenv(text, "API_URL", Some("http://new")),
+            "# config\nAPI_URL=http://new\nOTHER=1\n"
+        );
+        assert_eq!(update_dotenv(text, "API_URL", None), "# config\nOTHER=1\n");
+        assert_eq!(update_dotenv("", "MSG", Some("hi there")), "MSG=\"hi there\"\n");
+        assert_eq!(update_dotenv("API_URL_2=x\n", "API_URL", None), "API_URL_2=x\n");
+    }
+}
@@ -0,0 +1,237 @@
+//! Implementation of the `SystemStats` tool: CPU, memory, disk and GPU usage of
+//! the machine winx runs on, so the agent can tell "the build is slow" from
+//! "the box is out of memory" before it guesses.
+//!
+//! CPU and memory come from `/proc` (Linux; elsewhere they are reported as
+//! unavailable), disk from `df -Pk` for the workspace and home filesystems, and
+//! GPUs from `nvidia-smi` when it is installed. Nothing here needs a session
+//! lock beyond reading the workspace root.
+
+use std::fmt::Write as FmtWrite;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+use crate::errors::{Result, WinxError};
+use crate::state::bash_state::BashState;
+use crate::types::{DiskStats, GpuStats, SystemStats, SystemStatsOutput};
+
+/// Interval between the two `/proc/stat` samples CPU usage is computed from.
+const CPU_SAMPLE_MS: u64 = 200;
+
+/// `(busy, total)` jiffies from the aggregate `cpu` line of `/proc/stat`.
+fn parse_cpu_times(stat: &str) -> Option<(u64, u64)> {
+    let line = stat.lines().find(|line| line.starts_with("cpu "))?;
+    let fields: Vec<u64> = line.split_whitespace().skip(1).filter_map(|f| f.parse().ok()).collect();
+    let total: u64 = fields.iter().sum();
+    // idle + iowait
+    let idle = fields.get(3).copied().unwrap_or(0) + fields.get(4).copied().unwrap_or(0);
+    Some((total.saturating_sub(idle), total))
+}
+
+async fn cpu_usage_percent() -> Option<f64> {
+    let (busy_before, total_before) =
+        parse_cpu_times(&std::fs::read_to_string("/proc/stat").ok()?)?;
+    tokio::time::sleep(Duration::from_millis(CPU_SAMPLE_MS)).await;
+    let (busy_after, total_after) = parse_cpu_times(&std::fs::read_to_string("/proc/stat").ok()?)?;
+    let total = total_after.saturating_sub(total_before);
+    #[allow(clippy::cast_precision_loss)]
+    (total > 0).then(|| busy_after.saturating_sub(busy_before) as f64 * 100.0 / total as f64)
+}
+
+fn parse_load_average(text: &str) -> Option<[f64; 3]> {
+    let mut fields = text.split_whitespace().map(|f| f.parse::<f64>().ok());
+    Some([fields.next()??, fields.next()??, fields.next()??])
+}
+
+/// `(total, available)` bytes from `/proc/meminfo`.
+fn parse_meminfo(text: &str) -> Option<(u64, u64)> {
+    let kib = |key: &str| {
+        text.lines()
+            .find_map(|line| line.strip_prefix(key))
+            .and_then(|rest| rest.trim().trim_end_matches("kB").trim().parse::<u64>().ok())
+            .map(|kib| kib * 1024)
+    };
+    Some((kib("MemTotal:")?, kib("MemAvailable:")?))
+}
+
+/// Filesystems from `df -Pk` output (POSIX format, 1 KiB blocks).
+fn parse_df(text: &str) -> Vec<DiskStats> {
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 6 {
+                return None;
+            }
+            Some(DiskStats {
+                mount: fields[5..].join(" "),
+                total_bytes: fields[1].parse::<u64>().ok()? * 1024,
+                available_bytes: fields[3].parse::<u64>().ok()? * 1024,
+            })
+        })
+        .collect()
+}
+
+fn disks(paths: &[PathBuf]) -> Vec<DiskStats> {
+    let Ok(output) = Command::new("df").arg("-Pk").args(paths).output() else {
+        return Vec::new();
+    };
+    let mut disks = parse_df(&String::from_utf8_lossy(&output.stdout));
+    disks.sort_by(|a, b| a.mount.cmp(&b.mount));
+    disks.dedup_by(|a, b| a.mount == b.mount);
+    disks
+}
+
+/// GPUs from `nvidia-smi --query-gpu=name,utilization.gpu,memory.used,memory.total,
+/// temperature.gpu --format=csv,noheader,nounits`.
+fn parse_nvidia_smi(text: &str) -> Vec<GpuStats> {
+    text.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if fields.len() < 5 {
+                return None;
+            }
+            Some(GpuStats {
+                name: fields[0].to_string(),
+                utilization_percent: fields[1].parse().ok(),
+                memory_used_mib: fields[2].parse().ok(),
+                memory_total_mib: fields[3].parse().ok(),
+                temperature_c: fields[4].parse().ok(),
+            })
+        })
+        .collect()
+}
+
+fn gpus() -> Vec<GpuStats> {
+    if !crate::utils::capabilities::available("nvidia-smi") {
+        return Vec::new();
+    }
+    Command::new("nvidia-smi")
+        .args([
+            "--query-gpu=name,utilization.gpu,memory.used,memory.total,temperature.gpu",
+            "--format=csv,noheader,nounits",
+        ])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| parse_nvidia_smi(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default()
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn gib(bytes: u64) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+}
+
+fn render(stats: &SystemStatsOutput) -> String {
+    let mut out = format!("CPU: {} core(s)", stats.cpu_cores);
+    if let Some(usage) = stats.cpu_usage_percent {
+        let _ = write!(out, ", {usage:.0}% busy");
+    }
+    if let Some([one, five, fifteen]) = stats.load_average {
+        let _ = write!(out, ", load {one:.2} {five:.2} {fifteen:.2}");
+    }
+    if let (Some(total), Some(available)) = (stats.memory_total_bytes, stats.memory_available_bytes)
+    {
+        let _ =
+            write!(out, "\nMemory: {:.1} GiB available of {:.1} GiB", gib(available), gib(total));
+    }
+    for disk in &stats.disks {
+        let _ = write!(
+            out,
+            "\nDisk {}: {:.1} GiB free of {:.1} GiB",
+            disk.mount,
+            gib(disk.available_bytes),
+            gib(disk.total_bytes)
+        );
+    }
+    for gpu in &stats.gpus {
+        let _ = write!(out, "\nGPU {}:", gpu.name);
+        if let Some(util) = gpu.utilization_percent {
+            let _ = write!(out, " {util:.0}% busy");
+        }
+        if let (Some(used), Some(total)) = (gpu.memory_used_mib, gpu.memory_total_mib) {
+            let _ = write!(out, ", {used:.0}/{total:.0} MiB");
+        }
+        if let Some(temp) = gpu.temperature_c {
+            let _ = write!(out, ", {temp:.0}°C");
+        }
+    }
+    for note in &stats.notes {
+        let _ = write!(out, "\nNote: {note}");
+    }
+    out
+}
+
+#[instrument(level = "info", skip(bash_state_arc, _args))]
+pub async fn handle_tool_call(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    _args: SystemStats,
+) -> Result<(String, Value)> {
+    let workspace_root = {
+        let guard = bash_state_arc.lock().await;
+        let bash_state = guard.as_ref().ok_or(WinxError::BashStateNotInitialized)?;
+        bash_state.workspace_root.clone()
+    };
+
+    let mut stats = SystemStatsOutput {
+        cpu_cores: std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get),
+        cpu_usage_percent: cpu_usage_percent().await,
+        load_average: std::fs::read_to_string("/proc/loadavg")
+            .ok()
+            .and_then(|text| parse_load_average(&text)),
+        ..SystemStatsOutput::default()
+    };
+    if let Some((total, available)) =
+        std::fs::read_to_string("/proc/meminfo").ok().and_then(|text| parse_meminfo(&text))
+    {
+        stats.memory_total_bytes = Some(total);
+        stats.memory_available_bytes = Some(available);
+    } else {
+        stats.notes.push("CPU and memory usage are read from /proc (Linux only)".to_string());
+    }
+    let mut paths = vec![workspace_root];
+    paths.extend(home::home_dir());
+    paths.retain(|path| path.exists());
+    stats.disks = tokio::task::spawn_blocking(move || disks(&paths))
+        .await
+        .map_err(|e| WinxError::CommandExecutionError(format!("SystemStats failed: {e}")))?;
+    stats.gpus = tokio::task::spawn_blocking(gpus)
+        .await
+        .map_err(|e| WinxError::CommandExecutionError(format!("SystemStats failed: {e}")))?;
+
+    Ok((render(&stats), crate::tools::structured_json(&stats)?))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn parses_proc_files() {
+        let stat = "cpu  100 0 50 800 50 0 0 0 0 0\ncpu0 1 2 3 4\n";
+        assert_eq!(parse_cpu_times(stat), Some((150, 1000)));
+        assert_eq!(parse_load_average("0.50 1.25 2.00 1/300 4242\n"), Some([0.5, 1.25, 2.0]));
+        let meminfo = "MemTotal:       16000000 kB\nMemFree: 1 kB\nMemAvailable:    8000000 kB\n";
+        assert_eq!(parse_meminfo(meminfo), Some((16_000_000 * 1024, 8_000_000 * 1024)));
+    }
+
+    #[test]
+    fn parses_df_and_nvidia_smi() {
+        let df = "Filesystem 1024-blocks Used Available Capacity Mounted on\n\
+                  /dev/sda1 1000 400 600 40% /mnt/my disk\n";
+        let disks = parse_df(df);
+        assert_eq!(disks[0].mount, "/mnt/my disk");
+        assert_eq!(disks[0].available_bytes, 600 * 1024);
+        let gpus = parse_nvidia_smi("NVIDIA A100, 37, 1024, 40960, 51\n");
+        assert_eq!(gpus[0].name, "NVIDIA A100");
+        assert_eq!(gpus[0].memory_total_mib, Some(40960.0));
+    }
+}
@@ -0,0 +1,248 @@
+//! Implementation of the `ScanTodos` tool: TODO/FIXME/HACK comments across the
+//! workspace with where they are, who wrote them and how long ago, so the agent
+//! (or the user) can turn them into a task list.
+//!
+//! A tag only counts right after a comment marker (`//`, `#`, `/*`, `*`, `--`,
+//! `;`, `<!--`), so identifiers like `todo_list` and most prose don't match.
+//! Author and age come from one `git blame --line-porcelain` per file with hits;
+//! outside a git repo, and for uncommitted or untracked lines, they're absent.
+
+use std::collections::HashMap;
+use std::fmt::Write as FmtWrite;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use regex::Regex;
+use serde_json::Value;
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+use crate::errors::{Result, WinxError};
+use crate::state::bash_state::BashState;
+use crate::types::{ScanTodos, TodoItem, TodoScanOutput};
+use crate::utils::path::resolve_in_workspace;
+use crate::utils::repo::walk_workspace_files;
+
+const DEFAULT_TAGS: &[&str] = &["TODO", "FIXME", "HACK"];
+const DEFAULT_MAX_RESULTS: usize = 200;
+/// Skip files larger than this when scanning (generated or vendored blobs).
+const MAX_FILE_SIZE: u64 = 2_000_000;
+/// Files blamed per call; items in the rest are listed without author/age.
+const MAX_BLAMED_FILES: usize = 200;
+
+/// The matcher for `tags`: a comment marker, the tag, an optional `(owner)` and
+/// separator, then the text.
+fn tag_regex(tags: &[String]) -> Result<Regex> {
+    let alternatives: Vec<String> = tags.iter().map(|tag| regex::escape(tag)).collect();
+    Regex::new(&format!(
+        r"(?://+|#+|/\*+|^\s*\*|--|;+|<!--)\s*@?({})\b(?:\([^)]*\))?[:\s]*(.*)",
+        alternatives.join("|")
+    ))
+    .map_err(|e| WinxError::InvalidInput(format!("invalid tags: {e}")))
+}
+
+/// `(line, tag, text)` of one tagged comment.
+type Hit = (usize, String, String);
+
+/// Every tagged comment in `text`.
+fn find_tags(re: &Regex, text: &str) -> Vec<Hit> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let caps = re.captures(line)?;
+            let body = caps[2].trim_end().trim_end_matches("*/").trim_end_matches("-->").trim();
+            Some((index + 1, caps[1].to_string(), body.to_string()))
+        })
+        .collect()
+}
+
+fn scan(root: &Path, re: &Regex) -> Vec<(PathBuf, Vec<Hit>)> {
+    let candidates =
+        if root.is_file() { vec![root.to_path_buf()] } else { walk_workspace_files(root) };
+    candidates
+        .into_iter()
+        .filter(|path| path.metadata().is_ok_and(|m| m.len() <= MAX_FILE_SIZE))
+        .filter_map(|path| {
+            // Binary and undecodable files have no comments to report.
+            let (text, _) = crate::utils::text_encoding::read_text(&path).ok()?;
+            let hits = find_tags(re, &text);
+            (!hits.is_empty()).then_some((path, hits))
+        })
+        .collect()
+}
+
+/// Author and author time per final line number, from `git blame
+/// --line-porcelain` output. Uncommitted lines are left out.
+fn parse_blame(porcelain: &str) -> HashMap<usize, (String, u64)> {
+    let mut lines = HashMap::new();
+    let mut current: Option<usize> = None;
+    let mut author = String::new();
+    let mut uncommitted = false;
+    for line in porcelain.lines() {
+        if line.starts_with('\t') {
+            current = None;
+            continue;
+        }
+        let mut fields = line.split(' ');
+        let first = fields.next().unwrap_or("");
+        if first.len() == 40 && first.bytes().all(|b| b.is_ascii_hexdigit()) {
+            current = fields.nth(1).and_then(|n| n.parse().ok());
+            uncommitted = first.bytes().all(|b| b == b'0');
+        } else if let Some(name) = line.strip_prefix("author ") {
+            author = name.to_string();
+        } else if let Some(time) = line.strip_prefix("author-time ") {
+            if let (Some(number), Ok(time)) = (current, time.parse()) {
+                if !uncommitted {
+                    lines.insert(number, (author.clone(), time));
+                }
+            }
+        }
+    }
+    lines
+}
+
+fn blame(path: &Path) -> Option<HashMap<usize, (String, u64)>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path.parent()?)
+        .args(["blame", "--line-porcelain", "--"])
+        .arg(path.file_name()?)
+        .output()
+        .ok()?;
+    output.status.success().then(|| parse_blame(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn collect(root: &Path, re: &Regex, workspace_root: &Path) -> TodoScanOutput {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let use_git = crate::utils::capabilities::available("git");
+    let mut output = TodoScanOutput::default();
+    for (index, (path, hits)) in scan(root, re).into_iter().enumerate() {
+        let blamed = if use_git && index < MAX_BLAMED_FILES { blame(&path) } else { None };
+        output.blamed |= blamed.is_some();
+        let file = path.strip_prefix(workspace_root).unwrap_or(&path).display().to_string();
+        for (line, tag, text) in hits {
+            let origin = blamed.as_ref().and_then(|lines| lines.get(&line));
+            *output.by_tag.entry(tag.clone()).or_insert(0) += 1;
+            output.items.push(TodoItem {
+                file: file.clone(),
+                line,
+                tag,
+                text,
+                author: origin.map(|(author, _)| author.clone()),
+                authored_at: origin.map(|(_, time)| *time),
+                age_days: origin.map(|(_, time)| now.saturating_sub(*time) / 86_400),
+            });
+        }
+    }
+    output.total = output.items.len();
+    // Oldest first: long-standing debt is what a task list most needs to surface.
+    output.items.sort_by(|a, b| {
+        (a.authored_at.is_none(), a.authored_at, &a.file, a.line).cmp(&(
+            b.authored_at.is_none(),
+            b.authored_at,
+            &b.file,
+            b.line,
+        ))
+    });
+    output
+}
+
+fn render(output: &TodoScanOutput) -> String {
+    if output.total == 0 {
+        return "No tagged comments found.".to_string();
+    }
+    let counts: Vec<String> =
+        output.by_tag.iter().map(|(tag, count)| format!("{tag} {count}")).collect();
+    let mut text = format!("{} tagged comment(s): {}", output.total, counts.join(", "));
+    if output.items.len() < output.total {
+        let _ = write!(text, " (showing the {} oldest)", output.items.len());
+    }
+    text.push('\n');
+    for item in &output.items {
+        let _ = write!(text, "\n{}:{}  {}  {}", item.file, item.line, item.tag, item.text);
+        if let (Some(author), Some(days)) = (&item.author, item.age_days) {
+            let _ = write!(text, "  ({author}, {days}d ago)");
+        }
+    }
+    if !output.blamed {
+        text.push_str("\n\nNo git history for these files, so authors and ages are unknown.");
+    }
+    text
+}
+
+#[instrument(level = "info", skip(bash_state_arc, args))]
+pub async fn handle_tool_call(
+    bash_state_arc: &Arc<Mutex<Option<BashState>>>,
+    args: ScanTodos,
+) -> Result<(String, Value)> {
+    let (cwd, workspace_root) = {
+        let guard = bash_state_arc.lock().await;
+        let bash_state = guard.as_ref().ok_or(WinxError::BashStateNotInitialized)?;
+        (bash_state.cwd.clone(), bash_state.workspace_root.clone())
+    };
+    let root = resolve_in_workspace(&args.path, &cwd, &workspace_root).map_err(|e| {
+        WinxError::PathSecurityError { path: PathBuf::from(&args.path), message: e.to_string() }
+    })?;
+    if !root.exists() {
+        return Err(WinxError::FileAccessError {
+            path: root,
+            message: "path not found".to_string(),
+        });
+    }
+    let tags: Vec<String> =
+        args.tags.iter().map(|tag| tag.trim().to_string()).filter(|tag| !tag.is_empty()).collect();
+    let tags =
+        if tags.is_empty() { DEFAULT_TAGS.iter().map(ToString::to_string).collect() } else { tags };
+    let re = tag_regex(&tags)?;
+    let max_results = if args.max_results == 0 { DEFAULT_MAX_RESULTS } else { args.max_results };
+
+    // Reading every file and running git blame both block; keep them off the
+    // async workers.
+    let mut output = tokio::task::spawn_blocking(move || collect(&root, &re, &workspace_root))
+        .await
+        .map_err(|e| WinxError::CommandExecutionError(format!("ScanTodos failed: {e}")))?;
+    output.items.truncate(max_results);
+    Ok((render(&output), crate::tools::structured_json(&output)?))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn finds_tags_only_in_comments() {
+        let re =
+            tag_regex(&DEFAULT_TAGS.iter().map(ToString::to_string).collect::<Vec<_>>()).unwrap();
+        let text = "let todo_list = 1; // TODO: drop this\n\
+                    # FIXME(ana) handle errors\n\
+                    /* HACK around the parser */\n\
+                    let s = \"TODO in a string\";\n\
+                    // TODOS are plural here\n";
+        let hits = find_tags(&re, text);
+        assert_eq!(
+            hits,
+            vec![
+                (1, "TODO".to_string(), "drop this".to_string()),
+                (2, "FIXME".to_string(), "handle errors".to_string()),
+                (3, "HACK".to_string(), "around the parser".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_blame_porcelain() {
+        let sha = "a".repeat(40);
+        let zero = "0".repeat(40);
+        let porcelain = format!(
+            "{sha} 1 1 1\nauthor Ana\nauthor-time 1700000000\nsummary x\n\tfn main() {{}}\n\
+             {sha} 2 2\nauthor Ana\nauthor-time 1700000000\n\t// TODO\n\
+             {zero} 3 3 1\nauthor Not Committed Yet\nauthor-time 1800000000\n\t// FIXME\n"
+        );
+        let lines = parse_blame(&porcelain);
+        assert_eq!(lines.get(&2), Some(&("Ana".to_string(), 1_700_000_000)));
+        assert!(!lines.contains_key(&3));
+    }
+}
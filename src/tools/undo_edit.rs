@@ -17,6 +17,7 @@ use crate::state::bash_state::BashState;
 use crate::tools::file_write_or_edit::{ensure_parent_dirs, hash_content, write_no_follow};
 use crate::types::{normalize_thread_id, UndoEdit};
 use crate::utils::path::{expand_user, validate_path_in_workspace};
+use crate::utils::text_encoding;
 
 #[instrument(level = "info", skip(bash_state_arc, undo))]
 pub async fn handle_tool_call(
@@ -41,15 +42,18 @@ pub async fn handle_tool_call(
     };
     let path = validate_path_in_workspace(&path, &bash_state.workspace_root)
         .map_err(|e| WinxError::PathSecurityError { path: path.clone(), message: e.to_string() })?;
+    crate::utils::protected_paths::check_path(&path, &bash_state.workspace_root)?;
     let file_path_str = path.to_string_lossy().to_string();
 
     // Refuse if the file changed since the edit we'd undo. The whitelist holds the
     // hash of the content winx last wrote; if the disk no longer matches, an undo
     // would silently discard those newer (external) changes.
-    let on_disk = std::fs::read_to_string(&path).ok();
+    let on_disk = text_encoding::read_text(&path).ok();
     let wrote_hash =
         bash_state.whitelist_for_overwrite.get(&file_path_str).map(|w| w.file_hash.clone());
-    let unchanged = matches!((&on_disk, &wrote_hash), (Some(c), Some(h)) if &hash_content(c) == h);
+    let unchanged =
+        matches!((&on_disk, &wrote_hash), (Some((c, _)), Some(h)) if &hash_content(c) == h);
+    let encoding = on_disk.map(|(_, encoding)| encoding).unwrap_or_default();
     if !unchanged {
         return Err(WinxError::FileAccessError {
             path,
@@ -75,7 +79,11 @@ pub async fn handle_tool_call(
     // later edit's hash gate matches the reverted content (or drop it, forcing a
     // re-read, when there was none).
     ensure_parent_dirs(&path)?;
-    write_no_follow(&path, checkpoint.prior_content.as_bytes())?;
+    // Same encoding as the edit kept (a BOM or latin-1 file stays one).
+    let bytes = text_encoding::encode(&checkpoint.prior_content, encoding)
+        .unwrap_or_else(|_| checkpoint.prior_content.clone().into_bytes());
+    write_no_follow(&path, &bytes)?;
+    crate::utils::symbol_cache::invalidate(&path);
     match checkpoint.prior_whitelist {
         Some(whitelist) => {
             bash_state.whitelist_for_overwrite.insert(file_path_str.clone(), whitelist);
@@ -490,6 +490,26 @@ pub struct ReadFiles {
     #[serde(default)]
     pub thread_id: String,
 
+    /// Optional per-file token budget, overriding the default (24k tokens for
+    /// source files, 8k for everything else). Content past the budget is cut and
+    /// the output says where to continue.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<usize>,
+
+    /// Optional byte budget for streaming huge files (logs, dumps). When set,
+    /// each file is read as a window of at most this many bytes starting at
+    /// `byte_offset`, without loading the rest of the file, and the output ends
+    /// with the `byte_offset` to pass next. Windowed reads don't show line
+    /// numbers and don't count as a read for `FileWriteOrEdit`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<u64>,
+
+    /// Continuation cursor returned by a previous `max_bytes` read: the byte
+    /// offset to resume from. Ignored unless `max_bytes` is set; only valid
+    /// with a single path in `file_paths`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub byte_offset: Option<u64>,
+
     // Internal fields - not part of MCP schema (parsed from file_paths)
     #[serde(skip)]
     #[schemars(skip)]
@@ -511,6 +531,12 @@ impl<'de> Deserialize<'de> for ReadFiles {
             file_paths: Option<Vec<String>>,
             #[serde(default)]
             thread_id: Option<String>,
+            #[serde(default)]
+            max_tokens: Option<usize>,
+            #[serde(default)]
+            max_bytes: Option<u64>,
+            #[serde(default)]
+            byte_offset: Option<u64>,
         }
 
         let input = serde_json::Value::deserialize(deserializer)?;
@@ -544,7 +570,15 @@ impl<'de> Deserialize<'de> for ReadFiles {
             end_line_nums.push(end);
         }
 
-        Ok(ReadFiles { file_paths: clean_file_paths, thread_id, start_line_nums, end_line_nums })
+        Ok(ReadFiles {
+            file_paths: clean_file_paths,
+            thread_id,
+            max_tokens: helper.max_tokens,
+            max_bytes: helper.max_bytes,
+            byte_offset: helper.byte_offset,
+            start_line_nums,
+            end_line_nums,
+        })
     }
 }
 
@@ -998,6 +1032,674 @@ pub struct UndoEdit {
     pub thread_id: String,
 }
 
+/// Parameters for the `MoveFile` tool: move or rename a file or directory.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MoveFile {
+    /// File or directory to move (absolute, ~ allowed).
+    pub source: String,
+
+    /// Full new path, not a directory to move into (absolute, ~ allowed).
+    pub destination: String,
+
+    /// Replace an existing destination file. Never replaces a directory.
+    #[serde(default)]
+    pub overwrite: bool,
+
+    /// The thread ID for this session
+    pub thread_id: String,
+}
+
+/// Parameters for the `CopyFile` tool: copy a file, or a directory recursively.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CopyFile {
+    /// File or directory to copy (absolute, ~ allowed).
+    pub source: String,
+
+    /// Full path of the copy, not a directory to copy into (absolute, ~ allowed).
+    pub destination: String,
+
+    /// Replace an existing destination file. Never replaces a directory.
+    #[serde(default)]
+    pub overwrite: bool,
+
+    /// The thread ID for this session
+    pub thread_id: String,
+}
+
+/// Parameters for the `DeleteFile` tool: move a file or directory to the
+/// workspace trash (`.winx/trash/`), or delete it permanently.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DeleteFile {
+    /// File or directory to delete (absolute, ~ allowed).
+    pub path: String,
+
+    /// Required to delete a non-empty directory.
+    #[serde(default)]
+    pub recursive: bool,
+
+    /// Delete immediately instead of moving to `.winx/trash/`.
+    #[serde(default)]
+    pub permanent: bool,
+
+    /// The thread ID for this session
+    pub thread_id: String,
+}
+
+/// Parameters for the `CreateDirectory` tool: `mkdir -p` inside the workspace.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CreateDirectory {
+    /// Directory to create, with any missing parents (absolute, ~ allowed).
+    pub path: String,
+
+    /// The thread ID for this session
+    pub thread_id: String,
+}
+
+/// Parameters for the `CreateArchive` tool: pack files and directories into a
+/// `.zip`, `.tar`, `.tar.gz`/`.tgz` or `.tar.zst`/`.tzst` (format by extension).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CreateArchive {
+    /// Archive to create (absolute, ~ allowed); its extension picks the format.
+    pub archive_path: String,
+
+    /// Files and directories to include. They must be under the current working
+    /// directory, and are stored relative to it.
+    pub paths: Vec<String>,
+
+    /// Replace an existing archive.
+    #[serde(default)]
+    pub overwrite: bool,
+
+    /// The thread ID for this session
+    pub thread_id: String,
+}
+
+/// Parameters for the `ExtractArchive` tool: unpack a `.zip`, `.tar`,
+/// `.tar.gz`/`.tgz` or `.tar.zst`/`.tzst` into a workspace directory.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExtractArchive {
+    /// Archive to unpack (absolute, ~ allowed).
+    pub archive_path: String,
+
+    /// Directory to unpack into; created if missing (absolute, ~ allowed).
+    pub destination: String,
+
+    /// Replace files that already exist in the destination.
+    #[serde(default)]
+    pub overwrite: bool,
+
+    /// The thread ID for this session
+    pub thread_id: String,
+}
+
+/// Parameters for the `MemoryWrite` tool: remember a durable fact across
+/// sessions, or replace/forget earlier ones.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MemoryWrite {
+    /// One self-contained fact, e.g. "uses pnpm, not npm". May be empty when
+    /// `replaces` is given, to only forget.
+    #[serde(default)]
+    pub fact: String,
+
+    /// Remember it for every workspace instead of only the current one.
+    #[serde(default)]
+    pub global: bool,
+
+    /// Ids (from `MemorySearch`) of memories this one supersedes; they are
+    /// deleted.
+    #[serde(default)]
+    pub replaces: Vec<String>,
+
+    /// Optional thread ID identifying the shell session to operate on. When
+    /// omitted, the most recently active session is used.
+    #[serde(default)]
+    pub thread_id: String,
+}
+
+/// Parameters for the `MemorySearch` tool: recall remembered facts by keyword.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MemorySearch {
+    /// Keywords to look for. Empty lists every memory for this workspace
+    /// (and the global ones), newest first.
+    #[serde(default)]
+    pub query: String,
+
+    /// Maximum memories to return. 0 means the default.
+    #[serde(default)]
+    pub max_results: usize,
+
+    /// Optional thread ID identifying the shell session to operate on. When
+    /// omitted, the most recently active session is used.
+    #[serde(default)]
+    pub thread_id: String,
+}
+
+/// Parameters for the `RenameSymbol` tool: rename an identifier across files
+/// using the syntax tree, so strings, comments and longer names that contain it
+/// are left alone.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RenameSymbol {
+    /// The identifier to rename (exact, e.g. `parse_config`).
+    pub name: String,
+
+    /// The new identifier.
+    pub new_name: String,
+
+    /// File or directory to rename within. Empty = the whole workspace.
+    /// Relative paths resolve against the current directory.
+    #[serde(default)]
+    pub path: String,
+
+    /// Write the changes. When false (the default), only a preview of every
+    /// changed line is returned.
+    #[serde(default)]
+    pub apply: bool,
+
+    /// Optional thread ID identifying the shell session to operate on. When
+    /// omitted, the most recently active session is used.
+    #[serde(default)]
+    pub thread_id: String,
+}
+
+/// Parameters for the `ScanTodos` tool: TODO/FIXME/HACK comments across the
+/// workspace, with author and age from `git blame`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScanTodos {
+    /// File or directory to scan. Empty = the whole workspace. Relative paths
+    /// resolve against the current directory.
+    #[serde(default)]
+    pub path: String,
+
+    /// Comment tags to look for (case-sensitive). Empty = `TODO`, `FIXME`,
+    /// `HACK`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Maximum items to return. 0 means the default.
+    #[serde(default)]
+    pub max_results: usize,
+
+    /// Optional thread ID identifying the shell session to operate on. When
+    /// omitted, the most recently active session is used.
+    #[serde(default)]
+    pub thread_id: String,
+}
+
+/// One tagged comment found by `ScanTodos`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TodoItem {
+    /// Workspace-relative path.
+    pub file: String,
+    /// 1-based line number.
+    pub line: usize,
+    /// The tag that matched (`TODO`, `FIXME`, ...).
+    pub tag: String,
+    /// The comment text after the tag.
+    pub text: String,
+    /// Who last changed the line, per `git blame`; absent outside git or for
+    /// uncommitted lines.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// When that change was authored, in Unix seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authored_at: Option<u64>,
+    /// Whole days since `authored_at`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age_days: Option<u64>,
+}
+
+/// Structured result of a `ScanTodos` call (mirrors the text block).
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct TodoScanOutput {
+    /// Items found, oldest first (items without blame data last).
+    pub items: Vec<TodoItem>,
+    /// Items found before `max_results` was applied.
+    pub total: usize,
+    /// Item count per tag.
+    pub by_tag: std::collections::BTreeMap<String, usize>,
+    /// Whether author/age came from `git blame`.
+    pub blamed: bool,
+}
+
+/// Parameters for the `Scaffold` tool: create a project skeleton or module
+/// boilerplate from a named template.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Scaffold {
+    /// Template to use: `rust-bin`, `rust-lib`, `node`, `python`, `rust-module`,
+    /// `python-module`, or a directory name under `~/.winx/templates`. Empty
+    /// lists the available templates.
+    #[serde(default)]
+    pub template: String,
+
+    /// Project or module name, substituted for `{{name}}` (and, with `-` turned
+    /// into `_`, `{{snake_name}}`) in the template's paths and contents.
+    #[serde(default)]
+    pub name: String,
+
+    /// Directory to create the files in. Empty = the current directory.
+    /// Relative paths resolve against the current directory.
+    #[serde(default)]
+    pub path: String,
+
+    /// Optional thread ID identifying the shell session to operate on. When
+    /// omitted, the most recently active session is used.
+    #[serde(default)]
+    pub thread_id: String,
+}
+
+/// Operation for the `NotebookEdit` tool.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotebookOperation {
+    /// Every cell with its index, type, output count and source.
+    List,
+    /// Replace the source of the cell at `index` (optionally changing its type).
+    Edit,
+    /// Add a cell before `index` (omitted = at the end).
+    Insert,
+    /// Remove the cell at `index`.
+    Delete,
+    /// Clear the outputs and execution count of the code cell at `index`, or of
+    /// every code cell when `index` is omitted.
+    ClearOutputs,
+}
+
+/// Jupyter cell type.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotebookCellType {
+    Code,
+    Markdown,
+    Raw,
+}
+
+/// Parameters for the `NotebookEdit` tool: cell-level edits to a Jupyter
+/// notebook, instead of editing its JSON as text.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NotebookEdit {
+    /// The `.ipynb` file. Relative paths resolve against the current directory.
+    pub path: String,
+
+    pub operation: NotebookOperation,
+
+    /// 0-based cell index (see `list`). Required for `edit` and `delete`.
+    #[serde(default)]
+    pub index: Option<usize>,
+
+    /// The cell's new source for `edit` and `insert`, as plain text.
+    #[serde(default)]
+    pub source: String,
+
+    /// Cell type for `insert` (default `code`), or a new type for `edit`.
+    #[serde(default)]
+    pub cell_type: Option<NotebookCellType>,
+
+    /// Optional thread ID identifying the shell session to operate on. When
+    /// omitted, the most recently active session is used.
+    #[serde(default)]
+    pub thread_id: String,
+}
+
+/// Operation for the `ShellEnv` tool.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShellEnvOperation {
+    /// Show the variable's value (masked for secret-looking names).
+    Get,
+    /// Export the variable with `value`.
+    Set,
+    /// Unset the variable.
+    Unset,
+}
+
+/// Parameters for the `ShellEnv` tool: read or change an environment variable
+/// of the persistent shell session.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ShellEnv {
+    pub operation: ShellEnvOperation,
+
+    /// Variable name, e.g. `DATABASE_URL`.
+    pub name: String,
+
+    /// The value for `set`.
+    #[serde(default)]
+    pub value: String,
+
+    /// For `set`/`unset`: a dotenv file (e.g. `.env`) to write the same change
+    /// to, created if needed. Empty = only the shell session changes.
+    #[serde(default)]
+    pub dotenv: String,
+
+    /// Optional thread ID identifying the shell session to operate on. When
+    /// omitted, the most recently active session is used.
+    #[serde(default)]
+    pub thread_id: String,
+}
+
+/// Parameters for the `SystemStats` tool: CPU, memory, disk and GPU usage of
+/// the machine winx runs on.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SystemStats {
+    /// Optional thread ID identifying the shell session to operate on. When
+    /// omitted, the most recently active session is used.
+    #[serde(default)]
+    pub thread_id: String,
+}
+
+/// Usage of one filesystem.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DiskStats {
+    /// Mount point.
+    pub mount: String,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+}
+
+/// Usage of one GPU, as `nvidia-smi` reports it.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct GpuStats {
+    pub name: String,
+    pub utilization_percent: Option<f64>,
+    pub memory_used_mib: Option<f64>,
+    pub memory_total_mib: Option<f64>,
+    pub temperature_c: Option<f64>,
+}
+
+/// Structured result of a `SystemStats` call (mirrors the text block).
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct SystemStatsOutput {
+    /// Logical CPUs available to winx.
+    pub cpu_cores: usize,
+    /// Share of CPU time spent busy over a short sample, across all cores.
+    pub cpu_usage_percent: Option<f64>,
+    /// 1, 5 and 15 minute load averages.
+    pub load_average: Option<[f64; 3]>,
+    pub memory_total_bytes: Option<u64>,
+    /// Memory available to new processes without swapping.
+    pub memory_available_bytes: Option<u64>,
+    /// The filesystems holding the workspace and the home directory.
+    pub disks: Vec<DiskStats>,
+    /// NVIDIA GPUs; empty when `nvidia-smi` is not installed.
+    pub gpus: Vec<GpuStats>,
+    /// Anything that could not be measured on this platform.
+    pub notes: Vec<String>,
+}
+
+/// Operation for the `Browser` tool.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BrowserOperation {
+    /// Load `url` and wait for the page's load event.
+    Navigate,
+    /// PNG of the visible viewport.
+    Screenshot,
+    /// The page's rendered text (`document.body.innerText`).
+    Text,
+    /// Click the first element matching `selector`.
+    Click,
+    /// Evaluate `expression` in the page and return its JSON value.
+    Eval,
+    /// Shut the browser down.
+    Close,
+}
+
+/// Parameters for the `Browser` tool: drive a headless Chromium over the Chrome
+/// `DevTools` Protocol.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Browser {
+    pub operation: BrowserOperation,
+
+    /// For `navigate`: an http(s) URL, or `about:blank`. Local and private
+    /// hosts need `WINX_PRIVATE_URLS=1` on the server.
+    #[serde(default)]
+    pub url: String,
+
+    /// For `click`: a CSS selector, e.g. `button[type=submit]`.
+    #[serde(default)]
+    pub selector: String,
+
+    /// For `eval`: a JavaScript expression; promises are awaited.
+    #[serde(default)]
+    pub expression: String,
+
+    /// Optional thread ID identifying the shell session to operate on. When
+    /// omitted, the most recently active session is used.
+    #[serde(default)]
+    pub thread_id: String,
+}
+
+/// Operation for the `GitHub` tool.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GitHubOperation {
+    /// Issues of the repository (pull requests left out), newest first.
+    ListIssues,
+    /// Open an issue with `title` and `body`.
+    CreateIssue,
+    /// Open a pull request from the current branch into `base`.
+    CreatePullRequest,
+    /// Conversation, review and inline comments of pull request `number`.
+    PrComments,
+    /// Recent GitHub Actions runs for `branch`.
+    CiStatus,
+    /// Log tails of the failed jobs of Actions run `run_id`.
+    RunLogs,
+}
+
+/// Parameters for the `GitHub` tool: issues, pull requests and Actions runs of
+/// the workspace's `origin` repository on GitHub.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GitHub {
+    pub operation: GitHubOperation,
+
+    /// For `create_issue` / `create_pull_request`.
+    #[serde(default)]
+    pub title: String,
+
+    /// Markdown body for `create_issue` / `create_pull_request`.
+    #[serde(default)]
+    pub body: String,
+
+    /// Pull request number for `pr_comments`.
+    #[serde(default)]
+    pub number: Option<u64>,
+
+    /// Actions run id for `run_logs` (from `ci_status`).
+    #[serde(default)]
+    pub run_id: Option<u64>,
+
+    /// For `ci_status`: the branch (empty = the current branch).
+    #[serde(default)]
+    pub branch: String,
+
+    /// For `create_pull_request`: the branch to merge into (empty = the
+    /// repository's default branch).
+    #[serde(default)]
+    pub base: String,
+
+    /// For `list_issues`: `open` (default), `closed` or `all`.
+    #[serde(default)]
+    pub state: String,
+
+    /// For `list_issues` / `ci_status`: how many to list (default 30 issues or
+    /// 10 runs, max 100).
+    #[serde(default)]
+    pub limit: usize,
+
+    /// Optional thread ID identifying the shell session to operate on. When
+    /// omitted, the most recently active session is used.
+    #[serde(default)]
+    pub thread_id: String,
+}
+
+/// Parameters for the `HttpRequest` tool: one HTTP request, with its response
+/// summarized (and optionally saved to a file).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HttpRequest {
+    /// GET (default), POST, PUT, PATCH, DELETE, HEAD or OPTIONS.
+    #[serde(default)]
+    pub method: String,
+
+    /// An http(s) URL, or with `profile` a path relative to its `base_url`.
+    /// Hosts resolving to loopback, private or link-local addresses are
+    /// refused unless the server sets `WINX_PRIVATE_URLS=1`.
+    pub url: String,
+
+    /// Request headers, e.g. `{"Accept": "text/csv"}`.
+    #[serde(default)]
+    pub headers: std::collections::BTreeMap<String, String>,
+
+    /// A JSON body; sets `Content-Type: application/json` unless given.
+    #[serde(default)]
+    pub json: Option<serde_json::Value>,
+
+    /// A raw body, for anything that isn't JSON. Not together with `json`.
+    #[serde(default)]
+    pub body: String,
+
+    /// A named auth profile from the global winx config (`[http.profiles.<name>]`):
+    /// its headers are added, and only sent to URLs under its `base_url`.
+    #[serde(default)]
+    pub profile: String,
+
+    /// Save the full response body to this file (replacing it), e.g. to keep a
+    /// large or binary download out of the context.
+    #[serde(default)]
+    pub save_to: String,
+
+    /// Body bytes shown in the result (default 20000).
+    #[serde(default)]
+    pub max_bytes: usize,
+
+    /// Optional thread ID identifying the shell session to operate on. When
+    /// omitted, the most recently active session is used.
+    #[serde(default)]
+    pub thread_id: String,
+}
+
+/// Parameters for the `AuditQuery` tool: search the audit log of mutating
+/// calls (`[audit] enabled` in the global config) and verify its hash chain.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AuditQuery {
+    /// Only calls of this tool, e.g. `BashCommand`.
+    #[serde(default)]
+    pub tool: String,
+
+    /// Only calls made in this `thread_id`.
+    #[serde(default)]
+    pub session: String,
+
+    /// Only calls whose summary or touched files contain this text.
+    #[serde(default)]
+    pub path: String,
+
+    /// Only calls at or after this time, in Unix seconds.
+    #[serde(default)]
+    pub since: u64,
+
+    /// Only calls that failed.
+    #[serde(default)]
+    pub failed_only: bool,
+
+    /// The newest N matching calls (default 50).
+    #[serde(default)]
+    pub limit: usize,
+}
+
+/// Parameters for the `AuditDependencies` tool: known vulnerabilities (from
+/// OSV) and licenses of a project's locked dependencies.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AuditDependencies {
+    /// A lockfile (`Cargo.lock`, `package-lock.json` or `requirements.txt`), or
+    /// a directory holding any of them. Empty = the workspace root. Relative
+    /// paths resolve against the current directory.
+    #[serde(default)]
+    pub path: String,
+
+    /// Skip the OSV vulnerability lookup (no network access); only licenses are
+    /// summarized.
+    #[serde(default)]
+    pub offline: bool,
+
+    /// Optional thread ID identifying the shell session to operate on. When
+    /// omitted, the most recently active session is used.
+    #[serde(default)]
+    pub thread_id: String,
+}
+
+/// One known vulnerability affecting a locked dependency.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DependencyVulnerability {
+    /// Package name as it appears in the lockfile.
+    pub package: String,
+    /// Locked version.
+    pub version: String,
+    /// OSV ecosystem (`crates.io`, `npm`, `PyPI`).
+    pub ecosystem: String,
+    /// OSV id (e.g. `RUSTSEC-2020-0071`, `GHSA-...`).
+    pub id: String,
+    /// Other ids for the same issue, such as CVEs.
+    pub aliases: Vec<String>,
+    /// Severity as the advisory states it (`HIGH`, a CVSS vector, or `unknown`).
+    pub severity: String,
+    /// One-line description.
+    pub summary: String,
+    /// Versions that fix it, when the advisory lists them.
+    pub fixed_in: Vec<String>,
+}
+
+/// Structured result of an `AuditDependencies` call (mirrors the text block).
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct DependencyAuditOutput {
+    /// Lockfiles read, relative to the workspace root.
+    pub lockfiles: Vec<String>,
+    /// Distinct locked packages.
+    pub packages: usize,
+    /// Whether OSV was queried (false when offline or the lookup failed).
+    pub vulnerabilities_checked: bool,
+    pub vulnerabilities: Vec<DependencyVulnerability>,
+    /// Packages per license expression; `unknown` when the lockfile (or local
+    /// package cache) doesn't record it.
+    pub licenses: std::collections::BTreeMap<String, usize>,
+    /// `name version (license)` for packages whose every license option is
+    /// copyleft (GPL, LGPL, AGPL, MPL, EUPL, SSPL).
+    pub copyleft: Vec<String>,
+    /// Anything skipped or not checked.
+    pub notes: Vec<String>,
+}
+
+/// Parameters for the `GetFileChanges` tool: report workspace files created,
+/// modified or deleted since the previous call in this session.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GetFileChanges {
+    /// Optional thread ID identifying the shell session to operate on. When
+    /// omitted, the most recently active session is used.
+    #[serde(default)]
+    pub thread_id: String,
+
+    /// Start a fresh baseline without reporting anything (e.g. after a large
+    /// `git checkout` whose changes the agent already knows about).
+    #[serde(default)]
+    pub reset: bool,
+}
+
+/// Structured result of a `GetFileChanges` call (mirrors the text block).
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct FileChangesOutput {
+    /// True when this call only recorded the baseline (first call or `reset`).
+    pub baseline: bool,
+    /// Number of workspace files being tracked.
+    pub watched_files: usize,
+    /// Workspace-relative paths of files that appeared.
+    pub created: Vec<String>,
+    /// Workspace-relative paths of files whose content changed outside winx.
+    pub modified: Vec<String>,
+    /// Workspace-relative paths of files that disappeared.
+    pub deleted: Vec<String>,
+    /// Modified files this session had read: re-read them before editing.
+    pub stale_reads: Vec<String>,
+}
+
 /// Parameters for the `ContextSave` tool
 ///
 /// This struct represents the parameters needed to save context information
@@ -1042,12 +1744,23 @@ pub struct ReadImage {
     /// Path to the image file to read
     ///
     /// This can be an absolute path or a path relative to the current working directory.
+    /// An `http(s)://` URL is also accepted when the server sets `WINX_IMAGE_URLS=1`.
     pub file_path: String,
 
     /// Optional thread ID identifying the shell session to operate on. When
     /// omitted, the most recently active session is used.
     #[serde(default)]
     pub thread_id: String,
+
+    /// Optional maximum width in pixels. Wider images are downscaled (aspect
+    /// ratio kept) before being returned, to save context. Needs `ImageMagick`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_width: Option<u32>,
+
+    /// Optional JPEG quality (1-100). When set, the image is re-encoded as JPEG,
+    /// usually far smaller than a PNG screenshot. Needs `ImageMagick`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jpeg_quality: Option<u8>,
 }
 
 /// Operation for the `CodeMap` tool.
@@ -1194,6 +1907,22 @@ pub struct ReferencesOutput {
     pub hits: Vec<ReferenceHit>,
 }
 
+/// Structured result of a `CodeMap` call: an outline or the references,
+/// depending on the operation.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum CodeMapOutput {
+    Outline(OutlineOutput),
+    References(ReferencesOutput),
+}
+
+/// Structured result of the tools whose result is text: the text block itself,
+/// so clients that render structured content don't lose it.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TextOutput {
+    pub text: String,
+}
+
 #[cfg(test)]
 mod thread_id_tests {
     use super::normalize_thread_id;
@@ -0,0 +1,460 @@
+//! Tamper-evident audit log of mutating tool calls, for environments that must
+//! account for everything an agent did. Off unless the global config has
+//!
+//! ```toml
+//! [audit]
+//! enabled = true
+//! max_bytes = 10_000_000   # rotate past this size
+//! keep = 5                 # rotated files kept (audit.jsonl.1 … .5)
+//! ```
+//!
+//! Every call of a tool not annotated read-only is appended to
+//! `audit/audit.jsonl` in the winx state dir (a protected path): the redacted
+//! argument summary, the outcome and, for file tools, the SHA-256 of each
+//! touched file before and after. Each line carries the hash of the line
+//! before it (`prev`) and its own (`hash`), so an edited, dropped or reordered
+//! line breaks the chain; [`query`] (the `AuditQuery` tool) reports where.
+//! Rotation carries the chain over into the new file. Appends take an `flock`
+//! on `audit.lock`, so several winx processes can share one log.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::errors::{Result, WinxError};
+use crate::utils::project_config::AuditConfig;
+
+const LOG_FILE: &str = "audit.jsonl";
+const LOCK_FILE: &str = "audit.lock";
+/// `prev` of the first entry ever written.
+const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+/// How far back from the end of the log to look for the last entry.
+const TAIL_BYTES: u64 = 256 * 1024;
+/// Entries returned by a query when `limit` is 0.
+const DEFAULT_LIMIT: usize = 50;
+
+/// A file touched by a call, with its content hash before and after
+/// (`None` = the file did not exist).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub seq: u64,
+    /// Unix seconds.
+    pub time: u64,
+    pub tool: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub thread_id: String,
+    /// The redacted audit summary of the arguments.
+    pub summary: String,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<FileChange>,
+    pub prev: String,
+    pub hash: String,
+}
+
+impl Entry {
+    /// SHA-256 of the entry serialized with an empty `hash`, `prev` included.
+    fn compute_hash(&self) -> String {
+        let unsealed = Entry { hash: String::new(), ..self.clone() };
+        let digest = Sha256::digest(serde_json::to_vec(&unsealed).unwrap_or_default());
+        hex(&digest)
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    use std::fmt::Write as FmtWrite;
+    bytes.iter().fold(String::with_capacity(bytes.len() * 2), |mut out, byte| {
+        let _ = write!(out, "{byte:02x}");
+        out
+    })
+}
+
+/// `<state dir>/audit`, next to the bash session state.
+pub fn audit_dir() -> Option<PathBuf> {
+    let state_dir = crate::state::persistence::get_state_dir().ok()?;
+    Some(state_dir.parent()?.join("audit"))
+}
+
+/// SHA-256 of a file's content, `None` if it can't be read.
+pub fn file_hash(path: &Path) -> Option<String> {
+    let mut file = File::open(path).ok()?;
+    if !file.metadata().ok()?.is_file() {
+        return None;
+    }
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).ok()?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Some(hex(&hasher.finalize()))
+}
+
+/// Files a file tool's arguments name, as given (possibly relative).
+pub fn touched_paths(tool: &str, args: Option<&Value>) -> Vec<String> {
+    let Some(args) = args else {
+        return Vec::new();
+    };
+    let s = |key: &str| args.get(key).and_then(Value::as_str).map(str::to_string);
+    let paths = match tool {
+        "FileWriteOrEdit" | "UndoEdit" => vec![s("file_path")],
+        "NotebookEdit" | "DeleteFile" => vec![s("path")],
+        "MoveFile" => vec![s("source"), s("destination")],
+        "CopyFile" => vec![s("destination")],
+        "MultiFileEdit" => args
+            .get("files")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .map(|file| file.get("file_path").and_then(Value::as_str).map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    };
+    paths.into_iter().flatten().filter(|path| !path.is_empty()).collect()
+}
+
+/// Holds an exclusive `flock` on the lock file until dropped.
+struct DirLock(File);
+
+impl DirLock {
+    fn acquire(dir: &Path) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(dir.join(LOCK_FILE))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::io::AsRawFd;
+            // SAFETY: the fd is valid for the life of `file`; the lock is
+            // released when it is closed.
+            if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+                return Err(std::io::Error::last_os_error().into());
+            }
+        }
+        Ok(Self(file))
+    }
+}
+
+fn rotated(dir: &Path, n: usize) -> PathBuf {
+    dir.join(format!("{LOG_FILE}.{n}"))
+}
+
+/// The log files, oldest first.
+fn log_files(dir: &Path, keep: usize) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> =
+        (1..=keep).rev().map(|n| rotated(dir, n)).filter(|path| path.exists()).collect();
+    files.push(dir.join(LOG_FILE));
+    files
+}
+
+/// The last entry in `path`, read from its tail.
+fn last_entry(path: &Path) -> Option<Entry> {
+    let mut file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(TAIL_BYTES))).ok()?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).ok()?;
+    let tail = String::from_utf8_lossy(&tail);
+    tail.lines().rev().find_map(|line| serde_json::from_str(line).ok())
+}
+
+/// Past `max_bytes`, shift `audit.jsonl` to `.1`, `.1` to `.2` and so on,
+/// dropping the files beyond `keep`.
+fn rotate_if_needed(dir: &Path, config: &AuditConfig) -> Result<()> {
+    let current = dir.join(LOG_FILE);
+    let size = fs::metadata(&current).map_or(0, |m| m.len());
+    if config.max_bytes == 0 || size < config.max_bytes {
+        return Ok(());
+    }
+    if config.keep == 0 {
+        // Nothing kept: start over, still chained to the last entry.
+        return Ok(fs::remove_file(&current)?);
+    }
+    let _ = fs::remove_file(rotated(dir, config.keep));
+    for n in (1..config.keep).rev() {
+        if rotated(dir, n).exists() {
+            fs::rename(rotated(dir, n), rotated(dir, n + 1))?;
+        }
+    }
+    Ok(fs::rename(&current, rotated(dir, 1))?)
+}
+
+/// Append an entry for one call to the log in `dir`, chained to the last one.
+pub fn append_in(
+    dir: &Path,
+    config: &AuditConfig,
+    tool: &str,
+    thread_id: &str,
+    summary: &str,
+    ok: bool,
+    files: Vec<FileChange>,
+) -> Result<Entry> {
+    fs::create_dir_all(dir)?;
+    let _lock = DirLock::acquire(dir)?;
+    // The chain continues across rotation, so find the last entry before it.
+    let last = log_files(dir, config.keep).iter().rev().find_map(|path| last_entry(path));
+    rotate_if_needed(dir, config)?;
+    let mut entry = Entry {
+        seq: last.as_ref().map_or(0, |last| last.seq + 1),
+        time: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+        tool: tool.to_string(),
+        thread_id: thread_id.to_string(),
+        summary: summary.to_string(),
+        ok,
+        files,
+        prev: last.map_or_else(|| GENESIS.to_string(), |last| last.hash),
+        hash: String::new(),
+    };
+    entry.hash = entry.compute_hash();
+    let mut line = serde_json::to_string(&entry)
+        .map_err(|e| WinxError::SerializationError(format!("audit entry: {e}")))?;
+    line.push('\n');
+    let mut log = OpenOptions::new().create(true).append(true).open(dir.join(LOG_FILE))?;
+    log.write_all(line.as_bytes())?;
+    Ok(entry)
+}
+
+/// Filters for [`query`]; empty fields match everything.
+#[derive(Debug, Default)]
+pub struct Filter {
+    pub tool: String,
+    pub thread_id: String,
+    /// Substring of the summary or of a touched file's path.
+    pub path: String,
+    /// Unix seconds.
+    pub since: u64,
+    pub failed_only: bool,
+    pub limit: usize,
+}
+
+impl Filter {
+    fn matches(&self, entry: &Entry) -> bool {
+        (self.tool.is_empty() || entry.tool == self.tool)
+            && (self.thread_id.is_empty() || entry.thread_id == self.thread_id)
+            && (self.path.is_empty()
+                || entry.summary.contains(&self.path)
+                || entry.files.iter().any(|file| file.path.contains(&self.path)))
+            && entry.time >= self.since
+            && !(self.failed_only && entry.ok)
+    }
+}
+
+/// The outcome of reading the whole log.
+#[derive(Debug, Default)]
+pub struct QueryResult {
+    /// The newest matching entries, oldest first.
+    pub entries: Vec<Entry>,
+    pub total: usize,
+    /// Where the chain breaks, if it does.
+    pub broken: Option<String>,
+}
+
+/// Read every kept log file in `dir`, verifying the chain, and return the
+/// entries matching `filter`.
+pub fn query_in(dir: &Path, keep: usize, filter: &Filter) -> Result<QueryResult> {
+    let mut result = QueryResult::default();
+    let mut prev: Option<String> = None;
+    for path in log_files(dir, keep) {
+        let Ok(file) = File::open(&path) else {
+            continue;
+        };
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            let entry: Entry = match serde_json::from_str(&line) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    result.broken.get_or_insert(format!("{name}:{}: unparseable: {e}", index + 1));
+                    prev = None;
+                    continue;
+                }
+            };
+            if entry.compute_hash() != entry.hash {
+                result.broken.get_or_insert(format!("entry #{} was modified", entry.seq));
+            } else if prev.as_ref().is_some_and(|prev| *prev != entry.prev) {
+                result.broken.get_or_insert(format!(
+                    "entry #{} does not follow the one before it",
+                    entry.seq
+                ));
+            }
+            prev = Some(entry.hash.clone());
+            result.total += 1;
+            if filter.matches(&entry) {
+                result.entries.push(entry);
+            }
+        }
+    }
+    let limit = if filter.limit == 0 { DEFAULT_LIMIT } else { filter.limit };
+    let skip = result.entries.len().saturating_sub(limit);
+    result.entries.drain(..skip);
+    Ok(result)
+}
+
+/// `YYYY-MM-DD HH:MM:SS` (UTC) for Unix seconds.
+fn utc(secs: u64) -> String {
+    // Days to civil date, after Howard Hinnant's `civil_from_days`.
+    let days = secs / 86_400;
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    let rem = secs % 86_400;
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// The `AuditQuery` report.
+pub fn render(log: &Path, enabled: bool, result: &QueryResult) -> String {
+    use std::fmt::Write as FmtWrite;
+    let mut out = format!("Audit log: {} ({} entries", log.display(), result.total);
+    match &result.broken {
+        None => out.push_str(", hash chain intact)\n"),
+        Some(broken) => {
+            let _ =
+                writeln!(out, ")\nHASH CHAIN BROKEN: {broken}. The log has been tampered with.");
+        }
+    }
+    if !enabled {
+        out.push_str(
+            "Audit logging is off; set `[audit] enabled = true` in the global winx config.\n",
+        );
+    }
+    if result.entries.is_empty() {
+        out.push_str("No matching entries.\n");
+    }
+    let short = |hash: &Option<String>| {
+        hash.as_deref().map_or_else(|| "-".to_string(), |hash| hash.chars().take(12).collect())
+    };
+    for entry in &result.entries {
+        let outcome = if entry.ok { "ok" } else { "FAILED" };
+        let _ = write!(out, "#{} {} {} {outcome}", entry.seq, utc(entry.time), entry.tool);
+        if !entry.thread_id.is_empty() {
+            let _ = write!(out, " [{}]", entry.thread_id);
+        }
+        let _ = writeln!(out, " — {}", entry.summary);
+        for file in &entry.files {
+            let (before, after) = (short(&file.before), short(&file.after));
+            let _ = writeln!(out, "    {} {before} -> {after}", file.path);
+        }
+    }
+    out
+}
+
+/// [`append_in`] on the global audit dir, if `[audit] enabled`.
+pub fn append(
+    tool: &str,
+    thread_id: &str,
+    summary: &str,
+    ok: bool,
+    files: Vec<FileChange>,
+) -> Result<()> {
+    let config = crate::utils::project_config::audit_config();
+    if !config.enabled {
+        return Ok(());
+    }
+    let dir = audit_dir().ok_or_else(|| WinxError::FileAccessError {
+        path: PathBuf::from("<state dir>/audit"),
+        message: "no state directory for the audit log".to_string(),
+    })?;
+    append_in(&dir, &config, tool, thread_id, summary, ok, files).map(|_| ())
+}
+
+/// [`query_in`] on the global audit dir.
+pub fn query(filter: &Filter) -> Result<(PathBuf, QueryResult)> {
+    let config = crate::utils::project_config::audit_config();
+    let dir = audit_dir().ok_or_else(|| WinxError::FileAccessError {
+        path: PathBuf::from("<state dir>/audit"),
+        message: "no state directory for the audit log".to_string(),
+    })?;
+    let result = query_in(&dir, config.keep, filter)?;
+    Ok((dir.join(LOG_FILE), result))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    fn config() -> AuditConfig {
+        AuditConfig { enabled: true, max_bytes: 0, keep: 2 }
+    }
+
+    #[test]
+    fn chain_detects_edited_lines_and_survives_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let change = FileChange { path: "/w/a.rs".into(), before: None, after: Some("ab".into()) };
+        append_in(
+            dir.path(),
+            &config(),
+            "FileWriteOrEdit",
+            "t",
+            "path=/w/a.rs",
+            true,
+            vec![change],
+        )
+        .unwrap();
+        // Rotate before every further append.
+        let small = AuditConfig { max_bytes: 1, ..config() };
+        append_in(dir.path(), &small, "BashCommand", "t", "cmd=\"ls\"", true, vec![]).unwrap();
+        append_in(dir.path(), &small, "DeleteFile", "u", "path=x", false, vec![]).unwrap();
+        assert!(rotated(dir.path(), 2).exists());
+
+        let all = query_in(dir.path(), 2, &Filter::default()).unwrap();
+        assert_eq!((all.total, all.broken.as_deref()), (3, None));
+        assert_eq!(all.entries.iter().map(|e| e.seq).collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(all.entries[1].prev, all.entries[0].hash);
+
+        let filter = Filter { path: "a.rs".into(), ..Filter::default() };
+        assert_eq!(query_in(dir.path(), 2, &filter).unwrap().entries.len(), 1);
+        let filter = Filter { failed_only: true, ..Filter::default() };
+        assert_eq!(query_in(dir.path(), 2, &filter).unwrap().entries[0].tool, "DeleteFile");
+
+        let log = rotated(dir.path(), 1);
+        let text = fs::read_to_string(&log).unwrap().replace("cmd=\\\"ls\\\"", "cmd=\\\"rm\\\"");
+        fs::write(&log, text).unwrap();
+        let tampered = query_in(dir.path(), 2, &Filter::default()).unwrap();
+        assert_eq!(tampered.broken.as_deref(), Some("entry #1 was modified"));
+    }
+
+    #[test]
+    fn touched_paths_hashes_and_dates() {
+        assert_eq!(utc(0), "1970-01-01 00:00:00");
+        assert_eq!(utc(1_709_210_096), "2024-02-29 12:34:56");
+
+        let args = serde_json::json!({"source": "a", "destination": "b"});
+        assert_eq!(touched_paths("MoveFile", Some(&args)), ["a", "b"]);
+        let args = serde_json::json!({"files": [{"file_path": "/x"}, {"file_path": "/y"}]});
+        assert_eq!(touched_paths("MultiFileEdit", Some(&args)), ["/x", "/y"]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("f");
+        assert_eq!(file_hash(&file), None);
+        fs::write(&file, "hello").unwrap();
+        assert_eq!(
+            file_hash(&file).as_deref(),
+            Some("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+        );
+    }
+}
@@ -232,6 +232,124 @@ fn collect_command_texts(node: Node<'_>, src: &[u8], out: &mut Vec<String>) {
     }
 }
 
+/// Commands that create, modify or delete the paths they are given.
+const WRITE_COMMANDS: &[&str] = &[
+    "rm", "rmdir", "unlink", "shred", "truncate", "chmod", "chown", "chgrp", "touch", "mkdir",
+    "tee",
+];
+
+/// Commands that only write their LAST path argument (the rest are sources).
+const COPY_COMMANDS: &[&str] = &["cp", "install", "ln", "rsync"];
+
+/// A path a command line would write to or delete (see [`write_targets`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteTarget {
+    pub path: String,
+    /// The destination of `cp`, `mv`, ...: when it is a directory, the sources
+    /// land inside it and what is already there stays.
+    pub into_dir: bool,
+}
+
+impl WriteTarget {
+    fn whole(path: String) -> Self {
+        Self { path, into_dir: false }
+    }
+}
+
+/// The paths a command line would write to or delete: path arguments of
+/// write commands (`rm`, `mv`, `tee`, `sed -i`, the destination of `cp`, `dd
+/// of=`, ...) and output redirect targets (`> f`, `>> f`, `&> f`).
+///
+/// Best-effort, for the protected-path guard: arguments are unquoted, a leading
+/// `$HOME`/`${HOME}` is left for the caller to expand like `~`, and other
+/// expansions or code hidden in `bash -c '...'` are not seen. A command that
+/// doesn't parse yields no targets.
+pub fn write_targets(command: &str) -> Vec<WriteTarget> {
+    let parse_src = neutralize_supplementary(command.trim());
+    let mut parser = Parser::new();
+    let language: tree_sitter::Language = tree_sitter_bash::LANGUAGE.into();
+    if parser.set_language(&language).is_err() {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(parse_src.as_ref(), None) else {
+        return Vec::new();
+    };
+    let mut targets = Vec::new();
+    collect_write_targets(tree.root_node(), parse_src.as_bytes(), &mut targets);
+    targets
+}
+
+fn collect_write_targets(node: Node<'_>, src: &[u8], out: &mut Vec<WriteTarget>) {
+    match node.kind() {
+        "command" => {
+            let name = node
+                .child_by_field_name("name")
+                .and_then(|n| n.utf8_text(src).ok())
+                .and_then(|n| n.rsplit('/').next())
+                .unwrap_or_default();
+            let mut cursor = node.walk();
+            let args: Vec<String> = node
+                .children_by_field_name("argument", &mut cursor)
+                .filter_map(|arg| arg.utf8_text(src).ok().map(unquote))
+                .collect();
+            let paths = || args.iter().filter(|a| !a.starts_with('-')).cloned();
+            let in_place = args.iter().any(|a| a.starts_with("-i") || a == "--in-place");
+            if WRITE_COMMANDS.contains(&name) {
+                out.extend(paths().map(WriteTarget::whole));
+            } else if name == "mv" {
+                // Every source is moved away; the last argument is the destination.
+                let paths: Vec<String> = paths().collect();
+                let last = paths.len().saturating_sub(1);
+                out.extend(paths.into_iter().enumerate().map(|(index, path)| WriteTarget {
+                    path,
+                    into_dir: index > 0 && index == last,
+                }));
+            } else if name == "dd" {
+                // `if=` is read; only `of=` is written.
+                out.extend(
+                    args.iter()
+                        .filter_map(|a| a.strip_prefix("of="))
+                        .map(|a| WriteTarget::whole(a.into())),
+                );
+            } else if COPY_COMMANDS.contains(&name) {
+                // `rsync --delete` also removes what the destination already holds.
+                let deletes = name == "rsync" && args.iter().any(|a| a.starts_with("--delete"));
+                out.extend(
+                    paths().next_back().map(|path| WriteTarget { path, into_dir: !deletes }),
+                );
+            } else if name == "sed" && in_place {
+                // The first non-flag argument is the script.
+                out.extend(paths().skip(1).map(WriteTarget::whole));
+            }
+        }
+        "file_redirect" => {
+            let mut cursor = node.walk();
+            let writes = node
+                .children(&mut cursor)
+                .any(|c| matches!(c.kind(), ">" | ">>" | "&>" | "&>>" | ">|"));
+            if let Some(dest) = node.child_by_field_name("destination") {
+                if writes {
+                    out.extend(
+                        dest.utf8_text(src).ok().map(|path| WriteTarget::whole(unquote(path))),
+                    );
+                }
+            }
+        }
+        _ => {}
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_write_targets(child, src, out);
+    }
+}
+
+/// Strip one level of surrounding quotes from a shell word.
+fn unquote(word: &str) -> String {
+    let stripped =
+        ['"', '\''].iter().find_map(|&q| word.strip_prefix(q).and_then(|w| w.strip_suffix(q)));
+    stripped.unwrap_or(word).to_string()
+}
+
 /// Commands that can execute arbitrary code passed as a string argument.
 ///
 /// If a `code_writer` allowlist permits any of these, the allowlist is
@@ -265,11 +383,49 @@ pub fn detect_allowlist_bypass(allowed: &[String]) -> Vec<String> {
     hits
 }
 
+/// Why `command` is hard to undo, if it is: recursive `rm`, `git reset --hard`,
+/// `git clean -f`, a force push or `git branch -D`, anywhere in the line.
+/// Best-effort (see [`extract_command_texts`]), for asking the user first; a
+/// line that doesn't parse yields `None`.
+pub fn destructive_action(command: &str) -> Option<String> {
+    extract_command_texts(command).ok()?.iter().find_map(|text| {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let program = std::path::Path::new(words.first()?).file_name()?.to_str()?;
+        let args = &words[1..];
+        let has = |flag: &str| args.contains(&flag);
+        let short_flag = |c: char| {
+            args.iter().any(|a| a.starts_with('-') && !a.starts_with("--") && a.contains(c))
+        };
+        match (program, args.first().copied()) {
+            ("rm", _) if short_flag('r') || short_flag('R') || has("--recursive") => {
+                let targets: Vec<&str> =
+                    args.iter().copied().filter(|a| !a.starts_with('-')).collect();
+                Some(format!("recursively deletes {}", targets.join(" ")))
+            }
+            ("git", Some("reset")) if has("--hard") => {
+                Some("discards uncommitted changes (git reset --hard)".to_string())
+            }
+            ("git", Some("clean")) if short_flag('f') || has("--force") => {
+                Some("deletes untracked files (git clean)".to_string())
+            }
+            ("git", Some("push")) if short_flag('f') || has("--force") => {
+                Some("force-pushes, overwriting remote history".to_string())
+            }
+            ("git", Some("branch")) if has("-D") => {
+                Some("deletes a branch without checking it is merged".to_string())
+            }
+            _ => None,
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::assert_single_statement;
+    use super::destructive_action;
     use super::detect_allowlist_bypass;
     use super::extract_command_texts;
+    use super::write_targets;
     use proptest::prelude::*;
 
     proptest! {
@@ -282,6 +438,11 @@ mod tests {
             let _ = assert_single_statement(&cmd, true);
         }
 
+        #[test]
+        fn write_targets_never_panics(cmd in "[\\s\\S]{0,80}") {
+            let _ = write_targets(&cmd);
+        }
+
         #[test]
         fn extract_command_texts_never_panics(cmd in "[\\s\\S]{0,80}") {
             let _ = extract_command_texts(&cmd);
@@ -405,4 +566,38 @@ mod tests {
         assert!(error.contains("NUL byte"));
         assert!(error.contains("\\\\x00"));
     }
+
+    fn target_paths(command: &str) -> Vec<String> {
+        write_targets(command).into_iter().map(|target| target.path).collect()
+    }
+
+    #[test]
+    fn write_targets_finds_destructive_paths_and_redirects() {
+        assert_eq!(target_paths("rm -rf .git/objects 'my dir'"), vec![".git/objects", "my dir"]);
+        assert_eq!(target_paths("cp ~/.ssh/id_rsa backup/"), vec!["backup/"]);
+        assert_eq!(target_paths("echo hi > /etc/motd && cat /etc/hosts"), vec!["/etc/motd"]);
+        assert_eq!(target_paths("sed -i 's/a/b/' conf.txt"), vec!["conf.txt"]);
+        assert_eq!(target_paths("dd if=/dev/zero of=disk.img"), vec!["disk.img"]);
+        assert!(target_paths("cat /etc/hosts | grep x").is_empty());
+    }
+
+    #[test]
+    fn write_targets_marks_copy_and_move_destinations() {
+        let into: Vec<bool> =
+            write_targets("mv a b dest && cp x .").iter().map(|t| t.into_dir).collect();
+        assert_eq!(into, vec![false, false, true, true]);
+        assert!(!write_targets("rsync -a --delete empty/ .winx/")[0].into_dir);
+    }
+
+    #[test]
+    fn destructive_action_flags_hard_to_undo_commands() {
+        assert_eq!(
+            destructive_action("cd x && rm -rf build dist").as_deref(),
+            Some("recursively deletes build dist")
+        );
+        assert!(destructive_action("git reset --hard HEAD~1").is_some());
+        assert!(destructive_action("git push -f origin main").is_some());
+        assert!(destructive_action("rm notes.txt").is_none());
+        assert!(destructive_action("git push origin main && git reset --soft HEAD").is_none());
+    }
 }
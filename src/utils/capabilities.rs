@@ -0,0 +1,207 @@
+//! Optional external binaries: probe once, degrade gracefully, report in `doctor`.
+//!
+//! Several features shell out (git for repo context, python3 for syntax checks,
+//! curl/ImageMagick for `ReadImage`, tar/zip for archives, screen/tmux for
+//! attachable sessions). Each call site asks [`available`] first and takes its
+//! fallback when the binary is missing, instead of spawning it and failing
+//! mid-call. Diffs are always computed in-process (`similar`), so they never
+//! depend on a `diff` binary.
+
+use std::collections::HashMap;
+use std::fmt::Write as FmtWrite;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+/// An external binary some feature uses, and what happens without it.
+pub struct Capability {
+    pub program: &'static str,
+    /// What the binary enables.
+    pub purpose: &'static str,
+    /// How winx behaves when it is missing.
+    pub fallback: &'static str,
+}
+
+/// Every optional binary winx knows how to use.
+pub const CAPABILITIES: &[Capability] = &[
+    Capability {
+        program: "bash",
+        purpose: "the shell session",
+        fallback: "required: BashCommand cannot start a shell without it",
+    },
+    Capability {
+        program: "git",
+        purpose: "repo context and ContextSave git status/diff",
+        fallback: "repo context falls back to a plain directory walk; git sections are omitted",
+    },
+    Capability {
+        program: "python3",
+        purpose: "Python syntax checks after edits",
+        fallback: "`python` is tried, then the check is skipped",
+    },
+    Capability {
+        program: "node",
+        purpose: "`node --check` after JavaScript edits (WINX_EXTERNAL_CHECKS)",
+        fallback: "only the tree-sitter parse runs",
+    },
+    Capability {
+        program: "cargo",
+        purpose: "`cargo check` after Rust edits (WINX_EXTERNAL_CHECKS)",
+        fallback: "only the tree-sitter parse runs",
+    },
+    Capability {
+        program: "rustfmt",
+        purpose: "auto-format of .rs files (.winx/config.toml [format])",
+        fallback: "Rust files are written unformatted",
+    },
+    Capability {
+        program: "prettier",
+        purpose: "auto-format of JS/TS/CSS/JSON/Markdown (.winx/config.toml [format])",
+        fallback: "those files are written unformatted",
+    },
+    Capability {
+        program: "black",
+        purpose: "auto-format of .py files (.winx/config.toml [format])",
+        fallback: "Python files are written unformatted",
+    },
+    Capability {
+        program: "gofmt",
+        purpose: "auto-format of .go files (.winx/config.toml [format])",
+        fallback: "Go files are written unformatted",
+    },
+    Capability {
+        program: "rg",
+        purpose: "ripgrep hint in the Initialize prompt",
+        fallback: "the hint is omitted",
+    },
+    Capability {
+        program: "curl",
+        purpose:
+            "ReadImage URLs (WINX_IMAGE_URLS), AuditDependencies' OSV lookup, GitHub, HttpRequest",
+        fallback: "URL reads return an error naming curl; audits list licenses only",
+    },
+    Capability {
+        program: "tar",
+        purpose: "CreateArchive/ExtractArchive for .tar, .tar.gz and .tar.zst",
+        fallback: "tar formats return an error naming tar",
+    },
+    Capability {
+        program: "zstd",
+        purpose: ".tar.zst archives",
+        fallback: ".tar.zst returns an error; .tar.gz still works",
+    },
+    Capability {
+        program: "zip",
+        purpose: "CreateArchive for .zip",
+        fallback: "creating .zip returns an error naming zip",
+    },
+    Capability {
+        program: "unzip",
+        purpose: "ExtractArchive for .zip",
+        fallback: "extracting .zip returns an error naming unzip",
+    },
+    Capability {
+        program: "magick",
+        purpose: "ReadImage max_width/jpeg_quality",
+        fallback: "`convert` is tried, then resize/re-encode returns an error",
+    },
+    Capability {
+        program: "nvidia-smi",
+        purpose: "GPU usage in SystemStats",
+        fallback: "SystemStats lists no GPUs",
+    },
+    Capability {
+        program: "chromium",
+        purpose: "the Browser tool (or google-chrome, or WINX_CHROME)",
+        fallback: "Browser returns an error naming the binaries it looked for",
+    },
+    Capability {
+        program: "screen",
+        purpose: "WINX_USE_SCREEN attachable sessions",
+        fallback: "the shell runs directly in the PTY (not attachable)",
+    },
+    Capability {
+        program: "tmux",
+        purpose: "WINX_ATTACH_TERMINAL=tmux",
+        fallback: "screen is used if present, else no attachable session",
+    },
+    Capability { program: "zsh", purpose: "WINX_SHELL=zsh", fallback: "bash is used" },
+];
+
+/// Whether `program` is an executable on `PATH`. Probed once per program per
+/// process (a `PATH` lookup, no spawn) and cached.
+pub fn available(program: &str) -> bool {
+    static PROBED: OnceLock<Mutex<HashMap<String, bool>>> = OnceLock::new();
+    let cache = PROBED.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(&found) = cache.lock().unwrap_or_else(PoisonError::into_inner).get(program) {
+        return found;
+    }
+    let found = find_on_path(program);
+    cache.lock().unwrap_or_else(PoisonError::into_inner).insert(program.to_string(), found);
+    found
+}
+
+fn find_on_path(program: &str) -> bool {
+    let Some(path) = std::env::var_os("PATH") else {
+        return false;
+    };
+    std::env::split_paths(&path).any(|dir| is_executable(&dir.join(program)))
+}
+
+#[cfg(unix)]
+pub(crate) fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata().is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn is_executable(path: &Path) -> bool {
+    path.is_file() || path.with_extension("exe").is_file()
+}
+
+/// The `winx doctor` report: one line per optional binary, with the fallback
+/// for each missing one.
+pub fn doctor_report() -> String {
+    let mut out = String::from("winx capabilities\n\n");
+    let mut missing = 0;
+    for cap in CAPABILITIES {
+        if available(cap.program) {
+            let _ = writeln!(out, "  ok       {:<8} {}", cap.program, cap.purpose);
+        } else {
+            missing += 1;
+            let _ = writeln!(out, "  missing  {:<8} {}", cap.program, cap.purpose);
+            let _ = writeln!(out, "           -> {}", cap.fallback);
+        }
+    }
+    if missing == 0 {
+        out.push_str("\nAll optional binaries found.\n");
+    } else {
+        let _ = writeln!(
+            out,
+            "\n{missing} optional binar{} missing; the features above degrade as described.",
+            if missing == 1 { "y" } else { "ies" }
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probes_path_and_caches() {
+        assert!(!available("winx-definitely-not-a-real-binary"));
+        // Second lookup comes from the cache and agrees.
+        assert!(!available("winx-definitely-not-a-real-binary"));
+        #[cfg(unix)]
+        assert!(available("sh"));
+    }
+
+    #[test]
+    fn doctor_lists_every_capability() {
+        let report = doctor_report();
+        for cap in CAPABILITIES {
+            assert!(report.contains(cap.program), "{} missing from report", cap.program);
+        }
+    }
+}
@@ -0,0 +1,302 @@
+//! `winx completions <shell>` and `winx man`: shell completion scripts and a
+//! roff man page generated from the clap command tree, so they never drift from
+//! the real subcommands and flags.
+//!
+//! Completion covers subcommands (at any depth) and their flags; values and
+//! positionals fall back to the shell's file completion.
+
+use std::fmt::Write as FmtWrite;
+
+use clap::{Arg, Command};
+
+/// Shells `winx completions` can generate a script for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    #[value(name = "powershell")]
+    PowerShell,
+}
+
+/// A (sub)command and the subcommand names leading to it.
+struct Node<'a> {
+    path: Vec<&'a str>,
+    cmd: &'a Command,
+}
+
+fn nodes(cmd: &Command) -> Vec<Node<'_>> {
+    fn walk<'a>(cmd: &'a Command, path: Vec<&'a str>, out: &mut Vec<Node<'a>>) {
+        for sub in cmd.get_subcommands().filter(|sub| !sub.is_hide_set()) {
+            let mut sub_path = path.clone();
+            sub_path.push(sub.get_name());
+            walk(sub, sub_path, out);
+        }
+        out.push(Node { path, cmd });
+    }
+    let mut out = Vec::new();
+    walk(cmd, Vec::new(), &mut out);
+    out.reverse();
+    out
+}
+
+fn options(cmd: &Command) -> impl Iterator<Item = &Arg> {
+    cmd.get_arguments().filter(|arg| !arg.is_positional() && !arg.is_hide_set())
+}
+
+fn help(arg: &Arg) -> String {
+    arg.get_help().map(ToString::to_string).unwrap_or_default()
+}
+
+fn about(cmd: &Command) -> String {
+    cmd.get_about().map(ToString::to_string).unwrap_or_default()
+}
+
+/// Every word that may follow `cmd`: its subcommands, then `--long` and `-s`.
+fn words(cmd: &Command) -> Vec<String> {
+    let mut words: Vec<String> =
+        cmd.get_subcommands().filter(|s| !s.is_hide_set()).map(|s| s.get_name().into()).collect();
+    for arg in options(cmd) {
+        words.extend(arg.get_long().map(|long| format!("--{long}")));
+        words.extend(arg.get_short().map(|short| format!("-{short}")));
+    }
+    words
+}
+
+fn bash(name: &str, nodes: &[Node]) -> String {
+    let func = format!("_{}", name.replace('-', "_"));
+    let mut out = format!(
+        "{func}() {{\n    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\" cmdpath=\"\" word opts\n    \
+         for word in \"${{COMP_WORDS[@]:1:COMP_CWORD-1}}\"; do\n        \
+         case \"$cmdpath $word\" in\n"
+    );
+    let subpaths: Vec<String> = nodes
+        .iter()
+        .filter(|n| !n.path.is_empty())
+        .map(|n| format!("\" {}\"", n.path.join(" ")))
+        .collect();
+    if !subpaths.is_empty() {
+        let _ = writeln!(out, "            {}) cmdpath=\"$cmdpath $word\" ;;", subpaths.join("|"));
+    }
+    out.push_str("        esac\n    done\n    case \"$cmdpath\" in\n");
+    for node in nodes {
+        let key =
+            if node.path.is_empty() { String::new() } else { format!(" {}", node.path.join(" ")) };
+        let _ = writeln!(out, "        \"{key}\") opts=\"{}\" ;;", words(node.cmd).join(" "));
+    }
+    let _ = write!(
+        out,
+        "    esac\n    COMPREPLY=($(compgen -W \"$opts\" -- \"$cur\"))\n}}\ncomplete -o default -F \
+         {func} {name}\n"
+    );
+    out
+}
+
+fn fish_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn fish(name: &str, nodes: &[Node]) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        // True exactly when the words typed so far select this node.
+        let condition = match node.path.last() {
+            None => "__fish_use_subcommand".to_string(),
+            Some(last) => {
+                let children: Vec<&str> =
+                    node.cmd.get_subcommands().map(Command::get_name).collect();
+                if children.is_empty() {
+                    format!("__fish_seen_subcommand_from {last}")
+                } else {
+                    let children = children.join(" ");
+                    format!(
+                        "__fish_seen_subcommand_from {last}; and not \
+                         __fish_seen_subcommand_from {children}"
+                    )
+                }
+            }
+        };
+        for sub in node.cmd.get_subcommands().filter(|s| !s.is_hide_set()) {
+            let _ = writeln!(
+                out,
+                "complete -c {name} -f -n {} -a {} -d {}",
+                fish_quote(&condition),
+                sub.get_name(),
+                fish_quote(&about(sub))
+            );
+        }
+        for arg in options(node.cmd) {
+            let mut line = format!("complete -c {name} -n {}", fish_quote(&condition));
+            if let Some(long) = arg.get_long() {
+                let _ = write!(line, " -l {long}");
+            }
+            if let Some(short) = arg.get_short() {
+                let _ = write!(line, " -s {short}");
+            }
+            if arg.get_action().takes_values() {
+                line.push_str(" -r");
+            }
+            let _ = writeln!(out, "{line} -d {}", fish_quote(&help(arg)));
+        }
+    }
+    out
+}
+
+fn powershell(name: &str, nodes: &[Node]) -> String {
+    let mut out = format!(
+        "Register-ArgumentCompleter -Native -CommandName '{name}' -ScriptBlock {{\n    \
+         param($wordToComplete, $commandAst, $cursorPosition)\n    $path = ''\n    foreach \
+         ($element in $commandAst.CommandElements | Select-Object -Skip 1) {{\n        if \
+         ($element.Extent.StartOffset -ge $cursorPosition) {{ break }}\n        $candidate = \
+         \"$path $element\"\n        if (@({}) -contains $candidate) {{ $path = $candidate }}\n    \
+         }}\n    $words = switch ($path) {{\n",
+        nodes
+            .iter()
+            .filter(|n| !n.path.is_empty())
+            .map(|n| format!("' {}'", n.path.join(" ")))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    for node in nodes {
+        let key =
+            if node.path.is_empty() { String::new() } else { format!(" {}", node.path.join(" ")) };
+        let words: Vec<String> = words(node.cmd).iter().map(|w| format!("'{w}'")).collect();
+        let _ = writeln!(out, "        '{key}' {{ @({}) }}", words.join(", "));
+    }
+    out.push_str(
+        "    }\n    $words | Where-Object { $_ -like \"$wordToComplete*\" } | \
+         ForEach-Object {\n        [System.Management.Automation.CompletionResult]::new(\
+         $_, $_, 'ParameterValue', $_)\n    \
+         }\n}\n",
+    );
+    out
+}
+
+/// The completion script for `shell`.
+pub fn completions(mut cmd: Command, shell: Shell) -> String {
+    cmd.build();
+    let name = cmd.get_bin_name().unwrap_or(cmd.get_name()).to_string();
+    let nodes = nodes(&cmd);
+    match shell {
+        Shell::Bash => bash(&name, &nodes),
+        // zsh runs the bash function through its bash-completion emulation.
+        Shell::Zsh => format!(
+            "#compdef {name}\nautoload -U +X bashcompinit && bashcompinit\n{}",
+            bash(&name, &nodes)
+        ),
+        Shell::Fish => fish(&name, &nodes),
+        Shell::PowerShell => powershell(&name, &nodes),
+    }
+}
+
+/// Escape text for a roff paragraph.
+fn roff(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    let escape_line = |line: &str| {
+        if line.starts_with(['.', '\'']) {
+            format!("\\&{line}")
+        } else {
+            line.to_string()
+        }
+    };
+    escaped.lines().map(escape_line).collect::<Vec<_>>().join("\n")
+}
+
+fn man_options(out: &mut String, cmd: &Command) {
+    for arg in cmd.get_arguments().filter(|arg| !arg.is_hide_set()) {
+        let mut forms = Vec::new();
+        if let Some(short) = arg.get_short() {
+            forms.push(format!("\\fB\\-{short}\\fR"));
+        }
+        if let Some(long) = arg.get_long() {
+            forms.push(format!("\\fB\\-\\-{}\\fR", roff(long)));
+        }
+        let value = arg
+            .get_value_names()
+            .and_then(|names| names.first())
+            .map_or_else(|| arg.get_id().as_str().to_uppercase(), ToString::to_string);
+        if arg.is_positional() {
+            forms.push(format!("\\fI{}\\fR", roff(&value)));
+        } else if arg.get_action().takes_values() {
+            if let Some(last) = forms.last_mut() {
+                let _ = write!(last, " \\fI{}\\fR", roff(&value));
+            }
+        }
+        let _ = writeln!(out, ".TP\n{}\n{}", forms.join(", "), roff(&help(arg)));
+    }
+}
+
+/// A man(1) page in roff covering every subcommand and flag.
+pub fn man_page(mut cmd: Command) -> String {
+    cmd.build();
+    let name = cmd.get_bin_name().unwrap_or(cmd.get_name()).to_string();
+    let version = cmd.get_version().unwrap_or_default();
+    let mut out = format!(
+        ".TH {} 1 \"\" \"{name} {version}\"\n.SH NAME\n{name} \\- {}\n.SH SYNOPSIS\n\\fB{name}\\fR \
+         [\\fIOPTIONS\\fR] [\\fICOMMAND\\fR]\n.SH OPTIONS\n",
+        name.to_uppercase(),
+        roff(&about(&cmd))
+    );
+    man_options(&mut out, &cmd);
+    out.push_str(".SH COMMANDS\n");
+    for node in nodes(&cmd).iter().filter(|n| !n.path.is_empty()) {
+        let _ = writeln!(
+            out,
+            ".SS \"{name} {}\"\n{}",
+            node.path.join(" "),
+            roff(
+                &node
+                    .cmd
+                    .get_long_about()
+                    .or(node.cmd.get_about())
+                    .map(ToString::to_string)
+                    .unwrap_or_default()
+            )
+        );
+        man_options(&mut out, node.cmd);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Command {
+        Command::new("winx")
+            .version("1.0")
+            .about("Test server")
+            .arg(Arg::new("verbose").short('v').long("verbose").action(clap::ArgAction::SetTrue))
+            .subcommand(
+                Command::new("config")
+                    .about("Edit config")
+                    .subcommand(Command::new("get").arg(Arg::new("key")))
+                    .subcommand(
+                        Command::new("set")
+                            .arg(Arg::new("workspace").long("workspace").help("Root")),
+                    ),
+            )
+    }
+
+    #[test]
+    fn completions_cover_nested_subcommands() {
+        let script = completions(sample(), Shell::Bash);
+        assert!(script.contains("complete -o default -F _winx winx"));
+        assert!(script.contains("\" config set\") opts=\"--workspace"));
+        assert!(script.contains("--verbose -v"));
+        let fish = completions(sample(), Shell::Fish);
+        assert!(fish.contains("-a set"));
+        assert!(fish.contains("'__fish_seen_subcommand_from set' -l workspace -r -d 'Root'"));
+        assert!(completions(sample(), Shell::Zsh).starts_with("#compdef winx"));
+        assert!(completions(sample(), Shell::PowerShell).contains("' config get'"));
+    }
+
+    #[test]
+    fn man_page_lists_commands_and_flags() {
+        let page = man_page(sample());
+        assert!(page.starts_with(".TH WINX 1"));
+        assert!(page.contains("\\fB\\-v\\fR, \\fB\\-\\-verbose\\fR"));
+        assert!(page.contains(".SS \"winx config set\""));
+        assert!(page.contains("\\fB\\-\\-workspace\\fR \\fIWORKSPACE\\fR\nRoot"));
+    }
+}
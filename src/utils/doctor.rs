@@ -0,0 +1,287 @@
+//! `winx doctor`: the capability report plus environment checks for the usual
+//! "it doesn't start" causes, each failing check with the fix to apply.
+//!
+//! Checks, in order: the shell `BashCommand` will spawn, that the state and
+//! memory dirs are writable, that the global/project `.winx` files parse (and
+//! whether hooks are trusted), and every known MCP client config that exists —
+//! valid JSON/TOML, has a winx entry, and that entry's command is runnable.
+
+use std::fmt::Write as FmtWrite;
+use std::path::{Path, PathBuf};
+
+use crate::errors::WinxError;
+use crate::utils::capabilities::{available, is_executable};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug)]
+struct Check {
+    status: Status,
+    label: String,
+    /// What to do about a warning or failure.
+    fix: Option<String>,
+}
+
+impl Check {
+    fn ok(label: impl Into<String>) -> Self {
+        Self { status: Status::Ok, label: label.into(), fix: None }
+    }
+
+    fn warn(label: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { status: Status::Warn, label: label.into(), fix: Some(fix.into()) }
+    }
+
+    fn fail(label: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { status: Status::Fail, label: label.into(), fix: Some(fix.into()) }
+    }
+}
+
+fn shell_checks() -> Vec<Check> {
+    let mut checks = Vec::new();
+    if available("bash") {
+        checks.push(Check::ok("bash found for BashCommand"));
+    } else {
+        checks.push(Check::fail(
+            "bash not found on PATH",
+            "install bash, or add its directory to the PATH your MCP client launches winx with",
+        ));
+    }
+    match std::env::var("WINX_SHELL").as_deref() {
+        Ok("zsh") if !available("zsh") => {
+            checks.push(Check::warn("WINX_SHELL=zsh but zsh is not on PATH", "install zsh"));
+        }
+        Ok(shell) if !matches!(shell, "zsh" | "bash") => checks.push(Check::warn(
+            format!("WINX_SHELL={shell} is not supported; bash is used"),
+            "set WINX_SHELL=zsh or unset it",
+        )),
+        _ => {}
+    }
+    checks
+}
+
+/// Whether a file can be created in `dir`.
+fn writable(dir: &Path) -> std::io::Result<()> {
+    tempfile::NamedTempFile::new_in(dir).map(drop)
+}
+
+fn dir_check(what: &str, dir: crate::errors::Result<PathBuf>) -> Check {
+    match dir {
+        Ok(dir) => match writable(&dir) {
+            Ok(()) => Check::ok(format!("{what} writable ({})", dir.display())),
+            Err(e) => Check::fail(
+                format!("{what} not writable ({}): {e}", dir.display()),
+                format!("chown/chmod u+rwx {}", dir.display()),
+            ),
+        },
+        Err(e) => Check::fail(
+            format!("{what} unavailable: {e}"),
+            "make sure $HOME is set and its .local/share is writable",
+        ),
+    }
+}
+
+fn state_checks() -> Vec<Check> {
+    vec![
+        dir_check(
+            "session state dir",
+            crate::state::persistence::get_state_dir()
+                .map_err(|e| WinxError::DataLoadingError(format!("{e:#}"))),
+        ),
+        dir_check("memory dir", crate::tools::context_save::resolve_memory_dir()),
+    ]
+}
+
+fn toml_check(path: &Path) -> Option<Check> {
+    let text = std::fs::read_to_string(path).ok()?;
+    Some(match toml::from_str::<toml::Table>(&text) {
+        Ok(_) => Check::ok(format!("{} parses", path.display())),
+        Err(e) => Check::fail(
+            format!("{} is invalid TOML: {}", path.display(), e.message()),
+            "fix the file; until then winx ignores it",
+        ),
+    })
+}
+
+fn workspace_checks(workspace: &Path) -> Vec<Check> {
+    let mut files = vec![workspace.join(crate::utils::project_config::CONFIG_FILE)];
+    files.extend(crate::utils::project_config::global_path());
+    files.push(workspace.join(crate::utils::hooks::HOOKS_FILE));
+    let mut checks: Vec<Check> = files.iter().filter_map(|path| toml_check(path)).collect();
+    if crate::utils::hooks::untrusted_notice(workspace).is_some() {
+        checks.push(Check::warn(
+            format!("{} is not trusted, so its hooks don't run", crate::utils::hooks::HOOKS_FILE),
+            format!("review it, then run `winx trust-hooks {}`", workspace.display()),
+        ));
+    }
+    checks
+}
+
+/// MCP client config files winx documents, global ones first.
+fn client_configs(home: Option<&Path>, workspace: &Path) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if let Some(home) = home {
+        paths.extend([
+            home.join(".claude.json"),
+            home.join("Library/Application Support/Claude/claude_desktop_config.json"),
+            home.join(".config/Claude/claude_desktop_config.json"),
+            home.join(".cursor/mcp.json"),
+            home.join(".codex/config.toml"),
+            home.join(".kiro/settings/mcp.json"),
+        ]);
+    }
+    if let Ok(appdata) = std::env::var("APPDATA") {
+        paths.push(PathBuf::from(appdata).join("Claude/claude_desktop_config.json"));
+    }
+    paths.extend([
+        workspace.join(".mcp.json"),
+        workspace.join(".cursor/mcp.json"),
+        workspace.join(".vscode/mcp.json"),
+    ]);
+    paths
+}
+
+/// Every `command` string of an object whose key or command names winx.
+fn winx_commands(value: &serde_json::Value, key: &str, out: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            if let Some(command) = map.get("command").and_then(serde_json::Value::as_str) {
+                if key.contains("winx") || command.contains("winx") {
+                    out.push(command.to_string());
+                }
+            }
+            for (key, value) in map {
+                winx_commands(value, key, out);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                winx_commands(item, key, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn runnable(command: &str) -> bool {
+    let path = Path::new(command);
+    if path.components().count() > 1 {
+        is_executable(path)
+    } else {
+        available(command)
+    }
+}
+
+fn client_check(path: &Path, text: &str) -> Check {
+    let name = path.display();
+    let parsed = if path.extension().is_some_and(|ext| ext == "toml") {
+        toml::from_str::<serde_json::Value>(text).map_err(|e| e.message().to_string())
+    } else {
+        serde_json::from_str::<serde_json::Value>(text).map_err(|e| e.to_string())
+    };
+    let value = match parsed {
+        Ok(value) => value,
+        Err(e) => {
+            return Check::fail(
+                format!("{name} does not parse: {e}"),
+                "fix the syntax; the client ignores an invalid file",
+            )
+        }
+    };
+    let mut commands = Vec::new();
+    winx_commands(&value, "", &mut commands);
+    if commands.is_empty() {
+        return Check::ok(format!("{name}: no winx entry"));
+    }
+    match commands.iter().find(|command| !runnable(command)) {
+        Some(command) => Check::fail(
+            format!("{name}: winx command `{command}` is not runnable"),
+            "use the absolute path from `which winx-code-agent`; clients often launch with a \
+             minimal PATH",
+        ),
+        None => Check::ok(format!("{name}: winx entry ok")),
+    }
+}
+
+fn client_checks(home: Option<&Path>, workspace: &Path) -> Vec<Check> {
+    let checks: Vec<Check> = client_configs(home, workspace)
+        .iter()
+        .filter_map(|path| Some(client_check(path, &std::fs::read_to_string(path).ok()?)))
+        .collect();
+    if checks.is_empty() {
+        return vec![Check::warn(
+            "no MCP client config found",
+            "add winx to your client (see the README's install section)",
+        )];
+    }
+    checks
+}
+
+fn render_section(out: &mut String, title: &str, checks: &[Check]) -> usize {
+    let _ = writeln!(out, "\n{title}\n");
+    let mut problems = 0;
+    for check in checks {
+        let tag = match check.status {
+            Status::Ok => "ok",
+            Status::Warn => "warn",
+            Status::Fail => "FAIL",
+        };
+        let _ = writeln!(out, "  {tag:<8} {}", check.label);
+        if let Some(fix) = &check.fix {
+            problems += 1;
+            let _ = writeln!(out, "           -> {fix}");
+        }
+    }
+    problems
+}
+
+/// The full `winx doctor` report for `workspace`.
+pub fn report(workspace: &Path) -> String {
+    let mut out = crate::utils::capabilities::doctor_report();
+    let home = home::home_dir();
+    let problems = render_section(&mut out, "shell", &shell_checks())
+        + render_section(&mut out, "state", &state_checks())
+        + render_section(&mut out, "configuration", &workspace_checks(workspace))
+        + render_section(&mut out, "MCP clients", &client_checks(home.as_deref(), workspace));
+    if problems == 0 {
+        out.push_str("\nEnvironment looks good.\n");
+    } else {
+        let _ = writeln!(out, "\n{problems} problem(s) found; apply the fixes marked ->.");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn client_configs_are_parsed_and_winx_commands_checked() {
+        let json = Path::new("/x/.mcp.json");
+        let ok = r#"{"mcpServers": {"winx": {"command": "sh"}}}"#;
+        assert_eq!(client_check(json, ok).status, Status::Ok);
+        let missing = r#"{"mcpServers": {"winx": {"command": "/no/such/winx-code-agent"}}}"#;
+        assert_eq!(client_check(json, missing).status, Status::Fail);
+        assert_eq!(client_check(json, "{ not json").status, Status::Fail);
+        let other = r#"{"servers": {"fs": {"command": "npx"}}}"#;
+        assert!(client_check(json, other).label.contains("no winx entry"));
+
+        let toml = Path::new("/x/.codex/config.toml");
+        let entry = "[mcp_servers.winx]\ncommand = \"/no/such/winx\"\n";
+        assert_eq!(client_check(toml, entry).status, Status::Fail);
+    }
+
+    #[test]
+    fn invalid_project_config_is_reported() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join(".winx")).unwrap();
+        std::fs::write(dir.path().join(".winx/config.toml"), "[format\n").unwrap();
+        let checks = workspace_checks(dir.path());
+        assert!(checks.iter().any(|c| c.status == Status::Fail && c.label.contains("config.toml")));
+    }
+}
@@ -0,0 +1,80 @@
+//! `winx --dry-run`: tools that change something report what they would do
+//! instead of doing it, for evaluating prompts and demos safely.
+//!
+//! `FileWriteOrEdit` and `MultiFileEdit` run every check they normally do and
+//! return the diff they would write (the gate is in `commit_edit`, so nothing
+//! reaches disk and no formatter or hook runs). Every other tool not annotated
+//! read-only is answered in `call_tool` by [`describe`] without running;
+//! `BashCommand` never starts a command.
+
+use std::fmt::Write as FmtWrite;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde_json::Value;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turn dry-run mode on for the rest of the process.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Whether a mutating call still runs under dry-run because it simulates its
+/// own writes: file edits, and a `RenameSymbol` preview.
+pub fn simulated_by_tool(tool: &str, args: Option<&Value>) -> bool {
+    let apply = args.and_then(|args| args.get("apply")).and_then(Value::as_bool);
+    match tool {
+        "FileWriteOrEdit" | "MultiFileEdit" => true,
+        "RenameSymbol" => apply != Some(true),
+        _ => false,
+    }
+}
+
+/// What a mutating call that was not run would have done. `summary` is the
+/// call's (redacted) audit summary.
+pub fn describe(tool: &str, args: Option<&Value>, summary: &str) -> String {
+    let command = args
+        .and_then(|args| args.get("action_json"))
+        .and_then(|action| action.get("command"))
+        .and_then(Value::as_str);
+    let mut out = match (tool, command) {
+        ("BashCommand", Some(command)) => {
+            let mut out = format!("Dry run: would run `{command}`.");
+            let targets = crate::utils::bash_parser::write_targets(command);
+            if !targets.is_empty() {
+                let paths: Vec<&str> = targets.iter().map(|t| t.path.as_str()).collect();
+                let _ = write!(out, "\nIt writes or deletes: {}.", paths.join(", "));
+            }
+            if let Some(action) = crate::utils::bash_parser::destructive_action(command) {
+                let _ = write!(out, "\nIt is hard to undo: {action}.");
+            }
+            out
+        }
+        _ if summary.is_empty() => format!("Dry run: would call {tool}."),
+        _ => format!("Dry run: would call {tool} ({summary})."),
+    };
+    out.push_str("\nNothing was changed and no process was started (winx runs with --dry-run).");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn describes_commands_without_running_them() {
+        let args = json!({"action_json": {"command": "rm -rf build"}});
+        let text = describe("BashCommand", Some(&args), "cmd=\"rm -rf build\"");
+        assert!(text.starts_with("Dry run: would run `rm -rf build`."), "got {text}");
+        assert!(text.contains("It writes or deletes: build."), "got {text}");
+        assert!(text.contains("hard to undo"), "got {text}");
+
+        let text = describe("DeleteFile", None, "path=a.txt");
+        assert!(text.starts_with("Dry run: would call DeleteFile (path=a.txt)."), "got {text}");
+    }
+}
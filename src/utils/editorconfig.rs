@@ -0,0 +1,206 @@
+//! Minimal `.editorconfig` resolution for file writes.
+//!
+//! Supports the properties that shape what winx writes: `indent_style`,
+//! `indent_size`/`tab_width`, `end_of_line`, `charset` and
+//! `insert_final_newline`. Files are collected from the target's directory
+//! upwards until one declares `root = true`; closer files and later sections
+//! win, as in the spec. Section globs support `*`, `**`, `?`, `[...]` and
+//! `{a,b}`.
+
+use std::path::Path;
+
+use glob::{MatchOptions, Pattern};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndentStyle {
+    Space,
+    Tab,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndOfLine {
+    Lf,
+    Crlf,
+}
+
+/// The properties that apply to one file. `None` = not specified.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EditorConfig {
+    pub indent_style: Option<IndentStyle>,
+    pub indent_size: Option<usize>,
+    pub end_of_line: Option<EndOfLine>,
+    pub charset: Option<String>,
+    pub insert_final_newline: Option<bool>,
+}
+
+impl EditorConfig {
+    fn set(&mut self, key: &str, value: &str) {
+        match key {
+            "indent_style" => {
+                self.indent_style = match value {
+                    "space" => Some(IndentStyle::Space),
+                    "tab" => Some(IndentStyle::Tab),
+                    _ => None,
+                };
+            }
+            // `indent_size = tab` defers to tab_width; keep the numeric one.
+            "indent_size" | "tab_width" => {
+                if let Ok(size) = value.parse::<usize>() {
+                    if size > 0 && (key == "indent_size" || self.indent_size.is_none()) {
+                        self.indent_size = Some(size);
+                    }
+                }
+            }
+            "end_of_line" => {
+                self.end_of_line = match value {
+                    "lf" => Some(EndOfLine::Lf),
+                    "crlf" => Some(EndOfLine::Crlf),
+                    _ => None,
+                };
+            }
+            "charset" => self.charset = Some(value.to_string()),
+            "insert_final_newline" => self.insert_final_newline = value.parse().ok(),
+            _ => {}
+        }
+    }
+}
+
+/// Resolve the `.editorconfig` properties for `path`.
+pub fn resolve(path: &Path) -> EditorConfig {
+    let mut files = Vec::new();
+    for dir in path.ancestors().skip(1) {
+        let Ok(text) = std::fs::read_to_string(dir.join(".editorconfig")) else {
+            continue;
+        };
+        let is_root = text.lines().map(parse_line).any(
+            |line| matches!(line, Line::Property(key, value) if key == "root" && value == "true"),
+        );
+        files.push((dir, text));
+        if is_root {
+            break;
+        }
+    }
+
+    let mut config = EditorConfig::default();
+    // Outermost first, so closer files override.
+    for (dir, text) in files.iter().rev() {
+        let Ok(rel) = path.strip_prefix(dir) else { continue };
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        let mut in_matching_section = false;
+        for line in text.lines().map(parse_line) {
+            match line {
+                Line::Section(glob) => in_matching_section = section_matches(&glob, &rel),
+                Line::Property(key, value) if in_matching_section => config.set(&key, &value),
+                _ => {}
+            }
+        }
+    }
+    config
+}
+
+enum Line {
+    Section(String),
+    Property(String, String),
+    Other,
+}
+
+fn parse_line(line: &str) -> Line {
+    let line = line.trim();
+    if line.starts_with('#') || line.starts_with(';') {
+        return Line::Other;
+    }
+    if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+        return Line::Section(section.to_string());
+    }
+    match line.split_once('=') {
+        Some((key, value)) => {
+            Line::Property(key.trim().to_lowercase(), value.trim().to_lowercase())
+        }
+        None => Line::Other,
+    }
+}
+
+/// Whether a section glob matches `rel` (the file path relative to the
+/// `.editorconfig`'s directory). A glob without `/` matches the file name at any
+/// depth; one with `/` is anchored to that directory.
+fn section_matches(glob: &str, rel: &str) -> bool {
+    let options = MatchOptions { require_literal_separator: true, ..MatchOptions::new() };
+    expand_braces(glob).iter().any(|glob| {
+        let anchored = if glob.contains('/') {
+            glob.trim_start_matches('/').to_string()
+        } else {
+            format!("**/{glob}")
+        };
+        Pattern::new(&anchored).is_ok_and(|p| p.matches_with(rel, options))
+    })
+}
+
+/// Expand the `{a,b}` alternations of a section glob into plain globs.
+fn expand_braces(glob: &str) -> Vec<String> {
+    let Some(open) = glob.find('{') else {
+        return vec![glob.to_string()];
+    };
+    let mut depth = 0;
+    let mut close = None;
+    let mut splits = Vec::new();
+    for (i, c) in glob[open..].char_indices().map(|(i, c)| (open + i, c)) {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(i);
+                    break;
+                }
+            }
+            ',' if depth == 1 => splits.push(i),
+            _ => {}
+        }
+    }
+    // Unbalanced, or `{single}` / numeric ranges: leave literal.
+    let Some(close) = close.filter(|_| !splits.is_empty()) else {
+        return vec![glob.to_string()];
+    };
+    let (prefix, suffix) = (&glob[..open], &glob[close + 1..]);
+    let mut bounds = vec![open];
+    bounds.extend(&splits);
+    bounds.push(close);
+    bounds
+        .windows(2)
+        .flat_map(|w| expand_braces(&format!("{prefix}{}{suffix}", &glob[w[0] + 1..w[1]])))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn expands_brace_alternations() {
+        assert_eq!(expand_braces("*.{js,ts}"), vec!["*.js", "*.ts"]);
+        assert_eq!(expand_braces("{a,b{c,d}}.rs"), vec!["a.rs", "bc.rs", "bd.rs"]);
+        assert_eq!(expand_braces("plain"), vec!["plain"]);
+    }
+
+    #[test]
+    fn closer_files_and_later_sections_win() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("web/src")).unwrap();
+        std::fs::write(
+            root.join(".editorconfig"),
+            "root = true\n[*]\nindent_style = space\nindent_size = 4\n\
+             insert_final_newline = true\n[Makefile]\nindent_style = tab\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("web/.editorconfig"), "[*.{js,ts}]\nindent_size = 2\n").unwrap();
+
+        let ts = resolve(&root.join("web/src/app.ts"));
+        assert_eq!(ts.indent_style, Some(IndentStyle::Space));
+        assert_eq!(ts.indent_size, Some(2));
+        assert_eq!(ts.insert_final_newline, Some(true));
+        assert_eq!(resolve(&root.join("Makefile")).indent_style, Some(IndentStyle::Tab));
+        assert_eq!(resolve(&root.join("web/src/lib.rs")).indent_size, Some(4));
+    }
+}
@@ -0,0 +1,166 @@
+//! Polling-based workspace change detection for `GetFileChanges` and MCP
+//! resource subscriptions.
+//!
+//! A [`WorkspaceSnapshot`] is the `(mtime, len)` stamp of every workspace file
+//! (gitignore-aware, via the shared walker). Diffing two snapshots yields what
+//! was created, modified or deleted in between — e.g. edits the user made in
+//! their IDE mid-conversation. A [`WatchList`] does the same for the handful of
+//! files clients subscribed to, keyed by resource URI. Polling needs no OS
+//! watcher or background thread and behaves the same on every platform and filesystem (including network
+//! mounts, where inotify/FSEvents silently miss changes).
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::utils::repo::walk_workspace_files;
+use crate::utils::symbol_cache::FileStamp;
+
+/// Stamps of every tracked workspace file at one point in time.
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceSnapshot {
+    root: PathBuf,
+    files: HashMap<PathBuf, FileStamp>,
+}
+
+/// Absolute paths that changed between two snapshots, each list sorted.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    pub created: Vec<PathBuf>,
+    pub modified: Vec<PathBuf>,
+    pub deleted: Vec<PathBuf>,
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.modified.is_empty() && self.deleted.is_empty()
+    }
+}
+
+impl WorkspaceSnapshot {
+    /// Stamp every file under `root` (capped like the other workspace walks).
+    pub fn scan(root: &Path) -> Self {
+        let files = walk_workspace_files(root)
+            .into_iter()
+            .filter_map(|path| FileStamp::of(&path).map(|stamp| (path, stamp)))
+            .collect();
+        Self { root: root.to_path_buf(), files }
+    }
+
+    /// The directory this snapshot was taken of.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Number of files tracked.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// What changed going from `self` (older) to `newer`.
+    pub fn diff(&self, newer: &Self) -> SnapshotDiff {
+        let mut diff = SnapshotDiff::default();
+        for (path, stamp) in &newer.files {
+            match self.files.get(path) {
+                None => diff.created.push(path.clone()),
+                Some(old) if old != stamp => diff.modified.push(path.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.deleted =
+            self.files.keys().filter(|path| !newer.files.contains_key(*path)).cloned().collect();
+        diff.created.sort();
+        diff.modified.sort();
+        diff.deleted.sort();
+        diff
+    }
+}
+
+/// Files watched for resource subscriptions: resource URI → path and the
+/// stamp last seen (`None` while the file doesn't exist).
+#[derive(Debug, Default)]
+pub struct WatchList {
+    files: HashMap<String, (PathBuf, Option<FileStamp>)>,
+}
+
+impl WatchList {
+    /// Start watching `path` under `uri`, from its current state.
+    pub fn watch(&mut self, uri: String, path: PathBuf) {
+        let stamp = FileStamp::of(&path);
+        self.files.insert(uri, (path, stamp));
+    }
+
+    /// Stop watching `uri`; `false` if it wasn't watched.
+    pub fn unwatch(&mut self, uri: &str) -> bool {
+        self.files.remove(uri).is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// URIs whose file changed, appeared or disappeared since the last poll,
+    /// sorted. Each is re-stamped, so a change is reported once.
+    pub fn poll(&mut self) -> Vec<String> {
+        let mut changed: Vec<String> = self
+            .files
+            .iter_mut()
+            .filter_map(|(uri, (path, seen))| {
+                let now = FileStamp::of(path);
+                (now != *seen).then(|| {
+                    *seen = now;
+                    uri.clone()
+                })
+            })
+            .collect();
+        changed.sort();
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn diff_reports_created_modified_deleted() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::write(root.join("keep.txt"), "same").unwrap();
+        std::fs::write(root.join("edit.txt"), "short").unwrap();
+        std::fs::write(root.join("gone.txt"), "bye").unwrap();
+        let before = WorkspaceSnapshot::scan(&root);
+        assert_eq!(before.len(), 3);
+
+        std::fs::write(root.join("edit.txt"), "now much longer").unwrap();
+        std::fs::remove_file(root.join("gone.txt")).unwrap();
+        std::fs::write(root.join("new.txt"), "hi").unwrap();
+        let diff = before.diff(&WorkspaceSnapshot::scan(&root));
+
+        assert_eq!(diff.created, vec![root.join("new.txt")]);
+        assert_eq!(diff.modified, vec![root.join("edit.txt")]);
+        assert_eq!(diff.deleted, vec![root.join("gone.txt")]);
+    }
+
+    #[test]
+    fn watch_list_reports_each_change_once() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.txt");
+        std::fs::write(&file, "one").unwrap();
+        let mut watched = WatchList::default();
+        watched.watch("file:///a".to_string(), file.clone());
+        assert!(watched.poll().is_empty());
+
+        std::fs::write(&file, "three").unwrap();
+        assert_eq!(watched.poll(), ["file:///a"]);
+        assert!(watched.poll().is_empty());
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(watched.poll(), ["file:///a"]);
+        assert!(watched.unwatch("file:///a") && watched.is_empty());
+    }
+}
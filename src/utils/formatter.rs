@@ -0,0 +1,144 @@
+//! Auto-format on write: pipe the new content of an edited file through the
+//! project's formatter before it is written, chosen by extension.
+//!
+//! Opt-in per project (`[format] enabled = true` in
+//! [`CONFIG_FILE`](crate::utils::project_config::CONFIG_FILE)); custom
+//! `[format] commands` only come from the global config. Formatters read
+//! the content on stdin and print the result on stdout, so winx still performs
+//! the single (symlink-safe) write itself, and each formatter runs with the
+//! file's directory as cwd to pick up `rustfmt.toml`, `.prettierrc`,
+//! `pyproject.toml` and friends.
+
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use crate::utils::capabilities;
+use crate::utils::project_config;
+use crate::utils::syntax::output_with_timeout;
+
+/// How long a formatter may run before the edit is written unformatted.
+const FORMAT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Built-in formatter argv by extension; `{path}` is replaced by the file path.
+fn default_command(ext: &str) -> Option<&'static [&'static str]> {
+    Some(match ext {
+        "rs" => &["rustfmt", "--edition", "2021"],
+        "go" => &["gofmt"],
+        "py" | "pyi" => &["black", "--quiet", "--stdin-filename", "{path}", "-"],
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "json" | "css" | "scss" | "html" | "vue"
+        | "yaml" | "yml" | "md" => &["prettier", "--stdin-filepath", "{path}"],
+        _ => return None,
+    })
+}
+
+/// The outcome of formatting one file.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Formatted {
+    /// The formatter changed the content.
+    Changed { formatter: String, content: String },
+    /// The formatter failed (often a syntax error); the content is written as-is.
+    Failed { formatter: String, message: String },
+}
+
+/// Format `content` (the new text of `path`) if the workspace opted in and a
+/// formatter applies. `None` when formatting is off, no formatter is configured
+/// for the extension, a built-in formatter isn't installed, or the content is
+/// already formatted.
+pub fn format(workspace_root: &Path, path: &Path, content: &str) -> Option<Formatted> {
+    format_with(&project_config::load(workspace_root).format, path, content)
+}
+
+fn format_with(
+    config: &project_config::FormatConfig,
+    path: &Path,
+    content: &str,
+) -> Option<Formatted> {
+    if !config.enabled {
+        return None;
+    }
+    let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+    let path_str = path.to_string_lossy();
+    let (argv, configured): (Vec<String>, bool) = match config.commands.get(ext) {
+        Some(argv) => (argv.clone(), true),
+        None => (default_command(ext)?.iter().map(|s| (*s).to_string()).collect(), false),
+    };
+    let (program, rest) = argv.split_first()?;
+    if !capabilities::available(program) {
+        // A missing built-in is expected (not every project uses prettier); a
+        // formatter the project asked for by name is worth pointing out.
+        return configured.then(|| Formatted::Failed {
+            formatter: program.clone(),
+            message: "not found on PATH".to_string(),
+        });
+    }
+
+    let mut command = Command::new(program);
+    command.args(rest.iter().map(|arg| arg.replace("{path}", &path_str)));
+    if let Some(dir) = path.parent().filter(|dir| dir.is_dir()) {
+        command.current_dir(dir);
+    }
+    let Some(output) = output_with_timeout(command, Some(content), FORMAT_TIMEOUT) else {
+        return Some(Formatted::Failed {
+            formatter: program.clone(),
+            message: format!("timed out after {}s", FORMAT_TIMEOUT.as_secs()),
+        });
+    };
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr.lines().take(10).collect::<Vec<_>>().join("\n");
+        return Some(Formatted::Failed { formatter: program.clone(), message });
+    }
+    let formatted = String::from_utf8(output.stdout).ok()?;
+    // An empty result from non-empty input is a broken formatter, not a format.
+    if formatted == content || (formatted.is_empty() && !content.is_empty()) {
+        return None;
+    }
+    Some(Formatted::Changed { formatter: program.clone(), content: formatted })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    fn config(enabled: bool, txt: &[&str]) -> project_config::FormatConfig {
+        let argv = txt.iter().map(|arg| (*arg).to_string()).collect();
+        project_config::FormatConfig { enabled, commands: [("txt".to_string(), argv)].into() }
+    }
+
+    #[test]
+    fn formats_with_configured_command_only_when_enabled() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("notes.txt");
+        let uppercase = config(true, &["tr", "a-z", "A-Z"]);
+        assert_eq!(
+            format_with(&uppercase, &path, "hello\n"),
+            Some(Formatted::Changed { formatter: "tr".into(), content: "HELLO\n".into() })
+        );
+        // Already formatted: nothing to report.
+        assert_eq!(format_with(&uppercase, &path, "HELLO\n"), None);
+
+        let off = config(false, &["tr", "a-z", "A-Z"]);
+        assert_eq!(format_with(&off, &path, "hello\n"), None);
+    }
+
+    #[test]
+    fn reports_a_failing_formatter() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let result = format_with(&config(true, &["false"]), &dir.path().join("a.txt"), "x\n");
+        assert!(matches!(result, Some(Formatted::Failed { .. })), "got {result:?}");
+    }
+
+    #[test]
+    fn project_file_cannot_name_a_formatter() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join(".winx")).unwrap();
+        std::fs::write(
+            dir.path().join(project_config::CONFIG_FILE),
+            "[format]\nenabled = true\ncommands = { txt = [\"tr\", \"a-z\", \"A-Z\"] }\n",
+        )
+        .unwrap();
+        assert_eq!(format(dir.path(), &dir.path().join("notes.txt"), "hello\n"), None);
+    }
+}
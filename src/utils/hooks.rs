@@ -0,0 +1,337 @@
+//! Lifecycle hooks, read from `<workspace>/.winx/hooks.toml`. Example:
+//!
+//! ```toml
+//! [[on_session_start]]
+//! prompt = "Run `make lint` before committing."
+//!
+//! [[pre_command]]
+//! command = "prod|deploy"   # regex on the command line; omitted = every command
+//! run = "echo 'not from the agent' >&2; exit 1"   # a non-zero exit refuses it
+//!
+//! [[post_edit]]
+//! files = "src/**/*.rs"     # glob relative to the workspace; omitted = every file
+//! run = "cargo test -q 2>&1 | tail -20"
+//! timeout = 300             # seconds, default 60
+//!
+//! [[on_error]]
+//! prompt = "If the build fails, check docs/BUILDING.md first."
+//! ```
+//!
+//! `run` is executed with `sh -c` in the workspace root, with `WINX_EVENT`,
+//! `WINX_WORKSPACE` and, per event, `WINX_COMMAND`, `WINX_FILE`, `WINX_TOOL` or
+//! `WINX_ERROR` set. Its output and any `prompt` text are added to the tool
+//! response (to the error message, for `on_error`). A `pre_command` hook that
+//! exits non-zero refuses the command, with its output as the reason.
+//!
+//! A hooks file runs arbitrary commands, so a freshly cloned repo must not get
+//! to run its own: hooks only run once the user has approved that exact file
+//! with `winx trust-hooks`. Approval is by content hash, so any later edit,
+//! including one by the agent, has to be approved again.
+
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use glob::{MatchOptions, Pattern};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::errors::{Result, WinxError};
+
+/// Location of the hooks file, relative to the workspace root.
+pub const HOOKS_FILE: &str = ".winx/hooks.toml";
+
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+/// Hook output kept in the response (the tail, where test summaries are).
+const MAX_OUTPUT_CHARS: usize = 4000;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct HooksConfig {
+    pre_command: Vec<Hook>,
+    post_edit: Vec<Hook>,
+    on_session_start: Vec<Hook>,
+    on_error: Vec<Hook>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Hook {
+    /// Shell command, run with `sh -c` in the workspace root.
+    run: Option<String>,
+    /// Text shown to the agent.
+    prompt: Option<String>,
+    /// `pre_command` only: regex the command line must match.
+    command: Option<String>,
+    /// `post_edit` only: glob the edited file must match.
+    files: Option<String>,
+    /// Seconds before `run` is killed.
+    timeout: Option<u64>,
+}
+
+/// A point in a session that hooks can attach to.
+pub enum Event<'a> {
+    SessionStart,
+    PreCommand(&'a str),
+    PostEdit(&'a Path),
+    Error { tool: &'a str, message: &'a str },
+}
+
+impl Event<'_> {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::SessionStart => "on_session_start",
+            Self::PreCommand(_) => "pre_command",
+            Self::PostEdit(_) => "post_edit",
+            Self::Error { .. } => "on_error",
+        }
+    }
+
+    fn hooks<'c>(&self, config: &'c HooksConfig) -> &'c [Hook] {
+        match self {
+            Self::SessionStart => &config.on_session_start,
+            Self::PreCommand(_) => &config.pre_command,
+            Self::PostEdit(_) => &config.post_edit,
+            Self::Error { .. } => &config.on_error,
+        }
+    }
+
+    fn applies(&self, hook: &Hook, workspace_root: &Path) -> bool {
+        match self {
+            Self::PreCommand(command) => hook.command.as_deref().map_or(true, |pattern| {
+                regex::Regex::new(pattern).map_or_else(
+                    |e| {
+                        warn!("{HOOKS_FILE}: invalid pre_command regex {pattern:?}: {e}");
+                        false
+                    },
+                    |re| re.is_match(command),
+                )
+            }),
+            Self::PostEdit(path) => hook.files.as_deref().map_or(true, |glob| {
+                let rel = path.strip_prefix(workspace_root).unwrap_or(path);
+                let options =
+                    MatchOptions { require_literal_separator: true, ..MatchOptions::new() };
+                Pattern::new(glob).is_ok_and(|p| p.matches_path_with(rel, options))
+            }),
+            Self::SessionStart | Self::Error { .. } => true,
+        }
+    }
+
+    fn env(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Self::SessionStart => Vec::new(),
+            Self::PreCommand(command) => vec![("WINX_COMMAND", (*command).to_string())],
+            Self::PostEdit(path) => vec![("WINX_FILE", path.to_string_lossy().to_string())],
+            Self::Error { tool, message } => {
+                vec![("WINX_TOOL", (*tool).to_string()), ("WINX_ERROR", (*message).to_string())]
+            }
+        }
+    }
+}
+
+/// What the hooks for one event produced.
+#[derive(Debug, Default)]
+pub struct Outcome {
+    /// Prompt text and hook output, in hook order.
+    pub notes: Vec<String>,
+    /// Set when a `pre_command` hook refused the command.
+    pub blocked: Option<String>,
+}
+
+impl Outcome {
+    /// The notes as one block for a tool response, if there are any.
+    pub fn render(&self) -> Option<String> {
+        (!self.notes.is_empty()).then(|| self.notes.join("\n\n"))
+    }
+}
+
+enum Loaded {
+    Missing,
+    Untrusted,
+    Hooks(HooksConfig),
+}
+
+fn load(workspace_root: &Path) -> Loaded {
+    let Ok(text) = fs::read_to_string(workspace_root.join(HOOKS_FILE)) else {
+        return Loaded::Missing;
+    };
+    if !is_trusted(workspace_root, &text) {
+        return Loaded::Untrusted;
+    }
+    match toml::from_str(&text) {
+        Ok(config) => Loaded::Hooks(config),
+        Err(e) => {
+            warn!("ignoring invalid {HOOKS_FILE}: {e}");
+            Loaded::Missing
+        }
+    }
+}
+
+/// Run the hooks for `event` in `workspace_root`. Untrusted, missing or invalid
+/// hook files yield an empty outcome. Blocking (each hook may run until its
+/// timeout), so async callers use `spawn_blocking`.
+pub fn run(workspace_root: &Path, event: &Event) -> Outcome {
+    let mut outcome = Outcome::default();
+    let Loaded::Hooks(config) = load(workspace_root) else {
+        return outcome;
+    };
+    for hook in event.hooks(&config).iter().filter(|hook| event.applies(hook, workspace_root)) {
+        if let Some(prompt) = &hook.prompt {
+            outcome.notes.push(prompt.trim().to_string());
+        }
+        let Some(script) = &hook.run else { continue };
+        let timeout = Duration::from_secs(hook.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS));
+        let (ok, report) = run_script(workspace_root, event, script, timeout);
+        let note = format!("{} hook `{script}` {report}", event.name());
+        if !ok && matches!(event, Event::PreCommand(_)) {
+            outcome.blocked = Some(note);
+            break;
+        }
+        outcome.notes.push(note);
+    }
+    outcome
+}
+
+/// Run one hook script; returns whether it succeeded and a report of its exit
+/// status and output.
+fn run_script(
+    workspace_root: &Path,
+    event: &Event,
+    script: &str,
+    timeout: Duration,
+) -> (bool, String) {
+    let mut command = Command::new("sh");
+    command
+        .arg("-c")
+        .arg(script)
+        .current_dir(workspace_root)
+        .env("WINX_EVENT", event.name())
+        .env("WINX_WORKSPACE", workspace_root)
+        .envs(event.env());
+    let Some(output) = crate::utils::syntax::output_with_timeout(command, None, timeout) else {
+        return (false, format!("timed out after {}s", timeout.as_secs()));
+    };
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    let status = output.status.code().map_or_else(|| "killed".into(), |c| c.to_string());
+    (output.status.success(), format!("exit {status}:\n{}", tail(text.trim_end())))
+}
+
+fn tail(text: &str) -> &str {
+    let count = text.chars().count();
+    if count <= MAX_OUTPUT_CHARS {
+        return text;
+    }
+    let start = text.char_indices().nth(count - MAX_OUTPUT_CHARS).map_or(0, |(i, _)| i);
+    &text[start..]
+}
+
+/// A note for `Initialize` when the workspace has a hooks file that hasn't been
+/// approved (or was edited since).
+pub fn untrusted_notice(workspace_root: &Path) -> Option<String> {
+    matches!(load(workspace_root), Loaded::Untrusted).then(|| {
+        format!(
+            "{HOOKS_FILE} exists but is not trusted, so its hooks are not run. If the user wants \
+             them, they should review it and run `winx trust-hooks {}`.",
+            workspace_root.display()
+        )
+    })
+}
+
+/// The file recording approved hook files: next to the session state, which
+/// the protected-path guard keeps the agent from writing.
+fn trust_file() -> Option<PathBuf> {
+    let state_dir = crate::state::persistence::get_state_dir().ok()?;
+    Some(state_dir.parent()?.join("trusted_hooks"))
+}
+
+fn trust_entry(workspace_root: &Path, text: &str) -> String {
+    let digest = Sha256::digest(text.as_bytes());
+    let hash = digest.iter().fold(String::new(), |mut hash, b| {
+        let _ = write!(hash, "{b:02x}");
+        hash
+    });
+    let workspace = workspace_root.canonicalize().unwrap_or_else(|_| workspace_root.to_path_buf());
+    format!("{hash} {}", workspace.display())
+}
+
+fn is_trusted(workspace_root: &Path, text: &str) -> bool {
+    let Some(Ok(trusted)) = trust_file().map(fs::read_to_string) else {
+        return false;
+    };
+    let entry = trust_entry(workspace_root, text);
+    trusted.lines().any(|line| line == entry)
+}
+
+/// Approve the current hooks file of `workspace_root` (`winx trust-hooks`).
+pub fn trust(workspace_root: &Path) -> Result<String> {
+    let path = workspace_root.join(HOOKS_FILE);
+    let text = fs::read_to_string(&path).map_err(|e| WinxError::FileAccessError {
+        path: path.clone(),
+        message: format!("reading hooks: {e}"),
+    })?;
+    toml::from_str::<HooksConfig>(&text)
+        .map_err(|e| WinxError::ConfigurationError(format!("{}: {e}", path.display())))?;
+    let trust_file = trust_file().ok_or_else(|| {
+        WinxError::ConfigurationError("no state directory to record trust in".to_string())
+    })?;
+    if is_trusted(workspace_root, &text) {
+        return Ok(format!("{} is already trusted.\n", path.display()));
+    }
+    let mut trusted = fs::read_to_string(&trust_file).unwrap_or_default();
+    let _ = writeln!(trusted, "{}", trust_entry(workspace_root, &text));
+    fs::write(&trust_file, trusted).map_err(|e| WinxError::FileAccessError {
+        path: trust_file.clone(),
+        message: format!("recording trust: {e}"),
+    })?;
+    Ok(format!("Trusted {}; its hooks run from the next call.\n", path.display()))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    fn config(text: &str) -> HooksConfig {
+        toml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn filters_select_hooks() {
+        let ws = Path::new("/ws");
+        let hooks = config(
+            "[[pre_command]]\ncommand = 'deploy'\nrun = 'exit 1'\n\
+             [[post_edit]]\nfiles = 'src/**/*.rs'\nprompt = 'run tests'\n",
+        );
+        let pre = &hooks.pre_command[0];
+        assert!(Event::PreCommand("make deploy").applies(pre, ws));
+        assert!(!Event::PreCommand("cargo build").applies(pre, ws));
+        let post = &hooks.post_edit[0];
+        assert!(Event::PostEdit(Path::new("/ws/src/a/b.rs")).applies(post, ws));
+        assert!(!Event::PostEdit(Path::new("/ws/README.md")).applies(post, ws));
+    }
+
+    #[test]
+    fn untrusted_hooks_never_run() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join(".winx")).unwrap();
+        std::fs::write(
+            dir.path().join(HOOKS_FILE),
+            "[[pre_command]]\nrun = 'exit 1'\n[[on_session_start]]\nprompt = 'hi'\n",
+        )
+        .unwrap();
+        let outcome = run(dir.path(), &Event::PreCommand("ls"));
+        assert!(outcome.blocked.is_none() && outcome.notes.is_empty());
+        assert!(untrusted_notice(dir.path()).is_some());
+    }
+
+    #[test]
+    fn output_is_cut_to_its_tail() {
+        let long = "x".repeat(MAX_OUTPUT_CHARS) + "END";
+        assert!(tail(&long).ends_with("END"));
+        assert_eq!(tail(&long).chars().count(), MAX_OUTPUT_CHARS);
+    }
+}
@@ -0,0 +1,291 @@
+//! Tool-call metrics: calls, errors and wall-clock per tool since startup,
+//! served as the `mcp://stats` resource and, when `[telemetry] otlp_endpoint`
+//! is set in the global config, pushed to an OpenTelemetry collector.
+//!
+//! Export uses OTLP/HTTP with the JSON encoding (`/v1/metrics`, `/v1/traces`)
+//! through `curl`, so no OpenTelemetry SDK is linked in. Metrics are
+//! cumulative sums; each tool call is also one span. Spans are buffered and
+//! flushed with the metrics every [`EXPORT_INTERVAL`]; a collector that is
+//! down loses that batch rather than growing the buffer.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as FmtWrite;
+use std::process::Stdio;
+use std::sync::{Arc, Once};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+/// How often metrics and buffered spans are pushed to the collector.
+pub const EXPORT_INTERVAL: Duration = Duration::from_secs(60);
+/// Spans kept between exports; older ones are dropped.
+const MAX_PENDING_SPANS: usize = 2000;
+const EXPORT_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct ToolStats {
+    calls: u64,
+    errors: u64,
+    total_ms: u128,
+    max_ms: u128,
+}
+
+#[derive(Debug)]
+struct Span {
+    tool: String,
+    start_unix_nanos: u128,
+    end_unix_nanos: u128,
+    ok: bool,
+}
+
+#[derive(Debug)]
+pub struct Metrics {
+    started: Instant,
+    started_unix_nanos: u128,
+    tools: BTreeMap<String, ToolStats>,
+    pending_spans: Vec<Span>,
+    exporting: bool,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            started_unix_nanos: unix_nanos(SystemTime::now()),
+            tools: BTreeMap::new(),
+            pending_spans: Vec::new(),
+            exporting: false,
+        }
+    }
+}
+
+fn unix_nanos(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos())
+}
+
+fn attribute(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+fn resource() -> Value {
+    json!({ "attributes": [
+        attribute("service.name", "winx"),
+        attribute("service.version", env!("CARGO_PKG_VERSION")),
+    ] })
+}
+
+impl Metrics {
+    /// Record a tool call that just finished after `elapsed`.
+    pub fn record(&mut self, tool: &str, elapsed: Duration, ok: bool) {
+        let stats = self.tools.entry(tool.to_string()).or_default();
+        let ms = elapsed.as_millis();
+        stats.calls += 1;
+        stats.errors += u64::from(!ok);
+        stats.total_ms += ms;
+        stats.max_ms = stats.max_ms.max(ms);
+        if self.exporting {
+            if self.pending_spans.len() >= MAX_PENDING_SPANS {
+                self.pending_spans.remove(0);
+            }
+            let end = unix_nanos(SystemTime::now());
+            self.pending_spans.push(Span {
+                tool: tool.to_string(),
+                start_unix_nanos: end.saturating_sub(elapsed.as_nanos()),
+                end_unix_nanos: end,
+                ok,
+            });
+        }
+    }
+
+    /// The `mcp://stats` summary.
+    pub fn render(&self) -> String {
+        let (calls, errors) =
+            self.tools.values().fold((0, 0), |(c, e), s| (c + s.calls, e + s.errors));
+        let mut out = format!(
+            "# Winx stats\n\nUp {}s. {calls} tool call(s), {errors} error(s).\n",
+            self.started.elapsed().as_secs()
+        );
+        if self.tools.is_empty() {
+            return out;
+        }
+        out.push_str("\n| Tool | Calls | Errors | Avg ms | Max ms |\n|---|---|---|---|---|\n");
+        for (tool, s) in &self.tools {
+            let avg = s.total_ms / u128::from(s.calls.max(1));
+            let _ = writeln!(out, "| {tool} | {} | {} | {avg} | {} |", s.calls, s.errors, s.max_ms);
+        }
+        out
+    }
+
+    /// Cumulative OTLP metrics as of now.
+    fn otlp_metrics(&self) -> Value {
+        let now = unix_nanos(SystemTime::now()).to_string();
+        let start = self.started_unix_nanos.to_string();
+        let sum = |name: &str, unit: &str, value: fn(&ToolStats) -> String| {
+            let points: Vec<Value> = self
+                .tools
+                .iter()
+                .map(|(tool, stats)| {
+                    json!({
+                        "attributes": [attribute("tool", tool)],
+                        "startTimeUnixNano": start,
+                        "timeUnixNano": now,
+                        "asInt": value(stats),
+                    })
+                })
+                .collect();
+            json!({
+                "name": name,
+                "unit": unit,
+                // 2 = cumulative
+                "sum": { "aggregationTemporality": 2, "isMonotonic": true, "dataPoints": points },
+            })
+        };
+        json!({ "resourceMetrics": [{
+            "resource": resource(),
+            "scopeMetrics": [{
+                "scope": { "name": "winx" },
+                "metrics": [
+                    sum("winx.tool.calls", "1", |s| s.calls.to_string()),
+                    sum("winx.tool.errors", "1", |s| s.errors.to_string()),
+                    sum("winx.tool.duration", "ms", |s| s.total_ms.to_string()),
+                ],
+            }],
+        }] })
+    }
+
+    /// The buffered spans as an OTLP trace request, emptying the buffer.
+    fn take_otlp_spans(&mut self) -> Option<Value> {
+        if self.pending_spans.is_empty() {
+            return None;
+        }
+        let spans: Vec<Value> = self
+            .pending_spans
+            .drain(..)
+            .map(|span| {
+                json!({
+                    "traceId": format!("{:032x}", rand::random::<u128>()),
+                    "spanId": format!("{:016x}", rand::random::<u64>()),
+                    "name": format!("tools/call {}", span.tool),
+                    // 2 = server
+                    "kind": 2,
+                    "startTimeUnixNano": span.start_unix_nanos.to_string(),
+                    "endTimeUnixNano": span.end_unix_nanos.to_string(),
+                    "attributes": [attribute("tool", &span.tool)],
+                    // 1 = ok, 2 = error
+                    "status": { "code": if span.ok { 1 } else { 2 } },
+                })
+            })
+            .collect();
+        Some(json!({ "resourceSpans": [{
+            "resource": resource(),
+            "scopeSpans": [{ "scope": { "name": "winx" }, "spans": spans }],
+        }] }))
+    }
+}
+
+async fn post(url: &str, body: &Value) {
+    let child = tokio::process::Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--proto", "=http,https"])
+        .args(["--max-time", &EXPORT_TIMEOUT_SECS.to_string()])
+        .args(["--header", "Content-Type: application/json", "--data-binary", "@-", "--"])
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            warn!("OTLP export to {url} failed: {e}");
+            return;
+        }
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(body.to_string().as_bytes()).await;
+    }
+    match child.wait_with_output().await {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => {
+            warn!(
+                "OTLP export to {url} failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Err(e) => warn!("OTLP export to {url} failed: {e}"),
+    }
+}
+
+/// Start pushing `metrics` to the collector at `endpoint` every
+/// [`EXPORT_INTERVAL`], unless that is already running. Without curl nothing is
+/// exported (and no spans are buffered); that is logged once.
+pub async fn start_export(metrics: &Arc<Mutex<Metrics>>, endpoint: String) {
+    if !crate::utils::capabilities::available("curl") {
+        static WARNED: Once = Once::new();
+        WARNED.call_once(|| {
+            warn!("telemetry.otlp_endpoint is set but curl is not installed; not exporting");
+        });
+        return;
+    }
+    {
+        let mut guard = metrics.lock().await;
+        if guard.exporting {
+            return;
+        }
+        guard.exporting = true;
+    }
+    let metrics = metrics.clone();
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(EXPORT_INTERVAL).await;
+            let (metrics_body, spans_body) = {
+                let mut guard = metrics.lock().await;
+                (guard.otlp_metrics(), guard.take_otlp_spans())
+            };
+            post(&format!("{endpoint}/v1/metrics"), &metrics_body).await;
+            if let Some(spans_body) = spans_body {
+                post(&format!("{endpoint}/v1/traces"), &spans_body).await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn records_and_renders_per_tool_stats() {
+        let mut metrics = Metrics::default();
+        metrics.record("ReadFiles", Duration::from_millis(10), true);
+        metrics.record("ReadFiles", Duration::from_millis(30), false);
+        assert_eq!(
+            metrics.tools["ReadFiles"],
+            ToolStats { calls: 2, errors: 1, total_ms: 40, max_ms: 30 }
+        );
+        let text = metrics.render();
+        assert!(text.contains("2 tool call(s), 1 error(s)"));
+        assert!(text.contains("| ReadFiles | 2 | 1 | 20 | 30 |"));
+        // Spans are only buffered while exporting.
+        assert!(metrics.take_otlp_spans().is_none());
+    }
+
+    #[test]
+    fn builds_otlp_payloads() {
+        let mut metrics = Metrics { exporting: true, ..Metrics::default() };
+        metrics.record("BashCommand", Duration::from_millis(5), false);
+        let body = metrics.otlp_metrics();
+        let calls = &body["resourceMetrics"][0]["scopeMetrics"][0]["metrics"][0];
+        assert_eq!(calls["name"], "winx.tool.calls");
+        assert_eq!(calls["sum"]["dataPoints"][0]["asInt"], "1");
+        let spans = metrics.take_otlp_spans().unwrap();
+        let span = &spans["resourceSpans"][0]["scopeSpans"][0]["spans"][0];
+        assert_eq!(span["status"]["code"], 2);
+        assert_eq!(span["traceId"].as_str().unwrap().len(), 32);
+        assert!(metrics.pending_spans.is_empty());
+    }
+}
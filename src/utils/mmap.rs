@@ -529,6 +529,20 @@ pub fn read_file_to_string(path: &Path, max_file_size: u64) -> Result<String> {
     })
 }
 
+/// Like [`read_file_to_string`], but decodes a UTF-8 BOM or latin-1 file (see
+/// [`crate::utils::text_encoding`]) instead of rejecting it, and reports which.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or exceeds the size limit
+pub fn read_file_decoded(
+    path: &Path,
+    max_file_size: u64,
+) -> Result<(String, crate::utils::text_encoding::Encoding)> {
+    let bytes = read_file_optimized(path, max_file_size)?;
+    Ok(crate::utils::text_encoding::decode(bytes))
+}
+
 /// Read a text file in a parallel, line-by-line fashion
 ///
 /// This processes lines in parallel using Rayon for faster processing
@@ -3,19 +3,38 @@
 //! This module contains various utility functions and types used throughout
 //! the application, such as file and path handling, repository analysis, etc.
 
+pub mod audit_log;
 pub mod bash_parser;
+pub mod bug_report;
+pub mod capabilities;
+pub mod cli_docs;
+pub mod crash_report;
 pub mod display_tree;
+pub mod doctor;
+pub mod dry_run;
+pub mod editorconfig;
 pub mod encoder;
+pub mod file_watch;
+pub mod formatter;
+pub mod hooks;
+pub mod metrics;
 pub mod mmap;
 pub mod mode_prompts;
 pub mod output_compress;
 pub mod path;
 pub mod path_prob;
+pub mod project_config;
+pub mod prompt_library;
+pub mod protected_paths;
+pub mod rate_limit;
 pub mod redact;
 pub mod repo;
 pub mod scratch_file;
+pub mod symbol_cache;
 pub mod symbols;
 pub mod syntax;
+pub mod text_encoding;
+pub mod url_guard;
 pub mod workspace_stats;
 
 use crate::types::Initialize;
@@ -1,3 +1,4 @@
+use std::fmt::Write as FmtWrite;
 use std::io;
 use std::path::{Path, PathBuf};
 
@@ -234,6 +235,42 @@ pub fn glob_matches(pattern: &glob::Pattern, relative: &Path) -> bool {
         .is_some_and(|name| pattern.matches_with(name.to_string_lossy().as_ref(), opts))
 }
 
+/// The `file://` URI of an absolute path, percent-encoding anything outside
+/// the unreserved set (and `/`).
+pub fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            uri.push(char::from(byte));
+        } else {
+            let _ = write!(uri, "%{byte:02X}");
+        }
+    }
+    uri
+}
+
+/// The absolute path a `file://` URI names, or `None` if it isn't one.
+pub fn path_from_file_uri(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?.as_bytes();
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut i = 0;
+    while i < encoded.len() {
+        let escaped = (encoded[i] == b'%')
+            .then(|| encoded.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        if let Some(byte) = escaped {
+            bytes.push(byte);
+            i += 3;
+        } else {
+            bytes.push(encoded[i]);
+            i += 1;
+        }
+    }
+    let path = PathBuf::from(String::from_utf8(bytes).ok()?);
+    path.is_absolute().then_some(path)
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::unwrap_used)]
@@ -279,6 +316,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn file_uris_round_trip() {
+        let path = Path::new("/work/my project/src/ação.rs");
+        let uri = file_uri(path);
+        assert_eq!(uri, "file:///work/my%20project/src/a%C3%A7%C3%A3o.rs");
+        assert_eq!(path_from_file_uri(&uri).as_deref(), Some(path));
+        assert_eq!(path_from_file_uri("file://readme"), None);
+        assert_eq!(path_from_file_uri("https://x/y"), None);
+    }
+
     #[test]
     fn expand_user_leaves_non_tilde_paths_untouched() {
         assert_eq!(expand_user("/abs/path"), "/abs/path");
@@ -0,0 +1,531 @@
+//! Per-project settings, read from `<workspace>/.winx/config.toml` layered over
+//! the user's `~/.winx/config.toml` (project values win, except that
+//! `[protect] paths` and `[tools] disabled` from both apply and each `[limits]`
+//! value is the stricter of the two). `winx config get/set` reads and edits
+//! them.
+//!
+//! Everything here is opt-in and defaults to off, so a workspace without the
+//! file behaves exactly as before. Example:
+//!
+//! ```toml
+//! [format]
+//! enabled = true
+//! # Override or add a formatter per extension: content on stdin, formatted
+//! # content on stdout, `{path}` replaced by the file path. `[]` disables one.
+//! # Global file only: a project must not pick the programs run on every write.
+//! commands = { ts = ["npx", "prettier", "--stdin-filepath", "{path}"], md = [] }
+//!
+//! [protect]
+//! # Extra paths no edit, delete or shell write may touch (relative = workspace).
+//! paths = ["secrets/", "~/.kube"]
+//!
+//! [files]
+//! # Only these may be edited or created, whatever the mode allows.
+//! allowed_globs = ["src/**", "tests/**"]
+//!
+//! [tools]
+//! # Tools hidden from tools/list and refused in this workspace.
+//! disabled = ["DeleteFile"]
+//!
+//! [prompt]
+//! # Added to the Initialize response, after AGENTS.md/CLAUDE.md/WINX.md.
+//! append = "Use pnpm. Never touch the generated/ directory."
+//!
+//! [github]
+//! # Token for the GitHub tool. Only read from the global file, never a
+//! # project's (`winx config set --global github.token ...`).
+//! token = "github_pat_..."
+//!
+//! [http.profiles.staging]
+//! # Auth profile for the HttpRequest tool, global file only like the token.
+//! base_url = "https://staging.example.com/api"
+//! headers = { Authorization = "Bearer ..." }
+//!
+//! [limits]
+//! # Rate limits (see `crate::utils::rate_limit`); 0 = unlimited.
+//! calls_per_minute = 120
+//!
+//! [telemetry]
+//! # OTLP/HTTP collector for tool-call metrics and traces; global file only.
+//! otlp_endpoint = "http://localhost:4318"
+//!
+//! [audit]
+//! # Hash-chained log of mutating calls (see `crate::utils::audit_log`);
+//! # global file only.
+//! enabled = true
+//! ```
+//!
+//! Both files are protected paths, so the agent can't loosen them.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use toml::{Table, Value};
+use tracing::warn;
+
+use crate::errors::{Result, WinxError};
+
+/// Location of the config file, relative to the workspace root.
+pub const CONFIG_FILE: &str = ".winx/config.toml";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ProjectConfig {
+    pub format: FormatConfig,
+    pub protect: ProtectConfig,
+    pub files: FilesConfig,
+    pub tools: ToolsConfig,
+    pub prompt: PromptConfig,
+    pub github: GithubConfig,
+    pub http: HttpConfig,
+    pub telemetry: TelemetryConfig,
+    pub limits: LimitsConfig,
+    pub audit: AuditConfig,
+}
+
+/// Auto-format on write (see [`crate::utils::formatter`]).
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct FormatConfig {
+    pub enabled: bool,
+    /// Formatter argv by file extension, overriding the built-in defaults. Only
+    /// taken from the global config; see [`format_commands`].
+    pub commands: HashMap<String, Vec<String>>,
+}
+
+/// Extra protected paths (see [`crate::utils::protected_paths`]).
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ProtectConfig {
+    pub paths: Vec<String>,
+}
+
+/// Where winx may write, on top of the mode's globs.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct FilesConfig {
+    /// Globs (relative = workspace) every edited or created file must match.
+    /// `None` adds no restriction; an empty list makes the workspace read-only.
+    pub allowed_globs: Option<Vec<String>>,
+}
+
+/// Tools switched off for this workspace.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ToolsConfig {
+    pub disabled: Vec<String>,
+}
+
+/// Project instructions for the agent.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PromptConfig {
+    pub append: String,
+}
+
+/// Credentials for the `GitHub` tool; see [`github_token`].
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct GithubConfig {
+    pub token: String,
+}
+
+/// Named auth profiles for the `HttpRequest` tool; see [`http_profile`].
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    pub profiles: HashMap<String, HttpProfile>,
+}
+
+/// Rate limits enforced in `call_tool`; 0 means unlimited.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LimitsConfig {
+    /// Tool calls per session per minute.
+    pub calls_per_minute: u32,
+    /// `BashCommand` calls running at once, across sessions.
+    pub concurrent_commands: u32,
+    /// Edit payload bytes (`FileWriteOrEdit`, `MultiFileEdit`) per hour.
+    pub bytes_written_per_hour: u64,
+}
+
+/// Metrics and trace export; see [`otlp_endpoint`].
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct TelemetryConfig {
+    pub otlp_endpoint: String,
+}
+
+/// The audit log; see [`audit_config`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct AuditConfig {
+    pub enabled: bool,
+    /// Rotate the log once it reaches this size; 0 never rotates.
+    pub max_bytes: u64,
+    /// Rotated files kept.
+    pub keep: usize,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self { enabled: false, max_bytes: 10 * 1024 * 1024, keep: 5 }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HttpProfile {
+    /// The profile's headers are only sent to URLs under this one.
+    pub base_url: String,
+    pub headers: HashMap<String, String>,
+}
+
+/// Settings that `winx config set` accepts (`format.commands.<ext>` too).
+pub const KEYS: &[&str] = &[
+    "format.enabled",
+    "format.commands",
+    "protect.paths",
+    "files.allowed_globs",
+    "tools.disabled",
+    "prompt.append",
+    "github.token",
+    "telemetry.otlp_endpoint",
+    "limits.calls_per_minute",
+    "limits.concurrent_commands",
+    "limits.bytes_written_per_hour",
+    "audit.enabled",
+    "audit.max_bytes",
+    "audit.keep",
+];
+
+/// The user-wide config, `~/.winx/config.toml`, layered under every project's.
+pub fn global_path() -> Option<PathBuf> {
+    home::home_dir().map(|home| home.join(".winx").join("config.toml"))
+}
+
+fn read_table(path: &Path) -> Option<Table> {
+    let text = std::fs::read_to_string(path).ok()?;
+    toml::from_str(&text).map_err(|e| warn!("ignoring invalid {}: {e}", path.display())).ok()
+}
+
+/// Overlay `top` onto `base`: tables merge key by key, anything else replaces.
+fn merge(base: &mut Table, top: Table) {
+    for (key, value) in top {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base)), Value::Table(top)) => merge(base, top),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// The token for the `GitHub` tool from the global config. A project file is
+/// usually committed, so a token there is ignored rather than trusted.
+pub fn github_token() -> Option<String> {
+    let table = read_table(&global_path()?)?;
+    let token = table.get("github")?.get("token")?.as_str()?.trim();
+    (!token.is_empty()).then(|| token.to_string())
+}
+
+/// The `HttpRequest` auth profile `name` from the global config; like the
+/// GitHub token, profiles in a project file are ignored.
+pub fn http_profile(name: &str) -> Option<HttpProfile> {
+    let table = read_table(&global_path()?)?;
+    let profile = table.get("http")?.get("profiles")?.get(name)?.clone();
+    profile.try_into().map_err(|e| warn!("ignoring invalid http profile {name}: {e}")).ok()
+}
+
+/// The OTLP/HTTP collector from the global config; a cloned project must not
+/// be able to make winx report to a server of its choosing.
+pub fn otlp_endpoint() -> Option<String> {
+    let table = read_table(&global_path()?)?;
+    let endpoint = table.get("telemetry")?.get("otlp_endpoint")?.as_str()?.trim();
+    (!endpoint.is_empty()).then(|| endpoint.trim_end_matches('/').to_string())
+}
+
+/// `[audit]` from the global config: a project must not be able to switch
+/// off the log that records what was done in it.
+pub fn audit_config() -> AuditConfig {
+    let Some(audit) = global_path().and_then(|path| read_table(&path)?.remove("audit")) else {
+        return AuditConfig::default();
+    };
+    audit.try_into().unwrap_or_else(|e| {
+        warn!("ignoring invalid [audit] config: {e}");
+        AuditConfig::default()
+    })
+}
+
+/// `[format] commands` from the global config. They run on every write, so a
+/// cloned project naming its own programs there would get code execution
+/// without any trust step.
+fn format_commands() -> HashMap<String, Vec<String>> {
+    let Some(commands) =
+        global_path().and_then(|path| read_table(&path)?.get("format")?.get("commands").cloned())
+    else {
+        return HashMap::new();
+    };
+    commands.try_into().unwrap_or_else(|e| {
+        warn!("ignoring invalid [format] commands: {e}");
+        HashMap::new()
+    })
+}
+
+/// Whether limit `new` is stricter than `current`; 0 means unlimited.
+fn stricter_limit(current: Option<&Value>, new: &Value) -> bool {
+    match (current.and_then(Value::as_integer), new.as_integer()) {
+        (Some(current), Some(new)) => new != 0 && (current == 0 || new < current),
+        _ => true,
+    }
+}
+
+/// `layers` merged in order, each over the ones before it.
+fn merge_layers(layers: impl IntoIterator<Item = Table>) -> (Table, Vec<String>) {
+    let mut table = Table::new();
+    let mut protect = Vec::new();
+    let mut disabled: Vec<Value> = Vec::new();
+    let mut limits = Table::new();
+    for layer in layers {
+        // Protected paths accumulate: a project can add to the user's list but
+        // never drop an entry from it. Likewise it can disable more tools but
+        // not re-enable one, and only tighten a limit.
+        if let Some(Value::Array(paths)) = layer.get("protect").and_then(|p| p.get("paths")) {
+            protect.extend(paths.iter().filter_map(Value::as_str).map(str::to_string));
+        }
+        if let Some(Value::Array(tools)) = layer.get("tools").and_then(|t| t.get("disabled")) {
+            for tool in tools {
+                if !disabled.contains(tool) {
+                    disabled.push(tool.clone());
+                }
+            }
+        }
+        if let Some(Value::Table(layer_limits)) = layer.get("limits") {
+            for (key, value) in layer_limits {
+                if stricter_limit(limits.get(key), value) {
+                    limits.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        merge(&mut table, layer);
+    }
+    let mut accumulated = Table::new();
+    if !disabled.is_empty() {
+        let tools = Table::from_iter([("disabled".to_string(), Value::Array(disabled))]);
+        accumulated.insert("tools".to_string(), Value::Table(tools));
+    }
+    if !limits.is_empty() {
+        accumulated.insert("limits".to_string(), Value::Table(limits));
+    }
+    merge(&mut table, accumulated);
+    (table, protect)
+}
+
+/// The effective settings for a workspace as raw TOML: the global file, then
+/// the project file over it.
+fn merged_table(workspace_root: &Path) -> (Table, Vec<String>) {
+    let layers = [global_path(), Some(workspace_root.join(CONFIG_FILE))];
+    merge_layers(layers.iter().flatten().filter_map(|path| read_table(path)))
+}
+
+/// Load the workspace's config, layered over the global one. Missing files
+/// yield the defaults; an invalid one is logged and ignored rather than failing
+/// the tool call.
+pub fn load(workspace_root: &Path) -> ProjectConfig {
+    let (table, protect) = merged_table(workspace_root);
+    let mut config: ProjectConfig = Value::Table(table).try_into().unwrap_or_else(|e| {
+        warn!("ignoring invalid winx config for {}: {e}", workspace_root.display());
+        ProjectConfig::default()
+    });
+    config.protect.paths = protect;
+    config.format.commands = format_commands();
+    config
+}
+
+/// The config for `workspace_root`, or the global config alone when no
+/// workspace is initialized yet.
+pub fn load_or_global(workspace_root: Option<&Path>) -> ProjectConfig {
+    if let Some(root) = workspace_root {
+        return load(root);
+    }
+    let Some(table) = global_path().and_then(|path| read_table(&path)) else {
+        return ProjectConfig::default();
+    };
+    Value::Table(table).try_into().unwrap_or_else(|e| {
+        warn!("ignoring invalid global winx config: {e}");
+        ProjectConfig::default()
+    })
+}
+
+/// `[tools] disabled` for `workspace_root` (see [`load_or_global`]).
+pub fn disabled_tools(workspace_root: Option<&Path>) -> Vec<String> {
+    load_or_global(workspace_root).tools.disabled
+}
+
+/// The effective value of a dotted `key` for `workspace_root`.
+pub fn get(workspace_root: &Path, key: &str) -> Option<Value> {
+    let (table, _) = merged_table(workspace_root);
+    let mut value = &Value::Table(table);
+    for part in key.split('.') {
+        value = value.get(part)?;
+    }
+    Some(value.clone())
+}
+
+/// Set dotted `key` to `raw` (a TOML literal such as `true` or `["a"]`; any
+/// other text is taken as a string) in the config file at `path`. The file must
+/// still load afterwards, so a wrongly typed value is rejected.
+pub fn set(path: &Path, key: &str, raw: &str) -> Result<()> {
+    let known = KEYS.contains(&key)
+        || key.strip_prefix("format.commands.").is_some_and(|ext| !ext.is_empty())
+        || key.strip_prefix("http.profiles.").is_some_and(|rest| !rest.is_empty());
+    if !known {
+        return Err(WinxError::ConfigurationError(format!(
+            "unknown setting {key:?}; known: {}, format.commands.<ext>, http.profiles.<name>",
+            KEYS.join(", ")
+        )));
+    }
+    let global_only = key == "github.token"
+        || key == "telemetry.otlp_endpoint"
+        || key.starts_with("format.commands")
+        || key.starts_with("audit.")
+        || key.starts_with("http.profiles.");
+    if global_only && global_path().as_deref() != Some(path) {
+        return Err(WinxError::ConfigurationError(format!(
+            "{key} only goes in the global config (--global); project files get committed"
+        )));
+    }
+    let value = toml::from_str::<Table>(&format!("v = {raw}"))
+        .ok()
+        .and_then(|mut t| t.remove("v"))
+        .unwrap_or_else(|| Value::String(raw.to_string()));
+
+    let mut table = if path.exists() {
+        let text = std::fs::read_to_string(path)?;
+        toml::from_str(&text)
+            .map_err(|e| WinxError::ConfigurationError(format!("{}: {e}", path.display())))?
+    } else {
+        Table::new()
+    };
+    let mut parts: Vec<&str> = key.split('.').collect();
+    let last = parts.pop().unwrap_or(key);
+    let mut node = &mut table;
+    for part in parts {
+        let entry = node.entry(part).or_insert_with(|| Value::Table(Table::new()));
+        node = entry.as_table_mut().ok_or_else(|| {
+            WinxError::ConfigurationError(format!("{part} in {} is not a table", path.display()))
+        })?;
+    }
+    node.insert(last.to_string(), value);
+
+    Value::Table(table.clone()).try_into::<ProjectConfig>().map_err(|e| {
+        WinxError::ConfigurationError(format!("{key} = {raw} is not a valid value: {e}"))
+    })?;
+    let text = toml::to_string(&table)
+        .map_err(|e| WinxError::ConfigurationError(format!("serializing config: {e}")))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, text)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn missing_or_invalid_config_is_default() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert!(!load(dir.path()).format.enabled);
+
+        std::fs::create_dir_all(dir.path().join(".winx")).unwrap();
+        std::fs::write(dir.path().join(CONFIG_FILE), "format = [").unwrap();
+        assert!(!load(dir.path()).format.enabled);
+
+        std::fs::write(
+            dir.path().join(CONFIG_FILE),
+            "[format]\nenabled = true\ncommands = { md = [] }\n",
+        )
+        .unwrap();
+        let config = load(dir.path());
+        assert!(config.format.enabled);
+        assert!(!config.format.commands.contains_key("md"), "commands are global-only");
+        assert!(config.files.allowed_globs.is_none());
+    }
+
+    #[test]
+    fn parses_files_tools_and_prompt() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join(".winx")).unwrap();
+        std::fs::write(
+            dir.path().join(CONFIG_FILE),
+            "[files]\nallowed_globs = []\n[tools]\ndisabled = [\"BashCommand\"]\n\
+             [prompt]\nappend = \"Use pnpm.\"\n",
+        )
+        .unwrap();
+        let config = load(dir.path());
+        assert_eq!(config.files.allowed_globs, Some(Vec::new()));
+        assert_eq!(config.tools.disabled, ["BashCommand"]);
+        assert_eq!(config.prompt.append, "Use pnpm.");
+        assert_eq!(disabled_tools(Some(dir.path())), ["BashCommand"]);
+    }
+
+    #[test]
+    fn set_writes_typed_values_and_get_reads_them() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        set(&path, "format.enabled", "true").unwrap();
+        set(&path, "tools.disabled", r#"["DeleteFile"]"#).unwrap();
+        set(&path, "prompt.append", "Use pnpm").unwrap();
+        assert!(set(&path, "format.enabled", "maybe").is_err(), "type is checked");
+        assert!(set(&path, "nope.key", "1").is_err(), "key is checked");
+        assert!(set(&path, "github.token", "secret").is_err(), "token is global-only");
+        assert!(set(&path, "format.commands.rs", r#"["sh"]"#).is_err(), "global-only");
+        assert!(set(&path, "http.profiles.api.base_url", "https://x").is_err(), "global-only");
+
+        assert_eq!(get(dir.path(), "format.enabled"), Some(Value::Boolean(true)));
+        assert_eq!(get(dir.path(), "prompt.append"), Some(Value::String("Use pnpm".into())));
+        let config = load(dir.path());
+        assert!(config.format.enabled);
+        assert_eq!(config.tools.disabled, ["DeleteFile"]);
+    }
+
+    #[test]
+    fn project_tables_merge_over_global_ones() {
+        let mut base: Table =
+            toml::from_str("[format]\nenabled = true\n[tools]\ndisabled = [\"A\"]").unwrap();
+        merge(&mut base, toml::from_str("[tools]\ndisabled = [\"B\"]").unwrap());
+        let config: ProjectConfig = Value::Table(base).try_into().unwrap();
+        assert!(config.format.enabled);
+        assert_eq!(config.tools.disabled, ["B"]);
+    }
+
+    #[test]
+    fn project_cannot_loosen_global_tools_or_limits() {
+        let global: Table = toml::from_str(
+            "[tools]\ndisabled = [\"BashCommand\"]\n\
+             [limits]\ncalls_per_minute = 60\nconcurrent_commands = 2\n",
+        )
+        .unwrap();
+        let project: Table = toml::from_str(
+            "[tools]\ndisabled = [\"DeleteFile\"]\n\
+             [limits]\ncalls_per_minute = 0\nconcurrent_commands = 5\n\
+             bytes_written_per_hour = 1000\n",
+        )
+        .unwrap();
+        let (table, _) = merge_layers([global, project]);
+        let config: ProjectConfig = Value::Table(table).try_into().unwrap();
+        assert_eq!(config.tools.disabled, ["BashCommand", "DeleteFile"]);
+        assert_eq!(config.limits.calls_per_minute, 60, "0 (unlimited) doesn't lift a limit");
+        assert_eq!(config.limits.concurrent_commands, 2);
+        assert_eq!(config.limits.bytes_written_per_hour, 1000, "a project can tighten");
+
+        let stricter: Table = toml::from_str("[limits]\ncalls_per_minute = 30\n").unwrap();
+        let (table, _) =
+            merge_layers([toml::from_str("[limits]\ncalls_per_minute = 60\n").unwrap(), stricter]);
+        assert_eq!(table["limits"]["calls_per_minute"].as_integer(), Some(30));
+    }
+}
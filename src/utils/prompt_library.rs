@@ -0,0 +1,205 @@
+//! User prompt library, read from `~/.winx/prompts/*.md`. Example
+//! (`~/.winx/prompts/review.md`):
+//!
+//! ```markdown
+//! ---
+//! name: Review
+//! description: Review a file for bugs and style
+//! args: [file, focus?]
+//! ---
+//! Review {{file}} carefully. Pay special attention to {{focus}}.
+//! ```
+//!
+//! The front matter is optional: `name` defaults to the file stem, and an arg
+//! ending in `?` is optional (it renders as an empty string when omitted).
+//! `{{arg}}` placeholders in the body are filled from the `prompts/get`
+//! arguments. The directory is re-read on every request, so new or edited
+//! prompts show up without restarting the server.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value};
+use tracing::warn;
+
+use crate::errors::{Result, WinxError};
+
+/// Prompt files larger than this are skipped.
+const MAX_PROMPT_FILE_SIZE: u64 = 256 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptArg {
+    pub name: String,
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserPrompt {
+    pub name: String,
+    pub description: Option<String>,
+    pub args: Vec<PromptArg>,
+    pub body: String,
+}
+
+pub fn prompts_dir() -> Option<PathBuf> {
+    home::home_dir().map(|home| home.join(".winx").join("prompts"))
+}
+
+/// `[a, b?]` or `a, b?` → args, a trailing `?` marking an optional one.
+fn parse_args(value: &str) -> Vec<PromptArg> {
+    let value = value.trim();
+    let value = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')).unwrap_or(value);
+    value
+        .split(',')
+        .map(|arg| arg.trim().trim_matches(|c| c == '"' || c == '\''))
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| match arg.strip_suffix('?') {
+            Some(name) => PromptArg { name: name.trim().to_string(), required: false },
+            None => PromptArg { name: arg.to_string(), required: true },
+        })
+        .collect()
+}
+
+/// Parse a prompt file; `stem` is the name used when the front matter has none.
+pub fn parse(stem: &str, text: &str) -> UserPrompt {
+    let mut prompt = UserPrompt {
+        name: stem.to_string(),
+        description: None,
+        args: Vec::new(),
+        body: text.to_string(),
+    };
+    let Some(rest) = text.strip_prefix("---\n").or_else(|| text.strip_prefix("---\r\n")) else {
+        return prompt;
+    };
+    let Some(end) = rest.find("\n---") else {
+        return prompt;
+    };
+    for line in rest[..end].lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+        match key.trim() {
+            "name" if !value.is_empty() => prompt.name = value.to_string(),
+            "description" if !value.is_empty() => prompt.description = Some(value.to_string()),
+            "args" => prompt.args = parse_args(value),
+            _ => {}
+        }
+    }
+    let body = &rest[end + "\n---".len()..];
+    prompt.body = body.split_once('\n').map_or("", |(_, body)| body).to_string();
+    prompt
+}
+
+/// Every `*.md` prompt in `dir`, sorted by name. Unreadable or oversized files
+/// are skipped with a warning; on a duplicate name the first file wins.
+pub fn load_dir(dir: &Path) -> Vec<UserPrompt> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+        .collect();
+    paths.sort();
+    let mut prompts: Vec<UserPrompt> = Vec::new();
+    for path in paths {
+        if !path.metadata().is_ok_and(|m| m.is_file() && m.len() <= MAX_PROMPT_FILE_SIZE) {
+            warn!(path = %path.display(), "skipping prompt file: not a file or too large");
+            continue;
+        }
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) => {
+                warn!(path = %path.display(), "skipping prompt file: {e}");
+                continue;
+            }
+        };
+        let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let prompt = parse(&stem, &text);
+        if prompts.iter().any(|p| p.name == prompt.name) {
+            warn!(path = %path.display(), name = %prompt.name, "skipping duplicate prompt name");
+            continue;
+        }
+        prompts.push(prompt);
+    }
+    prompts.sort_by(|a, b| a.name.cmp(&b.name));
+    prompts
+}
+
+/// The prompts in `~/.winx/prompts`.
+pub fn load() -> Vec<UserPrompt> {
+    prompts_dir().map(|dir| load_dir(&dir)).unwrap_or_default()
+}
+
+impl UserPrompt {
+    /// The body with `{{arg}}` placeholders filled from `arguments`. Missing
+    /// required arguments are an error; missing optional ones render empty.
+    pub fn render(&self, arguments: Option<&Map<String, Value>>) -> Result<String> {
+        let value_of = |name: &str| {
+            arguments.and_then(|args| args.get(name)).map(|value| match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+        };
+        let missing: Vec<&str> = self
+            .args
+            .iter()
+            .filter(|arg| arg.required && value_of(&arg.name).is_none())
+            .map(|arg| arg.name.as_str())
+            .collect();
+        if !missing.is_empty() {
+            return Err(WinxError::InvalidInput(format!(
+                "prompt {} needs argument(s): {}",
+                self.name,
+                missing.join(", ")
+            )));
+        }
+        let mut body = self.body.clone();
+        for arg in &self.args {
+            body = body.replace(
+                &format!("{{{{{}}}}}", arg.name),
+                &value_of(&arg.name).unwrap_or_default(),
+            );
+        }
+        Ok(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_front_matter_and_renders_args() {
+        let text = "---\nname: Review\ndescription: \"Review a file\"\nargs: [file, focus?]\n---\n\
+                    Review {{file}}. Focus: {{focus}}\n";
+        let prompt = parse("review", text);
+        assert_eq!(prompt.name, "Review");
+        assert_eq!(prompt.description.as_deref(), Some("Review a file"));
+        assert_eq!(
+            prompt.args,
+            vec![
+                PromptArg { name: "file".into(), required: true },
+                PromptArg { name: "focus".into(), required: false },
+            ]
+        );
+        let args = json!({"file": "src/main.rs"});
+        assert_eq!(prompt.render(args.as_object()).unwrap(), "Review src/main.rs. Focus: \n");
+        assert!(prompt.render(None).is_err());
+    }
+
+    #[test]
+    fn loads_md_files_named_by_stem() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("tests.md"), "Write tests.\n").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+        let prompts = load_dir(dir.path());
+        assert_eq!(prompts.len(), 1);
+        assert_eq!(
+            (prompts[0].name.as_str(), prompts[0].body.as_str()),
+            ("tests", "Write tests.\n")
+        );
+    }
+}
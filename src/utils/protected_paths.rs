@@ -0,0 +1,217 @@
+//! Protected paths: locations that no edit, undo or shell write may touch.
+//!
+//! Defaults are `~/.ssh`, `/etc`, the workspace's `.git/objects`, the project
+//! and global `.winx/config.toml`, and winx's own state dir. A project adds more under
+//! `[protect] paths` in [`CONFIG_FILE`], and an operator via
+//! `WINX_PROTECTED_PATHS` (`:`-separated). Relative entries are resolved
+//! against the workspace root. The edit tools (`FileWriteOrEdit`,
+//! `MultiFileEdit`, `UndoEdit`, `RenameSymbol`) and the file-management tools
+//! (`MoveFile`, `CopyFile`, `DeleteFile`, `CreateDirectory`) check every path
+//! they touch; `BashCommand` checks the paths its command line would write (see
+//! [`write_targets`]). Anything protected, or a directory holding it, is
+//! refused; `WINX_ALLOW_PROTECTED=1` on the server turns the guard off.
+//!
+//! The shell check is a guard against accidents, not a sandbox: writes hidden in
+//! scripts, `bash -c '...'` or variable expansions are not seen (use
+//! `WINX_SANDBOX` for kernel-enforced confinement).
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::errors::{Result, WinxError};
+use crate::utils::bash_parser::write_targets;
+use crate::utils::path::expand_user;
+use crate::utils::project_config::CONFIG_FILE;
+
+fn guard_disabled() -> bool {
+    std::env::var("WINX_ALLOW_PROTECTED").is_ok_and(|v| v == "1" || v == "true")
+}
+
+/// Every protected root for this workspace, normalized.
+fn protected_roots(workspace_root: &Path) -> Vec<PathBuf> {
+    // The project and global configs are protected too, so the agent can't
+    // lift its own protections by editing them.
+    let mut entries: Vec<String> = ["~/.ssh", "/etc", ".git/objects", CONFIG_FILE]
+        .iter()
+        .map(|entry| (*entry).to_string())
+        .collect();
+    if let Some(global) = crate::utils::project_config::global_path() {
+        entries.push(global.to_string_lossy().to_string());
+    }
+    let state_dir = crate::state::persistence::get_state_dir().ok();
+    if let Some(winx_dir) = state_dir.as_deref().and_then(Path::parent) {
+        entries.push(winx_dir.to_string_lossy().to_string());
+    }
+    if let Ok(extra) = std::env::var("WINX_PROTECTED_PATHS") {
+        entries.extend(extra.split(':').filter(|e| !e.is_empty()).map(str::to_string));
+    }
+    entries.extend(crate::utils::project_config::load(workspace_root).protect.paths);
+
+    let workspace = normalize(workspace_root);
+    entries
+        .iter()
+        .map(|entry| normalize(&workspace_root.join(expand_home(entry))))
+        // A workspace the user deliberately opened inside a protected location
+        // (e.g. /etc/nixos) stays editable; nested entries like .git/objects
+        // still apply.
+        .filter(|root| !workspace.starts_with(root))
+        .collect()
+}
+
+/// `~` and a leading `$HOME`/`${HOME}` expanded to the home directory.
+fn expand_home(path: &str) -> String {
+    for prefix in ["${HOME}", "$HOME"] {
+        if let Some(rest) = path.strip_prefix(prefix) {
+            return expand_user(&format!("~{rest}"));
+        }
+    }
+    expand_user(path)
+}
+
+/// Canonicalize as much of `path` as exists (resolving symlinks such as macOS's
+/// `/etc` -> `/private/etc`) and lexically clean the rest, so a not-yet-created
+/// file still compares correctly.
+fn normalize(path: &Path) -> PathBuf {
+    let mut existing = path.to_path_buf();
+    let mut tail = Vec::new();
+    while !existing.exists() {
+        let Some(name) = existing.file_name() else { break };
+        tail.push(name.to_os_string());
+        if !existing.pop() {
+            break;
+        }
+    }
+    let mut out = existing.canonicalize().unwrap_or(existing);
+    for part in tail.iter().rev() {
+        out.push(part);
+    }
+    let mut clean = PathBuf::new();
+    for component in out.components() {
+        match component {
+            Component::ParentDir => {
+                clean.pop();
+            }
+            Component::CurDir => {}
+            other => clean.push(other),
+        }
+    }
+    clean
+}
+
+/// The protected root `target` would touch: one it is inside of or, unless
+/// only written `into` (a `cp`/`mv` destination directory), one below it that
+/// deleting or moving `target` would take along.
+fn protected_root<'a>(target: &Path, roots: &'a [PathBuf], into: bool) -> Option<&'a PathBuf> {
+    roots.iter().find(|root| target.starts_with(root) || (!into && root.starts_with(target)))
+}
+
+fn refusal(path: &Path, root: &Path) -> WinxError {
+    let why = if path.starts_with(root) {
+        format!("is protected (under {})", root.display())
+    } else {
+        format!("contains the protected {}", root.display())
+    };
+    WinxError::PathSecurityError {
+        path: path.to_path_buf(),
+        message: format!(
+            "{} {why}, so winx won't write, move or delete it. If this is intentional, ask the \
+             user to do it themselves.",
+            path.display()
+        ),
+    }
+}
+
+/// Refuse a write/delete of `path` (absolute) if it is protected or contains a
+/// protected path.
+pub fn check_path(path: &Path, workspace_root: &Path) -> Result<()> {
+    if guard_disabled() {
+        return Ok(());
+    }
+    let target = normalize(path);
+    match protected_root(&target, &protected_roots(workspace_root), false) {
+        Some(root) => Err(refusal(&target, root)),
+        None => Ok(()),
+    }
+}
+
+/// Refuse a shell command that would write to or delete a protected path.
+/// Relative targets are resolved against `cwd`.
+pub fn check_command(command: &str, cwd: &Path, workspace_root: &Path) -> Result<()> {
+    if guard_disabled() {
+        return Ok(());
+    }
+    let targets = write_targets(command);
+    if targets.is_empty() {
+        return Ok(());
+    }
+    let roots = protected_roots(workspace_root);
+    for target in targets {
+        let path = normalize(&cwd.join(expand_home(&target.path)));
+        if let Some(root) = protected_root(&path, &roots, target.into_dir) {
+            return Err(refusal(&path, root));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn default_roots_are_refused() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let ws = dir.path().canonicalize().unwrap();
+        assert!(check_path(&ws.join(".git/objects/ab/cdef"), &ws).is_err());
+        assert!(check_path(&ws.join("src/../.git/objects/x"), &ws).is_err());
+        assert!(check_path(Path::new("/etc/hosts"), &ws).is_err());
+        assert!(check_path(&ws.join("src/main.rs"), &ws).is_ok());
+        assert!(check_path(&ws.join(".git/config"), &ws).is_ok());
+        assert!(check_path(&ws.join(".winx/config.toml"), &ws).is_err());
+    }
+
+    #[test]
+    fn parents_of_protected_roots_are_refused() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let ws = dir.path().canonicalize().unwrap();
+        assert!(check_path(&ws.join(".git"), &ws).is_err());
+        assert!(check_path(&ws.join(".winx"), &ws).is_err());
+        assert!(check_path(&ws.join("src/.."), &ws).is_err());
+        assert!(check_path(Path::new("/"), &ws).is_err());
+        assert!(check_command("rm -rf .git", &ws, &ws).is_err());
+        assert!(check_command("mv .winx x", &ws, &ws).is_err());
+        assert!(check_command("rm -rf ../", &ws.join("src"), &ws).is_err());
+        assert!(check_command("rsync -a --delete empty/ ./", &ws, &ws).is_err());
+        // Copying or moving into a directory leaves what is already there.
+        assert!(check_command("cp notes.txt . && mv a.txt ..", &ws, &ws).is_ok());
+        assert!(check_command("rm -rf .github", &ws, &ws).is_ok());
+    }
+
+    #[test]
+    fn shell_writes_to_protected_paths_are_refused() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let ws = dir.path().canonicalize().unwrap();
+        assert!(check_command("rm -rf .git/objects", &ws, &ws).is_err());
+        assert!(check_command("echo x >> /etc/hosts", &ws, &ws).is_err());
+        assert!(check_command("cat /etc/hosts > hosts.txt", &ws, &ws).is_ok());
+        assert!(check_command("dd if=.git/objects/pack/p.idx of=p.idx", &ws, &ws).is_ok());
+        assert!(check_command("dd if=/dev/zero of=/etc/hosts", &ws, &ws).is_err());
+        assert!(check_command("cargo build", &ws, &ws).is_ok());
+    }
+
+    #[test]
+    fn project_config_adds_paths() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let ws = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(ws.join(".winx")).unwrap();
+        std::fs::write(ws.join(".winx/config.toml"), "[protect]\npaths = [\"secrets\"]\n").unwrap();
+        assert!(check_path(&ws.join("secrets/prod.env"), &ws).is_err());
+        assert!(check_command("mv a.txt secrets/", &ws, &ws).is_err());
+    }
+
+    #[test]
+    fn workspace_inside_a_protected_root_stays_editable() {
+        let roots = protected_roots(Path::new("/etc/nixos"));
+        assert!(!roots.iter().any(|root| root == &normalize(Path::new("/etc"))));
+    }
+}
@@ -0,0 +1,192 @@
+//! Rate limits from `[limits]` in the winx config, enforced in `call_tool`
+//! before a tool runs, so a runaway agent loop can't hammer the machine:
+//!
+//! ```toml
+//! [limits]
+//! calls_per_minute = 120          # per session (thread_id)
+//! concurrent_commands = 2         # BashCommand calls running at once
+//! bytes_written_per_hour = 50_000_000  # FileWriteOrEdit/MultiFileEdit payloads
+//! ```
+//!
+//! Every limit defaults to 0, meaning unlimited. A refused call reports which
+//! limit it hit and when to retry.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use crate::utils::project_config::LimitsConfig;
+
+const MINUTE: Duration = Duration::from_secs(60);
+const HOUR: Duration = Duration::from_secs(3600);
+/// Suggested wait when the concurrent-command limit is hit; there is no way to
+/// know when a running command will finish.
+const COMMAND_RETRY_AFTER: Duration = Duration::from_secs(5);
+
+/// Why a call was refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Refusal {
+    /// The `[limits]` key that was hit.
+    pub limit: &'static str,
+    pub retry_after: Duration,
+    pub message: String,
+}
+
+/// Holds one slot of `concurrent_commands` until dropped.
+#[derive(Debug)]
+pub struct CommandPermit {
+    running: Arc<AtomicUsize>,
+}
+
+impl Drop for CommandPermit {
+    fn drop(&mut self) {
+        self.running.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Limiter {
+    /// Call times in the last minute, per session.
+    calls: HashMap<String, VecDeque<Instant>>,
+    /// `(time, bytes)` of write payloads in the last hour.
+    written: VecDeque<(Instant, u64)>,
+    running_commands: Arc<AtomicUsize>,
+}
+
+/// Bytes of edit content a write tool's arguments carry.
+pub fn write_payload_bytes(tool: &str, args: Option<&Value>) -> u64 {
+    let content = |entry: &Value| {
+        entry.get("text_or_search_replace_blocks").and_then(Value::as_str).map_or(0, str::len)
+    };
+    let bytes = match (tool, args) {
+        ("FileWriteOrEdit", Some(args)) => content(args),
+        ("MultiFileEdit", Some(args)) => args
+            .get("files")
+            .and_then(Value::as_array)
+            .map_or(0, |files| files.iter().map(content).sum()),
+        _ => 0,
+    };
+    bytes as u64
+}
+
+impl Limiter {
+    /// Admit a call of `tool` from `session` at `now`, recording it, or refuse
+    /// it. A `BashCommand` gets a permit to hold while it runs.
+    pub fn admit(
+        &mut self,
+        tool: &str,
+        session: &str,
+        write_bytes: u64,
+        limits: &LimitsConfig,
+        now: Instant,
+    ) -> Result<Option<CommandPermit>, Refusal> {
+        if limits.calls_per_minute > 0 {
+            let calls = self.calls.entry(session.to_string()).or_default();
+            while calls.front().is_some_and(|t| now.duration_since(*t) >= MINUTE) {
+                calls.pop_front();
+            }
+            if calls.len() >= limits.calls_per_minute as usize {
+                let oldest = calls.front().copied().unwrap_or(now);
+                return Err(Refusal {
+                    limit: "calls_per_minute",
+                    retry_after: MINUTE.saturating_sub(now.duration_since(oldest)),
+                    message: format!(
+                        "more than {} tool calls in a minute for this session",
+                        limits.calls_per_minute
+                    ),
+                });
+            }
+        }
+
+        if limits.bytes_written_per_hour > 0 && write_bytes > 0 {
+            while self.written.front().is_some_and(|(t, _)| now.duration_since(*t) >= HOUR) {
+                self.written.pop_front();
+            }
+            let total: u64 = self.written.iter().map(|(_, bytes)| bytes).sum();
+            if total + write_bytes > limits.bytes_written_per_hour {
+                let oldest = self.written.front().map_or(now, |(t, _)| *t);
+                return Err(Refusal {
+                    limit: "bytes_written_per_hour",
+                    retry_after: HOUR.saturating_sub(now.duration_since(oldest)),
+                    message: format!(
+                        "writing {write_bytes} more bytes would pass {} bytes in an hour \
+                         ({total} written)",
+                        limits.bytes_written_per_hour
+                    ),
+                });
+            }
+        }
+
+        let permit = if tool == "BashCommand" && limits.concurrent_commands > 0 {
+            let max = limits.concurrent_commands as usize;
+            let acquired =
+                self.running_commands.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |running| {
+                    (running < max).then_some(running + 1)
+                });
+            if acquired.is_err() {
+                return Err(Refusal {
+                    limit: "concurrent_commands",
+                    retry_after: COMMAND_RETRY_AFTER,
+                    message: format!("{max} BashCommand call(s) already running"),
+                });
+            }
+            Some(CommandPermit { running: self.running_commands.clone() })
+        } else {
+            None
+        };
+
+        // Only admitted calls count against the limits.
+        if limits.calls_per_minute > 0 {
+            self.calls.entry(session.to_string()).or_default().push_back(now);
+        }
+        if limits.bytes_written_per_hour > 0 && write_bytes > 0 {
+            self.written.push_back((now, write_bytes));
+        }
+        Ok(permit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn calls_per_minute_is_per_session_and_slides() {
+        let limits = LimitsConfig { calls_per_minute: 2, ..LimitsConfig::default() };
+        let mut limiter = Limiter::default();
+        let start = Instant::now();
+        assert!(limiter.admit("ReadFiles", "a", 0, &limits, start).is_ok());
+        assert!(limiter.admit("ReadFiles", "a", 0, &limits, start).is_ok());
+        let refusal = limiter.admit("ReadFiles", "a", 0, &limits, start).unwrap_err();
+        assert_eq!((refusal.limit, refusal.retry_after), ("calls_per_minute", MINUTE));
+        assert!(limiter.admit("ReadFiles", "b", 0, &limits, start).is_ok());
+        assert!(limiter.admit("ReadFiles", "a", 0, &limits, start + MINUTE).is_ok());
+    }
+
+    #[test]
+    fn commands_and_write_bytes_are_capped() {
+        let limits = LimitsConfig {
+            concurrent_commands: 1,
+            bytes_written_per_hour: 10,
+            ..LimitsConfig::default()
+        };
+        let mut limiter = Limiter::default();
+        let now = Instant::now();
+        let permit = limiter.admit("BashCommand", "a", 0, &limits, now).unwrap();
+        assert!(limiter.admit("BashCommand", "b", 0, &limits, now).is_err());
+        drop(permit);
+        assert!(limiter.admit("BashCommand", "b", 0, &limits, now).is_ok());
+
+        let args = json!({"files": [{"text_or_search_replace_blocks": "12345678"}]});
+        let bytes = write_payload_bytes("MultiFileEdit", Some(&args));
+        assert_eq!(bytes, 8);
+        assert!(limiter.admit("MultiFileEdit", "a", bytes, &limits, now).is_ok());
+        let refusal = limiter.admit("MultiFileEdit", "a", bytes, &limits, now).unwrap_err();
+        assert_eq!(refusal.limit, "bytes_written_per_hour");
+    }
+}
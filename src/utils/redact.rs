@@ -65,6 +65,13 @@ fn rules() -> &'static [(&'static str, Regex)] {
     })
 }
 
+/// Whether an environment variable name looks like it holds a secret, so its
+/// value is masked rather than shown (`winx report-bug`, the `Env` tool).
+pub fn is_secret_name(name: &str) -> bool {
+    let name = name.to_ascii_uppercase();
+    ["TOKEN", "SECRET", "KEY", "PASSWORD", "CREDENTIAL"].iter().any(|s| name.contains(s))
+}
+
 /// Whether redaction is disabled via `WINX_NO_REDACT`.
 fn disabled() -> bool {
     std::env::var("WINX_NO_REDACT").is_ok_and(|v| v != "0" && !v.is_empty())
@@ -74,7 +81,16 @@ fn disabled() -> bool {
 /// when nothing matched, so the common case is cheap. The `regex` crate is
 /// linear-time, so this is safe on adversarial/large input.
 pub fn redact(text: &str) -> Cow<'_, str> {
-    if disabled() || text.is_empty() {
+    if disabled() {
+        return Cow::Borrowed(text);
+    }
+    redact_always(text)
+}
+
+/// [`redact`] ignoring `WINX_NO_REDACT`, for text meant to leave the machine
+/// (e.g. `winx report-bug` output pasted into a public issue).
+pub fn redact_always(text: &str) -> Cow<'_, str> {
+    if text.is_empty() {
         return Cow::Borrowed(text);
     }
     let mut owned: Option<String> = None;
@@ -174,6 +174,9 @@ pub fn walk_workspace_files(root: &Path) -> Vec<PathBuf> {
 /// Recently-changed files from git history, newest first, topological order,
 /// merges skipped — the CLI mirror of wcgw's pygit2 revwalk.
 fn get_recent_git_files(root: &Path, count: usize, existing: &HashSet<&str>) -> Vec<String> {
+    if !crate::utils::capabilities::available("git") {
+        return Vec::new();
+    }
     let output = Command::new("git")
         .arg("-C")
         .arg(root)
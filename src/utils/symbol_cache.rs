@@ -0,0 +1,105 @@
+//! Process-wide, incrementally maintained definition index for the repo map.
+//!
+//! Re-parsing every supported file on each `Outline` call made large repo maps
+//! stall for seconds in long sessions. Entries here are keyed by canonical path
+//! and stamped with the file's `(mtime, len)`; a lookup re-extracts only files
+//! whose stamp changed, and winx's own writes/edits call [`invalidate`] so the
+//! next map reflects them immediately even when the mtime granularity is coarse.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError};
+use std::time::SystemTime;
+
+use crate::utils::symbols::Symbol;
+
+/// Drop the whole index past this many entries rather than tracking recency:
+/// a workspace switch is the only realistic way to get here, and the old
+/// entries are dead weight after one.
+const MAX_CACHED_FILES: usize = 50_000;
+
+/// Cheap change detector for a file: modification time plus length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl FileStamp {
+    /// Stamp `path` from its metadata, or `None` if it can't be stat'ed.
+    pub fn of(path: &Path) -> Option<Self> {
+        let meta = std::fs::metadata(path).ok()?;
+        Some(Self { modified: meta.modified().ok(), len: meta.len() })
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    stamp: FileStamp,
+    symbols: Arc<Vec<Symbol>>,
+}
+
+fn index() -> MutexGuard<'static, HashMap<PathBuf, Entry>> {
+    static INDEX: OnceLock<Mutex<HashMap<PathBuf, Entry>>> = OnceLock::new();
+    INDEX.get_or_init(|| Mutex::new(HashMap::new())).lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Cached definitions for `path` if its on-disk stamp still matches `stamp`.
+pub fn get(path: &Path, stamp: FileStamp) -> Option<Arc<Vec<Symbol>>> {
+    index().get(path).filter(|e| e.stamp == stamp).map(|e| Arc::clone(&e.symbols))
+}
+
+/// Record freshly extracted definitions for `path` at `stamp`.
+pub fn insert(path: &Path, stamp: FileStamp, symbols: Vec<Symbol>) -> Arc<Vec<Symbol>> {
+    let symbols = Arc::new(symbols);
+    let mut idx = index();
+    if idx.len() >= MAX_CACHED_FILES && !idx.contains_key(path) {
+        idx.clear();
+    }
+    idx.insert(path.to_path_buf(), Entry { stamp, symbols: Arc::clone(&symbols) });
+    symbols
+}
+
+/// Forget `path` so the next lookup re-extracts it. Called on every write/edit
+/// winx performs; external changes are caught by the stamp comparison instead.
+pub fn invalidate(path: &Path) {
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let mut idx = index();
+    idx.remove(&canonical);
+    idx.remove(path);
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sym(name: &str) -> Symbol {
+        Symbol {
+            name: name.to_string(),
+            kind: "function".to_string(),
+            line: 1,
+            is_definition: true,
+        }
+    }
+
+    #[test]
+    fn stale_stamp_misses_and_invalidate_forgets() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().canonicalize().unwrap().join("a.rs");
+        std::fs::write(&path, "fn a() {}\n").unwrap();
+        let stamp = FileStamp::of(&path).unwrap();
+        insert(&path, stamp, vec![sym("a")]);
+        assert_eq!(get(&path, stamp).unwrap()[0].name, "a");
+
+        // A different length is a different stamp even within one mtime tick.
+        std::fs::write(&path, "fn a() {}\nfn b() {}\n").unwrap();
+        let changed = FileStamp::of(&path).unwrap();
+        assert!(get(&path, changed).is_none());
+
+        insert(&path, changed, vec![sym("a"), sym("b")]);
+        invalidate(&path);
+        assert!(get(&path, changed).is_none());
+    }
+}
@@ -48,6 +48,12 @@ fn lang_and_query(ext: &str) -> Option<(tree_sitter::Language, &'static str)> {
     Some(pair)
 }
 
+/// The tree-sitter grammar for `ext`, for callers that walk the syntax tree
+/// themselves (`RenameSymbol`).
+pub(crate) fn language_for(ext: &str) -> Option<tree_sitter::Language> {
+    lang_and_query(ext).map(|(language, _)| language)
+}
+
 /// Whether `ext` has a symbol extractor (used to filter repo-map candidates).
 pub fn supports(ext: &str) -> bool {
     lang_and_query(ext).is_some()
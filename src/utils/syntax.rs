@@ -1,6 +1,8 @@
 use std::fmt::Write as FmtWrite;
+use std::io::Read;
 use std::path::Path;
-use std::process::{Command, Stdio};
+use std::process::{Command, Output, Stdio};
+use std::time::{Duration, Instant};
 
 use tree_sitter::{Node, Parser};
 
@@ -199,24 +201,150 @@ fn python_warning(path: &Path, content: &str) -> Option<String> {
     })
 }
 
-/// The available Python interpreter, probed once. The probe used to re-spawn
-/// `python --version` on every `.py` write.
-fn python_interpreter() -> Option<&'static str> {
-    static PYTHON: std::sync::OnceLock<Option<&'static str>> = std::sync::OnceLock::new();
-    *PYTHON.get_or_init(|| {
-        if Command::new("python3").arg("--version").output().is_ok() {
-            Some("python3")
-        } else if Command::new("python").arg("--version").output().is_ok() {
-            Some("python")
-        } else {
-            None
+/// How long an external checker may run before it is killed and skipped.
+const EXTERNAL_CHECK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// At most this many `cargo check` errors are appended to an edit result.
+const MAX_CARGO_ERRORS: usize = 3;
+
+/// Whether the external checker `name` (`node`, `cargo`) is enabled through
+/// `WINX_EXTERNAL_CHECKS` (a comma list, or `1`/`all` for every checker). Off by
+/// default: these run real toolchains, and `cargo check` can take a while.
+fn external_checker_enabled(setting: &str, name: &str) -> bool {
+    setting.split(',').map(str::trim).any(|v| v == name || v == "1" || v == "all")
+}
+
+/// Errors from an opt-in external checker for the file just written at `path`:
+/// `node --check` for plain JavaScript, and `cargo check` of the enclosing crate
+/// for Rust (reporting only errors located in this file). Runs after the
+/// in-process parse passes, to catch what a grammar can't (type errors,
+/// unresolved names, ESM/CJS mistakes). `None` when disabled, the toolchain is
+/// missing, the check times out, or the file is clean.
+pub fn external_check_warning(path: &Path) -> Option<String> {
+    let setting = std::env::var("WINX_EXTERNAL_CHECKS").ok()?;
+    let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+    match ext {
+        "js" | "mjs" | "cjs" if external_checker_enabled(&setting, "node") => node_warning(path),
+        "rs" if external_checker_enabled(&setting, "cargo") => cargo_warning(path),
+        _ => None,
+    }
+}
+
+fn node_warning(path: &Path) -> Option<String> {
+    if !crate::utils::capabilities::available("node") {
+        return None;
+    }
+    let mut command = Command::new("node");
+    command.arg("--check").arg(path);
+    let output = output_with_timeout(command, None, EXTERNAL_CHECK_TIMEOUT)?;
+    (!output.status.success()).then(|| {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        format!("Syntax warning: node --check reported:\n{}", stderr.trim())
+    })
+}
+
+fn cargo_warning(path: &Path) -> Option<String> {
+    if !crate::utils::capabilities::available("cargo") {
+        return None;
+    }
+    let crate_dir = path.ancestors().skip(1).find(|dir| dir.join("Cargo.toml").is_file())?;
+    let mut command = Command::new("cargo");
+    command.args(["check", "--quiet", "--message-format=json"]).current_dir(crate_dir);
+    let output = output_with_timeout(command, None, EXTERNAL_CHECK_TIMEOUT)?;
+    let errors = cargo_errors_for(&String::from_utf8_lossy(&output.stdout), crate_dir, path);
+    if errors.is_empty() {
+        return None;
+    }
+    let mut message = String::from("Build warning: cargo check reported errors in this file:");
+    for rendered in &errors {
+        let _ = write!(message, "\n{}", rendered.trim_end());
+    }
+    Some(message)
+}
+
+/// The rendered `error`-level compiler messages from `cargo check
+/// --message-format=json` output whose primary span is in `path`.
+fn cargo_errors_for(stdout: &str, crate_dir: &Path, path: &Path) -> Vec<String> {
+    let target = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let in_target = |span: &serde_json::Value| {
+        span["is_primary"].as_bool() == Some(true)
+            && span["file_name"].as_str().is_some_and(|file| {
+                let file = crate_dir.join(file);
+                file.canonicalize().unwrap_or(file) == target
+            })
+    };
+    stdout
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|msg| msg["reason"] == "compiler-message" && msg["message"]["level"] == "error")
+        .filter(|msg| msg["message"]["spans"].as_array().is_some_and(|s| s.iter().any(in_target)))
+        .filter_map(|msg| msg["message"]["rendered"].as_str().map(str::to_string))
+        .take(MAX_CARGO_ERRORS)
+        .collect()
+}
+
+/// Run `command` to completion with `stdin` as its input, killing it (and
+/// returning `None`) after `timeout`. stdin is fed and stdout/stderr drained on
+/// threads so a chatty child can't block on a full pipe.
+pub(crate) fn output_with_timeout(
+    mut command: Command,
+    stdin: Option<&str>,
+    timeout: Duration,
+) -> Option<Output> {
+    let stdin_cfg = if stdin.is_some() { Stdio::piped() } else { Stdio::null() };
+    let mut child =
+        command.stdin(stdin_cfg).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn().ok()?;
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        let owned = input.to_owned();
+        std::thread::spawn(move || {
+            use std::io::Write;
+            let _ = pipe.write_all(owned.as_bytes());
+        });
+    }
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
         }
+    };
+    Some(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
     })
 }
 
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+/// The available Python interpreter (probed once, see [`capabilities`]).
+///
+/// [`capabilities`]: crate::utils::capabilities
+fn python_interpreter() -> Option<&'static str> {
+    ["python3", "python"].into_iter().find(|p| crate::utils::capabilities::available(p))
+}
+
 #[cfg(test)]
 mod tests {
-    use super::syntax_warning;
+    use super::{cargo_errors_for, external_checker_enabled, syntax_warning};
     use std::path::Path;
 
     #[test]
@@ -257,4 +385,33 @@ mod tests {
         assert!(syntax_warning(Path::new("a.php"), "<?php echo 1; ?>\n").is_none());
         assert!(syntax_warning(Path::new("a.php"), "<?php echo (1; ?>\n").is_some());
     }
+
+    #[test]
+    fn external_checkers_are_opt_in_by_name() {
+        assert!(external_checker_enabled("node,cargo", "cargo"));
+        assert!(external_checker_enabled("all", "node"));
+        assert!(!external_checker_enabled("node", "cargo"));
+        assert!(!external_checker_enabled("", "node"));
+    }
+
+    #[test]
+    fn cargo_errors_are_filtered_to_the_edited_file() {
+        let dir = Path::new("/nonexistent-crate");
+        let msg = |file: &str, level: &str| {
+            serde_json::json!({
+                "reason": "compiler-message",
+                "message": {
+                    "level": level,
+                    "rendered": format!("{level} in {file}"),
+                    "spans": [{"file_name": file, "is_primary": true}],
+                },
+            })
+            .to_string()
+        };
+        let stdout = [msg("src/lib.rs", "error"), msg("src/other.rs", "error")].join("\n")
+            + "\n"
+            + &msg("src/lib.rs", "warning");
+        let errors = cargo_errors_for(&stdout, dir, &dir.join("src/lib.rs"));
+        assert_eq!(errors, vec!["error in src/lib.rs".to_string()]);
+    }
 }
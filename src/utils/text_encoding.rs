@@ -0,0 +1,94 @@
+//! Detect and preserve a text file's on-disk encoding.
+//!
+//! Files are decoded once into a `String` for hashing, matching and diffing,
+//! and re-encoded the same way on write, so editing a file with a UTF-8 BOM or
+//! a legacy latin-1 file doesn't silently rewrite it as plain UTF-8. The BOM is
+//! not part of the decoded text (the agent never sees or has to reproduce it).
+
+use std::path::Path;
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    /// UTF-8 with a leading byte-order mark.
+    Utf8Bom,
+    /// ISO-8859-1: any bytes that aren't valid UTF-8 are read as latin-1, where
+    /// every byte maps to the code point of the same value.
+    Latin1,
+}
+
+impl Encoding {
+    /// The `.editorconfig` `charset` value for this encoding.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Utf8 => "utf-8",
+            Self::Utf8Bom => "utf-8-bom",
+            Self::Latin1 => "latin1",
+        }
+    }
+
+    /// Parse an `.editorconfig` `charset`; `None` for charsets winx can't write
+    /// (UTF-16), which then fall back to UTF-8.
+    pub fn from_charset(charset: &str) -> Option<Self> {
+        match charset {
+            "utf-8" => Some(Self::Utf8),
+            "utf-8-bom" => Some(Self::Utf8Bom),
+            "latin1" => Some(Self::Latin1),
+            _ => None,
+        }
+    }
+}
+
+/// Decode file bytes, detecting the encoding.
+pub fn decode(bytes: Vec<u8>) -> (String, Encoding) {
+    if let Some(rest) = bytes.strip_prefix(UTF8_BOM) {
+        if let Ok(text) = std::str::from_utf8(rest) {
+            return (text.to_string(), Encoding::Utf8Bom);
+        }
+    }
+    match String::from_utf8(bytes) {
+        Ok(text) => (text, Encoding::Utf8),
+        Err(e) => (e.into_bytes().iter().map(|&b| char::from(b)).collect(), Encoding::Latin1),
+    }
+}
+
+/// Encode `text` for writing. Fails with the first character the encoding
+/// can't represent (anything above U+00FF in a latin-1 file).
+pub fn encode(text: &str, encoding: Encoding) -> Result<Vec<u8>, char> {
+    match encoding {
+        Encoding::Utf8 => Ok(text.as_bytes().to_vec()),
+        Encoding::Utf8Bom => Ok([UTF8_BOM, text.as_bytes()].concat()),
+        Encoding::Latin1 => text.chars().map(|c| u8::try_from(c).map_err(|_| c)).collect(),
+    }
+}
+
+/// Read and decode the file at `path`.
+pub fn read_text(path: &Path) -> std::io::Result<(String, Encoding)> {
+    std::fs::read(path).map(decode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_bom_and_latin1() {
+        let (text, enc) = decode(b"\xEF\xBB\xBFhi\n".to_vec());
+        assert_eq!((text.as_str(), enc), ("hi\n", Encoding::Utf8Bom));
+        assert_eq!(encode(&text, enc), Ok(b"\xEF\xBB\xBFhi\n".to_vec()));
+
+        let (text, enc) = decode(b"caf\xE9\n".to_vec());
+        assert_eq!((text.as_str(), enc), ("caf\u{e9}\n", Encoding::Latin1));
+        assert_eq!(encode(&text, enc), Ok(b"caf\xE9\n".to_vec()));
+
+        assert_eq!(decode("caf\u{e9}".as_bytes().to_vec()).1, Encoding::Utf8);
+    }
+
+    #[test]
+    fn latin1_rejects_wide_characters() {
+        assert_eq!(encode("price: \u{20ac}", Encoding::Latin1), Err('\u{20ac}'));
+    }
+}
@@ -0,0 +1,195 @@
+//! Outbound URL check for the tools that fetch on the agent's behalf
+//! (`HttpRequest`, `Browser`).
+//!
+//! By default only public addresses are reachable: a host that resolves to a
+//! loopback, private, link-local (including the `169.254.169.254` cloud metadata
+//! endpoint), shared (CGNAT), unique-local or unspecified address is refused, so
+//! neither a prompt-injected agent nor an HTTP-transport client can use the
+//! server to probe its own machine or network. Every address a host resolves to
+//! is checked, and the caller gets them back to pin the connection to (curl's
+//! `--resolve`) so a second lookup can't answer differently.
+//! `WINX_PRIVATE_URLS=1` lifts the check, e.g. to test a local dev server.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use crate::errors::{Result, WinxError};
+
+/// Set to `1` to let `HttpRequest` and `Browser` reach non-public addresses.
+pub const ALLOW_ENV: &str = "WINX_PRIVATE_URLS";
+
+/// Whether `WINX_PRIVATE_URLS` lifts the check.
+pub fn private_allowed() -> bool {
+    std::env::var(ALLOW_ENV).is_ok_and(|v| v == "1" || v == "true")
+}
+
+/// The host and port of an http(s) URL: userinfo dropped, IPv6 brackets
+/// stripped, the port defaulted from the scheme. `None` for anything else,
+/// including authorities with characters that browsers and curl read
+/// differently (`\`, whitespace, a `%`-escaped host).
+pub fn host_port(url: &str) -> Option<(String, u16)> {
+    let (scheme, rest) = url.trim().split_once("://")?;
+    let default_port = match scheme.to_ascii_lowercase().as_str() {
+        "http" => 80,
+        "https" => 443,
+        _ => return None,
+    };
+    let authority = &rest[..rest.find(['/', '?', '#']).unwrap_or(rest.len())];
+    if authority.contains('\\') || authority.chars().any(char::is_whitespace) {
+        return None;
+    }
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, hp)| hp);
+    if host_port.contains('%') {
+        return None;
+    }
+    let (host, port) = if let Some(bracketed) = host_port.strip_prefix('[') {
+        let (host, after) = bracketed.split_once(']')?;
+        (host, after.strip_prefix(':'))
+    } else {
+        match host_port.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (host_port, None),
+        }
+    };
+    let port = match port {
+        None | Some("") => default_port,
+        Some(port) => port.parse().ok()?,
+    };
+    (!host.is_empty()).then(|| (host.to_ascii_lowercase(), port))
+}
+
+/// Whether `ip` is anything but a public unicast address.
+pub fn is_internal(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => is_internal_v4(v4),
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_internal_v4(v4);
+            }
+            let segments = v6.segments();
+            // NAT64 (64:ff9b::/96) embeds an IPv4 address in the low 32 bits.
+            if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+                let [.., hi, lo] = segments;
+                return is_internal_v4(Ipv4Addr::from((u32::from(hi) << 16) | u32::from(lo)));
+            }
+            v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || (segments[0] & 0xfe00) == 0xfc00 // unique local, incl. AWS's fd00:ec2::254
+                || (segments[0] & 0xffc0) == 0xfe80 // link-local
+                || (segments[0] & 0xffc0) == 0xfec0 // site-local (deprecated)
+        }
+    }
+}
+
+fn is_internal_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || a == 0
+        || (a == 100 && (b & 0xc0) == 64) // shared address space (CGNAT)
+        || (a == 192 && b == 0 && ip.octets()[2] == 0) // IETF protocol assignments
+        || a >= 240
+}
+
+/// Refuse `url` unless every address its host resolves to is public. Returns
+/// the host, port and those addresses, for the caller to pin the connection
+/// to; `None` when `WINX_PRIVATE_URLS` lifts the check.
+///
+/// # Errors
+///
+/// Returns an error if the URL has no usable host, the host doesn't resolve,
+/// or it resolves to a non-public address.
+pub async fn check_public(url: &str) -> Result<Option<(String, u16, Vec<SocketAddr>)>> {
+    if private_allowed() {
+        return Ok(None);
+    }
+    let (host, port) = host_port(url)
+        .ok_or_else(|| WinxError::InvalidInput(format!("no usable host in {url:?}")))?;
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), port))
+        .await
+        .map_err(|e| WinxError::InvalidInput(format!("could not resolve {host}: {e}")))?
+        .collect();
+    if addrs.is_empty() {
+        return Err(WinxError::InvalidInput(format!("{host} did not resolve to any address")));
+    }
+    if let Some(addr) = addrs.iter().find(|addr| is_internal(addr.ip())) {
+        return Err(WinxError::RecoverableSuggestionError {
+            message: format!(
+                "{host} resolves to {}, a loopback, private or link-local address",
+                addr.ip()
+            ),
+            suggestion: format!(
+                "Only public hosts are reachable by default; set {ALLOW_ENV}=1 on the server \
+                 to allow local and private addresses"
+            ),
+        });
+    }
+    Ok(Some((host, port, addrs)))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    fn internal(ip: &str) -> bool {
+        is_internal(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn host_port_parses_the_authority() {
+        let hp = |url| host_port(url).map(|(h, p)| format!("{h}:{p}"));
+        assert_eq!(hp("https://Example.com/x").as_deref(), Some("example.com:443"));
+        assert_eq!(hp("http://example.com:8080?q").as_deref(), Some("example.com:8080"));
+        assert_eq!(hp("http://user:pw@10.0.0.1/").as_deref(), Some("10.0.0.1:80"));
+        assert_eq!(hp("http://[::1]:3000/").as_deref(), Some("::1:3000"));
+        assert_eq!(hp("http://evil.example#@127.0.0.1").as_deref(), Some("evil.example:80"));
+        assert!(hp("http://evil.example\\@127.0.0.1/").is_none());
+        assert!(hp("http://127.0.0.%31/").is_none());
+        assert!(hp("file:///etc/passwd").is_none());
+        assert!(hp("http:///path").is_none());
+        assert!(hp("http://host:99999/").is_none());
+    }
+
+    #[test]
+    fn non_public_addresses_are_internal() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "::1",
+            "::",
+            "fe80::1",
+            "fd00::1",
+            "fd00:ec2::254",
+            "::ffff:127.0.0.1",
+            "64:ff9b::a9fe:a9fe",
+        ] {
+            assert!(internal(ip), "{ip} should be internal");
+        }
+        for ip in ["93.184.216.34", "8.8.8.8", "100.128.0.1", "2606:4700::1111"] {
+            assert!(!internal(ip), "{ip} should be public");
+        }
+    }
+
+    #[tokio::test]
+    async fn loopback_urls_are_refused() {
+        if private_allowed() {
+            return;
+        }
+        assert!(check_public("http://127.0.0.1:8080/").await.is_err());
+        assert!(check_public("http://localhost/").await.is_err());
+        assert!(check_public("http://[::1]/").await.is_err());
+        assert!(check_public("http://2130706433/").await.is_err());
+    }
+}
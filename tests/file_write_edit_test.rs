@@ -45,6 +45,9 @@ async fn read_file_before_edit(
     let read_files = ReadFiles {
         file_paths: vec![file_path.to_string_lossy().to_string()],
         thread_id: String::new(),
+        max_tokens: None,
+        max_bytes: None,
+        byte_offset: None,
         start_line_nums: vec![None],
         end_line_nums: vec![None],
     };
@@ -62,6 +65,9 @@ async fn read_file_range_before_edit(
     let read_files = ReadFiles {
         file_paths: vec![format!("{}:{start}-{end}", file_path.to_string_lossy())],
         thread_id: String::new(),
+        max_tokens: None,
+        max_bytes: None,
+        byte_offset: None,
         start_line_nums: vec![Some(start)],
         end_line_nums: vec![Some(end)],
     };
@@ -238,6 +244,9 @@ def add(a: int, b: int) -> int:
     let read_files = ReadFiles {
         file_paths: vec![file_path.to_string_lossy().to_string()],
         thread_id: String::new(),
+        max_tokens: None,
+        max_bytes: None,
+        byte_offset: None,
         start_line_nums: vec![None],
         end_line_nums: vec![None],
     };
@@ -403,6 +412,38 @@ async fn test_edit_rejects_stale_file_hash() -> Result<()> {
     };
 
     assert!(error.to_string().contains("changed on disk since you last read"));
+    match error {
+        WinxError::FileChangedExternally { diff, .. } => {
+            assert!(diff.contains("-old"), "got {diff}");
+            assert!(diff.contains("+changed outside winx"), "got {diff}");
+        }
+        other => {
+            return Err(WinxError::ArgumentParseError(format!(
+                "expected FileChangedExternally, got {other:?}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_overwrite_preserves_bom_and_crlf() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let bash_state_arc = create_initialized_state(&temp_dir, "test-bom-crlf").await?;
+    let file_path = temp_dir.path().join("legacy.txt");
+    std::fs::write(&file_path, b"\xEF\xBB\xBFone\r\ntwo\r\n")?;
+    read_file_before_edit(&bash_state_arc, &file_path).await?;
+
+    let file_edit = FileWriteOrEdit {
+        file_path: file_path.to_string_lossy().to_string(),
+        percentage_to_change: 100,
+        text_or_search_replace_blocks: "one\nTWO\n".to_string(),
+        thread_id: "test-bom-crlf".to_string(),
+    };
+    winx_code_agent::tools::file_write_or_edit::handle_tool_call(&bash_state_arc, file_edit)
+        .await?;
+
+    assert_eq!(std::fs::read(&file_path)?, b"\xEF\xBB\xBFone\r\nTWO\r\n");
     Ok(())
 }
 
@@ -420,6 +461,9 @@ async fn test_whitelist_after_read() -> Result<()> {
     let read_files = ReadFiles {
         file_paths: vec![file_path.to_string_lossy().to_string()],
         thread_id: String::new(),
+        max_tokens: None,
+        max_bytes: None,
+        byte_offset: None,
         start_line_nums: vec![None],
         end_line_nums: vec![None],
     };
@@ -461,6 +505,7 @@ Modified line one.
 // ==================== Test 4: Multiple SEARCH/REPLACE Blocks ====================
 
 #[tokio::test(flavor = "multi_thread")]
+#[allow(clippy::too_many_lines)]
 async fn test_multiple_search_replace_blocks() -> Result<()> {
     let temp_dir = TempDir::new()?;
     let bash_state_arc = create_initialized_state(&temp_dir, "test-multi-blocks").await?;
@@ -505,6 +550,9 @@ if __name__ == "__main__":
     let read_files = ReadFiles {
         file_paths: vec![file_path.to_string_lossy().to_string()],
         thread_id: String::new(),
+        max_tokens: None,
+        max_bytes: None,
+        byte_offset: None,
         start_line_nums: vec![None],
         end_line_nums: vec![None],
     };
@@ -854,6 +902,9 @@ async fn test_search_replace_not_found() -> Result<()> {
     let read_files = ReadFiles {
         file_paths: vec![file_path.to_string_lossy().to_string()],
         thread_id: String::new(),
+        max_tokens: None,
+        max_bytes: None,
+        byte_offset: None,
         start_line_nums: vec![None],
         end_line_nums: vec![None],
     };
@@ -913,6 +964,9 @@ async fn test_empty_replacement() -> Result<()> {
     let read_files = ReadFiles {
         file_paths: vec![file_path.to_string_lossy().to_string()],
         thread_id: String::new(),
+        max_tokens: None,
+        max_bytes: None,
+        byte_offset: None,
         start_line_nums: vec![None],
         end_line_nums: vec![None],
     };
@@ -973,6 +1027,9 @@ async fn test_overwrite_existing_file_full_content() -> Result<()> {
     let read_files = ReadFiles {
         file_paths: vec![file_path.to_string_lossy().to_string()],
         thread_id: String::new(),
+        max_tokens: None,
+        max_bytes: None,
+        byte_offset: None,
         start_line_nums: vec![None],
         end_line_nums: vec![None],
     };
@@ -83,6 +83,9 @@ async fn test_full_lifecycle_workflow() -> Result<()> {
         let read_cmd = ReadFiles {
             file_paths: vec![data_file.to_string_lossy().to_string()],
             thread_id: String::new(),
+            max_tokens: None,
+            max_bytes: None,
+            byte_offset: None,
             start_line_nums: vec![],
             end_line_nums: vec![],
         };
@@ -483,6 +483,9 @@ async fn test_read_files_single_file() -> Result<()> {
     let read = ReadFiles {
         file_paths: vec![file_path.to_string_lossy().to_string()],
         thread_id: String::new(),
+        max_tokens: None,
+        max_bytes: None,
+        byte_offset: None,
         start_line_nums: vec![None],
         end_line_nums: vec![None],
     };
@@ -521,6 +524,9 @@ async fn test_read_files_multiple_files() -> Result<()> {
     let read = ReadFiles {
         file_paths: vec![file1.to_string_lossy().to_string(), file2.to_string_lossy().to_string()],
         thread_id: String::new(),
+        max_tokens: None,
+        max_bytes: None,
+        byte_offset: None,
         start_line_nums: vec![None, None],
         end_line_nums: vec![None, None],
     };
@@ -557,6 +563,9 @@ async fn test_read_files_with_line_range() -> Result<()> {
     let read = ReadFiles {
         file_paths: vec![file_path.to_string_lossy().to_string()],
         thread_id: String::new(),
+        max_tokens: None,
+        max_bytes: None,
+        byte_offset: None,
         start_line_nums: vec![Some(2)],
         end_line_nums: vec![Some(4)],
     };
@@ -606,6 +615,73 @@ async fn test_read_files_with_wcgw_path_suffix_range() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_read_files_byte_window_continues_from_cursor() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("app.log");
+    let log = (0..100).map(|i| format!("entry {i:03}\n")).collect::<Vec<_>>().concat();
+    std::fs::write(&file_path, &log)?;
+
+    let bash_state_arc: Arc<Mutex<Option<BashState>>> = Arc::new(Mutex::new(None));
+    let init = Initialize {
+        init_type: InitializeType::FirstCall,
+        mode_name: ModeName::Wcgw,
+        any_workspace_path: temp_dir.path().to_string_lossy().to_string(),
+        thread_id: String::new(),
+        code_writer_config: None,
+        initial_files_to_read: vec![],
+        task_id_to_resume: String::new(),
+    };
+    winx_code_agent::tools::initialize::handle_tool_call(&bash_state_arc, init).await?;
+
+    // 25 bytes = two whole 10-byte lines; the partial third is left for next time.
+    let read: ReadFiles = serde_json::from_value(json!({
+        "file_paths": [file_path.to_string_lossy()],
+        "max_bytes": 25
+    }))
+    .map_err(|error| WinxError::ArgumentParseError(error.to_string()))?;
+    let response =
+        winx_code_agent::tools::read_files::handle_tool_call(&bash_state_arc, read).await?;
+    assert!(response.contains("entry 001"));
+    assert!(!response.contains("entry 002"));
+    assert!(response.contains("byte_offset=20"), "got {response}");
+
+    let read: ReadFiles = serde_json::from_value(json!({
+        "file_paths": [file_path.to_string_lossy()],
+        "max_bytes": 25,
+        "byte_offset": 20
+    }))
+    .map_err(|error| WinxError::ArgumentParseError(error.to_string()))?;
+    let response =
+        winx_code_agent::tools::read_files::handle_tool_call(&bash_state_arc, read).await?;
+    assert!(response.contains("entry 002"));
+    assert!(!response.contains("entry 001"));
+
+    // A zero token budget still advances by one line instead of looping.
+    let read: ReadFiles = serde_json::from_value(json!({
+        "file_paths": [file_path.to_string_lossy()],
+        "max_bytes": 25,
+        "max_tokens": 0
+    }))
+    .map_err(|error| WinxError::ArgumentParseError(error.to_string()))?;
+    let response =
+        winx_code_agent::tools::read_files::handle_tool_call(&bash_state_arc, read).await?;
+    assert!(response.contains("entry 000"));
+    assert!(response.contains("byte_offset=10"), "got {response}");
+
+    // A cursor belongs to one file.
+    let read: ReadFiles = serde_json::from_value(json!({
+        "file_paths": [file_path.to_string_lossy(), file_path.to_string_lossy()],
+        "max_bytes": 25,
+        "byte_offset": 20
+    }))
+    .map_err(|error| WinxError::ArgumentParseError(error.to_string()))?;
+    let result = winx_code_agent::tools::read_files::handle_tool_call(&bash_state_arc, read).await;
+    assert!(result.is_err());
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_file_write_or_edit_treats_search_marker_as_edit_even_with_high_percentage(
 ) -> Result<()> {
@@ -628,6 +704,9 @@ async fn test_file_write_or_edit_treats_search_marker_as_edit_even_with_high_per
     let read = ReadFiles {
         file_paths: vec![file_path.to_string_lossy().to_string()],
         thread_id: String::new(),
+        max_tokens: None,
+        max_bytes: None,
+        byte_offset: None,
         start_line_nums: vec![None],
         end_line_nums: vec![None],
     };
@@ -674,6 +753,9 @@ async fn test_read_files_nonexistent() -> Result<()> {
     let read = ReadFiles {
         file_paths: vec![temp_dir.path().join("nonexistent.txt").to_string_lossy().to_string()],
         thread_id: String::new(),
+        max_tokens: None,
+        max_bytes: None,
+        byte_offset: None,
         start_line_nums: vec![None],
         end_line_nums: vec![None],
     };
@@ -1072,8 +1154,12 @@ async fn test_read_image_png() -> Result<()> {
     };
     winx_code_agent::tools::initialize::handle_tool_call(&bash_state_arc, init).await?;
 
-    let read_image =
-        ReadImage { file_path: image_path.to_string_lossy().to_string(), thread_id: String::new() };
+    let read_image = ReadImage {
+        file_path: image_path.to_string_lossy().to_string(),
+        thread_id: String::new(),
+        max_width: None,
+        jpeg_quality: None,
+    };
 
     let (mime_type, base64_data) =
         winx_code_agent::tools::read_image::handle_tool_call(&bash_state_arc, read_image).await?;
@@ -1129,8 +1215,12 @@ async fn test_read_image_jpeg() -> Result<()> {
     };
     winx_code_agent::tools::initialize::handle_tool_call(&bash_state_arc, init).await?;
 
-    let read_image =
-        ReadImage { file_path: image_path.to_string_lossy().to_string(), thread_id: String::new() };
+    let read_image = ReadImage {
+        file_path: image_path.to_string_lossy().to_string(),
+        thread_id: String::new(),
+        max_width: None,
+        jpeg_quality: None,
+    };
 
     let (mime_type, _base64_data) =
         winx_code_agent::tools::read_image::handle_tool_call(&bash_state_arc, read_image).await?;
@@ -1163,6 +1253,8 @@ async fn test_read_image_nonexistent() -> Result<()> {
     let read_image = ReadImage {
         file_path: temp_dir.path().join("nonexistent.png").to_string_lossy().to_string(),
         thread_id: String::new(),
+        max_width: None,
+        jpeg_quality: None,
     };
 
     let result =
@@ -1197,8 +1289,12 @@ async fn test_read_image_non_image_file() -> Result<()> {
     };
     winx_code_agent::tools::initialize::handle_tool_call(&bash_state_arc, init).await?;
 
-    let read_image =
-        ReadImage { file_path: text_path.to_string_lossy().to_string(), thread_id: String::new() };
+    let read_image = ReadImage {
+        file_path: text_path.to_string_lossy().to_string(),
+        thread_id: String::new(),
+        max_width: None,
+        jpeg_quality: None,
+    };
 
     // ReadImage should still work (returns base64 of any file with guessed MIME type)
     // It falls back to image/jpeg for unknown types per the implementation
@@ -37,6 +37,9 @@ async fn read_file(arc: &Arc<Mutex<Option<BashState>>>, path: &std::path::Path)
     let rf = ReadFiles {
         file_paths: vec![path.to_string_lossy().to_string()],
         thread_id: String::new(),
+        max_tokens: None,
+        max_bytes: None,
+        byte_offset: None,
         start_line_nums: vec![None],
         end_line_nums: vec![None],
     };